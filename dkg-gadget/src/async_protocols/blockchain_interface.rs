@@ -13,13 +13,14 @@
 // limitations under the License.
 use crate::{
 	async_protocols::BatchKey,
+	db::StoredSignedProposalBatch,
 	debug_logger::DebugLogger,
 	gossip_engine::GossipEngineIface,
 	gossip_messages::{dkg_message::sign_and_send_messages, public_key_gossip::gossip_public_key},
 	metrics::Metrics,
 	storage::proposals::save_signed_proposals_in_storage,
 	worker::{DKGWorker, HasLatestHeader, KeystoreExt, TestBundle},
-	Client, DKGApi, DKGKeystore,
+	Client, DKGApi, DKGKeystore, DKGRpcLinks,
 };
 use codec::Encode;
 use curv::{elliptic::curves::Secp256k1, BigInt};
@@ -45,7 +46,10 @@ use sp_runtime::traits::{Block, Get, NumberFor};
 use std::{collections::HashMap, fmt::Debug, marker::PhantomData, sync::Arc};
 use webb_proposals::Proposal;
 
-use super::KeygenPartyId;
+use super::{
+	frost::{FrostLocalKey, FrostSignature},
+	KeygenPartyId,
+};
 
 #[async_trait::async_trait]
 #[auto_impl::auto_impl(Arc,&,&mut)]
@@ -75,12 +79,29 @@ pub trait BlockchainInterface: Send + Sync + Unpin {
 		batch_key: BatchKey,
 		message: BigInt,
 	) -> Result<(), DKGError>;
+	fn process_frost_vote_result(
+		&self,
+		signature: FrostSignature,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			Self::BatchId,
+			Self::MaxProposalLength,
+			Self::MaxProposalsInBatch,
+			Self::Clock,
+		>,
+		session_id: SessionId,
+		batch_key: BatchKey,
+	) -> Result<(), DKGError>;
 	fn gossip_public_key(&self, key: PublicKeyMessage) -> Result<(), DKGError>;
 	fn store_public_key(
 		&self,
 		key: LocalKey<Secp256k1>,
 		session_id: SessionId,
 	) -> Result<(), DKGError>;
+	fn store_frost_public_key(
+		&self,
+		key: FrostLocalKey,
+		session_id: SessionId,
+	) -> Result<(), DKGError>;
 	fn get_authority_set(&self) -> Vec<(KeygenPartyId, Public)>;
 	fn get_gossip_engine(&self) -> Option<&Self::GossipEngine>;
 	/// Returns the present time
@@ -128,6 +149,7 @@ pub struct DKGProtocolEngine<
 	pub local_keystore: Arc<RwLock<Option<Arc<LocalKeystore>>>>,
 	pub metrics: Arc<Option<Metrics>>,
 	pub test_bundle: Option<TestBundle>,
+	pub rpc_links: DKGRpcLinks,
 	pub logger: DebugLogger,
 	pub _pd: PhantomData<(BE, BatchId, MaxProposalsInBatch, MaxSignatureLength)>,
 }
@@ -225,9 +247,8 @@ impl<
 	}
 }
 
-#[async_trait::async_trait]
-impl<B, BE, C, GE> BlockchainInterface
-	for DKGProtocolEngine<
+impl<B, BE, C, GE>
+	DKGProtocolEngine<
 		B,
 		BE,
 		C,
@@ -242,62 +263,25 @@ impl<B, BE, C, GE> BlockchainInterface
 	C: Client<B, BE> + 'static,
 	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
 	BE: Backend<B> + Unpin + 'static,
-	MaxProposalLength: Get<u32> + Send + Sync + Clone + 'static + std::fmt::Debug + Unpin,
-	BatchId: Clone + Send + Sync + std::fmt::Debug + 'static + Unpin,
-	MaxProposalsInBatch: Get<u32> + Clone + Send + Sync + std::fmt::Debug + 'static + Unpin,
-	MaxSignatureLength: Get<u32> + Clone + Send + Sync + std::fmt::Debug + 'static + Unpin,
 	GE: GossipEngineIface + 'static,
 {
-	type Clock = NumberFor<B>;
-	type GossipEngine = Arc<GE>;
-	type MaxProposalLength = MaxProposalLength;
-	type BatchId = BatchId;
-	type MaxProposalsInBatch = MaxProposalsInBatch;
-	type MaxSignatureLength = MaxSignatureLength;
-
-	async fn verify_signature_against_authorities(
-		&self,
-		msg: SignedDKGMessage<Public>,
-	) -> Result<DKGMessage<Public>, DKGError> {
-		let client = &self.client;
-
-		DKGWorker::<_, _, _, GE>::verify_signature_against_authorities_inner(
-			&self.logger,
-			msg,
-			&self.latest_header,
-			client,
-		)
-		.await
-	}
-
-	fn sign_and_send_msg(&self, unsigned_msg: DKGMessage<Public>) -> Result<(), DKGError> {
-		sign_and_send_messages(self.gossip_engine.clone(), &self.keystore, unsigned_msg);
-		Ok(())
-	}
-
-	fn process_vote_result(
+	/// Converts the unsigned proposals of the batch to signed proposals using the given
+	/// signature, and submits the batch once every proposal of the `batch_key` has resolved.
+	///
+	/// The signed batch is also persisted until it is seen on-chain, so that it can be submitted
+	/// again if the node restarts before that, and is sent right away to the RPC subscribers.
+	fn store_signed_proposal_batch(
 		&self,
-		signature: SignatureRecid,
+		signature: Vec<u8>,
 		unsigned_proposal_batch: StoredUnsignedProposalBatch<
-			Self::BatchId,
-			Self::MaxProposalLength,
-			Self::MaxProposalsInBatch,
-			Self::Clock,
+			BatchId,
+			MaxProposalLength,
+			MaxProposalsInBatch,
+			NumberFor<B>,
 		>,
 		session_id: SessionId,
 		batch_key: BatchKey,
-		_message: BigInt,
-	) -> Result<(), DKGError> {
-		// Call worker.rs: handle_finished_round -> Proposal
-		// aggregate Proposal into Vec<Proposal>
-		self.logger.info(format!(
-			"PROCESS VOTE RESULT : session_id {session_id:?}, signature : {signature:?}"
-		));
-
-		let signature = convert_signature(&signature).ok_or_else(|| DKGError::CriticalError {
-			reason: "Unable to serialize signature".to_string(),
-		})?;
-
+	) {
 		let mut signed_proposals = vec![];
 
 		// convert all unsigned proposals to signed
@@ -309,19 +293,39 @@ impl<B, BE, C, GE> BlockchainInterface
 					.clone()
 					.try_into()
 					.expect("should not happen since its a valid proposal"),
-				signature: signature
-					.encode()
-					.try_into()
-					.expect("Signature exceeds runtime bounds!"),
+				signature: signature.clone().try_into().expect("Signature exceeds runtime bounds!"),
 			});
 		}
 
 		let signed_proposal_batch = SignedProposalBatch {
 			batch_id: unsigned_proposal_batch.batch_id,
 			proposals: signed_proposals.try_into().expect("Proposals exceeds runtime bounds!"),
-			signature: signature.encode().try_into().expect("Signature exceeds runtime bounds!"),
+			signature: signature.try_into().expect("Signature exceeds runtime bounds!"),
+			encoding: unsigned_proposal_batch.encoding,
 		};
 
+		if let Some(unsigned_proposal_hash) = unsigned_proposal_batch.hash() {
+			let stored = StoredSignedProposalBatch {
+				unsigned_proposal_hash,
+				session_id,
+				batch: signed_proposal_batch.clone(),
+			};
+			if let Err(err) = self.db.store_unsubmitted_signed_proposal(stored) {
+				self.logger
+					.error(format!("Failed to persist the signed proposal batch: {err:?}"));
+			}
+			if let Err(err) = self.db.remove_pending_signing_job(unsigned_proposal_hash) {
+				self.logger.error(format!("Failed to remove the finished signing job: {err:?}"));
+			}
+		}
+
+		if let Some(unsigned_proposal) = unsigned_proposal_batch.proposals.first() {
+			self.rpc_links.notify_signed_proposals((
+				unsigned_proposal.typed_chain_id,
+				signed_proposal_batch.clone(),
+			));
+		}
+
 		let mut lock = self.vote_results.write();
 		let proposals_for_this_batch = lock.entry(batch_key).or_default();
 
@@ -363,6 +367,114 @@ impl<B, BE, C, GE> BlockchainInterface
 				batch_key,
 			));
 		}
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, BE, C, GE> BlockchainInterface
+	for DKGProtocolEngine<
+		B,
+		BE,
+		C,
+		GE,
+		MaxProposalLength,
+		MaxAuthorities,
+		BatchId,
+		MaxProposalsInBatch,
+		MaxSignatureLength,
+	> where
+	B: Block,
+	C: Client<B, BE> + 'static,
+	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
+	BE: Backend<B> + Unpin + 'static,
+	MaxProposalLength: Get<u32> + Send + Sync + Clone + 'static + std::fmt::Debug + Unpin,
+	BatchId: Clone + Send + Sync + std::fmt::Debug + 'static + Unpin,
+	MaxProposalsInBatch: Get<u32> + Clone + Send + Sync + std::fmt::Debug + 'static + Unpin,
+	MaxSignatureLength: Get<u32> + Clone + Send + Sync + std::fmt::Debug + 'static + Unpin,
+	GE: GossipEngineIface + 'static,
+{
+	type Clock = NumberFor<B>;
+	type GossipEngine = Arc<GE>;
+	type MaxProposalLength = MaxProposalLength;
+	type BatchId = BatchId;
+	type MaxProposalsInBatch = MaxProposalsInBatch;
+	type MaxSignatureLength = MaxSignatureLength;
+
+	async fn verify_signature_against_authorities(
+		&self,
+		msg: SignedDKGMessage<Public>,
+	) -> Result<DKGMessage<Public>, DKGError> {
+		let client = &self.client;
+
+		DKGWorker::<_, _, _, GE>::verify_signature_against_authorities_inner(
+			&self.logger,
+			msg,
+			&self.latest_header,
+			client,
+		)
+		.await
+	}
+
+	fn sign_and_send_msg(&self, unsigned_msg: DKGMessage<Public>) -> Result<(), DKGError> {
+		sign_and_send_messages(self.gossip_engine.clone(), &self.keystore, unsigned_msg);
+		Ok(())
+	}
+
+	fn process_vote_result(
+		&self,
+		signature: SignatureRecid,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			Self::BatchId,
+			Self::MaxProposalLength,
+			Self::MaxProposalsInBatch,
+			Self::Clock,
+		>,
+		session_id: SessionId,
+		batch_key: BatchKey,
+		_message: BigInt,
+	) -> Result<(), DKGError> {
+		// Call worker.rs: handle_finished_round -> Proposal
+		// aggregate Proposal into Vec<Proposal>
+		self.logger.info(format!(
+			"PROCESS VOTE RESULT : session_id {session_id:?}, signature : {signature:?}"
+		));
+
+		let signature = convert_signature(&signature).ok_or_else(|| DKGError::CriticalError {
+			reason: "Unable to serialize signature".to_string(),
+		})?;
+
+		self.store_signed_proposal_batch(
+			signature.encode(),
+			unsigned_proposal_batch,
+			session_id,
+			batch_key,
+		);
+
+		Ok(())
+	}
+
+	fn process_frost_vote_result(
+		&self,
+		signature: FrostSignature,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			Self::BatchId,
+			Self::MaxProposalLength,
+			Self::MaxProposalsInBatch,
+			Self::Clock,
+		>,
+		session_id: SessionId,
+		batch_key: BatchKey,
+	) -> Result<(), DKGError> {
+		self.logger.info(format!(
+			"PROCESS FROST VOTE RESULT : session_id {session_id:?}, signature : {signature:?}"
+		));
+
+		self.store_signed_proposal_batch(
+			signature.to_bytes(),
+			unsigned_proposal_batch,
+			session_id,
+			batch_key,
+		);
 
 		Ok(())
 	}
@@ -390,6 +502,15 @@ impl<B, BE, C, GE> BlockchainInterface
 		self.db.store_local_key(session_id, key)
 	}
 
+	fn store_frost_public_key(
+		&self,
+		key: FrostLocalKey,
+		session_id: SessionId,
+	) -> Result<(), DKGError> {
+		self.logger.debug(format!("Storing FROST local key for session {session_id:?}"));
+		self.db.store_frost_local_key(session_id, key)
+	}
+
 	fn get_authority_set(&self) -> Vec<(KeygenPartyId, Public)> {
 		(*self.best_authorities).clone()
 	}
@@ -0,0 +1,633 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Debug, sync::Arc};
+
+use dkg_logging::debug_logger::RoundsEventType;
+use dkg_primitives::types::{DKGError, DKGMessage, NetworkMsgPayload, GE};
+use dkg_runtime_primitives::{
+	crypto::Public,
+	gossip_messages::{DKGKeygenMessage, DKGOfflineMessage, PublicKeyMessage},
+	MaxAuthorities, StoredUnsignedProposalBatch,
+};
+use futures::{FutureExt, StreamExt};
+
+use super::{
+	reshare::{FrostReshare, FrostReshareMessage, ReshareSets},
+	FrostKeygen, FrostKeygenMessage, FrostKeygenMode, FrostLocalKey, FrostSigning,
+	FrostSigningMessage,
+};
+use crate::async_protocols::{
+	blockchain_interface::BlockchainInterface,
+	incoming::IncomingAsyncProtocolWrapper,
+	remote::{MetaHandlerStatus, ShutdownReason},
+	AsyncProtocolParameters, GenericAsyncHandler, KeygenPartyId, KeygenRound, ProtocolType,
+};
+
+impl<Out: Send + Debug + 'static> GenericAsyncHandler<'static, Out>
+where
+	(): Extend<Out>,
+{
+	/// Top-level function used to begin the execution of the WT-FROST keygen. The `mode`
+	/// determines whether a new key is generated, or an existing key is refreshed or reshared.
+	pub fn setup_frost_keygen<BI: BlockchainInterface + 'static>(
+		params: AsyncProtocolParameters<BI, MaxAuthorities>,
+		threshold: u16,
+		weights: Vec<u16>,
+		status: KeygenRound,
+		keygen_protocol_hash: [u8; 32],
+		mode: FrostKeygenMode,
+	) -> Result<GenericAsyncHandler<'static, ()>, DKGError> {
+		let status_handle = params.handle.clone();
+		let mut stop_rx =
+			status_handle.stop_rx.lock().take().ok_or_else(|| DKGError::GenericError {
+				reason: "execute called twice with the same AsyncProtocol Parameters".to_string(),
+			})?;
+
+		let start_rx =
+			status_handle.start_rx.lock().take().ok_or_else(|| DKGError::GenericError {
+				reason: "execute called twice with the same AsyncProtocol Parameters".to_string(),
+			})?;
+
+		let logger0 = params.logger.clone();
+		let logger1 = params.logger.clone();
+
+		let protocol = async move {
+			params.logger.info_keygen(
+				"Will execute WT-FROST keygen since local is in best authority set".to_string(),
+			);
+			// wait for the start signal
+			start_rx
+				.await
+				.map_err(|err| DKGError::StartKeygen { reason: err.to_string() })?;
+			// Set status of the handle
+			params.handle.set_status(MetaHandlerStatus::Keygen);
+			match mode {
+				FrostKeygenMode::Reshare { sets, old_key, public_key } =>
+					Self::run_frost_reshare(
+						params.clone(),
+						status,
+						keygen_protocol_hash,
+						sets,
+						old_key,
+						public_key,
+					)
+					.await?,
+				FrostKeygenMode::Refresh(local_key) =>
+					Self::run_frost_keygen(
+						params.clone(),
+						threshold,
+						weights,
+						status,
+						keygen_protocol_hash,
+						Some(local_key),
+					)
+					.await?,
+				FrostKeygenMode::Generate =>
+					Self::run_frost_keygen(
+						params.clone(),
+						threshold,
+						weights,
+						status,
+						keygen_protocol_hash,
+						None,
+					)
+					.await?,
+			}
+			params.logger.debug_keygen("WT-FROST keygen stage complete!");
+
+			Ok(())
+		}
+		.then(|res| async move {
+			match res {
+				Ok(_) => {
+					status_handle.set_status(MetaHandlerStatus::Complete);
+					logger0.info_keygen("🕸️  WT-FROST keygen completed".to_string());
+				},
+				Err(ref err) => {
+					// Do not update the status here, eventually the Keygen will fail and timeout.
+					logger0.error_keygen(format!("WT-FROST keygen failed with error: {err:?}"));
+				},
+			};
+			res
+		});
+
+		let protocol = Box::pin(async move {
+			tokio::select! {
+				res0 = protocol => res0,
+				res1 = stop_rx.recv() => {
+					logger1.info_keygen(format!("Stopper has been called {res1:?}"));
+					if let Some(res1) = res1 {
+						if res1 == ShutdownReason::DropCode {
+							Ok(())
+						} else {
+							Err(DKGError::GenericError { reason: "Keygen has stalled".into() })
+						}
+					} else {
+						Ok(())
+					}
+				}
+			}
+		});
+
+		Ok(GenericAsyncHandler { protocol })
+	}
+
+	/// Top-level function used to begin the execution of the WT-FROST signing
+	pub fn setup_frost_signing<BI: BlockchainInterface + 'static>(
+		params: AsyncProtocolParameters<BI, MaxAuthorities>,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			<BI as BlockchainInterface>::BatchId,
+			<BI as BlockchainInterface>::MaxProposalLength,
+			<BI as BlockchainInterface>::MaxProposalsInBatch,
+			<BI as BlockchainInterface>::Clock,
+		>,
+		signing_set: Vec<KeygenPartyId>,
+		local_key: Option<FrostLocalKey>,
+	) -> Result<GenericAsyncHandler<'static, ()>, DKGError> {
+		let status_handle = params.handle.clone();
+		let mut stop_rx =
+			status_handle.stop_rx.lock().take().ok_or_else(|| DKGError::GenericError {
+				reason: "execute called twice with the same AsyncProtocol Parameters".to_string(),
+			})?;
+
+		let start_rx =
+			status_handle.start_rx.lock().take().ok_or_else(|| DKGError::GenericError {
+				reason: "execute called twice with the same AsyncProtocol Parameters".to_string(),
+			})?;
+
+		let logger0 = params.logger.clone();
+		let logger2 = params.logger.clone();
+
+		let protocol = async move {
+			let local_key = local_key.ok_or_else(|| DKGError::GenericError {
+				reason: "Will skip signing since FROST local key does not exist".to_string(),
+			})?;
+
+			if !signing_set.contains(&params.party_i) {
+				params.logger.warn_signing("🕸️  We are not among signers, skipping".to_string());
+				return Err(DKGError::GenericError {
+					reason: "We are not among signers, skipping".to_string(),
+				})
+			}
+
+			start_rx
+				.await
+				.map_err(|err| DKGError::StartOffline { reason: err.to_string() })?;
+
+			params.handle.set_status(MetaHandlerStatus::OfflineAndVoting);
+			params.logger.debug_signing("Received unsigned proposal");
+
+			Self::run_frost_signing(
+				params.clone(),
+				unsigned_proposal_batch,
+				signing_set,
+				local_key,
+			)
+			.await?;
+
+			params.logger.info_signing("Concluded WT-FROST signing for this node");
+			Ok(())
+		}
+		.then(|res| async move {
+			status_handle.set_status(MetaHandlerStatus::Complete);
+			logger0.info_signing(format!("🕸️  WT-FROST signing protocol concluded with {res:?}"));
+			res
+		});
+
+		let protocol = Box::pin(async move {
+			tokio::select! {
+				res0 = protocol => res0,
+				res1 = stop_rx.recv() => {
+					logger2.info_signing(format!("Stopper has been called {res1:?}"));
+					if let Some(res1) = res1 {
+						if res1 == ShutdownReason::DropCode {
+							Ok(())
+						} else {
+							Err(DKGError::GenericError { reason: "Signing has stalled".into() })
+						}
+					} else {
+						Ok(())
+					}
+				}
+			}
+		});
+
+		Ok(GenericAsyncHandler { protocol })
+	}
+
+	async fn run_frost_keygen<BI: BlockchainInterface + 'static>(
+		params: AsyncProtocolParameters<BI, MaxAuthorities>,
+		threshold: u16,
+		weights: Vec<u16>,
+		status: KeygenRound,
+		keygen_protocol_hash: [u8; 32],
+		refreshed_key: Option<FrostLocalKey>,
+	) -> Result<(), DKGError> {
+		let i = params.party_i;
+		let n = params.best_authorities.len() as u16;
+		if weights.len() != n as usize {
+			return Err(DKGError::StartKeygen {
+				reason: format!("Expected {n} weights, got {}", weights.len()),
+			})
+		}
+
+		let ty: ProtocolType<
+			<BI as BlockchainInterface>::BatchId,
+			<BI as BlockchainInterface>::MaxProposalLength,
+			<BI as BlockchainInterface>::MaxProposalsInBatch,
+			<BI as BlockchainInterface>::Clock,
+		> = ProtocolType::Keygen {
+			ty: status,
+			i,
+			t: threshold,
+			n,
+			associated_block_id: params.associated_block_id,
+			keygen_protocol_hash,
+		};
+
+		let mut keygen = match refreshed_key {
+			Some(local_key) => {
+				if local_key.party_i != *i.as_ref() ||
+					local_key.threshold != threshold ||
+					local_key.weights != weights
+				{
+					return Err(DKGError::StartKeygen {
+						reason: "Cannot refresh a WT-FROST key generated by a different set"
+							.to_string(),
+					})
+				}
+
+				params.logger.info_keygen("Will refresh the WT-FROST key shares".to_string());
+				FrostKeygen::refresh(local_key, keygen_protocol_hash)?
+			},
+			None => FrostKeygen::new(*i.as_ref(), threshold, weights, keygen_protocol_hash)?,
+		};
+		let rx = params.handle.rx_keygen_signing.lock().take().ok_or_else(|| {
+			DKGError::GenericError { reason: "rx_keygen_signing already taken".to_string() }
+		})?;
+		let mut incoming = IncomingAsyncProtocolWrapper::new(rx, ty.clone(), params.clone());
+
+		let send = |recipient: Option<KeygenPartyId>, message: FrostKeygenMessage| {
+			let keygen_msg = serde_json::to_vec(&message)
+				.map_err(|err| DKGError::GenericError { reason: err.to_string() })?;
+			let payload = NetworkMsgPayload::Keygen(DKGKeygenMessage {
+				sender_id: *i.as_ref(),
+				keygen_msg,
+				keygen_protocol_hash,
+			});
+			Self::send_frost_message(&params, recipient, payload)
+		};
+
+		params.logger.round_event(
+			&ty,
+			RoundsEventType::ProceededToRound { session: params.session_id, round: 1 },
+		);
+		send(None, keygen.commitment_message())?;
+
+		let mut sent_shares = false;
+		loop {
+			if keygen.has_all_commitments() && !sent_shares {
+				params.logger.round_event(
+					&ty,
+					RoundsEventType::ProceededToRound { session: params.session_id, round: 2 },
+				);
+				for (recipient, message) in keygen.share_messages()? {
+					send(Some(KeygenPartyId::try_from(recipient)?), message)?;
+				}
+				sent_shares = true;
+			}
+
+			if sent_shares && keygen.has_all_shares() {
+				break
+			}
+
+			let msg = incoming.next().await.ok_or_else(|| DKGError::GenericError {
+				reason: "Incoming stream ended before WT-FROST keygen completed".to_string(),
+			})?;
+
+			let NetworkMsgPayload::Keygen(keygen_msg) = msg.body.payload else { continue };
+			if keygen_msg.keygen_protocol_hash != keygen_protocol_hash {
+				continue
+			}
+
+			let message = match serde_json::from_slice::<FrostKeygenMessage>(&keygen_msg.keygen_msg)
+			{
+				Ok(message) => message,
+				Err(err) => {
+					params.logger.warn_keygen(format!(
+						"Received invalid WT-FROST keygen message from {}: {err:?}",
+						msg.sender
+					));
+					continue
+				},
+			};
+
+			if !keygen.handle_message(msg.sender, message)? {
+				params.logger.debug_keygen(format!(
+					"Ignored duplicate or unexpected WT-FROST keygen message from {}",
+					msg.sender
+				));
+			}
+		}
+
+		let local_key = keygen.finish()?;
+		params
+			.logger
+			.info_keygen("Completed WT-FROST keygen stage successfully!".to_string());
+
+		let session_id = params.session_id;
+		let pub_key_msg = PublicKeyMessage {
+			session_id,
+			pub_key: local_key.public_key().to_bytes(true).to_vec(),
+			signature: vec![],
+		};
+
+		// store the key first, since handling the gossiped public key may need the local key
+		params.engine.store_frost_public_key(local_key, session_id)?;
+		params.engine.gossip_public_key(pub_key_msg)?;
+
+		Ok(())
+	}
+
+	async fn run_frost_reshare<BI: BlockchainInterface + 'static>(
+		params: AsyncProtocolParameters<BI, MaxAuthorities>,
+		status: KeygenRound,
+		keygen_protocol_hash: [u8; 32],
+		sets: ReshareSets,
+		old_key: Option<FrostLocalKey>,
+		public_key: GE,
+	) -> Result<(), DKGError> {
+		let i = params.party_i;
+		let n = params.best_authorities.len() as u16;
+		if sets.participants().len() != n as usize {
+			return Err(DKGError::StartKeygen {
+				reason: format!("Expected {n} resharing participants, got {sets:?}"),
+			})
+		}
+
+		let ty: ProtocolType<
+			<BI as BlockchainInterface>::BatchId,
+			<BI as BlockchainInterface>::MaxProposalLength,
+			<BI as BlockchainInterface>::MaxProposalsInBatch,
+			<BI as BlockchainInterface>::Clock,
+		> = ProtocolType::Keygen {
+			ty: status,
+			i,
+			t: sets.new_threshold,
+			n,
+			associated_block_id: params.associated_block_id,
+			keygen_protocol_hash,
+		};
+
+		params.logger.info_keygen(format!(
+			"Will reshare the WT-FROST key as party {:?} of the outgoing set and {:?} of the \
+			 incoming set",
+			sets.old_party(*i.as_ref()),
+			sets.new_party(*i.as_ref()),
+		));
+		let mut reshare =
+			FrostReshare::new(*i.as_ref(), sets, old_key, public_key, keygen_protocol_hash)?;
+		let rx = params.handle.rx_keygen_signing.lock().take().ok_or_else(|| {
+			DKGError::GenericError { reason: "rx_keygen_signing already taken".to_string() }
+		})?;
+		let mut incoming = IncomingAsyncProtocolWrapper::new(rx, ty.clone(), params.clone());
+
+		let send = |recipient: Option<KeygenPartyId>, message: FrostReshareMessage| {
+			let keygen_msg = serde_json::to_vec(&message)
+				.map_err(|err| DKGError::GenericError { reason: err.to_string() })?;
+			let payload = NetworkMsgPayload::Keygen(DKGKeygenMessage {
+				sender_id: *i.as_ref(),
+				keygen_msg,
+				keygen_protocol_hash,
+			});
+			Self::send_frost_message(&params, recipient, payload)
+		};
+
+		params.logger.round_event(
+			&ty,
+			RoundsEventType::ProceededToRound { session: params.session_id, round: 1 },
+		);
+		send(None, reshare.commitment_message())?;
+
+		let mut sent_shares = false;
+		loop {
+			if reshare.has_all_commitments() && !sent_shares {
+				params.logger.round_event(
+					&ty,
+					RoundsEventType::ProceededToRound { session: params.session_id, round: 2 },
+				);
+				for (recipient, message) in reshare.share_messages()? {
+					send(Some(KeygenPartyId::try_from(recipient)?), message)?;
+				}
+				sent_shares = true;
+			}
+
+			if sent_shares && reshare.has_all_shares() {
+				break
+			}
+
+			let msg = incoming.next().await.ok_or_else(|| DKGError::GenericError {
+				reason: "Incoming stream ended before WT-FROST resharing completed".to_string(),
+			})?;
+
+			let NetworkMsgPayload::Keygen(keygen_msg) = msg.body.payload else { continue };
+			if keygen_msg.keygen_protocol_hash != keygen_protocol_hash {
+				continue
+			}
+
+			let message =
+				match serde_json::from_slice::<FrostReshareMessage>(&keygen_msg.keygen_msg) {
+					Ok(message) => message,
+					Err(err) => {
+						params.logger.warn_keygen(format!(
+							"Received invalid WT-FROST resharing message from {}: {err:?}",
+							msg.sender
+						));
+						continue
+					},
+				};
+
+			if !reshare.handle_message(msg.sender, message)? {
+				params.logger.debug_keygen(format!(
+					"Ignored duplicate or unexpected WT-FROST resharing message from {}",
+					msg.sender
+				));
+			}
+		}
+
+		// Parties leaving the keygen set are done once their shares are sent
+		let Some(local_key) = reshare.finish()? else {
+			params
+				.logger
+				.info_keygen("Handed off the WT-FROST key shares to the incoming set".to_string());
+			return Ok(())
+		};
+
+		params
+			.logger
+			.info_keygen("Completed WT-FROST resharing stage successfully!".to_string());
+
+		let session_id = params.session_id;
+		let pub_key_msg = PublicKeyMessage {
+			session_id,
+			pub_key: local_key.public_key().to_bytes(true).to_vec(),
+			signature: vec![],
+		};
+
+		params.engine.store_frost_public_key(local_key, session_id)?;
+		params.engine.gossip_public_key(pub_key_msg)?;
+
+		Ok(())
+	}
+
+	async fn run_frost_signing<BI: BlockchainInterface + 'static>(
+		params: AsyncProtocolParameters<BI, MaxAuthorities>,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			<BI as BlockchainInterface>::BatchId,
+			<BI as BlockchainInterface>::MaxProposalLength,
+			<BI as BlockchainInterface>::MaxProposalsInBatch,
+			<BI as BlockchainInterface>::Clock,
+		>,
+		signing_set: Vec<KeygenPartyId>,
+		local_key: FrostLocalKey,
+	) -> Result<(), DKGError> {
+		let i = params.party_i;
+		let batch_key = params.get_next_batch_key();
+		let unsigned_proposal_hash = unsigned_proposal_batch.hash().ok_or_else(|| {
+			DKGError::Vote { reason: "The unsigned proposal for this stage is invalid".to_string() }
+		})?;
+
+		let ty = ProtocolType::FrostSigning {
+			unsigned_proposal_batch: Arc::new(unsigned_proposal_batch.clone()),
+			i,
+			associated_block_id: params.associated_block_id,
+		};
+
+		let signers = signing_set.iter().map(|party_i| *party_i.as_ref()).collect();
+		let mut signing = FrostSigning::new(local_key, signers, unsigned_proposal_hash)?;
+		let rx = params.handle.rx_keygen_signing.lock().take().ok_or_else(|| {
+			DKGError::GenericError { reason: "rx_keygen_signing already taken".to_string() }
+		})?;
+		let mut incoming = IncomingAsyncProtocolWrapper::new(rx, ty.clone(), params.clone());
+
+		let send = |message: FrostSigningMessage| {
+			let offline_msg = serde_json::to_vec(&message)
+				.map_err(|err| DKGError::GenericError { reason: err.to_string() })?;
+			let payload = NetworkMsgPayload::Offline(DKGOfflineMessage {
+				key: Vec::from(&unsigned_proposal_hash as &[u8]),
+				signer_set_id: *i.as_ref() as u64,
+				offline_msg,
+				unsigned_proposal_hash,
+			});
+			Self::send_frost_message(&params, None, payload)
+		};
+
+		params.logger.info_signing(format!(
+			"Will now begin the WT-FROST signing with signers {signing_set:?} and party {i}"
+		));
+		params.logger.round_event(
+			&ty,
+			RoundsEventType::ProceededToRound { session: params.session_id, round: 1 },
+		);
+		send(signing.nonce_message())?;
+
+		let mut sent_share = false;
+		loop {
+			if signing.has_all_nonces() && !sent_share {
+				params.logger.round_event(
+					&ty,
+					RoundsEventType::ProceededToRound { session: params.session_id, round: 2 },
+				);
+				send(signing.signature_share_message()?)?;
+				sent_share = true;
+			}
+
+			if sent_share && signing.has_all_shares() {
+				break
+			}
+
+			let msg = incoming.next().await.ok_or_else(|| DKGError::GenericError {
+				reason: "Incoming stream ended before WT-FROST signing completed".to_string(),
+			})?;
+
+			let NetworkMsgPayload::Offline(offline_msg) = msg.body.payload else { continue };
+			// only process messages which are from the respective proposal
+			if offline_msg.unsigned_proposal_hash != unsigned_proposal_hash {
+				continue
+			}
+
+			let message =
+				match serde_json::from_slice::<FrostSigningMessage>(&offline_msg.offline_msg) {
+					Ok(message) => message,
+					Err(err) => {
+						params.logger.warn_signing(format!(
+							"Received invalid WT-FROST signing message from {}: {err:?}",
+							msg.sender
+						));
+						continue
+					},
+				};
+
+			if !signing.handle_message(msg.sender, message)? {
+				params.logger.debug_signing(format!(
+					"Ignored duplicate or unexpected WT-FROST signing message from {}",
+					msg.sender
+				));
+			}
+		}
+
+		let signature = signing.finish()?;
+		params.engine.process_frost_vote_result(
+			signature,
+			unsigned_proposal_batch,
+			params.session_id,
+			batch_key,
+		)?;
+		params.logger.round_event(
+			&ty,
+			RoundsEventType::ProceededToRound { session: params.session_id, round: 9999999999 },
+		);
+
+		Ok(())
+	}
+
+	fn send_frost_message<BI: BlockchainInterface + 'static>(
+		params: &AsyncProtocolParameters<BI, MaxAuthorities>,
+		recipient: Option<KeygenPartyId>,
+		payload: NetworkMsgPayload,
+	) -> Result<(), DKGError> {
+		let recipient_id: Option<Public> = match recipient {
+			Some(recipient) => Some(
+				params
+					.best_authorities
+					.iter()
+					.find_map(|(id, p)| (id == &recipient).then(|| p.clone()))
+					.ok_or_else(|| DKGError::GenericError {
+						reason: format!("Failed to find authority id for {recipient:?}"),
+					})?,
+			),
+			None => None,
+		};
+
+		params.engine.sign_and_send_msg(DKGMessage {
+			associated_block_id: params.associated_block_id,
+			sender_id: params.authority_public_key.as_ref().clone(),
+			recipient_id,
+			payload,
+			session_id: params.session_id,
+			ssid: params.handle.ssid,
+		})
+	}
+}
@@ -0,0 +1,1179 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weighted Threshold FROST (Schnorr signatures over secp256k1)
+//!
+//! Every party `p` (in the range `[1, n]`) owns `weights[p - 1]` consecutive key ids, each key id
+//! being an evaluation point of the shared polynomial. The key is generated with a Pedersen DKG
+//! over all key ids, and a signature requires a set of parties whose combined weight is strictly
+//! greater than the threshold `t`.
+//!
+//! The secret shares are sent masked with a key derived from an ephemeral Diffie-Hellman exchange
+//! (the ephemeral public keys are broadcast along with the commitments), since direct messages
+//! may be gossiped to other peers when there is no direct connection to the recipient.
+//!
+//! An existing key can be proactively refreshed by the same parties: every party deals a
+//! polynomial with a zero constant term, and the new shares are added to the old ones. The group
+//! public key is unchanged, while the shares held before the refresh become useless.
+//!
+//! A signature is the pair `(R, z)` which satisfies `z * G = R + c * Y`, where `Y` is the group
+//! public key and `c = keccak256(R || Y || m) mod q` (points are SEC1 compressed).
+
+pub mod handler;
+pub mod reshare;
+
+use curv::{arithmetic::Converter, BigInt};
+use dkg_primitives::{
+	dkg_key_cli::FrostKeyShares,
+	types::{DKGError, FE, GE},
+};
+use dkg_runtime_primitives::keccak_256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Length of an encoded [`FrostSignature`]: compressed `R` (33 bytes) followed by `z` (32 bytes)
+pub const FROST_SIGNATURE_LENGTH: usize = 65;
+
+/// The identifier of a single key share (an evaluation point of the shared polynomial)
+pub type KeyId = u32;
+
+/// Returns the key ids owned by `party_i` given the per-party weights.
+pub fn key_ids_for_party(weights: &[u16], party_i: u16) -> Vec<KeyId> {
+	if party_i == 0 || party_i as usize > weights.len() {
+		return vec![]
+	}
+
+	let start: KeyId =
+		weights.iter().take(party_i as usize - 1).map(|w| *w as KeyId).sum::<KeyId>() + 1;
+	(start..start + weights[party_i as usize - 1] as KeyId).collect()
+}
+
+/// Proof of knowledge of the constant term of a party's polynomial
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofOfKnowledge {
+	pub r: GE,
+	pub z: FE,
+}
+
+/// The messages exchanged during the WT-FROST keygen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FrostKeygenMessage {
+	/// Round 1: broadcast of the polynomial commitments and the ephemeral encryption key
+	Commitments { party_i: u16, commitments: Vec<GE>, proof: ProofOfKnowledge, encryption_key: GE },
+	/// Round 1 of a refresh: broadcast of the commitments of the sender's zero-constant
+	/// polynomial (without the constant term) and the ephemeral encryption key
+	RefreshCommitments { party_i: u16, commitments: Vec<GE>, encryption_key: GE },
+	/// Round 2: the masked shares of the sender's polynomial for every key id owned by the
+	/// recipient
+	Shares { party_i: u16, shares: Vec<(KeyId, FE)> },
+}
+
+/// The messages exchanged during the WT-FROST signing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FrostSigningMessage {
+	/// Round 1: broadcast of the nonce commitments
+	Nonces { party_i: u16, hiding: GE, binding: GE },
+	/// Round 2: broadcast of the signature share
+	SignatureShare { party_i: u16, z: FE },
+}
+
+/// The output of a WT-FROST keygen for a single party
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrostLocalKey {
+	/// The party index in the range `[1, n]`
+	pub party_i: u16,
+	/// Signing requires a combined weight of at least `threshold + 1`
+	pub threshold: u16,
+	/// The number of key ids held by each party
+	pub weights: Vec<u16>,
+	/// The secret shares for every key id owned by this party
+	pub secret_shares: Vec<(KeyId, FE)>,
+	/// The coefficient-wise sum of every party's polynomial commitments
+	pub group_commitments: Vec<GE>,
+}
+
+impl FrostLocalKey {
+	/// The aggregated (group) public key
+	pub fn public_key(&self) -> GE {
+		self.group_commitments[0].clone()
+	}
+
+	/// The public counterpart of the secret share for `key_id`
+	pub fn public_key_share(&self, key_id: KeyId) -> GE {
+		evaluate_commitments(&self.group_commitments, key_id)
+	}
+
+	/// The number of parties that took part in the keygen
+	pub fn n(&self) -> u16 {
+		self.weights.len() as u16
+	}
+
+	/// The sum of the weights of the given parties
+	pub fn weight_of(&self, parties: &[u16]) -> u32 {
+		parties
+			.iter()
+			.filter_map(|p| self.weights.get((*p as usize).checked_sub(1)?))
+			.map(|w| *w as u32)
+			.sum()
+	}
+}
+
+impl From<FrostLocalKey> for FrostKeyShares {
+	fn from(key: FrostLocalKey) -> Self {
+		let FrostLocalKey { party_i, threshold, weights, secret_shares, group_commitments } = key;
+		Self { party_i, threshold, weights, secret_shares, group_commitments }
+	}
+}
+
+impl From<FrostKeyShares> for FrostLocalKey {
+	fn from(key: FrostKeyShares) -> Self {
+		let FrostKeyShares { party_i, threshold, weights, secret_shares, group_commitments } = key;
+		Self { party_i, threshold, weights, secret_shares, group_commitments }
+	}
+}
+
+/// A Schnorr signature produced by WT-FROST
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrostSignature {
+	pub r: GE,
+	pub z: FE,
+}
+
+impl FrostSignature {
+	/// Encodes the signature as `R (compressed) || z`
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = self.r.to_bytes(true).to_vec();
+		bytes.extend_from_slice(&scalar_to_bytes(&self.z));
+		bytes
+	}
+
+	/// Decodes a signature encoded with [`Self::to_bytes`]. Like the runtime, rejects a `z` which
+	/// is not reduced modulo the group order, so each signature has a single encoding.
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		if bytes.len() != FROST_SIGNATURE_LENGTH {
+			return None
+		}
+
+		let r = GE::from_bytes(&bytes[..33]).ok()?;
+		let z = BigInt::from_bytes(&bytes[33..]);
+		if &z >= FE::group_order() {
+			return None
+		}
+		Some(Self { r, z: FE::from_bigint(&z) })
+	}
+
+	/// Verifies the signature over `message` against the group public key
+	pub fn verify(&self, public_key: &GE, message: &[u8; 32]) -> bool {
+		let c = challenge(&self.r, public_key, message);
+		GE::generator() * &self.z == &self.r + public_key * &c
+	}
+}
+
+/// How a WT-FROST keygen obtains the key of its keygen set
+#[derive(Debug, Clone)]
+pub enum FrostKeygenMode {
+	/// Generates a new key
+	Generate,
+	/// Refreshes the shares of a key held by the same keygen set
+	Refresh(FrostLocalKey),
+	/// Reshares a key from the outgoing keygen set to the incoming one. `old_key` is only held
+	/// by the parties of the outgoing set.
+	Reshare { sets: reshare::ReshareSets, old_key: Option<FrostLocalKey>, public_key: GE },
+}
+
+/// The state of a single party during the WT-FROST keygen
+pub struct FrostKeygen {
+	party_i: u16,
+	threshold: u16,
+	weights: Vec<u16>,
+	context: [u8; 32],
+	polynomial: Vec<FE>,
+	encryption_secret: FE,
+	commitments: BTreeMap<u16, Vec<GE>>,
+	encryption_keys: BTreeMap<u16, GE>,
+	shares: BTreeMap<u16, Vec<(KeyId, FE)>>,
+	/// The key being refreshed, if this is a refresh rather than a fresh keygen
+	refreshed_key: Option<FrostLocalKey>,
+}
+
+impl FrostKeygen {
+	/// Creates a new keygen party. `context` binds the proofs of knowledge to this keygen
+	/// execution, and should be unique per execution (e.g., the keygen protocol hash).
+	pub fn new(
+		party_i: u16,
+		threshold: u16,
+		weights: Vec<u16>,
+		context: [u8; 32],
+	) -> Result<Self, DKGError> {
+		if party_i == 0 || party_i as usize > weights.len() {
+			return Err(DKGError::InvalidKeygenPartyId)
+		}
+
+		let total_weight: u32 = weights.iter().map(|w| *w as u32).sum();
+		if weights.iter().any(|w| *w == 0) || total_weight <= threshold as u32 {
+			return Err(DKGError::StartKeygen {
+				reason: format!(
+					"Invalid weights {weights:?} for threshold {threshold}: every party needs a \
+					 non-zero weight and the total weight must exceed the threshold"
+				),
+			})
+		}
+
+		let polynomial: Vec<FE> = (0..=threshold).map(|_| FE::random()).collect();
+		Ok(Self::with_polynomial(party_i, threshold, weights, context, polynomial, None))
+	}
+
+	/// Creates a party refreshing the shares of `local_key`. Every party of the original keygen
+	/// must take part with the same weights, and the resulting key has the same public key.
+	pub fn refresh(local_key: FrostLocalKey, context: [u8; 32]) -> Result<Self, DKGError> {
+		let own_key_ids: Vec<KeyId> = local_key.secret_shares.iter().map(|(id, _)| *id).collect();
+		if own_key_ids != key_ids_for_party(&local_key.weights, local_key.party_i) ||
+			local_key.group_commitments.len() != local_key.threshold as usize + 1
+		{
+			return Err(DKGError::StartKeygen {
+				reason: "Cannot refresh a malformed FROST local key".to_string(),
+			})
+		}
+
+		let polynomial: Vec<FE> = std::iter::once(FE::zero())
+			.chain((0..local_key.threshold).map(|_| FE::random()))
+			.collect();
+		Ok(Self::with_polynomial(
+			local_key.party_i,
+			local_key.threshold,
+			local_key.weights.clone(),
+			context,
+			polynomial,
+			Some(local_key),
+		))
+	}
+
+	fn with_polynomial(
+		party_i: u16,
+		threshold: u16,
+		weights: Vec<u16>,
+		context: [u8; 32],
+		polynomial: Vec<FE>,
+		refreshed_key: Option<FrostLocalKey>,
+	) -> Self {
+		let commitments: Vec<GE> = polynomial.iter().map(|a| GE::generator() * a).collect();
+		let encryption_secret = FE::random();
+		let encryption_key = GE::generator() * &encryption_secret;
+		let own_shares = key_ids_for_party(&weights, party_i)
+			.into_iter()
+			.map(|id| (id, evaluate_polynomial(&polynomial, id)))
+			.collect();
+
+		Self {
+			party_i,
+			threshold,
+			weights,
+			context,
+			polynomial,
+			encryption_secret,
+			commitments: BTreeMap::from([(party_i, commitments)]),
+			encryption_keys: BTreeMap::from([(party_i, encryption_key)]),
+			shares: BTreeMap::from([(party_i, own_shares)]),
+			refreshed_key,
+		}
+	}
+
+	pub fn n(&self) -> u16 {
+		self.weights.len() as u16
+	}
+
+	/// Returns the round 1 message for this party
+	pub fn commitment_message(&self) -> FrostKeygenMessage {
+		if self.is_refresh() {
+			// the constant term is zero, which is checked by the recipients instead of proven
+			return FrostKeygenMessage::RefreshCommitments {
+				party_i: self.party_i,
+				commitments: self.commitments[&self.party_i][1..].to_vec(),
+				encryption_key: self.encryption_keys[&self.party_i].clone(),
+			}
+		}
+
+		let a0 = &self.polynomial[0];
+		let k = FE::random();
+		let r = GE::generator() * &k;
+		let c = pok_challenge(self.party_i, &self.context, &(GE::generator() * a0), &r);
+		FrostKeygenMessage::Commitments {
+			party_i: self.party_i,
+			commitments: self.commitments[&self.party_i].clone(),
+			proof: ProofOfKnowledge { r, z: k + a0 * c },
+			encryption_key: self.encryption_keys[&self.party_i].clone(),
+		}
+	}
+
+	/// Returns the round 2 messages for this party, along with the intended recipient.
+	///
+	/// Requires the commitments (and thus the encryption keys) of every party.
+	pub fn share_messages(&self) -> Result<Vec<(u16, FrostKeygenMessage)>, DKGError> {
+		if !self.has_all_commitments() {
+			return Err(DKGError::GenericError {
+				reason: "Cannot compute the FROST shares before receiving all commitments"
+					.to_string(),
+			})
+		}
+
+		Ok((1..=self.n())
+			.filter(|p| *p != self.party_i)
+			.map(|p| {
+				let shares = key_ids_for_party(&self.weights, p)
+					.into_iter()
+					.map(|id| {
+						let mask = self.share_mask(self.party_i, p, id);
+						(id, evaluate_polynomial(&self.polynomial, id) + mask)
+					})
+					.collect();
+				(p, FrostKeygenMessage::Shares { party_i: self.party_i, shares })
+			})
+			.collect())
+	}
+
+	/// Handles an incoming message. Returns `Ok(false)` if the message was ignored.
+	pub fn handle_message(
+		&mut self,
+		sender: u16,
+		message: FrostKeygenMessage,
+	) -> Result<bool, DKGError> {
+		match message {
+			FrostKeygenMessage::Commitments { party_i, commitments, proof, encryption_key } => {
+				if party_i != sender ||
+					!self.is_valid_party(sender) ||
+					self.commitments.contains_key(&sender) ||
+					self.is_refresh()
+				{
+					return Ok(false)
+				}
+
+				let invalid_commitments = || DKGError::KeygenMisbehaviour {
+					reason: "Invalid FROST polynomial commitments".to_string(),
+					bad_actors: vec![sender as usize],
+				};
+				// the length is checked first, the proof of knowledge reads the constant term
+				if commitments.len() != self.threshold as usize + 1 {
+					return Err(invalid_commitments())
+				}
+
+				let c = pok_challenge(sender, &self.context, &commitments[0], &proof.r);
+				if GE::generator() * &proof.z != &proof.r + &commitments[0] * &c {
+					return Err(invalid_commitments())
+				}
+				check_encryption_key(sender, &encryption_key)?;
+
+				self.commitments.insert(sender, commitments);
+				self.encryption_keys.insert(sender, encryption_key);
+			},
+
+			FrostKeygenMessage::RefreshCommitments { party_i, commitments, encryption_key } => {
+				if party_i != sender ||
+					!self.is_valid_party(sender) ||
+					self.commitments.contains_key(&sender) ||
+					!self.is_refresh()
+				{
+					return Ok(false)
+				}
+
+				if commitments.len() != self.threshold as usize {
+					return Err(DKGError::KeygenMisbehaviour {
+						reason: "Invalid FROST refresh commitments".to_string(),
+						bad_actors: vec![sender as usize],
+					})
+				}
+
+				check_encryption_key(sender, &encryption_key)?;
+
+				let commitments = std::iter::once(GE::zero()).chain(commitments).collect();
+				self.commitments.insert(sender, commitments);
+				self.encryption_keys.insert(sender, encryption_key);
+			},
+
+			FrostKeygenMessage::Shares { party_i, shares } => {
+				if party_i != sender ||
+					!self.is_valid_party(sender) ||
+					self.shares.contains_key(&sender)
+				{
+					return Ok(false)
+				}
+
+				self.shares.insert(sender, shares);
+			},
+		}
+
+		Ok(true)
+	}
+
+	/// Whether this party refreshes an existing key rather than generating a new one
+	pub fn is_refresh(&self) -> bool {
+		self.refreshed_key.is_some()
+	}
+
+	pub fn has_all_commitments(&self) -> bool {
+		self.commitments.len() == self.n() as usize
+	}
+
+	pub fn has_all_shares(&self) -> bool {
+		self.shares.len() == self.n() as usize
+	}
+
+	/// Verifies the received shares against the commitments, and computes the local key
+	pub fn finish(self) -> Result<FrostLocalKey, DKGError> {
+		if !self.has_all_commitments() || !self.has_all_shares() {
+			return Err(DKGError::GenericError {
+				reason: "FROST keygen is missing commitments or shares".to_string(),
+			})
+		}
+
+		let own_key_ids = key_ids_for_party(&self.weights, self.party_i);
+		let shares: BTreeMap<u16, Vec<(KeyId, FE)>> = self
+			.shares
+			.iter()
+			.map(|(sender, shares)| {
+				let shares = if *sender == self.party_i {
+					shares.clone()
+				} else {
+					shares
+						.iter()
+						.map(|(id, share)| {
+							(*id, share - &self.share_mask(*sender, self.party_i, *id))
+						})
+						.collect()
+				};
+				(*sender, shares)
+			})
+			.collect();
+
+		let mut bad_actors = vec![];
+		for (sender, shares) in shares.iter() {
+			let commitments = &self.commitments[sender];
+			let received_ids: Vec<KeyId> = shares.iter().map(|(id, _)| *id).collect();
+			let valid = received_ids == own_key_ids &&
+				shares.iter().all(|(id, share)| {
+					GE::generator() * share == evaluate_commitments(commitments, *id)
+				});
+			if !valid {
+				bad_actors.push(*sender as usize);
+			}
+		}
+
+		if !bad_actors.is_empty() {
+			return Err(DKGError::KeygenMisbehaviour {
+				reason: "Invalid FROST secret shares".to_string(),
+				bad_actors,
+			})
+		}
+
+		// when refreshing, the zero-constant polynomials are added on top of the existing key
+		let (base_shares, base_commitments) = match self.refreshed_key {
+			Some(key) => (key.secret_shares, key.group_commitments),
+			None => (
+				own_key_ids.iter().map(|id| (*id, FE::zero())).collect(),
+				vec![GE::zero(); self.threshold as usize + 1],
+			),
+		};
+
+		let secret_shares = base_shares
+			.into_iter()
+			.map(|(id, base_share)| {
+				let share = shares
+					.values()
+					.flat_map(|shares| shares.iter().filter(|(i, _)| *i == id))
+					.fold(base_share, |acc, (_, share)| acc + share);
+				(id, share)
+			})
+			.collect();
+
+		let group_commitments = base_commitments
+			.into_iter()
+			.enumerate()
+			.map(|(k, base_commitment)| {
+				self.commitments
+					.values()
+					.fold(base_commitment, |acc, commitments| acc + &commitments[k])
+			})
+			.collect();
+
+		Ok(FrostLocalKey {
+			party_i: self.party_i,
+			threshold: self.threshold,
+			weights: self.weights,
+			secret_shares,
+			group_commitments,
+		})
+	}
+
+	/// The one-time mask of the share for `key_id` sent from `sender` to `recipient`, derived
+	/// from the Diffie-Hellman secret between the two parties' ephemeral encryption keys.
+	fn share_mask(&self, sender: u16, recipient: u16, key_id: KeyId) -> FE {
+		let counterparty = if sender == self.party_i { recipient } else { sender };
+		let shared_secret = &self.encryption_keys[&counterparty] * &self.encryption_secret;
+		share_mask(&self.context, &shared_secret, sender, recipient, key_id)
+	}
+
+	fn is_valid_party(&self, party_i: u16) -> bool {
+		party_i != 0 && party_i <= self.n()
+	}
+}
+
+/// The state of a single party during the WT-FROST signing
+pub struct FrostSigning {
+	local_key: FrostLocalKey,
+	signers: Vec<u16>,
+	message: [u8; 32],
+	hiding_nonce: FE,
+	binding_nonce: FE,
+	nonces: BTreeMap<u16, (GE, GE)>,
+	shares: BTreeMap<u16, FE>,
+}
+
+impl FrostSigning {
+	/// Creates a new signing party for `message` amongst the `signers` (keygen party indexes)
+	pub fn new(
+		local_key: FrostLocalKey,
+		mut signers: Vec<u16>,
+		message: [u8; 32],
+	) -> Result<Self, DKGError> {
+		signers.sort_unstable();
+		signers.dedup();
+
+		if !signers.contains(&local_key.party_i) ||
+			signers.iter().any(|p| *p == 0 || *p > local_key.n()) ||
+			local_key.weight_of(&signers) <= local_key.threshold as u32
+		{
+			return Err(DKGError::InvalidSigningSet)
+		}
+
+		let hiding_nonce = FE::random();
+		let binding_nonce = FE::random();
+		let nonces = BTreeMap::from([(
+			local_key.party_i,
+			(GE::generator() * &hiding_nonce, GE::generator() * &binding_nonce),
+		)]);
+
+		Ok(Self {
+			local_key,
+			signers,
+			message,
+			hiding_nonce,
+			binding_nonce,
+			nonces,
+			shares: BTreeMap::new(),
+		})
+	}
+
+	/// Returns the round 1 message for this party
+	pub fn nonce_message(&self) -> FrostSigningMessage {
+		let (hiding, binding) = self.nonces[&self.local_key.party_i].clone();
+		FrostSigningMessage::Nonces { party_i: self.local_key.party_i, hiding, binding }
+	}
+
+	/// Handles an incoming message. Returns `Ok(false)` if the message was ignored.
+	pub fn handle_message(
+		&mut self,
+		sender: u16,
+		message: FrostSigningMessage,
+	) -> Result<bool, DKGError> {
+		match message {
+			FrostSigningMessage::Nonces { party_i, hiding, binding } => {
+				if party_i != sender ||
+					!self.signers.contains(&sender) ||
+					self.nonces.contains_key(&sender)
+				{
+					return Ok(false)
+				}
+
+				self.nonces.insert(sender, (hiding, binding));
+			},
+
+			FrostSigningMessage::SignatureShare { party_i, z } => {
+				if party_i != sender ||
+					!self.signers.contains(&sender) ||
+					self.shares.contains_key(&sender)
+				{
+					return Ok(false)
+				}
+
+				self.shares.insert(sender, z);
+			},
+		}
+
+		Ok(true)
+	}
+
+	pub fn has_all_nonces(&self) -> bool {
+		self.nonces.len() == self.signers.len()
+	}
+
+	pub fn has_all_shares(&self) -> bool {
+		self.shares.len() == self.signers.len()
+	}
+
+	/// Computes this party's signature share, which is the round 2 message.
+	///
+	/// Requires the nonce commitments of every signer.
+	pub fn signature_share_message(&mut self) -> Result<FrostSigningMessage, DKGError> {
+		if !self.has_all_nonces() {
+			return Err(DKGError::GenericError {
+				reason: "Cannot compute the FROST signature share before receiving all nonces"
+					.to_string(),
+			})
+		}
+
+		let party_i = self.local_key.party_i;
+		let (group_commitment, binding_factors) = self.group_commitment();
+		let c = challenge(&group_commitment, &self.local_key.public_key(), &self.message);
+		let key_ids = self.signing_key_ids();
+		let weighted_secret =
+			self.local_key.secret_shares.iter().fold(FE::zero(), |acc, (id, share)| {
+				acc + lagrange_coefficient(*id, &key_ids) * share
+			});
+		let z = &self.hiding_nonce +
+			&self.binding_nonce * &binding_factors[&party_i] +
+			c * weighted_secret;
+
+		self.shares.insert(party_i, z.clone());
+		Ok(FrostSigningMessage::SignatureShare { party_i, z })
+	}
+
+	/// Verifies every signature share and aggregates them into a signature
+	pub fn finish(self) -> Result<FrostSignature, DKGError> {
+		if !self.has_all_nonces() || !self.has_all_shares() {
+			return Err(DKGError::GenericError {
+				reason: "FROST signing is missing nonces or signature shares".to_string(),
+			})
+		}
+
+		let public_key = self.local_key.public_key();
+		let (group_commitment, binding_factors) = self.group_commitment();
+		let c = challenge(&group_commitment, &public_key, &self.message);
+		let key_ids = self.signing_key_ids();
+
+		let mut bad_actors = vec![];
+		for (party_i, z) in self.shares.iter() {
+			let (hiding, binding) = &self.nonces[party_i];
+			let public_share = key_ids_for_party(&self.local_key.weights, *party_i)
+				.into_iter()
+				.fold(GE::zero(), |acc, id| {
+					acc + self.local_key.public_key_share(id) * lagrange_coefficient(id, &key_ids)
+				});
+			let expected = hiding + binding * &binding_factors[party_i] + public_share * &c;
+			if GE::generator() * z != expected {
+				bad_actors.push(*party_i as usize);
+			}
+		}
+
+		if !bad_actors.is_empty() {
+			return Err(DKGError::SignMisbehaviour {
+				reason: "Invalid FROST signature share".to_string(),
+				bad_actors,
+			})
+		}
+
+		let z = self.shares.values().fold(FE::zero(), |acc, z| acc + z);
+		let signature = FrostSignature { r: group_commitment, z };
+		if !signature.verify(&public_key, &self.message) {
+			return Err(DKGError::Vote {
+				reason: "Aggregated FROST signature is invalid".to_string(),
+			})
+		}
+
+		Ok(signature)
+	}
+
+	/// Returns the group commitment `R` and the binding factor of each signer
+	fn group_commitment(&self) -> (GE, BTreeMap<u16, FE>) {
+		let mut encoded_commitments = vec![];
+		for (party_i, (hiding, binding)) in self.nonces.iter() {
+			encoded_commitments.extend_from_slice(&party_i.to_be_bytes());
+			encoded_commitments.extend_from_slice(&hiding.to_bytes(true));
+			encoded_commitments.extend_from_slice(&binding.to_bytes(true));
+		}
+
+		let binding_factors: BTreeMap<u16, FE> = self
+			.nonces
+			.keys()
+			.map(|party_i| {
+				let rho = hash_to_scalar(&[
+					b"FROST-rho",
+					&party_i.to_be_bytes(),
+					&self.message,
+					&encoded_commitments,
+				]);
+				(*party_i, rho)
+			})
+			.collect();
+
+		let group_commitment =
+			self.nonces.iter().fold(GE::zero(), |acc, (party_i, (hiding, binding))| {
+				acc + hiding + binding * &binding_factors[party_i]
+			});
+
+		(group_commitment, binding_factors)
+	}
+
+	fn signing_key_ids(&self) -> Vec<KeyId> {
+		self.signers
+			.iter()
+			.flat_map(|p| key_ids_for_party(&self.local_key.weights, *p))
+			.collect()
+	}
+}
+
+/// Computes the Schnorr challenge `c = keccak256(R || Y || m) mod q`
+pub fn challenge(r: &GE, public_key: &GE, message: &[u8; 32]) -> FE {
+	hash_to_scalar(&[&r.to_bytes(true), &public_key.to_bytes(true), message])
+}
+
+fn pok_challenge(party_i: u16, context: &[u8; 32], a0: &GE, r: &GE) -> FE {
+	hash_to_scalar(&[
+		b"FROST-pok",
+		&party_i.to_be_bytes(),
+		context,
+		&a0.to_bytes(true),
+		&r.to_bytes(true),
+	])
+}
+
+/// The one-time mask of the share for `key_id` sent from `sender` to `recipient`, given the
+/// Diffie-Hellman secret between their ephemeral encryption keys
+fn share_mask(
+	context: &[u8; 32],
+	shared_secret: &GE,
+	sender: u16,
+	recipient: u16,
+	key_id: KeyId,
+) -> FE {
+	hash_to_scalar(&[
+		b"FROST-share",
+		context,
+		&shared_secret.to_bytes(true),
+		&sender.to_be_bytes(),
+		&recipient.to_be_bytes(),
+		&key_id.to_be_bytes(),
+	])
+}
+
+/// Rejects the identity as the ephemeral Diffie-Hellman key of a party, as it would make the
+/// masks of the shares exchanged with that party public.
+fn check_encryption_key(sender: u16, encryption_key: &GE) -> Result<(), DKGError> {
+	if encryption_key.is_zero() {
+		return Err(DKGError::KeygenMisbehaviour {
+			reason: "Invalid FROST encryption key".to_string(),
+			bad_actors: vec![sender as usize],
+		})
+	}
+	Ok(())
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> FE {
+	let data: Vec<u8> = parts.iter().flat_map(|part| part.iter().copied()).collect();
+	FE::from_bigint(&BigInt::from_bytes(&keccak_256(&data)))
+}
+
+fn scalar_to_bytes(scalar: &FE) -> [u8; 32] {
+	let bytes = scalar.to_bigint().to_bytes();
+	let mut out = [0u8; 32];
+	out[32 - bytes.len()..].copy_from_slice(&bytes);
+	out
+}
+
+fn evaluate_polynomial(coefficients: &[FE], x: KeyId) -> FE {
+	let x = FE::from(x as u64);
+	coefficients
+		.iter()
+		.rev()
+		.fold(FE::zero(), |acc, coefficient| acc * &x + coefficient)
+}
+
+fn evaluate_commitments(commitments: &[GE], x: KeyId) -> GE {
+	let x = FE::from(x as u64);
+	commitments
+		.iter()
+		.rev()
+		.fold(GE::zero(), |acc, commitment| acc * &x + commitment)
+}
+
+fn lagrange_coefficient(key_id: KeyId, key_ids: &[KeyId]) -> FE {
+	let x_i = FE::from(key_id as u64);
+	let (numerator, denominator) = key_ids.iter().filter(|id| **id != key_id).fold(
+		(FE::from(1u64), FE::from(1u64)),
+		|(num, den), id| {
+			let x_j = FE::from(*id as u64);
+			(num * &x_j, den * (&x_j - &x_i))
+		},
+	);
+	numerator * denominator.invert().expect("key ids are distinct; qed")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // allow unwraps in tests
+mod tests {
+	use super::*;
+
+	fn run_keygen(threshold: u16, weights: Vec<u16>) -> Vec<FrostLocalKey> {
+		let n = weights.len() as u16;
+		let parties: Vec<FrostKeygen> = (1..=n)
+			.map(|i| FrostKeygen::new(i, threshold, weights.clone(), [7u8; 32]).unwrap())
+			.collect();
+		run_parties(parties)
+	}
+
+	fn run_refresh(keys: &[FrostLocalKey]) -> Vec<FrostLocalKey> {
+		let parties: Vec<FrostKeygen> = keys
+			.iter()
+			.map(|k| FrostKeygen::refresh(k.clone(), [8u8; 32]).unwrap())
+			.collect();
+		run_parties(parties)
+	}
+
+	fn run_parties(mut parties: Vec<FrostKeygen>) -> Vec<FrostLocalKey> {
+		let commitments: Vec<_> = parties.iter().map(|p| p.commitment_message()).collect();
+		for (sender, message) in commitments.into_iter().enumerate() {
+			for (idx, party) in parties.iter_mut().enumerate() {
+				if idx != sender {
+					assert!(party.handle_message(sender as u16 + 1, message.clone()).unwrap());
+				}
+			}
+		}
+
+		let shares: Vec<_> = parties.iter().map(|p| p.share_messages().unwrap()).collect();
+		for (sender, messages) in shares.into_iter().enumerate() {
+			for (recipient, message) in messages {
+				let party = &mut parties[recipient as usize - 1];
+				assert!(party.handle_message(sender as u16 + 1, message).unwrap());
+			}
+		}
+
+		parties.into_iter().map(|p| p.finish().unwrap()).collect()
+	}
+
+	fn run_signing(keys: &[FrostLocalKey], signers: Vec<u16>, message: [u8; 32]) -> FrostSignature {
+		let mut parties: Vec<FrostSigning> = signers
+			.iter()
+			.map(|i| FrostSigning::new(keys[*i as usize - 1].clone(), signers.clone(), message))
+			.collect::<Result<_, _>>()
+			.unwrap();
+
+		let nonces: Vec<_> = parties.iter().map(|p| p.nonce_message()).collect();
+		for (sender, message) in signers.iter().zip(nonces) {
+			for party in parties.iter_mut().filter(|p| p.local_key.party_i != *sender) {
+				assert!(party.handle_message(*sender, message.clone()).unwrap());
+			}
+		}
+
+		let shares: Vec<_> =
+			parties.iter_mut().map(|p| p.signature_share_message().unwrap()).collect();
+		for (sender, message) in signers.iter().zip(shares) {
+			for party in parties.iter_mut().filter(|p| p.local_key.party_i != *sender) {
+				assert!(party.handle_message(*sender, message.clone()).unwrap());
+			}
+		}
+
+		let signatures: Vec<_> = parties.into_iter().map(|p| p.finish().unwrap()).collect();
+		assert!(signatures.windows(2).all(|w| w[0] == w[1]));
+		signatures[0].clone()
+	}
+
+	#[test]
+	fn should_assign_consecutive_key_ids() {
+		let weights = vec![1, 3, 2];
+		assert_eq!(key_ids_for_party(&weights, 1), vec![1]);
+		assert_eq!(key_ids_for_party(&weights, 2), vec![2, 3, 4]);
+		assert_eq!(key_ids_for_party(&weights, 3), vec![5, 6]);
+		assert!(key_ids_for_party(&weights, 0).is_empty());
+		assert!(key_ids_for_party(&weights, 4).is_empty());
+	}
+
+	#[test]
+	fn should_generate_the_same_public_key_for_all_parties() {
+		let keys = run_keygen(2, vec![1, 2, 1]);
+		let public_key = keys[0].public_key();
+		assert!(keys.iter().all(|k| k.public_key() == public_key));
+		for key in keys.iter() {
+			for (id, share) in key.secret_shares.iter() {
+				assert_eq!(GE::generator() * share, key.public_key_share(*id));
+			}
+		}
+	}
+
+	#[test]
+	fn should_sign_with_enough_combined_weight() {
+		let keys = run_keygen(2, vec![1, 2, 1]);
+		let message = keccak_256(b"webb");
+		// party 2 alone holds 2 key ids, so it needs one more party to exceed the threshold
+		let signature = run_signing(&keys, vec![2, 3], message);
+		assert!(signature.verify(&keys[0].public_key(), &message));
+		let signature = run_signing(&keys, vec![1, 2, 3], message);
+		assert!(signature.verify(&keys[0].public_key(), &message));
+		assert!(!signature.verify(&keys[0].public_key(), &keccak_256(b"other")));
+
+		let decoded = FrostSignature::from_bytes(&signature.to_bytes()).unwrap();
+		assert_eq!(decoded, signature);
+	}
+
+	#[test]
+	fn should_produce_signatures_verifiable_by_the_runtime() {
+		let keys = run_keygen(2, vec![1, 2, 1]);
+		let data = b"webb".to_vec();
+		let signature = run_signing(&keys, vec![1, 2], keccak_256(&data)).to_bytes();
+		let public_key = keys[0].public_key().to_bytes(true).to_vec();
+		assert!(dkg_runtime_primitives::utils::verify_schnorr_signature(
+			&data,
+			&signature,
+			&public_key
+		));
+		assert!(!dkg_runtime_primitives::utils::verify_schnorr_signature(
+			b"other",
+			&signature,
+			&public_key
+		));
+	}
+
+	#[test]
+	fn should_reject_signing_set_below_threshold() {
+		let keys = run_keygen(2, vec![1, 2, 1]);
+		let message = keccak_256(b"webb");
+		assert!(FrostSigning::new(keys[0].clone(), vec![1, 3], message).is_err());
+		assert!(FrostSigning::new(keys[0].clone(), vec![2, 3], message).is_err());
+	}
+
+	#[test]
+	fn should_refresh_shares_without_changing_the_public_key() {
+		let keys = run_keygen(2, vec![1, 2, 1]);
+		let refreshed = run_refresh(&keys);
+		let public_key = keys[0].public_key();
+		assert!(refreshed.iter().all(|k| k.public_key() == public_key));
+		for (old, new) in keys.iter().zip(refreshed.iter()) {
+			assert_ne!(old.secret_shares, new.secret_shares);
+			for (id, share) in new.secret_shares.iter() {
+				assert_eq!(GE::generator() * share, new.public_key_share(*id));
+			}
+		}
+
+		let message = keccak_256(b"webb");
+		let signature = run_signing(&refreshed, vec![2, 3], message);
+		assert!(signature.verify(&public_key, &message));
+	}
+
+	fn run_reshare(
+		keys: &[FrostLocalKey],
+		sets: reshare::ReshareSets,
+	) -> BTreeMap<u16, Option<FrostLocalKey>> {
+		use reshare::{FrostReshare, FrostReshareMessage};
+
+		let public_key = keys[0].public_key();
+		let mut parties: BTreeMap<u16, FrostReshare> = sets
+			.participants()
+			.into_iter()
+			.map(|p| {
+				let old_key = sets.old_party(p).map(|i| keys[i as usize - 1].clone());
+				(p, FrostReshare::new(p, sets.clone(), old_key, public_key.clone(), [9u8; 32]))
+			})
+			.map(|(p, party)| (p, party.unwrap()))
+			.collect();
+
+		let commitments: Vec<(u16, FrostReshareMessage)> =
+			parties.iter().map(|(p, party)| (*p, party.commitment_message())).collect();
+		for (sender, message) in commitments {
+			for (_, party) in parties.iter_mut().filter(|(p, _)| **p != sender) {
+				assert!(party.handle_message(sender, message.clone()).unwrap());
+			}
+		}
+
+		let shares: Vec<(u16, Vec<(u16, FrostReshareMessage)>)> =
+			parties.iter().map(|(p, party)| (*p, party.share_messages().unwrap())).collect();
+		for (sender, messages) in shares {
+			for (recipient, message) in messages {
+				let party = parties.get_mut(&recipient).unwrap();
+				assert!(party.handle_message(sender, message).unwrap());
+			}
+		}
+
+		parties.into_iter().map(|(p, party)| (p, party.finish().unwrap())).collect()
+	}
+
+	#[test]
+	fn should_reshare_the_key_to_a_new_set() {
+		let keys = run_keygen(1, vec![1, 1, 1]);
+		let public_key = keys[0].public_key();
+		// the first outgoing party leaves, and two parties join the incoming set
+		let sets = reshare::ReshareSets {
+			old_parties: vec![5, 1, 2],
+			old_weights: vec![1, 1, 1],
+			old_threshold: 1,
+			dealers: vec![1, 2, 5],
+			new_parties: vec![1, 2, 3, 4],
+			new_weights: vec![1, 2, 1, 1],
+			new_threshold: 3,
+		};
+		let mut reshared = run_reshare(&keys, sets);
+		assert!(reshared.remove(&5).unwrap().is_none());
+		let new_keys: Vec<FrostLocalKey> = reshared.into_values().map(|key| key.unwrap()).collect();
+		assert_eq!(new_keys.len(), 4);
+		for (idx, key) in new_keys.iter().enumerate() {
+			assert_eq!(key.party_i, idx as u16 + 1);
+			assert_eq!(key.public_key(), public_key);
+			for (id, share) in key.secret_shares.iter() {
+				assert_eq!(GE::generator() * share, key.public_key_share(*id));
+			}
+		}
+
+		let message = keccak_256(b"webb");
+		let signature = run_signing(&new_keys, vec![2, 3, 4], message);
+		assert!(signature.verify(&public_key, &message));
+		assert!(FrostSigning::new(new_keys[0].clone(), vec![1, 2], message).is_err());
+	}
+
+	#[test]
+	fn should_reshare_the_key_from_a_threshold_of_the_outgoing_set() {
+		let keys = run_keygen(1, vec![1, 1, 1]);
+		let public_key = keys[0].public_key();
+		// the leaving party does not deal, the parties staying in the keygen set hold enough shares
+		let sets = reshare::ReshareSets {
+			old_parties: vec![5, 1, 2],
+			old_weights: vec![1, 1, 1],
+			old_threshold: 1,
+			dealers: vec![1, 2],
+			new_parties: vec![1, 2, 3],
+			new_weights: vec![1, 1, 1],
+			new_threshold: 1,
+		};
+		assert_eq!(sets.participants(), vec![1, 2, 3]);
+		let reshared = run_reshare(&keys, sets);
+		let new_keys: Vec<FrostLocalKey> = reshared.into_values().map(|key| key.unwrap()).collect();
+		assert_eq!(new_keys.len(), 3);
+		for key in new_keys.iter() {
+			assert_eq!(key.public_key(), public_key);
+		}
+
+		let message = keccak_256(b"webb");
+		let signature = run_signing(&new_keys, vec![1, 3], message);
+		assert!(signature.verify(&public_key, &message));
+	}
+
+	#[test]
+	fn should_ignore_keygen_commitments_during_a_refresh() {
+		let keys = run_keygen(1, vec![1, 1, 1]);
+		let keygen = FrostKeygen::new(2, 1, vec![1, 1, 1], [8u8; 32]).unwrap();
+		let mut refresh = FrostKeygen::refresh(keys[0].clone(), [8u8; 32]).unwrap();
+		assert!(!refresh.handle_message(2, keygen.commitment_message()).unwrap());
+		let commitments = FrostKeygenMessage::RefreshCommitments {
+			party_i: 2,
+			commitments: vec![],
+			encryption_key: GE::generator() * FE::random(),
+		};
+		assert!(refresh.handle_message(2, commitments).is_err());
+	}
+
+	#[test]
+	fn should_blame_party_sending_empty_commitments() {
+		let mut keygen = FrostKeygen::new(1, 1, vec![1, 1, 1], [0u8; 32]).unwrap();
+		let proof =
+			match FrostKeygen::new(2, 1, vec![1, 1, 1], [0u8; 32]).unwrap().commitment_message() {
+				FrostKeygenMessage::Commitments { proof, .. } => proof,
+				other => panic!("Expected commitments, got {other:?}"),
+			};
+		let commitments = FrostKeygenMessage::Commitments {
+			party_i: 2,
+			commitments: vec![],
+			proof,
+			encryption_key: GE::generator() * FE::random(),
+		};
+		match keygen.handle_message(2, commitments) {
+			Err(DKGError::KeygenMisbehaviour { bad_actors, .. }) => assert_eq!(bad_actors, vec![2]),
+			other => panic!("Expected misbehaviour, got {other:?}"),
+		}
+	}
+
+	#[test]
+	fn should_blame_party_sending_an_identity_encryption_key() {
+		let mut keygen = FrostKeygen::new(1, 1, vec![1, 1, 1], [0u8; 32]).unwrap();
+		let commitments =
+			match FrostKeygen::new(2, 1, vec![1, 1, 1], [0u8; 32]).unwrap().commitment_message() {
+				FrostKeygenMessage::Commitments { party_i, commitments, proof, .. } =>
+					FrostKeygenMessage::Commitments {
+						party_i,
+						commitments,
+						proof,
+						encryption_key: GE::zero(),
+					},
+				other => panic!("Expected commitments, got {other:?}"),
+			};
+		match keygen.handle_message(2, commitments) {
+			Err(DKGError::KeygenMisbehaviour { bad_actors, .. }) => assert_eq!(bad_actors, vec![2]),
+			other => panic!("Expected misbehaviour, got {other:?}"),
+		}
+	}
+
+	#[test]
+	fn should_reject_non_canonical_signatures() {
+		let signature = FrostSignature { r: GE::generator().to_point(), z: FE::from(1u64) };
+		let bytes = signature.to_bytes();
+		assert_eq!(FrostSignature::from_bytes(&bytes), Some(signature));
+
+		// `1 + q` is congruent to `z` and still fits in 32 bytes
+		let z = (BigInt::from(1u64) + FE::group_order()).to_bytes();
+		assert_eq!(z.len(), 32);
+		let mut non_canonical = bytes[..33].to_vec();
+		non_canonical.extend_from_slice(&z);
+		assert_eq!(FrostSignature::from_bytes(&non_canonical), None);
+
+		let mut overflowing = bytes[..33].to_vec();
+		overflowing.extend_from_slice(&[0xff; 32]);
+		assert_eq!(FrostSignature::from_bytes(&overflowing), None);
+	}
+
+	#[test]
+	fn should_blame_party_sending_invalid_shares() {
+		let weights = vec![1, 1, 1];
+		let mut parties: Vec<FrostKeygen> = (1..=3)
+			.map(|i| FrostKeygen::new(i, 1, weights.clone(), [0u8; 32]).unwrap())
+			.collect();
+		let commitments: Vec<_> = parties.iter().map(|p| p.commitment_message()).collect();
+		for (sender, message) in commitments.into_iter().enumerate() {
+			for (idx, party) in parties.iter_mut().enumerate() {
+				if idx != sender {
+					party.handle_message(sender as u16 + 1, message.clone()).unwrap();
+				}
+			}
+		}
+
+		let shares: Vec<_> = parties.iter().map(|p| p.share_messages().unwrap()).collect();
+		for (sender, messages) in shares.into_iter().enumerate() {
+			for (recipient, message) in messages {
+				let message = match message {
+					// party 2 sends a corrupted share
+					FrostKeygenMessage::Shares { party_i: 2, shares } =>
+						FrostKeygenMessage::Shares {
+							party_i: 2,
+							shares: shares
+								.into_iter()
+								.map(|(id, s)| (id, s + FE::from(1u64)))
+								.collect(),
+						},
+					message => message,
+				};
+				parties[recipient as usize - 1]
+					.handle_message(sender as u16 + 1, message)
+					.unwrap();
+			}
+		}
+
+		match parties.remove(0).finish() {
+			Err(DKGError::KeygenMisbehaviour { bad_actors, .. }) => assert_eq!(bad_actors, vec![2]),
+			other => panic!("Expected misbehaviour, got {other:?}"),
+		}
+	}
+}
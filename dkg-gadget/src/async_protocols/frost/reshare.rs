@@ -0,0 +1,464 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resharing of a WT-FROST key from an outgoing keygen set to an incoming one
+//!
+//! A subset of the outgoing parties holding more than the outgoing threshold's weight, the
+//! dealers, reshares the key. Every dealer deals its Lagrange-weighted part `d_p` of the group
+//! secret, interpolated over the key ids of the dealers, with a fresh polynomial of the incoming
+//! threshold's degree, and sends the shares to the incoming parties. The dealt polynomials sum to
+//! a sharing of the same secret amongst the incoming set, so the group public key is unchanged.
+//! `d_p * G` can be computed from the outgoing group commitments, which the dealers broadcast so
+//! that incoming parties that did not take part in the previous keygen can verify every dealing.
+//!
+//! The participants of a resharing are the incoming parties and the dealers, each identified by a
+//! participant index used for messaging, which is distinct from its party index within either
+//! set. The outgoing parties which do not deal take no part in the resharing.
+
+use super::{
+	check_encryption_key, evaluate_commitments, evaluate_polynomial, key_ids_for_party,
+	lagrange_coefficient, share_mask, FrostLocalKey, KeyId,
+};
+use dkg_primitives::types::{DKGError, FE, GE};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The commitments of an outgoing party's dealing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReshareDealing {
+	/// Commitments to the dealt polynomial, whose constant term is the dealer's part of the secret
+	pub commitments: Vec<GE>,
+	/// The group commitments of the key being reshared
+	pub old_group_commitments: Vec<GE>,
+}
+
+/// The messages exchanged during a WT-FROST resharing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FrostReshareMessage {
+	/// Round 1: broadcast of the ephemeral encryption key, along with the dealing commitments
+	/// if the sender belongs to the outgoing set
+	Commitments { party_i: u16, dealing: Option<ReshareDealing>, encryption_key: GE },
+	/// Round 2: the masked shares of the sender's dealing for every key id owned by the
+	/// recipient in the incoming set
+	Shares { party_i: u16, shares: Vec<(KeyId, FE)> },
+}
+
+/// The outgoing and incoming keygen sets of a resharing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReshareSets {
+	/// The participant index of every outgoing party, ordered by outgoing party index
+	pub old_parties: Vec<u16>,
+	/// The number of key ids held by each outgoing party
+	pub old_weights: Vec<u16>,
+	/// The signing threshold of the outgoing set
+	pub old_threshold: u16,
+	/// The participant index of every outgoing party dealing the key, in ascending order
+	pub dealers: Vec<u16>,
+	/// The participant index of every incoming party, ordered by incoming party index
+	pub new_parties: Vec<u16>,
+	/// The number of key ids held by each incoming party
+	pub new_weights: Vec<u16>,
+	/// The signing threshold of the incoming set
+	pub new_threshold: u16,
+}
+
+impl ReshareSets {
+	/// The party index within the outgoing set of a participant, if any
+	pub fn old_party(&self, participant: u16) -> Option<u16> {
+		self.old_parties.iter().position(|p| *p == participant).map(|i| i as u16 + 1)
+	}
+
+	/// Whether a participant deals the key to the incoming set
+	pub fn is_dealer(&self, participant: u16) -> bool {
+		self.dealers.contains(&participant)
+	}
+
+	/// The party index within the incoming set of a participant, if any
+	pub fn new_party(&self, participant: u16) -> Option<u16> {
+		self.new_parties.iter().position(|p| *p == participant).map(|i| i as u16 + 1)
+	}
+
+	/// Every participant index, in ascending order
+	pub fn participants(&self) -> Vec<u16> {
+		let mut participants: Vec<u16> =
+			self.dealers.iter().chain(self.new_parties.iter()).copied().collect();
+		participants.sort_unstable();
+		participants.dedup();
+		participants
+	}
+
+	/// The key ids of the outgoing set held by the dealers, in ascending order
+	fn dealer_key_ids(&self) -> Vec<KeyId> {
+		let mut key_ids: Vec<KeyId> = self
+			.dealers
+			.iter()
+			.filter_map(|dealer| self.old_party(*dealer))
+			.flat_map(|old_party| key_ids_for_party(&self.old_weights, old_party))
+			.collect();
+		key_ids.sort_unstable();
+		key_ids
+	}
+}
+
+/// The state of a single participant during a WT-FROST resharing
+pub struct FrostReshare {
+	party_i: u16,
+	sets: ReshareSets,
+	context: [u8; 32],
+	public_key: GE,
+	old_key: Option<FrostLocalKey>,
+	polynomial: Option<Vec<FE>>,
+	encryption_secret: FE,
+	dealings: BTreeMap<u16, ReshareDealing>,
+	encryption_keys: BTreeMap<u16, GE>,
+	shares: BTreeMap<u16, Vec<(KeyId, FE)>>,
+}
+
+impl FrostReshare {
+	/// Creates a new resharing participant. `old_key` is required if `party_i` is a dealer, and
+	/// `public_key` is the group public key being reshared.
+	pub fn new(
+		party_i: u16,
+		sets: ReshareSets,
+		old_key: Option<FrostLocalKey>,
+		public_key: GE,
+		context: [u8; 32],
+	) -> Result<Self, DKGError> {
+		if !sets.participants().contains(&party_i) {
+			return Err(DKGError::InvalidKeygenPartyId)
+		}
+
+		let new_total_weight: u32 = sets.new_weights.iter().map(|w| *w as u32).sum();
+		if sets.old_parties.len() != sets.old_weights.len() ||
+			sets.new_parties.len() != sets.new_weights.len() ||
+			sets.old_weights.iter().chain(sets.new_weights.iter()).any(|w| *w == 0) ||
+			new_total_weight <= sets.new_threshold as u32 ||
+			sets.dealers.iter().any(|dealer| sets.old_party(*dealer).is_none()) ||
+			sets.dealer_key_ids().len() <= sets.old_threshold as usize
+		{
+			return Err(DKGError::StartKeygen {
+				reason: format!("Invalid WT-FROST resharing sets {sets:?}"),
+			})
+		}
+
+		let polynomial = match (sets.old_party(party_i), old_key.as_ref()) {
+			(Some(old_party), Some(key)) if sets.is_dealer(party_i) => {
+				if key.party_i != old_party ||
+					key.weights != sets.old_weights ||
+					key.threshold != sets.old_threshold ||
+					key.public_key() != public_key
+				{
+					return Err(DKGError::StartKeygen {
+						reason: "The local key does not belong to the outgoing set".to_string(),
+					})
+				}
+
+				let dealer_key_ids = sets.dealer_key_ids();
+				let secret = key.secret_shares.iter().fold(FE::zero(), |acc, (id, share)| {
+					acc + lagrange_coefficient(*id, &dealer_key_ids) * share
+				});
+				Some(
+					std::iter::once(secret)
+						.chain((0..sets.new_threshold).map(|_| FE::random()))
+						.collect::<Vec<_>>(),
+				)
+			},
+			(Some(_), None) if sets.is_dealer(party_i) =>
+				return Err(DKGError::StartKeygen {
+					reason: "Cannot reshare without the local key of the outgoing set".to_string(),
+				}),
+			_ => None,
+		};
+
+		let encryption_secret = FE::random();
+		let encryption_key = GE::generator() * &encryption_secret;
+		let mut dealings = BTreeMap::new();
+		let mut shares = BTreeMap::new();
+		if let (Some(polynomial), Some(key)) = (polynomial.as_ref(), old_key.as_ref()) {
+			let commitments = polynomial.iter().map(|a| GE::generator() * a).collect();
+			dealings.insert(
+				party_i,
+				ReshareDealing {
+					commitments,
+					old_group_commitments: key.group_commitments.clone(),
+				},
+			);
+			if let Some(new_party) = sets.new_party(party_i) {
+				let own_shares = key_ids_for_party(&sets.new_weights, new_party)
+					.into_iter()
+					.map(|id| (id, evaluate_polynomial(polynomial, id)))
+					.collect();
+				shares.insert(party_i, own_shares);
+			}
+		}
+
+		Ok(Self {
+			party_i,
+			sets,
+			context,
+			public_key,
+			old_key,
+			polynomial,
+			encryption_secret,
+			dealings,
+			encryption_keys: BTreeMap::from([(party_i, encryption_key)]),
+			shares,
+		})
+	}
+
+	/// Returns the round 1 message for this participant
+	pub fn commitment_message(&self) -> FrostReshareMessage {
+		FrostReshareMessage::Commitments {
+			party_i: self.party_i,
+			dealing: self.dealings.get(&self.party_i).cloned(),
+			encryption_key: self.encryption_keys[&self.party_i].clone(),
+		}
+	}
+
+	/// Returns the round 2 messages for this participant, along with the intended recipient.
+	/// Participants which do not deal have no shares to send.
+	///
+	/// Requires the round 1 messages of every participant.
+	pub fn share_messages(&self) -> Result<Vec<(u16, FrostReshareMessage)>, DKGError> {
+		if !self.has_all_commitments() {
+			return Err(DKGError::GenericError {
+				reason: "Cannot compute the FROST shares before receiving all commitments"
+					.to_string(),
+			})
+		}
+
+		let Some(polynomial) = self.polynomial.as_ref() else { return Ok(vec![]) };
+		Ok(self
+			.sets
+			.new_parties
+			.iter()
+			.enumerate()
+			.filter(|(_, p)| **p != self.party_i)
+			.map(|(idx, p)| {
+				let shares = key_ids_for_party(&self.sets.new_weights, idx as u16 + 1)
+					.into_iter()
+					.map(|id| {
+						let mask = self.share_mask(self.party_i, *p, id);
+						(id, evaluate_polynomial(polynomial, id) + mask)
+					})
+					.collect();
+				(*p, FrostReshareMessage::Shares { party_i: self.party_i, shares })
+			})
+			.collect())
+	}
+
+	/// Handles an incoming message. Returns `Ok(false)` if the message was ignored.
+	pub fn handle_message(
+		&mut self,
+		sender: u16,
+		message: FrostReshareMessage,
+	) -> Result<bool, DKGError> {
+		match message {
+			FrostReshareMessage::Commitments { party_i, dealing, encryption_key } => {
+				if party_i != sender ||
+					!self.sets.participants().contains(&sender) ||
+					self.encryption_keys.contains_key(&sender)
+				{
+					return Ok(false)
+				}
+				check_encryption_key(sender, &encryption_key)?;
+
+				match (self.sets.old_party(sender), dealing) {
+					(Some(old_party), Some(dealing)) if self.sets.is_dealer(sender) => {
+						self.verify_dealing(sender, old_party, &dealing)?;
+						self.dealings.insert(sender, dealing);
+					},
+					(_, None) if !self.sets.is_dealer(sender) => {},
+					_ =>
+						return Err(DKGError::KeygenMisbehaviour {
+							reason: "Unexpected FROST resharing dealing".to_string(),
+							bad_actors: vec![sender as usize],
+						}),
+				}
+
+				self.encryption_keys.insert(sender, encryption_key);
+			},
+
+			FrostReshareMessage::Shares { party_i, shares } => {
+				if party_i != sender ||
+					!self.sets.is_dealer(sender) ||
+					self.sets.new_party(self.party_i).is_none() ||
+					self.shares.contains_key(&sender)
+				{
+					return Ok(false)
+				}
+
+				self.shares.insert(sender, shares);
+			},
+		}
+
+		Ok(true)
+	}
+
+	pub fn has_all_commitments(&self) -> bool {
+		self.encryption_keys.len() == self.sets.participants().len()
+	}
+
+	/// Whether every share for this participant was received. Always true for participants
+	/// which only belong to the outgoing set.
+	pub fn has_all_shares(&self) -> bool {
+		self.sets.new_party(self.party_i).is_none() || self.shares.len() == self.sets.dealers.len()
+	}
+
+	/// Verifies the received shares against the dealings, and computes the local key of the
+	/// incoming set. Returns `None` for participants which only belong to the outgoing set.
+	pub fn finish(self) -> Result<Option<FrostLocalKey>, DKGError> {
+		if !self.has_all_commitments() || !self.has_all_shares() {
+			return Err(DKGError::GenericError {
+				reason: "FROST resharing is missing commitments or shares".to_string(),
+			})
+		}
+
+		let Some(new_party) = self.sets.new_party(self.party_i) else { return Ok(None) };
+
+		// Every dealer must reshare the same key. The reference is our own key if we hold one,
+		// otherwise the group commitments sent by most dealers.
+		let reference = match self.old_key.as_ref() {
+			Some(key) => key.group_commitments.clone(),
+			None => {
+				let dealings: Vec<&ReshareDealing> = self.dealings.values().collect();
+				dealings
+					.iter()
+					.max_by_key(|d| {
+						dealings
+							.iter()
+							.filter(|o| o.old_group_commitments == d.old_group_commitments)
+							.count()
+					})
+					.map(|d| d.old_group_commitments.clone())
+					.unwrap_or_default()
+			},
+		};
+
+		let own_key_ids = key_ids_for_party(&self.sets.new_weights, new_party);
+		let mut bad_actors = vec![];
+		let mut received = vec![];
+		for (sender, shares) in self.shares.iter() {
+			let dealing = &self.dealings[sender];
+			let shares: Vec<(KeyId, FE)> = if *sender == self.party_i {
+				shares.clone()
+			} else {
+				shares
+					.iter()
+					.map(|(id, share)| (*id, share - &self.share_mask(*sender, self.party_i, *id)))
+					.collect()
+			};
+			let received_ids: Vec<KeyId> = shares.iter().map(|(id, _)| *id).collect();
+			let valid = dealing.old_group_commitments == reference &&
+				received_ids == own_key_ids &&
+				shares.iter().all(|(id, share)| {
+					GE::generator() * share == evaluate_commitments(&dealing.commitments, *id)
+				});
+			if valid {
+				received.push(shares);
+			} else {
+				bad_actors.push(*sender as usize);
+			}
+		}
+
+		if !bad_actors.is_empty() {
+			return Err(DKGError::KeygenMisbehaviour {
+				reason: "Invalid FROST resharing shares".to_string(),
+				bad_actors,
+			})
+		}
+
+		let secret_shares = own_key_ids
+			.iter()
+			.map(|id| {
+				let share = received
+					.iter()
+					.flat_map(|shares| shares.iter().filter(|(i, _)| i == id))
+					.fold(FE::zero(), |acc, (_, share)| acc + share);
+				(*id, share)
+			})
+			.collect();
+
+		let group_commitments: Vec<GE> = (0..=self.sets.new_threshold as usize)
+			.map(|k| {
+				self.dealings
+					.values()
+					.fold(GE::zero(), |acc, dealing| acc + &dealing.commitments[k])
+			})
+			.collect();
+
+		if group_commitments[0] != self.public_key {
+			return Err(DKGError::GenericError {
+				reason: "FROST resharing did not preserve the group public key".to_string(),
+			})
+		}
+
+		Ok(Some(FrostLocalKey {
+			party_i: new_party,
+			threshold: self.sets.new_threshold,
+			weights: self.sets.new_weights,
+			secret_shares,
+			group_commitments,
+		}))
+	}
+
+	/// Checks that a dealing commits to the dealer's part of the group secret
+	fn verify_dealing(
+		&self,
+		sender: u16,
+		old_party: u16,
+		dealing: &ReshareDealing,
+	) -> Result<(), DKGError> {
+		let valid = dealing.commitments.len() == self.sets.new_threshold as usize + 1 &&
+			dealing.old_group_commitments.len() == self.sets.old_threshold as usize + 1 &&
+			dealing.old_group_commitments.first() == Some(&self.public_key) &&
+			dealing.commitments[0] ==
+				dealer_public_share(
+					&dealing.old_group_commitments,
+					&self.sets.old_weights,
+					&self.sets.dealer_key_ids(),
+					old_party,
+				);
+		if !valid {
+			return Err(DKGError::KeygenMisbehaviour {
+				reason: "Invalid FROST resharing commitments".to_string(),
+				bad_actors: vec![sender as usize],
+			})
+		}
+
+		Ok(())
+	}
+
+	fn share_mask(&self, sender: u16, recipient: u16, key_id: KeyId) -> FE {
+		let counterparty = if sender == self.party_i { recipient } else { sender };
+		let shared_secret = &self.encryption_keys[&counterparty] * &self.encryption_secret;
+		share_mask(&self.context, &shared_secret, sender, recipient, key_id)
+	}
+}
+
+/// `d_p * G` for the outgoing party `old_party`, given the group commitments of the old key and
+/// the key ids of every dealer
+fn dealer_public_share(
+	old_group_commitments: &[GE],
+	old_weights: &[u16],
+	dealer_key_ids: &[KeyId],
+	old_party: u16,
+) -> GE {
+	key_ids_for_party(old_weights, old_party)
+		.into_iter()
+		.fold(GE::zero(), |acc, id| {
+			acc + evaluate_commitments(old_group_commitments, id) *
+				lagrange_coefficient(id, dealer_key_ids)
+		})
+}
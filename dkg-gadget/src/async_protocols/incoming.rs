@@ -113,6 +113,7 @@ impl TransformIncoming for SignedDKGMessage<Public> {
 		match (stream_type, &self.msg.payload) {
 			(ProtocolType::Keygen { .. }, NetworkMsgPayload::Keygen(..)) |
 			(ProtocolType::Offline { .. }, NetworkMsgPayload::Offline(..)) |
+			(ProtocolType::FrostSigning { .. }, NetworkMsgPayload::Offline(..)) |
 			(ProtocolType::Voting { .. }, NetworkMsgPayload::Vote(..)) => {
 				logger.checkpoint_message_raw(self.msg.payload.payload(), "CP-2.1-incoming");
 				// only clone if the downstream receiver expects this type
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod blockchain_interface;
+pub mod frost;
 pub mod incoming;
 pub mod keygen;
 pub mod remote;
@@ -322,6 +323,18 @@ pub enum ProtocolType<
 		i: OfflinePartyId,
 		associated_block_id: u64,
 	},
+	FrostSigning {
+		unsigned_proposal_batch: Arc<
+			StoredUnsignedProposalBatch<
+				BatchId,
+				MaxProposalLength,
+				MaxProposalsInBatch,
+				BlockNumber,
+			>,
+		>,
+		i: KeygenPartyId,
+		associated_block_id: u64,
+	},
 }
 
 impl<
@@ -335,7 +348,8 @@ impl<
 		match self {
 			Self::Keygen { associated_block_id, .. } |
 			Self::Offline { associated_block_id, .. } |
-			Self::Voting { associated_block_id, .. } => *associated_block_id,
+			Self::Voting { associated_block_id, .. } |
+			Self::FrostSigning { associated_block_id, .. } => *associated_block_id,
 		}
 	}
 
@@ -344,6 +358,7 @@ impl<
 			Self::Keygen { i, .. } => i.0,
 			Self::Offline { i, .. } => i.0,
 			Self::Voting { i, .. } => i.0,
+			Self::FrostSigning { i, .. } => i.0,
 		}
 	}
 	pub fn get_unsigned_proposal(
@@ -353,7 +368,8 @@ impl<
 	> {
 		match self {
 			Self::Offline { unsigned_proposal_batch, .. } |
-			Self::Voting { unsigned_proposal_batch, .. } => Some(unsigned_proposal_batch),
+			Self::Voting { unsigned_proposal_batch, .. } |
+			Self::FrostSigning { unsigned_proposal_batch, .. } => Some(unsigned_proposal_batch),
 			_ => None,
 		}
 	}
@@ -409,6 +425,13 @@ impl<
 					&unsigned_proposal_batch.batch_id
 				)
 			},
+			ProtocolType::FrostSigning { i, unsigned_proposal_batch, .. } => {
+				write!(
+					f,
+					"FrostSigning: (i, proposal) = ({}, {:?})",
+					i, &unsigned_proposal_batch.batch_id
+				)
+			},
 		}
 	}
 }
@@ -900,7 +923,8 @@ impl<
 	) -> Self {
 		match value {
 			ProtocolType::Keygen { .. } => AsyncProtocolType::Keygen,
-			ProtocolType::Offline { unsigned_proposal_batch, .. } => AsyncProtocolType::Signing {
+			ProtocolType::Offline { unsigned_proposal_batch, .. } |
+			ProtocolType::FrostSigning { unsigned_proposal_batch, .. } => AsyncProtocolType::Signing {
 				hash: unsigned_proposal_batch.hash().unwrap_or([0u8; 32]),
 			},
 			ProtocolType::Voting { unsigned_proposal_batch, .. } => AsyncProtocolType::Voting {
@@ -1,5 +1,9 @@
 #![allow(clippy::unwrap_used)] // allow unwraps in tests
-use crate::async_protocols::{blockchain_interface::BlockchainInterface, BatchKey};
+use crate::async_protocols::{
+	blockchain_interface::BlockchainInterface,
+	frost::{FrostLocalKey, FrostSignature},
+	BatchKey,
+};
 use codec::Encode;
 use curv::{elliptic::curves::Secp256k1, BigInt};
 use dkg_primitives::{
@@ -43,6 +47,47 @@ pub struct TestDummyIface {
 	// key is party_index, hash of data. Needed especially for local unit tests
 	pub vote_results: VoteResults,
 	pub keygen_key: Arc<Mutex<Option<LocalKey<Secp256k1>>>>,
+	pub frost_key: Arc<Mutex<Option<FrostLocalKey>>>,
+}
+
+impl TestDummyIface {
+	fn push_signed_proposal_batch(
+		&self,
+		signature: Vec<u8>,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			BatchId,
+			MaxProposalLength,
+			MaxProposalsInBatch,
+			u32,
+		>,
+		batch_key: BatchKey,
+	) {
+		let mut lock = self.vote_results.lock();
+		let mut signed_proposals = vec![];
+
+		// convert all unsigned proposals to signed
+		for unsigned_proposal in unsigned_proposal_batch.proposals.iter() {
+			signed_proposals.push(Proposal::Signed {
+				kind: unsigned_proposal.proposal.kind(),
+				data: unsigned_proposal
+					.data()
+					.clone()
+					.try_into()
+					.expect("should not happen since its a valid proposal"),
+				signature: signature.clone().try_into().expect("Signature exceeds runtime bounds!"),
+			});
+		}
+
+		let signed_proposal_batch = SignedProposalBatch {
+			batch_id: unsigned_proposal_batch.batch_id,
+			proposals: signed_proposals.try_into().expect("Proposals exceeds runtime bounds!"),
+			signature: signature.try_into().expect("Signature exceeds runtime bounds!"),
+			encoding: unsigned_proposal_batch.encoding,
+		};
+
+		let proposals_for_this_batch = lock.entry(batch_key).or_default();
+		proposals_for_this_batch.push(signed_proposal_batch);
+	}
 }
 
 #[async_trait::async_trait]
@@ -89,39 +134,28 @@ impl BlockchainInterface for TestDummyIface {
 		batch_key: BatchKey,
 		_message: BigInt,
 	) -> Result<(), DKGError> {
-		let mut lock = self.vote_results.lock();
-
 		let signature = convert_signature(&signature).ok_or_else(|| DKGError::CriticalError {
 			reason: "Unable to serialize signature".to_string(),
 		})?;
 
-		let mut signed_proposals = vec![];
-
-		// convert all unsigned proposals to signed
-		for unsigned_proposal in unsigned_proposal_batch.proposals.iter() {
-			signed_proposals.push(Proposal::Signed {
-				kind: unsigned_proposal.proposal.kind(),
-				data: unsigned_proposal
-					.data()
-					.clone()
-					.try_into()
-					.expect("should not happen since its a valid proposal"),
-				signature: signature
-					.encode()
-					.try_into()
-					.expect("Signature exceeds runtime bounds!"),
-			});
-		}
-
-		let signed_proposal_batch = SignedProposalBatch {
-			batch_id: unsigned_proposal_batch.batch_id,
-			proposals: signed_proposals.try_into().expect("Proposals exceeds runtime bounds!"),
-			signature: signature.encode().try_into().expect("Signature exceeds runtime bounds!"),
-		};
+		self.push_signed_proposal_batch(signature.encode(), unsigned_proposal_batch, batch_key);
 
-		let proposals_for_this_batch = lock.entry(batch_key).or_default();
-		proposals_for_this_batch.push(signed_proposal_batch);
+		Ok(())
+	}
 
+	fn process_frost_vote_result(
+		&self,
+		signature: FrostSignature,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			Self::BatchId,
+			Self::MaxProposalLength,
+			Self::MaxProposalsInBatch,
+			Self::Clock,
+		>,
+		_session_id: SessionId,
+		batch_key: BatchKey,
+	) -> Result<(), DKGError> {
+		self.push_signed_proposal_batch(signature.to_bytes(), unsigned_proposal_batch, batch_key);
 		Ok(())
 	}
 
@@ -135,6 +169,11 @@ impl BlockchainInterface for TestDummyIface {
 		Ok(())
 	}
 
+	fn store_frost_public_key(&self, key: FrostLocalKey, _: SessionId) -> Result<(), DKGError> {
+		*self.frost_key.lock() = Some(key);
+		Ok(())
+	}
+
 	fn get_authority_set(&self) -> Vec<(KeygenPartyId, Public)> {
 		(*self.best_authorities).clone()
 	}
@@ -1,9 +1,17 @@
 // Constants for dkg-gadget
 
 // ================= Common ======================== //
-pub const DKG_KEYGEN_PROTOCOL_NAME: &str = "/webb-tools/dkg/keygen/1";
+pub const DKG_KEYGEN_PROTOCOL_NAME: &str = "/webb-tools/dkg/keygen/2";
 
-pub const DKG_SIGNING_PROTOCOL_NAME: &str = "/webb-tools/dkg/signing/1";
+pub const DKG_SIGNING_PROTOCOL_NAME: &str = "/webb-tools/dkg/signing/2";
+
+/// The previous version of the keygen protocol, whose peers are sent uncompressed and whole
+/// messages
+pub const LEGACY_DKG_KEYGEN_PROTOCOL_NAME: &str = "/webb-tools/dkg/keygen/1";
+
+/// The previous version of the signing protocol, whose peers are sent uncompressed and whole
+/// messages
+pub const LEGACY_DKG_SIGNING_PROTOCOL_NAME: &str = "/webb-tools/dkg/signing/1";
 
 // ================= Worker ========================== //
 pub mod worker {
@@ -32,6 +40,9 @@ pub mod signing_manager {
 	// How often to poll the jobs to check completion status
 	pub const JOB_POLL_INTERVAL_IN_MILLISECONDS: u64 = 500;
 
+	// in adaptive mode, a job completed within this many blocks lets one more task run
+	pub const FAST_JOB_COMPLETION_IN_BLOCKS: u32 = 2;
+
 	// Number of signing sets to generate for every proposal
 	pub const MAX_POTENTIAL_SIGNING_SETS_PER_PROPOSAL: u8 = 2;
 }
@@ -49,6 +60,32 @@ pub mod network {
 	///
 	/// This is to prevent a malicious peer from spamming us with messages.
 	pub const MAX_DUPLICATED_MESSAGES_PER_PEER: usize = 8;
+
+	/// Maximum number of peers which are not authorities that a message is relayed through, when
+	/// some authorities are not connected to us.
+	pub const MAX_RELAY_PEERS: usize = 4;
+
+	/// Messages smaller than this are not compressed.
+	pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+	/// Maximum size of a chunk of a message. Larger messages are split in chunks for the peers
+	/// supporting it, so they do not exceed `MAX_MESSAGE_SIZE`.
+	pub const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+
+	/// Maximum size of a message reassembled from its chunks or decompressed.
+	pub const MAX_REASSEMBLED_MESSAGE_SIZE: usize = 2 * MAX_MESSAGE_SIZE as usize;
+
+	/// Maximum number of messages of a single peer whose chunks are still being received.
+	pub const MAX_INCOMPLETE_MESSAGES_PER_PEER: usize = 4;
+
+	/// Maximum size of the chunks buffered for the incomplete messages of a single peer.
+	pub const MAX_INCOMPLETE_BYTES_PER_PEER: usize = MAX_REASSEMBLED_MESSAGE_SIZE;
+
+	/// Maximum size of the chunks buffered for the incomplete messages of all the peers.
+	pub const MAX_INCOMPLETE_BYTES: usize = 4 * MAX_REASSEMBLED_MESSAGE_SIZE;
+
+	/// Time after which the chunks of a message which is still incomplete are dropped.
+	pub const INCOMPLETE_MESSAGE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
 }
 
 // ============= Keygen Manager ======================= //
@@ -59,3 +96,10 @@ pub mod keygen_manager {
 	/// There should never be any job enqueueing for keygen
 	pub const MAX_ENQUEUED_TASKS: usize = 0;
 }
+
+// ============= RPC ======================= //
+
+pub mod rpc {
+	/// Number of signed proposal batches buffered for each RPC subscriber
+	pub const SIGNED_PROPOSALS_CHANNEL_CAPACITY: usize = 256;
+}
@@ -3,6 +3,8 @@
 
 use std::{collections::BTreeMap, sync::Mutex};
 
+use super::{PendingSigningJob, StoredKeygenState, StoredSignedProposalBatch};
+use crate::async_protocols::frost::FrostLocalKey;
 use curv::elliptic::curves::Secp256k1;
 use dkg_primitives::{types::DKGError, SessionId};
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
@@ -12,6 +14,10 @@ type LockedMap<K, V> = Mutex<BTreeMap<K, V>>;
 /// In Memory storage backend for DKG database, this is used for testing purposes.
 pub struct DKGInMemoryDb {
 	local_keys: LockedMap<SessionId, LocalKey<Secp256k1>>,
+	frost_local_keys: LockedMap<SessionId, FrostLocalKey>,
+	keygen_state: Mutex<Option<StoredKeygenState>>,
+	pending_signing_jobs: LockedMap<[u8; 32], PendingSigningJob>,
+	unsubmitted_signed_proposals: LockedMap<[u8; 32], StoredSignedProposalBatch>,
 }
 
 impl Default for DKGInMemoryDb {
@@ -24,7 +30,13 @@ impl DKGInMemoryDb {
 	/// Create a new instance of [`DKGInMemoryDb`].
 	#[allow(unused)]
 	pub fn new() -> Self {
-		Self { local_keys: Mutex::new(BTreeMap::new()) }
+		Self {
+			local_keys: Mutex::new(BTreeMap::new()),
+			frost_local_keys: Mutex::new(BTreeMap::new()),
+			keygen_state: Mutex::new(None),
+			pending_signing_jobs: Mutex::new(BTreeMap::new()),
+			unsubmitted_signed_proposals: Mutex::new(BTreeMap::new()),
+		}
 	}
 }
 
@@ -50,4 +62,104 @@ impl super::DKGDbBackend for DKGInMemoryDb {
 		lock.insert(session_id, local_key);
 		Ok(())
 	}
+
+	fn get_frost_local_key(
+		&self,
+		session_id: SessionId,
+	) -> Result<Option<FrostLocalKey>, DKGError> {
+		let lock = self.frost_local_keys.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock frost_local_keys: {e}"),
+		})?;
+		Ok(lock.get(&session_id).cloned())
+	}
+
+	fn store_frost_local_key(
+		&self,
+		session_id: SessionId,
+		local_key: FrostLocalKey,
+	) -> Result<(), DKGError> {
+		let mut lock = self.frost_local_keys.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock frost_local_keys: {e}"),
+		})?;
+		lock.insert(session_id, local_key);
+		Ok(())
+	}
+
+	fn remove_frost_local_key(&self, session_id: SessionId) -> Result<(), DKGError> {
+		let mut lock = self.frost_local_keys.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock frost_local_keys: {e}"),
+		})?;
+		lock.remove(&session_id);
+		Ok(())
+	}
+
+	fn get_keygen_state(&self) -> Result<Option<StoredKeygenState>, DKGError> {
+		let lock = self.keygen_state.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock keygen_state: {e}"),
+		})?;
+		Ok(*lock)
+	}
+
+	fn store_keygen_state(&self, state: StoredKeygenState) -> Result<(), DKGError> {
+		let mut lock = self.keygen_state.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock keygen_state: {e}"),
+		})?;
+		*lock = Some(state);
+		Ok(())
+	}
+
+	fn get_pending_signing_jobs(&self) -> Result<Vec<PendingSigningJob>, DKGError> {
+		let lock = self.pending_signing_jobs.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock pending_signing_jobs: {e}"),
+		})?;
+		Ok(lock.values().cloned().collect())
+	}
+
+	fn store_pending_signing_job(&self, job: PendingSigningJob) -> Result<(), DKGError> {
+		let mut lock = self.pending_signing_jobs.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock pending_signing_jobs: {e}"),
+		})?;
+		lock.insert(job.unsigned_proposal_hash, job);
+		Ok(())
+	}
+
+	fn remove_pending_signing_job(&self, unsigned_proposal_hash: [u8; 32]) -> Result<(), DKGError> {
+		let mut lock = self.pending_signing_jobs.lock().map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to lock pending_signing_jobs: {e}"),
+		})?;
+		lock.remove(&unsigned_proposal_hash);
+		Ok(())
+	}
+
+	fn get_unsubmitted_signed_proposals(&self) -> Result<Vec<StoredSignedProposalBatch>, DKGError> {
+		let lock =
+			self.unsubmitted_signed_proposals.lock().map_err(|e| DKGError::CriticalError {
+				reason: format!("Failed to lock unsubmitted_signed_proposals: {e}"),
+			})?;
+		Ok(lock.values().cloned().collect())
+	}
+
+	fn store_unsubmitted_signed_proposal(
+		&self,
+		batch: StoredSignedProposalBatch,
+	) -> Result<(), DKGError> {
+		let mut lock =
+			self.unsubmitted_signed_proposals.lock().map_err(|e| DKGError::CriticalError {
+				reason: format!("Failed to lock unsubmitted_signed_proposals: {e}"),
+			})?;
+		lock.insert(batch.unsigned_proposal_hash, batch);
+		Ok(())
+	}
+
+	fn remove_unsubmitted_signed_proposal(
+		&self,
+		unsigned_proposal_hash: [u8; 32],
+	) -> Result<(), DKGError> {
+		let mut lock =
+			self.unsubmitted_signed_proposals.lock().map_err(|e| DKGError::CriticalError {
+				reason: format!("Failed to lock unsubmitted_signed_proposals: {e}"),
+			})?;
+		lock.remove(&unsigned_proposal_hash);
+		Ok(())
+	}
 }
@@ -1,18 +1,30 @@
+use crate::{async_protocols::frost::FrostLocalKey, keygen_manager::KeygenState};
+use codec::{Decode, Encode};
 use curv::elliptic::curves::Secp256k1;
-use dkg_primitives::{types::DKGError, SessionId};
+use dkg_primitives::{
+	dkg_key_cli::{DKGLocalKey, LocalKeyDb},
+	types::DKGError,
+	SessionId,
+};
+use dkg_runtime_primitives::{
+	BatchId, MaxProposalLength, MaxProposalsInBatch, MaxSignatureLength, SignedProposalBatch,
+};
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
 
 mod mem;
 mod offchain_storage;
+mod sled_db;
 
 pub use mem::DKGInMemoryDb;
 pub use offchain_storage::DKGOffchainStorageDb;
+pub use sled_db::DKGSledDb;
 
 /// A Database backend, specificly for the DKG to store and load important state
 ///
 /// The backend of this database could be using a persistence store or in-memory
 /// ephemeral store, depending on the use case. For example, during the tests we can switch
-/// to an in-memory store, and in production we could use sled database or Offchain storage.
+/// to an in-memory store, and in production we could use the [`DKGSledDb`] file backed
+/// database or the [`DKGOffchainStorageDb`] Offchain storage.
 #[auto_impl::auto_impl(Arc, &, &mut)]
 pub trait DKGDbBackend: Send + Sync + 'static {
 	/// Returns the DKG [`LocalKey<Secp256k1>`] at specific session, if any.
@@ -24,4 +36,99 @@ pub trait DKGDbBackend: Send + Sync + 'static {
 		session_id: SessionId,
 		local_key: LocalKey<Secp256k1>,
 	) -> Result<(), DKGError>;
+	/// Returns the WT-FROST [`FrostLocalKey`] at specific session, if any.
+	fn get_frost_local_key(&self, session_id: SessionId)
+		-> Result<Option<FrostLocalKey>, DKGError>;
+	/// Stores the [`FrostLocalKey`] at a specified session.
+	fn store_frost_local_key(
+		&self,
+		session_id: SessionId,
+		local_key: FrostLocalKey,
+	) -> Result<(), DKGError>;
+	/// Removes every stored revision of the [`FrostLocalKey`] at a specified session, overwriting
+	/// the stored shares first so they cannot be read back.
+	fn remove_frost_local_key(&self, session_id: SessionId) -> Result<(), DKGError>;
+	/// Returns the last persisted [`StoredKeygenState`], if any.
+	fn get_keygen_state(&self) -> Result<Option<StoredKeygenState>, DKGError>;
+	/// Stores the [`StoredKeygenState`], replacing the previous one.
+	fn store_keygen_state(&self, state: StoredKeygenState) -> Result<(), DKGError>;
+	/// Returns all the signing jobs this node was taking part in.
+	fn get_pending_signing_jobs(&self) -> Result<Vec<PendingSigningJob>, DKGError>;
+	/// Stores a signing job this node is taking part in.
+	fn store_pending_signing_job(&self, job: PendingSigningJob) -> Result<(), DKGError>;
+	/// Removes the signing job of the given unsigned proposal batch, if any.
+	fn remove_pending_signing_job(&self, unsigned_proposal_hash: [u8; 32]) -> Result<(), DKGError>;
+	/// Returns all the signed proposal batches that have not been seen on-chain yet.
+	fn get_unsubmitted_signed_proposals(&self) -> Result<Vec<StoredSignedProposalBatch>, DKGError>;
+	/// Stores a signed proposal batch until it is seen on-chain.
+	fn store_unsubmitted_signed_proposal(
+		&self,
+		batch: StoredSignedProposalBatch,
+	) -> Result<(), DKGError>;
+	/// Removes the signed proposal batch of the given unsigned proposal batch, if any.
+	fn remove_unsubmitted_signed_proposal(
+		&self,
+		unsigned_proposal_hash: [u8; 32],
+	) -> Result<(), DKGError>;
+}
+
+/// Gives the `dkg-key` subcommands access to the stored keys.
+impl LocalKeyDb for dyn DKGDbBackend {
+	fn get_local_key(&self, session_id: SessionId) -> Result<Option<DKGLocalKey>, sc_cli::Error> {
+		if let Some(key) = DKGDbBackend::get_local_key(self, session_id).map_err(cli_error)? {
+			return Ok(Some(DKGLocalKey::MpEcdsa(key)))
+		}
+		Ok(DKGDbBackend::get_frost_local_key(self, session_id)
+			.map_err(cli_error)?
+			.map(|key| DKGLocalKey::WTFrost(key.into())))
+	}
+
+	fn store_local_key(
+		&self,
+		session_id: SessionId,
+		local_key: DKGLocalKey,
+	) -> Result<(), sc_cli::Error> {
+		match local_key {
+			DKGLocalKey::MpEcdsa(key) => DKGDbBackend::store_local_key(self, session_id, key),
+			DKGLocalKey::WTFrost(key) =>
+				DKGDbBackend::store_frost_local_key(self, session_id, key.into()),
+		}
+		.map_err(cli_error)
+	}
+}
+
+fn cli_error(e: DKGError) -> sc_cli::Error {
+	sc_cli::Error::Application(Box::from(format!("{e:?}")))
+}
+
+/// The state of the [`KeygenManager`](crate::keygen_manager::KeygenManager) that is persisted,
+/// so that a restarted node knows which keygen it was running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct StoredKeygenState {
+	pub state: KeygenState,
+	pub latest_executed_session_id: Option<SessionId>,
+	pub finished_count: u64,
+}
+
+/// A signing job this node is taking part in.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct PendingSigningJob {
+	/// The hash of the unsigned proposal batch being signed
+	pub unsigned_proposal_hash: [u8; 32],
+	/// The session of the key used for signing
+	pub session_id: SessionId,
+	pub batch_id: BatchId,
+	/// The signing set the job was started for
+	pub ssid: u8,
+}
+
+/// A signed proposal batch produced by this node, which has not been seen on-chain yet.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct StoredSignedProposalBatch {
+	/// The hash of the unsigned proposal batch that was signed
+	pub unsigned_proposal_hash: [u8; 32],
+	/// The session of the key that signed the batch
+	pub session_id: SessionId,
+	pub batch:
+		SignedProposalBatch<BatchId, MaxProposalLength, MaxProposalsInBatch, MaxSignatureLength>,
 }
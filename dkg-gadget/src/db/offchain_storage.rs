@@ -2,9 +2,11 @@
 //! Unlike the in-memory database, this database is persistent and can be used to store
 //! the DKG state across multiple runs of the node.
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
-use crate::debug_logger::DebugLogger;
+use super::{PendingSigningJob, StoredKeygenState, StoredSignedProposalBatch};
+use crate::{async_protocols::frost::FrostLocalKey, debug_logger::DebugLogger};
+use codec::{Decode, Encode};
 use curv::elliptic::curves::Secp256k1;
 use dkg_primitives::{
 	types::DKGError,
@@ -13,6 +15,7 @@ use dkg_primitives::{
 };
 use dkg_runtime_primitives::offchain::crypto::{Pair as AppPair, Public};
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use parking_lot::Mutex;
 use sc_client_api::Backend;
 use sc_keystore::LocalKeystore;
 use sp_core::{offchain::OffchainStorage, Pair};
@@ -26,11 +29,14 @@ const STORAGE_PREFIX: &[u8] = b"dkg";
 /// A Database backend, specifically for the DKG to store and load important state
 /// implemented using Offchain Storage.
 ///
-/// This backend also uses the DKG Keystore to store the data in an encrypted form.
+/// This backend also uses the DKG Keystore to store the keys in an encrypted form. The protocol
+/// state and signed proposals are not secret, and are stored unencrypted.
 pub struct DKGOffchainStorageDb<B, BE> {
 	backend: Arc<BE>,
 	key_store: DKGKeystore,
 	local_keystore: Option<Arc<LocalKeystore>>,
+	/// Serializes the read-modify-write updates of the stored maps.
+	state_lock: Mutex<()>,
 	logger: DebugLogger,
 	__marker: std::marker::PhantomData<B>,
 }
@@ -46,6 +52,7 @@ impl<B, BE> DKGOffchainStorageDb<B, BE> {
 			backend,
 			key_store: dkg_key_store,
 			local_keystore,
+			state_lock: Mutex::new(()),
 			logger,
 			__marker: Default::default(),
 		}
@@ -70,6 +77,44 @@ mod keys {
 			Self { _prefix: *b"dkg", _key_name: *b"local_key", session_id }
 		}
 	}
+
+	#[derive(Debug, Clone, codec::Encode, codec::Decode)]
+	pub(super) struct FrostKey {
+		/// "dkg" letters.
+		_prefix: [u8; 3],
+		/// Key name "frost_key".
+		_key_name: [u8; 9],
+		/// Session ID.
+		pub session_id: SessionId,
+	}
+
+	impl FrostKey {
+		pub fn new(session_id: SessionId) -> Self {
+			Self { _prefix: *b"dkg", _key_name: *b"frost_key", session_id }
+		}
+	}
+
+	#[derive(Debug, Clone, codec::Encode, codec::Decode)]
+	pub(super) struct StateKey {
+		/// "dkg" letters.
+		_prefix: [u8; 3],
+		/// Key name.
+		_key_name: [u8; 12],
+	}
+
+	impl StateKey {
+		pub fn keygen_state() -> Self {
+			Self { _prefix: *b"dkg", _key_name: *b"keygen_state" }
+		}
+
+		pub fn signing_jobs() -> Self {
+			Self { _prefix: *b"dkg", _key_name: *b"signing_jobs" }
+		}
+
+		pub fn signed_proposals() -> Self {
+			Self { _prefix: *b"dkg", _key_name: *b"signed_props" }
+		}
+	}
 }
 
 impl<B, BE> super::DKGDbBackend for DKGOffchainStorageDb<B, BE>
@@ -111,6 +156,99 @@ where
 			.map_err(|e| DKGError::CriticalError { reason: e.to_string() })?;
 		self.encrypt_and_store(codec::Encode::encode(&db_key), value)
 	}
+
+	fn get_frost_local_key(
+		&self,
+		session_id: SessionId,
+	) -> Result<Option<FrostLocalKey>, DKGError> {
+		self.logger.trace(format!(
+			"Offchain Storage : Fetching FROST local keys for session {session_id:?}"
+		));
+		let db_key = keys::FrostKey::new(session_id);
+		let maybe_decrypted_bytes = self.load_and_decrypt(codec::Encode::encode(&db_key))?;
+		match maybe_decrypted_bytes {
+			Some(decrypted_bytes) => {
+				let local_key = serde_json::from_slice(&decrypted_bytes.0)
+					.map_err(|e| DKGError::CriticalError { reason: e.to_string() })?;
+				Ok(Some(local_key))
+			},
+			None => Ok(None),
+		}
+	}
+
+	fn store_frost_local_key(
+		&self,
+		session_id: SessionId,
+		local_key: FrostLocalKey,
+	) -> Result<(), DKGError> {
+		self.logger
+			.trace(format!("Offchain Storage : Store FROST local keys for session {session_id:?}"));
+		let db_key = keys::FrostKey::new(session_id);
+		let value = serde_json::to_vec(&local_key)
+			.map_err(|e| DKGError::CriticalError { reason: e.to_string() })?;
+		self.encrypt_and_store(codec::Encode::encode(&db_key), value)
+	}
+
+	fn remove_frost_local_key(&self, session_id: SessionId) -> Result<(), DKGError> {
+		self.logger.trace(format!(
+			"Offchain Storage : Remove FROST local keys for session {session_id:?}"
+		));
+		self.wipe(keys::FrostKey::new(session_id).encode())
+	}
+
+	fn get_keygen_state(&self) -> Result<Option<StoredKeygenState>, DKGError> {
+		self.load_decoded(keys::StateKey::keygen_state())
+	}
+
+	fn store_keygen_state(&self, state: StoredKeygenState) -> Result<(), DKGError> {
+		self.logger.trace(format!("Offchain Storage : Store keygen state {state:?}"));
+		self.replace(keys::StateKey::keygen_state().encode(), state.encode())
+	}
+
+	fn get_pending_signing_jobs(&self) -> Result<Vec<PendingSigningJob>, DKGError> {
+		let jobs: BTreeMap<[u8; 32], PendingSigningJob> =
+			self.load_decoded(keys::StateKey::signing_jobs())?.unwrap_or_default();
+		Ok(jobs.into_values().collect())
+	}
+
+	fn store_pending_signing_job(&self, job: PendingSigningJob) -> Result<(), DKGError> {
+		self.update_map(keys::StateKey::signing_jobs(), |jobs| {
+			jobs.insert(job.unsigned_proposal_hash, job);
+		})
+	}
+
+	fn remove_pending_signing_job(&self, unsigned_proposal_hash: [u8; 32]) -> Result<(), DKGError> {
+		self.update_map::<PendingSigningJob>(keys::StateKey::signing_jobs(), |jobs| {
+			jobs.remove(&unsigned_proposal_hash);
+		})
+	}
+
+	fn get_unsubmitted_signed_proposals(&self) -> Result<Vec<StoredSignedProposalBatch>, DKGError> {
+		let batches: BTreeMap<[u8; 32], StoredSignedProposalBatch> =
+			self.load_decoded(keys::StateKey::signed_proposals())?.unwrap_or_default();
+		Ok(batches.into_values().collect())
+	}
+
+	fn store_unsubmitted_signed_proposal(
+		&self,
+		batch: StoredSignedProposalBatch,
+	) -> Result<(), DKGError> {
+		self.update_map(keys::StateKey::signed_proposals(), |batches| {
+			batches.insert(batch.unsigned_proposal_hash, batch);
+		})
+	}
+
+	fn remove_unsubmitted_signed_proposal(
+		&self,
+		unsigned_proposal_hash: [u8; 32],
+	) -> Result<(), DKGError> {
+		self.update_map::<StoredSignedProposalBatch>(
+			keys::StateKey::signed_proposals(),
+			|batches| {
+				batches.remove(&unsigned_proposal_hash);
+			},
+		)
+	}
 }
 // ** These are wrapper types to make a typesafe difference between the encrypted and raw data.
 // ** This is to prevent accidental misuse of the data.
@@ -215,24 +353,68 @@ where
 		}
 	}
 
+	/// Loads and decodes the SCALE encoded value stored at the given key.
+	///
+	/// Returns None if the key is not found.
+	fn load_decoded<T: Decode>(&self, key: keys::StateKey) -> Result<Option<T>, DKGError> {
+		match self.load(key.encode())? {
+			Some(bytes) => T::decode(&mut &bytes[..])
+				.map(Some)
+				.map_err(|e| DKGError::CriticalError { reason: e.to_string() }),
+			None => Ok(None),
+		}
+	}
+
+	/// Applies the update to the map stored at the given key.
+	fn update_map<V: Encode + Decode>(
+		&self,
+		key: keys::StateKey,
+		update: impl FnOnce(&mut BTreeMap<[u8; 32], V>),
+	) -> Result<(), DKGError> {
+		let _guard = self.state_lock.lock();
+		let mut map = self.load_decoded(key.clone())?.unwrap_or_default();
+		update(&mut map);
+		self.replace(key.encode(), map.encode())
+	}
+
 	/// Stores the raw bytes in the offchain storage.
 	///
 	/// Note: This will overwrite any existing data at the given key.
 	/// Note: The stored data is not encrypted, so use this with care.
 	fn store(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), DKGError> {
-		let mut offchain_storage = self.backend.offchain_storage().ok_or_else(|| {
-			DKGError::CriticalError { reason: String::from("No Offchain Storage available!!") }
-		})?;
-		if offchain_storage.get(STORAGE_PREFIX, &key).is_some() {
+		if self.load(key.clone())?.is_some() {
 			self.logger.warn(format!(
 				"Overwriting already existing database entry at key 0x{}",
 				hex::encode(key.clone())
 			));
 		}
+		self.replace(key, value)
+	}
+
+	/// Stores the raw bytes in the offchain storage, for data that is expected to be updated
+	/// over time.
+	///
+	/// Note: The stored data is not encrypted, so use this with care.
+	fn replace(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), DKGError> {
+		let mut offchain_storage = self.backend.offchain_storage().ok_or_else(|| {
+			DKGError::CriticalError { reason: String::from("No Offchain Storage available!!") }
+		})?;
 		offchain_storage.set(STORAGE_PREFIX, &key, &value);
 		Ok(())
 	}
 
+	/// Overwrites the raw bytes stored at the given key with zeros, then removes them.
+	fn wipe(&self, key: Vec<u8>) -> Result<(), DKGError> {
+		let mut offchain_storage = self.backend.offchain_storage().ok_or_else(|| {
+			DKGError::CriticalError { reason: String::from("No Offchain Storage available!!") }
+		})?;
+		if let Some(value) = offchain_storage.get(STORAGE_PREFIX, &key) {
+			offchain_storage.set(STORAGE_PREFIX, &key, &vec![0u8; value.len()]);
+			offchain_storage.remove(STORAGE_PREFIX, &key);
+		}
+		Ok(())
+	}
+
 	/// Loads the raw bytes from the offchain storage.
 	///
 	/// Returns None if the key is not found.
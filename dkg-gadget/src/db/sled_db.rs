@@ -0,0 +1,426 @@
+//! DKG Database backend, implemented using a standalone [`sled`] database.
+//! Unlike the offchain storage database, the data lives outside of the chain database, so it
+//! survives a node resync or `purge-chain`, and can be placed on a separate (encrypted) volume.
+//!
+//! Every key is stored as a versioned record under `session_id || revision`, where storing a key
+//! for a session that already has one appends a new revision instead of overwriting it. The
+//! protocol state and signed proposals are versioned records too, but are simply overwritten.
+//!
+//! The key shares replaced by a WT-FROST refresh are zeroed and removed with all their
+//! revisions. sled only reclaims the log segments still holding the old bytes in the
+//! background, hence the advice to keep the database on an encrypted volume.
+
+use std::path::Path;
+
+use super::{PendingSigningJob, StoredKeygenState, StoredSignedProposalBatch};
+use crate::{async_protocols::frost::FrostLocalKey, debug_logger::DebugLogger};
+use codec::{Decode, Encode};
+use curv::elliptic::curves::Secp256k1;
+use dkg_primitives::{types::DKGError, SessionId};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Tree holding the [`LocalKey<Secp256k1>`] records.
+const LOCAL_KEYS_TREE: &[u8] = b"dkg_local_keys";
+/// Tree holding the [`FrostLocalKey`] records.
+const FROST_LOCAL_KEYS_TREE: &[u8] = b"dkg_frost_local_keys";
+/// Tree holding the [`StoredKeygenState`] under [`KEYGEN_STATE_KEY`].
+const STATE_TREE: &[u8] = b"dkg_state";
+/// Tree holding the [`PendingSigningJob`] records, keyed by unsigned proposal hash.
+const SIGNING_JOBS_TREE: &[u8] = b"dkg_signing_jobs";
+/// Tree holding the [`StoredSignedProposalBatch`] records, keyed by unsigned proposal hash.
+const SIGNED_PROPOSALS_TREE: &[u8] = b"dkg_signed_proposals";
+const KEYGEN_STATE_KEY: &[u8] = b"keygen_state";
+/// The format version of newly written records.
+const RECORD_VERSION: u16 = 1;
+
+/// A record as written to the database, tagged with the version of its format so that
+/// records written by older nodes can still be read after the format changes.
+#[derive(Debug, Clone, Encode, Decode)]
+struct VersionedRecord {
+	version: u16,
+	payload: Vec<u8>,
+}
+
+/// A Database backend, specifically for the DKG to store and load important state
+/// implemented using a file backed [`sled`] database.
+///
+/// Note: The data is not encrypted by this backend, the database should be placed on an
+/// encrypted volume.
+pub struct DKGSledDb {
+	local_keys: sled::Tree,
+	frost_local_keys: sled::Tree,
+	state: sled::Tree,
+	signing_jobs: sled::Tree,
+	signed_proposals: sled::Tree,
+	/// Serializes writes, so that two concurrent writes cannot pick the same revision.
+	write_lock: Mutex<()>,
+	logger: DebugLogger,
+}
+
+impl DKGSledDb {
+	/// Opens (or creates) the database at the given path.
+	pub fn open<P: AsRef<Path>>(path: P, logger: DebugLogger) -> Result<Self, DKGError> {
+		let db = sled::open(path.as_ref()).map_err(|e| DKGError::CriticalError {
+			reason: format!("Failed to open DKG database at {}: {e}", path.as_ref().display()),
+		})?;
+		Self::from_db(db, logger)
+	}
+
+	fn from_db(db: sled::Db, logger: DebugLogger) -> Result<Self, DKGError> {
+		let open_tree = |name: &[u8]| {
+			db.open_tree(name).map_err(|e| DKGError::CriticalError {
+				reason: format!("Failed to open DKG database tree: {e}"),
+			})
+		};
+		Ok(Self {
+			local_keys: open_tree(LOCAL_KEYS_TREE)?,
+			frost_local_keys: open_tree(FROST_LOCAL_KEYS_TREE)?,
+			state: open_tree(STATE_TREE)?,
+			signing_jobs: open_tree(SIGNING_JOBS_TREE)?,
+			signed_proposals: open_tree(SIGNED_PROPOSALS_TREE)?,
+			write_lock: Mutex::new(()),
+			logger,
+		})
+	}
+
+	/// Loads the latest revision stored for the session, if any.
+	fn load_latest<T: DeserializeOwned>(
+		&self,
+		tree: &sled::Tree,
+		session_id: SessionId,
+	) -> Result<Option<T>, DKGError> {
+		let latest =
+			tree.scan_prefix(session_id.to_be_bytes())
+				.next_back()
+				.transpose()
+				.map_err(|e| DKGError::CriticalError {
+					reason: format!("Failed to read from DKG database: {e}"),
+				})?;
+		match latest {
+			Some((_, bytes)) => decode_record(&bytes).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// Stores the value as a new revision for the session, and flushes it to disk.
+	fn store_revision<T: Serialize>(
+		&self,
+		tree: &sled::Tree,
+		session_id: SessionId,
+		value: &T,
+	) -> Result<(), DKGError> {
+		let map_err =
+			|e: sled::Error| DKGError::CriticalError { reason: format!("DKG database error: {e}") };
+		let record = encode_record(value)?;
+
+		let _guard = self.write_lock.lock();
+		let revision = match tree.scan_prefix(session_id.to_be_bytes()).next_back() {
+			Some(entry) => {
+				let (key, _) = entry.map_err(map_err)?;
+				revision_of(&key)?.checked_add(1).ok_or_else(|| DKGError::CriticalError {
+					reason: format!("Too many revisions for session {session_id}"),
+				})?
+			},
+			None => 0,
+		};
+		if revision > 0 {
+			self.logger.debug(format!(
+				"DKG Database : Storing revision {revision} of the key for session {session_id}"
+			));
+		}
+
+		tree.insert(record_key(session_id, revision), record).map_err(map_err)?;
+		tree.flush().map_err(map_err)?;
+		Ok(())
+	}
+
+	/// Overwrites every revision stored for the session with zeros, then removes them.
+	fn remove_revisions(&self, tree: &sled::Tree, session_id: SessionId) -> Result<(), DKGError> {
+		let _guard = self.write_lock.lock();
+		let revisions = tree
+			.scan_prefix(session_id.to_be_bytes())
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(map_sled_err)?;
+		for (key, value) in &revisions {
+			tree.insert(key, vec![0u8; value.len()]).map_err(map_sled_err)?;
+		}
+		tree.flush().map_err(map_sled_err)?;
+		for (key, _) in revisions {
+			tree.remove(key).map_err(map_sled_err)?;
+		}
+		tree.flush().map_err(map_sled_err)?;
+		Ok(())
+	}
+
+	/// Loads the SCALE encoded record stored at the key, if any.
+	fn load_state<T: Decode>(&self, tree: &sled::Tree, key: &[u8]) -> Result<Option<T>, DKGError> {
+		match tree.get(key).map_err(map_sled_err)? {
+			Some(bytes) => decode_state_record(&bytes).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// Loads all the SCALE encoded records of the tree.
+	fn load_all_states<T: Decode>(&self, tree: &sled::Tree) -> Result<Vec<T>, DKGError> {
+		tree.iter()
+			.values()
+			.map(|bytes| decode_state_record(&bytes.map_err(map_sled_err)?))
+			.collect()
+	}
+
+	/// Stores the value as a SCALE encoded record at the key, overwriting the previous one.
+	fn store_state<T: Encode>(
+		&self,
+		tree: &sled::Tree,
+		key: &[u8],
+		value: &T,
+	) -> Result<(), DKGError> {
+		let record = VersionedRecord { version: RECORD_VERSION, payload: value.encode() };
+		tree.insert(key, record.encode()).map_err(map_sled_err)?;
+		tree.flush().map_err(map_sled_err)?;
+		Ok(())
+	}
+
+	fn remove_state(&self, tree: &sled::Tree, key: &[u8]) -> Result<(), DKGError> {
+		tree.remove(key).map_err(map_sled_err)?;
+		tree.flush().map_err(map_sled_err)?;
+		Ok(())
+	}
+}
+
+impl super::DKGDbBackend for DKGSledDb {
+	fn get_local_key(
+		&self,
+		session_id: SessionId,
+	) -> Result<Option<LocalKey<Secp256k1>>, DKGError> {
+		self.logger
+			.trace(format!("DKG Database : Fetching local keys for session {session_id:?}"));
+		self.load_latest(&self.local_keys, session_id)
+	}
+
+	fn store_local_key(
+		&self,
+		session_id: SessionId,
+		local_key: LocalKey<Secp256k1>,
+	) -> Result<(), DKGError> {
+		self.logger
+			.trace(format!("DKG Database : Store local keys for session {session_id:?}"));
+		self.store_revision(&self.local_keys, session_id, &local_key)
+	}
+
+	fn get_frost_local_key(
+		&self,
+		session_id: SessionId,
+	) -> Result<Option<FrostLocalKey>, DKGError> {
+		self.logger
+			.trace(format!("DKG Database : Fetching FROST local keys for session {session_id:?}"));
+		self.load_latest(&self.frost_local_keys, session_id)
+	}
+
+	fn store_frost_local_key(
+		&self,
+		session_id: SessionId,
+		local_key: FrostLocalKey,
+	) -> Result<(), DKGError> {
+		self.logger
+			.trace(format!("DKG Database : Store FROST local keys for session {session_id:?}"));
+		self.store_revision(&self.frost_local_keys, session_id, &local_key)
+	}
+
+	fn remove_frost_local_key(&self, session_id: SessionId) -> Result<(), DKGError> {
+		self.logger
+			.debug(format!("DKG Database : Removing FROST local keys for session {session_id:?}"));
+		self.remove_revisions(&self.frost_local_keys, session_id)
+	}
+
+	fn get_keygen_state(&self) -> Result<Option<StoredKeygenState>, DKGError> {
+		self.load_state(&self.state, KEYGEN_STATE_KEY)
+	}
+
+	fn store_keygen_state(&self, state: StoredKeygenState) -> Result<(), DKGError> {
+		self.logger.trace(format!("DKG Database : Store keygen state {state:?}"));
+		self.store_state(&self.state, KEYGEN_STATE_KEY, &state)
+	}
+
+	fn get_pending_signing_jobs(&self) -> Result<Vec<PendingSigningJob>, DKGError> {
+		self.load_all_states(&self.signing_jobs)
+	}
+
+	fn store_pending_signing_job(&self, job: PendingSigningJob) -> Result<(), DKGError> {
+		self.store_state(&self.signing_jobs, &job.unsigned_proposal_hash, &job)
+	}
+
+	fn remove_pending_signing_job(&self, unsigned_proposal_hash: [u8; 32]) -> Result<(), DKGError> {
+		self.remove_state(&self.signing_jobs, &unsigned_proposal_hash)
+	}
+
+	fn get_unsubmitted_signed_proposals(&self) -> Result<Vec<StoredSignedProposalBatch>, DKGError> {
+		self.load_all_states(&self.signed_proposals)
+	}
+
+	fn store_unsubmitted_signed_proposal(
+		&self,
+		batch: StoredSignedProposalBatch,
+	) -> Result<(), DKGError> {
+		self.store_state(&self.signed_proposals, &batch.unsigned_proposal_hash, &batch)
+	}
+
+	fn remove_unsubmitted_signed_proposal(
+		&self,
+		unsigned_proposal_hash: [u8; 32],
+	) -> Result<(), DKGError> {
+		self.remove_state(&self.signed_proposals, &unsigned_proposal_hash)
+	}
+}
+
+/// `session_id || revision`, big endian so that the revisions of a session are sorted.
+fn record_key(session_id: SessionId, revision: u32) -> Vec<u8> {
+	let mut key = session_id.to_be_bytes().to_vec();
+	key.extend_from_slice(&revision.to_be_bytes());
+	key
+}
+
+fn revision_of(key: &[u8]) -> Result<u32, DKGError> {
+	key.get(std::mem::size_of::<SessionId>()..)
+		.and_then(|bytes| bytes.try_into().ok())
+		.map(u32::from_be_bytes)
+		.ok_or_else(|| DKGError::CriticalError {
+			reason: format!("Malformed DKG database key 0x{}", hex::encode(key)),
+		})
+}
+
+fn encode_record<T: Serialize>(value: &T) -> Result<Vec<u8>, DKGError> {
+	let payload =
+		serde_json::to_vec(value).map_err(|e| DKGError::CriticalError { reason: e.to_string() })?;
+	Ok(VersionedRecord { version: RECORD_VERSION, payload }.encode())
+}
+
+fn decode_record<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DKGError> {
+	let record = VersionedRecord::decode(&mut &bytes[..])
+		.map_err(|e| DKGError::CriticalError { reason: e.to_string() })?;
+	match record.version {
+		1 => serde_json::from_slice(&record.payload)
+			.map_err(|e| DKGError::CriticalError { reason: e.to_string() }),
+		version => Err(DKGError::CriticalError {
+			reason: format!("Unsupported DKG database record version {version}"),
+		}),
+	}
+}
+
+fn decode_state_record<T: Decode>(bytes: &[u8]) -> Result<T, DKGError> {
+	let record = VersionedRecord::decode(&mut &bytes[..])
+		.map_err(|e| DKGError::CriticalError { reason: e.to_string() })?;
+	match record.version {
+		1 => T::decode(&mut &record.payload[..])
+			.map_err(|e| DKGError::CriticalError { reason: e.to_string() }),
+		version => Err(DKGError::CriticalError {
+			reason: format!("Unsupported DKG database record version {version}"),
+		}),
+	}
+}
+
+fn map_sled_err(e: sled::Error) -> DKGError {
+	DKGError::CriticalError { reason: format!("DKG database error: {e}") }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temporary_db() -> DKGSledDb {
+		let db = sled::Config::new().temporary(true).open().unwrap();
+		DKGSledDb::from_db(db, DebugLogger::new("DKGSledDb", None).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn should_load_the_latest_revision() {
+		let db = temporary_db();
+		assert_eq!(db.load_latest::<Vec<u8>>(&db.local_keys, 1).unwrap(), None);
+
+		db.store_revision(&db.local_keys, 1, &vec![1u8]).unwrap();
+		db.store_revision(&db.local_keys, 1, &vec![2u8]).unwrap();
+		db.store_revision(&db.local_keys, 2, &vec![3u8]).unwrap();
+
+		assert_eq!(db.load_latest::<Vec<u8>>(&db.local_keys, 1).unwrap(), Some(vec![2u8]));
+		assert_eq!(db.load_latest::<Vec<u8>>(&db.local_keys, 2).unwrap(), Some(vec![3u8]));
+		assert_eq!(db.local_keys.scan_prefix(1u64.to_be_bytes()).count(), 2);
+		// trees are independent
+		assert_eq!(db.load_latest::<Vec<u8>>(&db.frost_local_keys, 1).unwrap(), None);
+	}
+
+	#[test]
+	fn should_not_read_back_a_removed_frost_key() {
+		use super::super::DKGDbBackend;
+		use dkg_primitives::types::{FE, GE};
+
+		let key = |secret: u64| FrostLocalKey {
+			party_i: 1,
+			threshold: 0,
+			weights: vec![1],
+			secret_shares: vec![(1, FE::from(secret))],
+			group_commitments: vec![GE::generator() * FE::from(secret)],
+		};
+		let path = std::env::temp_dir().join(format!("dkg-sled-db-test-{}", std::process::id()));
+		let open = || DKGSledDb::open(&path, DebugLogger::new("DKGSledDb", None).unwrap()).unwrap();
+
+		let db = open();
+		db.store_frost_local_key(1, key(5)).unwrap();
+		db.store_frost_local_key(1, key(6)).unwrap();
+		db.store_frost_local_key(2, key(7)).unwrap();
+		db.remove_frost_local_key(1).unwrap();
+		assert!(db.get_frost_local_key(1).unwrap().is_none());
+		assert_eq!(db.frost_local_keys.scan_prefix(1u64.to_be_bytes()).count(), 0);
+		drop(db);
+
+		// the removal survives a restart, and leaves the other sessions alone
+		let db = open();
+		assert!(db.get_frost_local_key(1).unwrap().is_none());
+		let kept = db.get_frost_local_key(2).unwrap().unwrap();
+		assert_eq!(kept.secret_shares, key(7).secret_shares);
+		drop(db);
+		std::fs::remove_dir_all(&path).unwrap();
+	}
+
+	#[test]
+	fn should_reject_unknown_record_versions() {
+		let record = VersionedRecord { version: RECORD_VERSION + 1, payload: b"[]".to_vec() };
+		assert!(decode_record::<Vec<u8>>(&record.encode()).is_err());
+
+		let record = VersionedRecord { version: RECORD_VERSION, payload: b"[7]".to_vec() };
+		assert_eq!(decode_record::<Vec<u8>>(&record.encode()).unwrap(), vec![7u8]);
+	}
+
+	#[test]
+	fn should_overwrite_and_remove_state_records() {
+		use super::super::DKGDbBackend;
+		use crate::keygen_manager::KeygenState;
+
+		let db = temporary_db();
+		assert_eq!(db.get_keygen_state().unwrap(), None);
+		let state = StoredKeygenState {
+			state: KeygenState::RunningKeygen,
+			latest_executed_session_id: Some(1),
+			finished_count: 1,
+		};
+		db.store_keygen_state(state).unwrap();
+		let state = StoredKeygenState {
+			state: KeygenState::KeygenCompleted { session_completed: 1 },
+			..state
+		};
+		db.store_keygen_state(state).unwrap();
+		assert_eq!(db.get_keygen_state().unwrap(), Some(state));
+
+		let job = |hash: u8| PendingSigningJob {
+			unsigned_proposal_hash: [hash; 32],
+			session_id: 1,
+			batch_id: hash as u32,
+			ssid: 0,
+		};
+		db.store_pending_signing_job(job(1)).unwrap();
+		db.store_pending_signing_job(job(2)).unwrap();
+		db.remove_pending_signing_job([1; 32]).unwrap();
+		assert_eq!(db.get_pending_signing_jobs().unwrap(), vec![job(2)]);
+	}
+}
@@ -1,5 +1,5 @@
 use crate::{
-	async_protocols::{remote::AsyncProtocolRemote, KeygenPartyId},
+	async_protocols::{frost::reshare::ReshareSets, remote::AsyncProtocolRemote, KeygenPartyId},
 	gossip_engine::GossipEngineIface,
 	utils::SendFuture,
 	worker::{DKGWorker, ProtoStageType},
@@ -34,7 +34,32 @@ pub enum KeygenProtocolSetupParameters<B: Block> {
 		stage: ProtoStageType,
 		keygen_protocol_hash: [u8; 32],
 	},
-	WTFrost {},
+	WTFrost {
+		best_authorities: Vec<(KeygenPartyId, Public)>,
+		authority_public_key: Public,
+		party_i: KeygenPartyId,
+		session_id: SessionId,
+		associated_block: NumberFor<B>,
+		threshold: u16,
+		/// The number of key shares held by each party, ordered as `best_authorities`
+		weights: Vec<u16>,
+		stage: ProtoStageType,
+		keygen_protocol_hash: [u8; 32],
+		/// The session whose key shares are refreshed instead of generating a new key
+		refresh_session: Option<SessionId>,
+		/// The key reshared to this keygen set instead of generating a new key
+		reshare_source: Option<KeyReshareSource>,
+	},
+}
+
+/// The key handed off by the outgoing keygen set during a resharing
+pub struct KeyReshareSource {
+	/// The session of the reshared key
+	pub session_id: SessionId,
+	/// The outgoing and incoming keygen sets, indexed by the participants of the resharing
+	pub sets: ReshareSets,
+	/// The reshared public key
+	pub public_key: Vec<u8>,
 }
 
 /// Setup parameters for the Signing protocol
@@ -56,7 +81,22 @@ pub enum SigningProtocolSetupParameters<B: Block> {
 		associated_block_id: NumberFor<B>,
 		ssid: u8,
 	},
-	WTFrost {},
+	WTFrost {
+		best_authorities: Vec<(KeygenPartyId, Public)>,
+		authority_public_key: Public,
+		party_i: KeygenPartyId,
+		session_id: SessionId,
+		stage: ProtoStageType,
+		unsigned_proposal_batch: StoredUnsignedProposalBatch<
+			BatchId,
+			MaxProposalLength,
+			MaxProposalsInBatch,
+			NumberFor<B>,
+		>,
+		signing_set: Vec<KeygenPartyId>,
+		associated_block_id: NumberFor<B>,
+		ssid: u8,
+	},
 }
 
 /// A type which is used directly by the Job Manager to initialize and manage the DKG protocol
@@ -96,7 +136,10 @@ where
 {
 	/// Loads the default DKG modules internally to be available at runtime
 	pub fn initialize(&self, dkg_worker: DKGWorker<B, BE, C, GE>) {
-		*self.dkgs.write() = vec![Arc::new(MpEcdsaDKG { dkg_worker }), Arc::new(WTFrostDKG {})]
+		*self.dkgs.write() = vec![
+			Arc::new(MpEcdsaDKG { dkg_worker: dkg_worker.clone() }),
+			Arc::new(WTFrostDKG { dkg_worker }),
+		]
 	}
 
 	/// Given a set of parameters, returns the keygen protocol initializer which can handle the
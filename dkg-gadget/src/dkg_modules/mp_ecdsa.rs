@@ -13,7 +13,6 @@ use dkg_primitives::types::DKGError;
 use dkg_runtime_primitives::{crypto::AuthorityId, DKGApi, MaxAuthorities, MaxProposalLength};
 use sc_client_api::Backend;
 use sp_runtime::traits::{Block, NumberFor};
-use std::sync::atomic::Ordering;
 
 /// DKG module for Multi-Party ECDSA
 pub struct MpEcdsaDKG<B, BE, C, GE>
@@ -69,7 +68,7 @@ where
 					let status = match stage {
 						ProtoStageType::KeygenGenesis => KeygenRound::Genesis,
 						ProtoStageType::KeygenStandard => KeygenRound::Next,
-						ProtoStageType::Signing { .. } => {
+						ProtoStageType::KeyReshare | ProtoStageType::Signing { .. } => {
 							unreachable!("Should not happen here")
 						},
 					};
@@ -87,12 +86,7 @@ where
 							let task = async move {
 								match meta_handler.await {
 									Ok(_) => {
-										keygen_manager.set_state(KeygenState::KeygenCompleted {
-											session_completed: session_id,
-										});
-										let _ = keygen_manager
-											.finished_count
-											.fetch_add(1, Ordering::SeqCst);
+										keygen_manager.keygen_completed(session_id);
 										signing_manager.keygen_unlock();
 										logger.info(
 											"The keygen meta handler has executed successfully"
@@ -1,27 +1,262 @@
-use crate::dkg_modules::{
-	KeygenProtocolSetupParameters, ProtocolInitReturn, SigningProtocolSetupParameters, DKG,
+use crate::{
+	async_protocols::{frost::FrostKeygenMode, GenericAsyncHandler, KeygenPartyId, KeygenRound},
+	dkg_modules::{
+		KeyReshareSource, KeygenProtocolSetupParameters, ProtocolInitReturn,
+		SigningProtocolSetupParameters, DKG,
+	},
+	gossip_engine::GossipEngineIface,
+	keygen_manager::KeygenState,
+	worker::{DKGWorker, ProtoStageType},
+	Client,
 };
 use async_trait::async_trait;
-use dkg_primitives::types::DKGError;
-use sp_runtime::traits::Block;
+use dkg_primitives::types::{DKGError, GE};
+use dkg_runtime_primitives::{
+	crypto::AuthorityId, DKGApi, MaxAuthorities, MaxProposalLength, SessionId,
+};
+use sc_client_api::Backend;
+use sp_runtime::traits::{Block, NumberFor};
 
 /// DKG module for Weighted Threshold Frost
-pub struct WTFrostDKG {}
+pub struct WTFrostDKG<B, BE, C, GE>
+where
+	B: Block,
+	BE: Backend<B>,
+	C: Client<B, BE>,
+	GE: GossipEngineIface,
+{
+	pub(super) dkg_worker: DKGWorker<B, BE, C, GE>,
+}
+
+impl<B, BE, C, GE> WTFrostDKG<B, BE, C, GE>
+where
+	B: Block,
+	BE: Backend<B> + Unpin + 'static,
+	C: Client<B, BE> + 'static,
+	GE: GossipEngineIface,
+	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
+{
+	/// Loads the local key of the outgoing keygen set when refreshing it, or dealing it to the
+	/// incoming keygen set
+	fn keygen_mode(
+		&self,
+		party_i: KeygenPartyId,
+		refresh_session: Option<SessionId>,
+		reshare_source: Option<KeyReshareSource>,
+	) -> Result<FrostKeygenMode, DKGError> {
+		let load_local_key = |session_id: SessionId| {
+			self.dkg_worker.db.get_frost_local_key(session_id)?.ok_or_else(|| {
+				DKGError::StartKeygen {
+					reason: format!(
+						"The WT-FROST local key of session {session_id} does not exist"
+					),
+				}
+			})
+		};
+
+		if let Some(session_id) = refresh_session {
+			return Ok(FrostKeygenMode::Refresh(load_local_key(session_id)?))
+		}
+
+		let Some(source) = reshare_source else { return Ok(FrostKeygenMode::Generate) };
+		let old_key = if source.sets.is_dealer(*party_i.as_ref()) {
+			Some(load_local_key(source.session_id)?)
+		} else {
+			None
+		};
+		let public_key = GE::from_bytes(&source.public_key).map_err(|err| {
+			DKGError::StartKeygen { reason: format!("Invalid reshared public key: {err:?}") }
+		})?;
+
+		Ok(FrostKeygenMode::Reshare { sets: source.sets, old_key, public_key })
+	}
+}
 
 #[async_trait]
-impl<B: Block> DKG<B> for WTFrostDKG {
+impl<B, BE, C, GE> DKG<B> for WTFrostDKG<B, BE, C, GE>
+where
+	B: Block,
+	BE: Backend<B> + Unpin + 'static,
+	C: Client<B, BE> + 'static,
+	GE: GossipEngineIface,
+	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
+{
 	async fn initialize_keygen_protocol(
 		&self,
-		_params: KeygenProtocolSetupParameters<B>,
+		params: KeygenProtocolSetupParameters<B>,
 	) -> Option<ProtocolInitReturn<B>> {
-		todo!()
+		if let KeygenProtocolSetupParameters::WTFrost {
+			best_authorities,
+			authority_public_key,
+			party_i,
+			session_id,
+			associated_block,
+			threshold,
+			weights,
+			stage,
+			keygen_protocol_hash,
+			refresh_session,
+			reshare_source,
+		} = params
+		{
+			const KEYGEN_SSID: u8 = 0;
+			let mode = match self.keygen_mode(party_i, refresh_session, reshare_source) {
+				Ok(mode) => mode,
+				Err(err) => {
+					self.dkg_worker
+						.logger
+						.error(format!("Cannot start the WT-FROST keygen: {err:?}"));
+					return None
+				},
+			};
+
+			match self.dkg_worker.generate_async_proto_params(
+				best_authorities,
+				authority_public_key,
+				party_i,
+				session_id,
+				stage,
+				crate::DKG_KEYGEN_PROTOCOL_NAME,
+				associated_block,
+				KEYGEN_SSID,
+			) {
+				Ok(async_proto_params) => {
+					let err_handler_tx = self.dkg_worker.error_handler_channel.tx.clone();
+
+					let remote = async_proto_params.handle.clone();
+					let keygen_manager = self.dkg_worker.keygen_manager.clone();
+					let status = match stage {
+						ProtoStageType::KeygenGenesis => KeygenRound::Genesis,
+						ProtoStageType::KeygenStandard | ProtoStageType::KeyReshare =>
+							KeygenRound::Next,
+						ProtoStageType::Signing { .. } => {
+							unreachable!("Should not happen here")
+						},
+					};
+
+					match GenericAsyncHandler::setup_frost_keygen(
+						async_proto_params,
+						threshold,
+						weights,
+						status,
+						keygen_protocol_hash,
+						mode,
+					) {
+						Ok(meta_handler) => {
+							let logger = self.dkg_worker.logger.clone();
+							let signing_manager = self.dkg_worker.signing_manager.clone();
+							signing_manager.keygen_lock();
+							let task = async move {
+								match meta_handler.await {
+									Ok(_) => {
+										keygen_manager.keygen_completed(session_id);
+										signing_manager.keygen_unlock();
+										logger.info(
+											"The WT-FROST keygen meta handler has executed successfully"
+												.to_string(),
+										);
+
+										Ok(())
+									},
+
+									Err(err) => {
+										logger.error(format!(
+											"Error executing WT-FROST meta handler {:?}",
+											&err
+										));
+										keygen_manager
+											.set_state(KeygenState::Failed { session_id });
+										signing_manager.keygen_unlock();
+										let _ = err_handler_tx.send(err.clone());
+										Err(err)
+									},
+								}
+							};
+
+							self.dkg_worker.logger.debug(format!("Created WT-FROST Keygen Protocol task for session {session_id} with status {status:?}"));
+							return Some((remote, Box::pin(task)))
+						},
+
+						Err(err) => {
+							self.dkg_worker
+								.logger
+								.error(format!("Error starting WT-FROST meta handler {:?}", &err));
+							self.dkg_worker.handle_dkg_error(err).await;
+						},
+					}
+				},
+
+				Err(err) => {
+					self.dkg_worker.handle_dkg_error(err).await;
+				},
+			}
+
+			None
+		} else {
+			unreachable!("Should not happen (keygen)")
+		}
 	}
 
 	async fn initialize_signing_protocol(
 		&self,
-		_params: SigningProtocolSetupParameters<B>,
+		params: SigningProtocolSetupParameters<B>,
 	) -> Result<ProtocolInitReturn<B>, DKGError> {
-		todo!()
+		if let SigningProtocolSetupParameters::WTFrost {
+			best_authorities,
+			authority_public_key,
+			party_i,
+			session_id,
+			stage,
+			unsigned_proposal_batch,
+			signing_set,
+			associated_block_id,
+			ssid,
+		} = params
+		{
+			self.dkg_worker.logger.debug(format!("{party_i:?} All Parameters: {best_authorities:?} | authority_pub_key: {authority_public_key:?} | session_id: {session_id:?} | stage: {stage:?} | unsigned_proposal_batch: {unsigned_proposal_batch:?} | signing_set: {signing_set:?} | associated_block_id: {associated_block_id:?}"));
+			let local_key = self.dkg_worker.db.get_frost_local_key(session_id)?;
+			let async_proto_params = self.dkg_worker.generate_async_proto_params(
+				best_authorities,
+				authority_public_key,
+				party_i,
+				session_id,
+				stage,
+				crate::DKG_SIGNING_PROTOCOL_NAME,
+				associated_block_id,
+				ssid,
+			)?;
+
+			let handle = async_proto_params.handle.clone();
+
+			let err_handler_tx = self.dkg_worker.error_handler_channel.tx.clone();
+			let meta_handler = GenericAsyncHandler::setup_frost_signing(
+				async_proto_params,
+				unsigned_proposal_batch,
+				signing_set,
+				local_key,
+			)?;
+			let logger = self.dkg_worker.logger.clone();
+			let task = async move {
+				match meta_handler.await {
+					Ok(_) => {
+						logger.info(
+							"The WT-FROST meta handler has executed successfully".to_string(),
+						);
+						Ok(())
+					},
+
+					Err(err) => {
+						logger.error(format!("Error executing WT-FROST meta handler {:?}", &err));
+						let _ = err_handler_tx.send(err.clone());
+						Err(err)
+					},
+				}
+			};
+
+			Ok((handle, Box::pin(task)))
+		} else {
+			unreachable!("Should not happen (signing)")
+		}
 	}
 
 	fn can_handle_keygen_request(&self, params: &KeygenProtocolSetupParameters<B>) -> bool {
@@ -55,6 +55,9 @@ pub trait GossipEngineIface: Send + Sync + 'static {
 
 	fn local_peer_id(&self) -> PeerId;
 	fn logger(&self) -> &DebugLogger;
+	/// Sets the current and next best authorities, which the gossiped messages are routed to.
+	/// Engines which do not know the authorities of their peers may ignore them.
+	fn set_authorities(&self, _authorities: Vec<AuthorityId>) {}
 }
 
 /// A Stub implementation of the GossipEngineIface.
@@ -155,4 +158,22 @@ impl HandshakeMessage {
 pub enum DKGNetworkMessage {
 	Handshake(HandshakeMessage),
 	DKGMessage(SignedDKGMessage<AuthorityId>),
+	/// The zstd compressed encoding of a [`DKGNetworkMessage::DKGMessage`].
+	Compressed(Vec<u8>),
+	/// A part of an encoded message which is too large for a single notification.
+	Chunk(MessageChunk),
+}
+
+/// A part of an encoded [`DKGNetworkMessage`] which is too large to be sent as a single
+/// notification. Only sent to the peers running the current version of the protocol.
+#[derive(Debug, Clone, PartialEq, Eq, codec::Decode, codec::Encode)]
+#[cfg_attr(feature = "scale-info", derive(scale_info::TypeInfo))]
+pub struct MessageChunk {
+	/// The blake2 hash of the whole encoded message.
+	pub message_id: [u8; 32],
+	/// The index of the chunk in the message.
+	pub index: u32,
+	/// The number of chunks of the message.
+	pub total: u32,
+	pub data: Vec<u8>,
 }
@@ -26,7 +26,7 @@
 //!
 //! From the [`GossipHandlerController`] which implements [`super::GossipEngineIface`], you can:
 //!  - send a DKG message to a specific peer.
-//!  - send a DKG message to all peers.
+//!  - send a DKG message to all the authorities.
 //!  - get a notification stream when you get a DKG message.
 //!  - Have access to the message queue, which is a FIFO queue of DKG messages.
 //!
@@ -39,15 +39,42 @@
 //! peers or only to a specific peer. on the other end, the DKG message is received by the DKG
 //! engine, and it is verified then it will be added to the Engine's internal stream of DKG
 //! messages, later the DKG Gadget will read this stream and process the DKG message.
+//!
+//! ### Routing:
+//!
+//! The peers tell us their authority id in the handshake. Once the worker set the current and next
+//! best authorities with [`super::GossipEngineIface::set_authorities`], the gossiped messages are
+//! only sent to the connected authorities, which relay them to the authorities they are connected
+//! to. When some authorities (or the recipient of the message) are not connected to us, the
+//! message is also relayed through up to [`MAX_RELAY_PEERS`] peers which are not authorities.
+//! Before the authorities are known, the messages are sent to all the peers.
+//!
+//! ### Compression and Chunking:
+//!
+//! The peers running the current version of the protocol are sent the DKG messages larger than
+//! [`COMPRESSION_THRESHOLD`] zstd compressed, and the ones still larger than [`MAX_CHUNK_SIZE`]
+//! split in chunks, which are reassembled by the receiver. Each peer may only have
+//! [`MAX_INCOMPLETE_MESSAGES_PER_PEER`] messages partially received, whose chunks may not take more
+//! than [`MAX_INCOMPLETE_BYTES_PER_PEER`], and the chunks of all the peers more than
+//! [`MAX_INCOMPLETE_BYTES`]. The messages still incomplete after [`INCOMPLETE_MESSAGE_TIMEOUT`] are
+//! dropped. The peers which negotiated the legacy version of the protocol are sent the messages
+//! uncompressed and whole.
 
 pub use crate::constants::network::*;
-use crate::{debug_logger::DebugLogger, metrics::Metrics, worker::HasLatestHeader, DKGKeystore};
+use crate::{
+	constants::{LEGACY_DKG_KEYGEN_PROTOCOL_NAME, LEGACY_DKG_SIGNING_PROTOCOL_NAME},
+	debug_logger::DebugLogger,
+	metrics::Metrics,
+	worker::HasLatestHeader,
+	DKGKeystore, DKG_KEYGEN_PROTOCOL_NAME, DKG_SIGNING_PROTOCOL_NAME,
+};
 use codec::{Decode, Encode};
 use dkg_primitives::types::{DKGError, SignedDKGMessage};
 use dkg_runtime_primitives::crypto::AuthorityId;
 use futures::StreamExt;
 use linked_hash_map::LinkedHashMap;
 use parking_lot::{Mutex, RwLock};
+use rand::seq::SliceRandom;
 use sc_network::{
 	config, error, multiaddr, Event, NetworkEventStream, NetworkNotification, NetworkPeers,
 	NetworkService, NetworkStateInfo, PeerId, ProtocolName, SyncEventStream,
@@ -65,6 +92,7 @@ use std::{
 		atomic::{AtomicBool, Ordering},
 		Arc,
 	},
+	time::Instant,
 };
 use tokio::sync::mpsc::UnboundedReceiver;
 
@@ -84,8 +112,10 @@ impl NetworkGossipEngineBuilder {
 	pub fn set_config(protocol_name: ProtocolName) -> config::NonDefaultSetConfig {
 		config::NonDefaultSetConfig {
 			handshake: None,
+			// the peers which only support the legacy protocol are sent uncompressed and whole
+			// messages
+			fallback_names: legacy_protocol_names(&protocol_name),
 			notifications_protocol: protocol_name,
-			fallback_names: Vec::new(),
 			max_notification_size: MAX_MESSAGE_SIZE,
 			set_config: config::SetConfig {
 				in_peers: 0,
@@ -120,6 +150,7 @@ impl NetworkGossipEngineBuilder {
 		let (handler_channel, handler_channel_rx) = tokio::sync::mpsc::unbounded_channel();
 		let (message_channel_tx, message_channel_rx) = tokio::sync::mpsc::unbounded_channel();
 		let gossip_enabled = Arc::new(AtomicBool::new(false));
+		let authorities = Arc::new(RwLock::new(None));
 		let handler = GossipHandler {
 			latest_header,
 			keystore: self.keystore,
@@ -130,6 +161,7 @@ impl NetworkGossipEngineBuilder {
 				NonZeroUsize::new(MAX_KNOWN_MESSAGES).expect("Constant is nonzero"),
 			))),
 			authority_id_to_peer_id: Arc::new(RwLock::new(HashMap::new())),
+			authorities: authorities.clone(),
 			gossip_enabled: gossip_enabled.clone(),
 			service,
 			sync_service,
@@ -142,9 +174,11 @@ impl NetworkGossipEngineBuilder {
 		let controller = GossipHandlerController {
 			local_peer_id,
 			protocol_name: self.protocol_name,
+			keystore: handler.keystore.clone(),
 			handler_channel,
 			message_notifications_channel: Arc::new(Mutex::new(Some(message_channel_rx))),
 			gossip_enabled,
+			authorities,
 			logger,
 			_pd: Default::default(),
 		};
@@ -164,6 +198,11 @@ mod rep {
 	pub const PEER_IMPERSONATED: Rep = Rep::new_fatal("Peer is impersonating another peer");
 	/// Reputation change when a peer sends us the same message over and over.
 	pub const DUPLICATE_MESSAGE: Rep = Rep::new(-(1 << 12), "Duplicate message");
+	/// Reputation change when a peer starts sending us more chunked messages than allowed.
+	pub const TOO_MANY_INCOMPLETE_MESSAGES: Rep =
+		Rep::new(-(1 << 10), "Too many incomplete messages");
+	/// Reputation change when the chunks a peer sent us exceed the buffered bytes limits.
+	pub const TOO_MUCH_INCOMPLETE_DATA: Rep = Rep::new(-(1 << 10), "Too much incomplete data");
 }
 
 /// Controls the behaviour of a [`GossipHandler`] it is connected to.
@@ -171,6 +210,8 @@ mod rep {
 pub struct GossipHandlerController<B: Block> {
 	local_peer_id: PeerId,
 	protocol_name: ProtocolName,
+	/// The DKG Keystore, used to leave our own authority out of the routing.
+	keystore: DKGKeystore,
 	/// a channel to send commands to the background task (Controller -> Background).
 	handler_channel: tokio::sync::mpsc::UnboundedSender<ToHandler>,
 	/// where messages are received
@@ -178,6 +219,8 @@ pub struct GossipHandlerController<B: Block> {
 		Arc<Mutex<Option<UnboundedReceiver<SignedDKGMessage<AuthorityId>>>>>,
 	/// Whether the gossip mechanism is enabled or not.
 	gossip_enabled: Arc<AtomicBool>,
+	/// The authorities the gossiped messages are routed to, shared with the handler.
+	authorities: Arc<RwLock<Option<HashSet<AuthorityId>>>>,
 	logger: DebugLogger,
 	/// Used to keep type information about the block. May
 	/// be useful for the future, so keeping it here
@@ -222,13 +265,22 @@ impl<B: Block> super::GossipEngineIface for GossipHandlerController<B> {
 	fn get_stream(&self) -> Option<UnboundedReceiver<SignedDKGMessage<AuthorityId>>> {
 		self.message_notifications_channel.lock().take()
 	}
+
+	fn set_authorities(&self, authorities: Vec<AuthorityId>) {
+		let local_authority_id = self.keystore.authority_id(&authorities);
+		let remote_authorities = authorities
+			.into_iter()
+			.filter(|authority_id| Some(authority_id) != local_authority_id.as_ref())
+			.collect();
+		*self.authorities.write() = Some(remote_authorities);
+	}
 }
 /// an Enum Representing the commands that can be sent to the background task.
 #[derive(Clone, Debug)]
 enum ToHandler {
 	/// Send a DKG message to a peer.
 	SendMessage { recipient: PeerId, message: SignedDKGMessage<AuthorityId> },
-	/// Gossip a DKG message to all peers.
+	/// Gossip a DKG message to the authorities.
 	Gossip(SignedDKGMessage<AuthorityId>),
 }
 
@@ -267,6 +319,10 @@ pub struct GossipHandler<B: Block + 'static> {
 	///
 	/// This is used to send messages to specific peer by knowing the authority id.
 	authority_id_to_peer_id: Arc<RwLock<HashMap<AuthorityId, PeerId>>>,
+	/// The current and next best authorities, other than us, the gossiped messages are routed to.
+	///
+	/// `None` until the worker sets them, in which case the messages are sent to all peers.
+	authorities: Arc<RwLock<Option<HashSet<AuthorityId>>>>,
 	/// Whether the gossip mechanism is enabled or not.
 	gossip_enabled: Arc<AtomicBool>,
 	logger: DebugLogger,
@@ -287,6 +343,7 @@ impl<B: Block + 'static> Clone for GossipHandler<B> {
 			sync_service: self.sync_service.clone(),
 			peers: self.peers.clone(),
 			authority_id_to_peer_id: self.authority_id_to_peer_id.clone(),
+			authorities: self.authorities.clone(),
 			gossip_enabled: self.gossip_enabled.clone(),
 			logger: self.logger.clone(),
 			metrics: self.metrics.clone(),
@@ -320,6 +377,25 @@ struct Peer<B: Block> {
 	///
 	/// Could be None if that peer did not handshake with us yet.
 	authority_id: Option<AuthorityId>,
+
+	/// Whether the peer negotiated the legacy version of the protocol, which does not support
+	/// compressed and chunked messages.
+	legacy_protocol: bool,
+	/// The chunks received so far of the messages the peer is sending us, by message id.
+	///
+	/// Bounded by `MAX_INCOMPLETE_MESSAGES_PER_PEER` and `MAX_INCOMPLETE_BYTES_PER_PEER`, in the
+	/// order their first chunk was received.
+	incomplete_messages: LinkedHashMap<[u8; 32], IncompleteMessage>,
+}
+
+/// The chunks received so far of a message sent by a peer.
+#[derive(Debug)]
+struct IncompleteMessage {
+	chunks: Vec<Option<Vec<u8>>>,
+	/// The size of the chunks received so far.
+	size: usize,
+	/// When the first chunk was received.
+	started_at: Instant,
 }
 
 impl<B: Block + 'static> GossipHandler<B> {
@@ -417,10 +493,12 @@ impl<B: Block + 'static> GossipHandler<B> {
 	async fn handle_network_event(&self, event: Event) {
 		match event {
 			Event::Dht(_) => {},
-			Event::NotificationStreamOpened { remote, protocol, .. }
+			Event::NotificationStreamOpened { remote, protocol, negotiated_fallback, .. }
 				if protocol == self.protocol_name =>
 			{
-				self.logger.debug(format!("Peer {remote} connected to gossip protocol"));
+				self.logger.debug(format!(
+					"Peer {remote} connected to gossip protocol (fallback: {negotiated_fallback:?})"
+				));
 				// Send our Handshake message to that peer.
 				if let Err(err) = self.send_handshake_message(remote).await {
 					self.logger
@@ -442,6 +520,8 @@ impl<B: Block + 'static> GossipHandler<B> {
 						// None. We will update it once we receive the handshake message from that
 						// peer.
 						authority_id: None,
+						legacy_protocol: negotiated_fallback.is_some(),
+						incomplete_messages: LinkedHashMap::new(),
 					},
 				);
 				debug_assert!(_was_in.is_none());
@@ -476,10 +556,10 @@ impl<B: Block + 'static> GossipHandler<B> {
 						continue
 					}
 					self.logger.debug(format!("Received message from {remote} from gossiping"));
-					let maybe_dkg_network_message =
-						<super::DKGNetworkMessage as Decode>::decode(&mut message.as_ref());
-					let m = match maybe_dkg_network_message {
-						Ok(m) => m,
+					let m = match self.unpack_network_message(remote, message.as_ref()) {
+						Ok(Some(m)) => m,
+						// the other chunks of the message are yet to be received
+						Ok(None) => continue,
 						Err(e) => {
 							self.logger.warn(format!("Failed to decode DKG Network message from peer {remote} with error: {e:?}"));
 							self.service.report_peer(remote, rep::UNEXPECTED_MESSAGE);
@@ -491,6 +571,9 @@ impl<B: Block + 'static> GossipHandler<B> {
 							self.on_handshake_message(remote, h).await,
 						super::DKGNetworkMessage::DKGMessage(s) =>
 							self.on_signed_dkg_message(remote, s).await,
+						// unpacked above
+						super::DKGNetworkMessage::Compressed(_) |
+						super::DKGNetworkMessage::Chunk(_) => {},
 					};
 				}
 			},
@@ -499,6 +582,83 @@ impl<B: Block + 'static> GossipHandler<B> {
 		}
 	}
 
+	/// Decodes a notification received from a peer, reassembling and decompressing the messages
+	/// sent in parts. Returns `None` while some chunks of the message are missing.
+	fn unpack_network_message(
+		&self,
+		who: PeerId,
+		notification: &[u8],
+	) -> Result<Option<super::DKGNetworkMessage>, DKGError> {
+		let mut message = decode_network_message(notification)?;
+		if let super::DKGNetworkMessage::Chunk(chunk) = message {
+			let message_id = chunk.message_id;
+			let payload = {
+				let now = Instant::now();
+				let mut peers = self.peers.write();
+				let mut buffered = 0;
+				for peer in peers.values_mut() {
+					evict_expired_messages(&mut peer.incomplete_messages, now);
+					buffered += buffered_size(&peer.incomplete_messages);
+				}
+				let Some(peer) = peers.get_mut(&who) else {
+					return Ok(None)
+				};
+				if !peer.incomplete_messages.contains_key(&message_id) &&
+					peer.incomplete_messages.len() >= MAX_INCOMPLETE_MESSAGES_PER_PEER
+				{
+					self.logger.warn(format!(
+						"Peer {who} sent too many incomplete messages, dropping the oldest one"
+					));
+					if let Some((_, dropped)) = peer.incomplete_messages.pop_front() {
+						buffered -= dropped.size;
+					}
+					self.service.report_peer(who, rep::TOO_MANY_INCOMPLETE_MESSAGES);
+				}
+				let evicted =
+					evict_for_chunk(&mut peer.incomplete_messages, &message_id, chunk.data.len());
+				if evicted > 0 {
+					self.logger.warn(format!(
+						"Peer {who} sent too much incomplete data, dropping its oldest messages"
+					));
+					buffered -= evicted;
+					self.service.report_peer(who, rep::TOO_MUCH_INCOMPLETE_DATA);
+				}
+				if buffered + chunk.data.len() > MAX_INCOMPLETE_BYTES {
+					self.logger.warn(format!(
+						"Too much incomplete data buffered, dropping message {message_id:?} of peer {who}"
+					));
+					peer.incomplete_messages.remove(&message_id);
+					self.service.report_peer(who, rep::TOO_MUCH_INCOMPLETE_DATA);
+					return Ok(None)
+				}
+				reassemble_chunk(&mut peer.incomplete_messages, chunk, now)?
+			};
+			let Some(payload) = payload else {
+				return Ok(None)
+			};
+			// hashed once the lock is released, as the message may be large
+			check_message_id(&message_id, &payload)?;
+			message = decode_network_message(&payload)?;
+		}
+
+		if let super::DKGNetworkMessage::Compressed(compressed) = message {
+			let payload =
+				sp_maybe_compressed_blob::decompress(&compressed, MAX_REASSEMBLED_MESSAGE_SIZE)
+					.map_err(|e| DKGError::GenericError {
+						reason: format!("Failed to decompress message: {e:?}"),
+					})?;
+			message = decode_network_message(&payload)?;
+		}
+
+		match message {
+			super::DKGNetworkMessage::Compressed(_) | super::DKGNetworkMessage::Chunk(_) =>
+				Err(DKGError::GenericError {
+					reason: "Nested compressed or chunked message".into(),
+				}),
+			message => Ok(Some(message)),
+		}
+	}
+
 	/// Creates and sends handshake message to the peer.
 	async fn send_handshake_message(&self, to_who: PeerId) -> Result<(), DKGError> {
 		let my_peer_id = self.service.local_peer_id();
@@ -616,21 +776,63 @@ impl<B: Block + 'static> GossipHandler<B> {
 	}
 
 	pub fn send_signed_dkg_message(&self, to_who: PeerId, message: SignedDKGMessage<AuthorityId>) {
+		self.send_signed_dkg_message_to_peers(vec![to_who], message)
+	}
+
+	/// Sends the message to the peers which do not know it yet. The message is packed once for
+	/// each version of the protocol run by the peers.
+	fn send_signed_dkg_message_to_peers(
+		&self,
+		peer_ids: Vec<PeerId>,
+		message: SignedDKGMessage<AuthorityId>,
+	) {
 		let message_hash = message.message_hash::<B>();
-		if let Some(ref mut peer) = self.peers.write().get_mut(&to_who) {
-			let new_to_them = peer.known_messages.insert(message_hash);
-			if !new_to_them {
-				return
+		let recipients = {
+			let mut peers = self.peers.write();
+			peer_ids
+				.into_iter()
+				.filter_map(|peer_id| match peers.get_mut(&peer_id) {
+					Some(peer) => peer
+						.known_messages
+						.insert(message_hash)
+						.then_some((peer_id, peer.legacy_protocol)),
+					None => {
+						self.logger.debug(format!("Peer {peer_id} does not exist in known peers"));
+						None
+					},
+				})
+				.collect::<Vec<_>>()
+		};
+		if recipients.is_empty() {
+			return
+		}
+
+		let payload = Encode::encode(&super::DKGNetworkMessage::DKGMessage(message));
+		let mut packed_notifications = HashMap::new();
+		for (peer_id, legacy_protocol) in recipients {
+			let notifications = packed_notifications
+				.entry(legacy_protocol)
+				.or_insert_with(|| pack_notifications(payload.clone(), legacy_protocol));
+			if notifications
+				.iter()
+				.any(|notification| notification.len() as u64 > MAX_MESSAGE_SIZE)
+			{
+				self.logger.warn(format!(
+					"Message {message_hash} is too large to be sent to peer {peer_id}"
+				));
+				continue
+			}
+			for notification in notifications.iter() {
+				self.service.write_notification(
+					peer_id,
+					self.protocol_name.clone(),
+					notification.clone(),
+				);
 			}
-			let message = super::DKGNetworkMessage::DKGMessage(message);
-			let msg = Encode::encode(&message);
-			self.service.write_notification(to_who, self.protocol_name.clone(), msg);
 
 			if let Some(metrics) = self.metrics.as_ref() {
 				metrics.dkg_propagated_messages.inc();
 			}
-		} else {
-			self.logger.debug(format!("Peer {to_who} does not exist in known peers"));
 		}
 	}
 
@@ -643,31 +845,219 @@ impl<B: Block + 'static> GossipHandler<B> {
 		// If we have a peer id, we send the message to that peer directly.
 		if let Some(peer_id) = maybe_peer_id {
 			self.logger.debug(format!("Sending message to recipient {peer_id} using p2p"));
-			self.send_signed_dkg_message(peer_id, message.clone());
-		// potential bug "fix"
-		//return
+			self.send_signed_dkg_message(peer_id, message);
+			return
 		} else if let Some(recipient_id) = &message.msg.recipient_id {
 			self.logger.debug(format!(
-				"No direct connection to {recipient_id}, falling back to gossiping"
+				"No direct connection to {recipient_id}, relaying message through the authorities"
 			));
 		} else {
-			self.logger
-				.debug("No specific recipient, broadcasting message to all peers".to_string());
+			self.logger.debug(
+				"No specific recipient, broadcasting message to the authorities".to_string(),
+			);
 		}
-		// Otherwise, we fallback to sending the message to all peers.
+		// Otherwise, we send the message to the authorities, which relay it.
 		let peer_ids = {
 			let peers_map = self.peers.read();
-			peers_map.keys().cloned().collect::<Vec<_>>()
+			gossip_targets(
+				peers_map.iter().map(|(peer_id, peer)| (*peer_id, peer.authority_id.as_ref())),
+				self.authorities.read().as_ref(),
+				message.msg.recipient_id.as_ref(),
+			)
 		};
 		if peer_ids.is_empty() {
 			let message_hash = message.message_hash::<B>();
 			self.logger.warn(format!("No peers to gossip message {message_hash}"));
 			return
 		}
-		for peer in peer_ids {
-			self.send_signed_dkg_message(peer, message.clone());
+		self.send_signed_dkg_message_to_peers(peer_ids, message);
+	}
+}
+
+/// Returns the peers a gossiped message is sent to: the connected authorities, and up to
+/// [`MAX_RELAY_PEERS`] other peers when some authorities or the recipient are not connected to us.
+///
+/// All the peers are returned when the authorities are not known.
+fn gossip_targets<'a>(
+	peers: impl IntoIterator<Item = (PeerId, Option<&'a AuthorityId>)>,
+	authorities: Option<&HashSet<AuthorityId>>,
+	recipient_id: Option<&AuthorityId>,
+) -> Vec<PeerId> {
+	let Some(authorities) = authorities else {
+		return peers.into_iter().map(|(peer_id, _)| peer_id).collect()
+	};
+
+	let (authority_peers, mut other_peers): (Vec<_>, Vec<_>) =
+		peers.into_iter().partition(|(_, authority_id)| {
+			authority_id.map_or(false, |authority_id| authorities.contains(authority_id))
+		});
+	let connected_authorities = authority_peers
+		.iter()
+		.filter_map(|(_, authority_id)| *authority_id)
+		.collect::<HashSet<_>>();
+	let mut targets = authority_peers.into_iter().map(|(peer_id, _)| peer_id).collect::<Vec<_>>();
+
+	let unreachable_recipient =
+		recipient_id.map_or(false, |recipient_id| !connected_authorities.contains(recipient_id));
+	if unreachable_recipient || connected_authorities.len() < authorities.len() {
+		other_peers.shuffle(&mut rand::thread_rng());
+		targets.extend(other_peers.into_iter().take(MAX_RELAY_PEERS).map(|(peer_id, _)| peer_id));
+	}
+
+	targets
+}
+
+/// Returns the previous versions of the DKG protocols, which do not support compressed and chunked
+/// messages
+fn legacy_protocol_names(protocol_name: &str) -> Vec<ProtocolName> {
+	match protocol_name {
+		DKG_KEYGEN_PROTOCOL_NAME => vec![LEGACY_DKG_KEYGEN_PROTOCOL_NAME.into()],
+		DKG_SIGNING_PROTOCOL_NAME => vec![LEGACY_DKG_SIGNING_PROTOCOL_NAME.into()],
+		_ => Vec::new(),
+	}
+}
+
+fn decode_network_message(mut bytes: &[u8]) -> Result<super::DKGNetworkMessage, DKGError> {
+	<super::DKGNetworkMessage as Decode>::decode(&mut bytes)
+		.map_err(|e| DKGError::GenericError { reason: format!("{e:?}") })
+}
+
+/// Packs an encoded message into the notifications sent to a peer. For the peers running the
+/// current version of the protocol, large messages are compressed, then split in chunks if they
+/// are still larger than [`MAX_CHUNK_SIZE`].
+fn pack_notifications(mut payload: Vec<u8>, legacy_protocol: bool) -> Vec<Vec<u8>> {
+	if legacy_protocol {
+		return vec![payload]
+	}
+
+	if payload.len() >= COMPRESSION_THRESHOLD {
+		let compressed = sp_maybe_compressed_blob::compress(&payload, MAX_REASSEMBLED_MESSAGE_SIZE)
+			.map(|compressed| Encode::encode(&super::DKGNetworkMessage::Compressed(compressed)));
+		// incompressible messages are sent as they are
+		if let Some(compressed) = compressed.filter(|compressed| compressed.len() < payload.len()) {
+			payload = compressed;
 		}
 	}
+
+	if payload.len() <= MAX_CHUNK_SIZE {
+		return vec![payload]
+	}
+
+	let message_id = sp_core::hashing::blake2_256(&payload);
+	let total = payload.chunks(MAX_CHUNK_SIZE).len() as u32;
+	payload
+		.chunks(MAX_CHUNK_SIZE)
+		.enumerate()
+		.map(|(index, data)| {
+			Encode::encode(&super::DKGNetworkMessage::Chunk(super::MessageChunk {
+				message_id,
+				index: index as u32,
+				total,
+				data: data.to_vec(),
+			}))
+		})
+		.collect()
+}
+
+/// Stores a chunk of a message sent by a peer. Returns the encoded message once all its chunks are
+/// received, which is yet to be checked against its id with [`check_message_id`].
+fn reassemble_chunk(
+	incomplete_messages: &mut LinkedHashMap<[u8; 32], IncompleteMessage>,
+	chunk: super::MessageChunk,
+	now: Instant,
+) -> Result<Option<Vec<u8>>, DKGError> {
+	let super::MessageChunk { message_id, index, total, data } = chunk;
+	let invalid_chunk = |reason: &str| DKGError::GenericError {
+		reason: format!("Invalid chunk {index}/{total} of message {message_id:?}: {reason}"),
+	};
+	if index >= total {
+		return Err(invalid_chunk("index out of bounds"))
+	}
+	if total as usize > MAX_REASSEMBLED_MESSAGE_SIZE / MAX_CHUNK_SIZE {
+		return Err(invalid_chunk("too many chunks"))
+	}
+	if data.len() > MAX_CHUNK_SIZE {
+		return Err(invalid_chunk("chunk too large"))
+	}
+
+	let message = incomplete_messages.entry(message_id).or_insert_with(|| IncompleteMessage {
+		chunks: vec![None; total as usize],
+		size: 0,
+		started_at: now,
+	});
+	if message.chunks.len() != total as usize {
+		return Err(invalid_chunk("inconsistent number of chunks"))
+	}
+	message.size += data.len();
+	if let Some(replaced) = message.chunks[index as usize].replace(data) {
+		message.size -= replaced.len();
+	}
+	if message.chunks.iter().any(Option::is_none) {
+		return Ok(None)
+	}
+
+	let payload = incomplete_messages
+		.remove(&message_id)
+		.map(|message| message.chunks)
+		.unwrap_or_default()
+		.into_iter()
+		.flatten()
+		.flatten()
+		.collect::<Vec<_>>();
+	Ok(Some(payload))
+}
+
+/// Checks that a message reassembled from its chunks matches its id.
+fn check_message_id(message_id: &[u8; 32], payload: &[u8]) -> Result<(), DKGError> {
+	if sp_core::hashing::blake2_256(payload) != *message_id {
+		return Err(DKGError::GenericError {
+			reason: format!("Reassembled message does not match its id {message_id:?}"),
+		})
+	}
+	Ok(())
+}
+
+/// The size of the chunks buffered for the incomplete messages of a peer.
+fn buffered_size(incomplete_messages: &LinkedHashMap<[u8; 32], IncompleteMessage>) -> usize {
+	incomplete_messages.values().map(|message| message.size).sum()
+}
+
+/// Drops the incomplete messages whose first chunk was received more than
+/// `INCOMPLETE_MESSAGE_TIMEOUT` ago.
+fn evict_expired_messages(
+	incomplete_messages: &mut LinkedHashMap<[u8; 32], IncompleteMessage>,
+	now: Instant,
+) {
+	while incomplete_messages.front().map_or(false, |(_, message)| {
+		now.saturating_duration_since(message.started_at) >= INCOMPLETE_MESSAGE_TIMEOUT
+	}) {
+		incomplete_messages.pop_front();
+	}
+}
+
+/// Drops the oldest incomplete messages of a peer, other than the one the chunk belongs to, until
+/// the chunk fits in `MAX_INCOMPLETE_BYTES_PER_PEER`. Returns the size of the dropped chunks.
+fn evict_for_chunk(
+	incomplete_messages: &mut LinkedHashMap<[u8; 32], IncompleteMessage>,
+	message_id: &[u8; 32],
+	chunk_size: usize,
+) -> usize {
+	let mut buffered = buffered_size(incomplete_messages);
+	let oldest = incomplete_messages
+		.iter()
+		.filter(|(id, _)| *id != message_id)
+		.map(|(id, message)| (*id, message.size))
+		.collect::<Vec<_>>();
+	let mut evicted = 0;
+	for (id, size) in oldest {
+		if buffered + chunk_size <= MAX_INCOMPLETE_BYTES_PER_PEER {
+			break
+		}
+		incomplete_messages.remove(&id);
+		buffered -= size;
+		evicted += size;
+	}
+	evicted
 }
 
 /// Wrapper around `LinkedHashMap` with bounded growth.
@@ -730,3 +1120,187 @@ impl<T: Hash + Eq> LruHashSet<T> {
 		self.set.insert(e, ())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{gossip_engine::DKGNetworkMessage, keyring::Keyring};
+
+	fn peers(authorities: &[Option<AuthorityId>]) -> Vec<(PeerId, Option<AuthorityId>)> {
+		authorities
+			.iter()
+			.map(|authority_id| (PeerId::random(), authority_id.clone()))
+			.collect()
+	}
+
+	fn targets(
+		peers: &[(PeerId, Option<AuthorityId>)],
+		authorities: Option<&HashSet<AuthorityId>>,
+		recipient_id: Option<&AuthorityId>,
+	) -> HashSet<PeerId> {
+		gossip_targets(
+			peers.iter().map(|(peer_id, authority_id)| (*peer_id, authority_id.as_ref())),
+			authorities,
+			recipient_id,
+		)
+		.into_iter()
+		.collect()
+	}
+
+	#[test]
+	fn messages_are_sent_to_all_peers_until_the_authorities_are_known() {
+		let peers = peers(&[Some(Keyring::Alice.public()), None, None]);
+		let all_peers = peers.iter().map(|(peer_id, _)| *peer_id).collect();
+		assert_eq!(targets(&peers, None, None), all_peers);
+	}
+
+	#[test]
+	fn messages_are_only_sent_to_the_connected_authorities() {
+		let authorities = HashSet::from([Keyring::Alice.public(), Keyring::Bob.public()]);
+		let peers = peers(&[
+			Some(Keyring::Alice.public()),
+			Some(Keyring::Bob.public()),
+			Some(Keyring::Charlie.public()),
+			None,
+		]);
+		let authority_peers = HashSet::from([peers[0].0, peers[1].0]);
+		assert_eq!(targets(&peers, Some(&authorities), None), authority_peers);
+		assert_eq!(
+			targets(&peers, Some(&authorities), Some(&Keyring::Bob.public())),
+			authority_peers
+		);
+	}
+
+	#[test]
+	fn messages_are_relayed_through_a_bounded_number_of_peers() {
+		let authorities = HashSet::from([Keyring::Alice.public(), Keyring::Bob.public()]);
+		let mut peers = peers(&[Some(Keyring::Alice.public())]);
+		peers.extend((0..2 * MAX_RELAY_PEERS).map(|_| (PeerId::random(), None)));
+
+		// bob is not connected to us
+		let targets = targets(&peers, Some(&authorities), None);
+		assert_eq!(targets.len(), 1 + MAX_RELAY_PEERS);
+		assert!(targets.contains(&peers[0].0));
+	}
+
+	fn unpack_notifications(
+		notifications: Vec<Vec<u8>>,
+	) -> Result<Option<DKGNetworkMessage>, DKGError> {
+		let mut incomplete_messages = LinkedHashMap::new();
+		let mut message = None;
+		for notification in notifications {
+			message = match decode_network_message(&notification)? {
+				DKGNetworkMessage::Chunk(chunk) => {
+					let message_id = chunk.message_id;
+					match reassemble_chunk(&mut incomplete_messages, chunk, Instant::now())? {
+						Some(payload) => {
+							check_message_id(&message_id, &payload)?;
+							Some(decode_network_message(&payload)?)
+						},
+						None => None,
+					}
+				},
+				message => Some(message),
+			};
+		}
+		Ok(message)
+	}
+
+	fn decompressed(message: Option<DKGNetworkMessage>) -> Vec<u8> {
+		match message {
+			Some(DKGNetworkMessage::Compressed(compressed)) =>
+				sp_maybe_compressed_blob::decompress(&compressed, MAX_REASSEMBLED_MESSAGE_SIZE)
+					.unwrap()
+					.to_vec(),
+			message => panic!("Expected a compressed message, got {message:?}"),
+		}
+	}
+
+	#[test]
+	fn large_messages_are_compressed_for_the_current_protocol() {
+		let payload = vec![7u8; 4 * MAX_CHUNK_SIZE];
+		assert_eq!(pack_notifications(payload.clone(), true), vec![payload.clone()]);
+
+		let notifications = pack_notifications(payload.clone(), false);
+		assert_eq!(notifications.len(), 1);
+		assert_eq!(decompressed(unpack_notifications(notifications).unwrap()), payload);
+	}
+
+	#[test]
+	fn incompressible_messages_are_split_in_chunks() {
+		let message = DKGNetworkMessage::Compressed(
+			(0..2 * MAX_CHUNK_SIZE).map(|_| rand::random::<u8>()).collect(),
+		);
+		let payload = Encode::encode(&message);
+
+		let notifications = pack_notifications(payload.clone(), false);
+		assert_eq!(notifications.len(), 3);
+		assert!(notifications
+			.iter()
+			.all(|notification| notification.len() as u64 <= MAX_MESSAGE_SIZE));
+		let unpacked = unpack_notifications(notifications.clone()).unwrap();
+		assert_eq!(unpacked.map(|message| Encode::encode(&message)), Some(payload));
+
+		// a missing chunk leaves the message incomplete
+		let mut incomplete = notifications.clone();
+		incomplete.remove(1);
+		assert!(unpack_notifications(incomplete).unwrap().is_none());
+
+		// a tampered chunk does not match the message id
+		let mut tampered = notifications;
+		let last = tampered[2].len() - 1;
+		tampered[2][last] ^= 0xff;
+		assert!(unpack_notifications(tampered).is_err());
+	}
+
+	fn chunk(message_id: u8, index: u32, total: u32) -> crate::gossip_engine::MessageChunk {
+		crate::gossip_engine::MessageChunk {
+			message_id: [message_id; 32],
+			index,
+			total,
+			data: vec![0; MAX_CHUNK_SIZE],
+		}
+	}
+
+	#[test]
+	fn incomplete_messages_expire() {
+		let mut incomplete_messages = LinkedHashMap::new();
+		let start = Instant::now();
+		reassemble_chunk(&mut incomplete_messages, chunk(1, 0, 2), start).unwrap();
+		reassemble_chunk(
+			&mut incomplete_messages,
+			chunk(2, 0, 2),
+			start + INCOMPLETE_MESSAGE_TIMEOUT / 2,
+		)
+		.unwrap();
+		assert_eq!(buffered_size(&incomplete_messages), 2 * MAX_CHUNK_SIZE);
+
+		evict_expired_messages(&mut incomplete_messages, start + INCOMPLETE_MESSAGE_TIMEOUT);
+		assert_eq!(incomplete_messages.keys().collect::<Vec<_>>(), vec![&[2; 32]]);
+	}
+
+	#[test]
+	fn incomplete_messages_of_a_peer_are_bounded_in_size() {
+		let mut incomplete_messages = LinkedHashMap::new();
+		let now = Instant::now();
+		let total = (MAX_INCOMPLETE_BYTES_PER_PEER / MAX_CHUNK_SIZE) as u32;
+		// resending a chunk does not count it twice
+		for _ in 0..2 {
+			for index in 0..total / 2 {
+				reassemble_chunk(&mut incomplete_messages, chunk(1, index, total), now).unwrap();
+			}
+		}
+		for index in 0..total / 2 {
+			reassemble_chunk(&mut incomplete_messages, chunk(2, index, total), now).unwrap();
+		}
+		assert_eq!(buffered_size(&incomplete_messages), MAX_INCOMPLETE_BYTES_PER_PEER);
+
+		// the oldest message is dropped to make room for the chunks of the others
+		assert_eq!(
+			evict_for_chunk(&mut incomplete_messages, &[2; 32], MAX_CHUNK_SIZE),
+			total as usize / 2 * MAX_CHUNK_SIZE
+		);
+		assert_eq!(incomplete_messages.keys().collect::<Vec<_>>(), vec![&[2; 32]]);
+		assert_eq!(evict_for_chunk(&mut incomplete_messages, &[2; 32], MAX_CHUNK_SIZE), 0);
+	}
+}
@@ -19,13 +19,36 @@ use crate::{
 	Client,
 };
 use codec::Encode;
-use dkg_primitives::types::{DKGError, DKGMessage, NetworkMsgPayload, SignedDKGMessage};
+use dkg_primitives::types::{DKGError, DKGMessage, NetworkMsgPayload, SessionId, SignedDKGMessage};
 use dkg_runtime_primitives::{
-	crypto::AuthorityId, gossip_messages::MisbehaviourMessage, AggregatedMisbehaviourReports,
-	DKGApi, MaxAuthorities, MaxProposalLength, MaxReporters, MaxSignatureLength, MisbehaviourType,
+	crypto::AuthorityId,
+	gossip_messages::MisbehaviourMessage,
+	utils::{to_slice_33, verify_signer_from_set_ecdsa},
+	AggregatedMisbehaviourReports, DKGApi, MaxAuthorities, MaxProposalLength, MaxReporters,
+	MaxSignatureLength, MisbehaviourEvidence, MisbehaviourType, SignedRoundMessage,
 };
 use sc_client_api::Backend;
+use sp_core::ecdsa;
 use sp_runtime::traits::{Block, Get, NumberFor};
+use std::collections::{hash_map::Entry, HashMap};
+
+/// Max votes remembered to detect the parties signing two votes for the same round
+const MAX_SEEN_VOTES: usize = 4096;
+
+/// A signing round of a party, in which it votes once
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VoteRound {
+	pub sender_id: AuthorityId,
+	pub session_id: SessionId,
+	pub associated_block_id: u64,
+	pub ssid: u8,
+	pub party_ind: u16,
+	pub round_key: Vec<u8>,
+	pub unsigned_proposal_hash: [u8; 32],
+}
+
+/// The partial signature and the signed message of the first vote seen in each signing round
+pub type SeenVoteStore = HashMap<VoteRound, (Vec<u8>, SignedRoundMessage<MaxSignatureLength>)>;
 
 pub(crate) async fn handle_misbehaviour_report<B, BE, C, GE>(
 	dkg_worker: &DKGWorker<B, BE, C, GE>,
@@ -91,6 +114,7 @@ where
 					offender: msg.offender.clone(),
 					reporters: Default::default(),
 					signatures: Default::default(),
+					evidence: None,
 				});
 			dkg_worker.logger.debug(format!("Reports: {reports:?}"));
 			if !reports.reporters.contains(&reporter) {
@@ -112,9 +136,86 @@ where
 	Ok(())
 }
 
+/// Remembers the first vote signed by each party of a signing round, and reports the party with
+/// both votes as evidence once it signs a different vote for the same round.
+pub(crate) async fn handle_vote_equivocation<B, BE, C, GE>(
+	dkg_worker: &DKGWorker<B, BE, C, GE>,
+	signed_dkg_msg: &SignedDKGMessage<AuthorityId>,
+) -> Result<(), DKGError>
+where
+	B: Block,
+	BE: Backend<B> + Unpin + 'static,
+	GE: GossipEngineIface + 'static,
+	C: Client<B, BE> + 'static,
+	MaxProposalLength: Get<u32> + Clone + Send + Sync + 'static + std::fmt::Debug,
+	MaxAuthorities: Get<u32> + Clone + Send + Sync + 'static + std::fmt::Debug,
+	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
+{
+	let msg = &signed_dkg_msg.msg;
+	let (NetworkMsgPayload::Vote(vote), Some(signature)) = (&msg.payload, &signed_dkg_msg.signature)
+	else {
+		return Ok(())
+	};
+
+	// only the votes signed by their sender are evidence against it
+	let message = msg.encode();
+	let sender = ecdsa::Public::from_raw(
+		to_slice_33(&msg.sender_id.encode()).ok_or(DKGError::InputOutOfBounds)?,
+	);
+	if !verify_signer_from_set_ecdsa(vec![sender], &message, signature).1 {
+		return Ok(())
+	}
+	let signed = SignedRoundMessage {
+		message: message.try_into().map_err(|_| DKGError::InputOutOfBounds)?,
+		signature: signature.clone().try_into().map_err(|_| DKGError::InputOutOfBounds)?,
+	};
+	let round = VoteRound {
+		sender_id: msg.sender_id.clone(),
+		session_id: msg.session_id,
+		associated_block_id: msg.associated_block_id,
+		ssid: msg.ssid,
+		party_ind: vote.party_ind,
+		round_key: vote.round_key.clone(),
+		unsigned_proposal_hash: vote.unsigned_proposal_hash,
+	};
+
+	let first = {
+		let mut seen = dkg_worker.seen_votes.write();
+		if seen.len() >= MAX_SEEN_VOTES {
+			seen.retain(|round, _| round.session_id.saturating_add(1) >= msg.session_id);
+			if seen.len() >= MAX_SEEN_VOTES {
+				seen.clear();
+			}
+		}
+		match seen.entry(round) {
+			Entry::Vacant(entry) => {
+				entry.insert((vote.partial_signature.clone(), signed));
+				return Ok(())
+			},
+			// the same vote sent again
+			Entry::Occupied(entry) if entry.get().0 == vote.partial_signature => return Ok(()),
+			Entry::Occupied(entry) => entry.get().1.clone(),
+		}
+	};
+
+	dkg_worker.logger.warn(format!(
+		"🕸️  DKG vote equivocation @ Session ({}) by {}",
+		msg.session_id, msg.sender_id
+	));
+	let report = MisbehaviourMessage {
+		misbehaviour_type: MisbehaviourType::Sign,
+		session_id: msg.session_id,
+		offender: msg.sender_id.clone(),
+		signature: vec![],
+	};
+	let evidence = MisbehaviourEvidence::Equivocation { first, second: signed };
+	gossip_misbehaviour_report(dkg_worker, report, Some(evidence)).await
+}
+
 pub(crate) async fn gossip_misbehaviour_report<B, BE, C, GE>(
 	dkg_worker: &DKGWorker<B, BE, C, GE>,
 	report: MisbehaviourMessage,
+	evidence: Option<MisbehaviourEvidence<MaxSignatureLength>>,
 ) -> Result<(), DKGError>
 where
 	B: Block,
@@ -185,18 +286,26 @@ where
 					offender: report.offender.clone(),
 					reporters: Default::default(),
 					signatures: Default::default(),
+					evidence: None,
 				});
+			// the evidence is not gossiped, the reporters attach what they detected
+			let new_evidence = evidence.is_some() && reports.evidence.is_none();
+			if new_evidence {
+				reports.evidence = evidence;
+			}
 
 			if reports.reporters.contains(&public) {
-				return Ok(())
+				if !new_evidence {
+					return Ok(())
+				}
+			} else {
+				reports.reporters.try_push(public).map_err(|_| DKGError::InputOutOfBounds)?;
+				reports
+					.signatures
+					.try_push(encoded_signature.try_into().map_err(|_| DKGError::InputOutOfBounds)?)
+					.map_err(|_| DKGError::InputOutOfBounds)?;
 			}
 
-			reports.reporters.try_push(public).map_err(|_| DKGError::InputOutOfBounds)?;
-			reports
-				.signatures
-				.try_push(encoded_signature.try_into().map_err(|_| DKGError::InputOutOfBounds)?)
-				.map_err(|_| DKGError::InputOutOfBounds)?;
-
 			dkg_worker
 				.logger
 				.debug("Gossiping misbehaviour report and signature".to_string());
@@ -242,7 +351,9 @@ where
 		reports.reporters.len()
 	));
 
-	let perform_store = reports.reporters.len() > threshold;
+	// evidence proves the misbehaviour with a single reporter
+	let perform_store = reports.reporters.len() > threshold ||
+		(reports.evidence.is_some() && !reports.reporters.is_empty());
 	match &reports.misbehaviour_type {
 		MisbehaviourType::Keygen =>
 			if perform_store {
@@ -1,11 +1,14 @@
 #![allow(clippy::needless_return)]
 
 use crate::{
-	async_protocols::{remote::AsyncProtocolRemote, KeygenPartyId, KeygenRound},
+	async_protocols::{
+		frost::reshare::ReshareSets, remote::AsyncProtocolRemote, KeygenPartyId, KeygenRound,
+	},
 	constants::keygen_manager::*,
-	dkg_modules::KeygenProtocolSetupParameters,
+	db::{DKGDbBackend, StoredKeygenState},
+	dkg_modules::{KeyReshareSource, KeygenProtocolSetupParameters},
 	gossip_engine::GossipEngineIface,
-	signing_manager::work_manager::{JobMetadata, PollMethod, WorkManager},
+	signing_manager::work_manager::{JobMetadata, PollMethod, WorkManager, WorkManagerConfig},
 	utils::SendFuture,
 	worker::{
 		AnticipatedKeygenExecutionStatus, DKGWorker, HasLatestHeader, KeystoreExt, ProtoStageType,
@@ -13,13 +16,16 @@ use crate::{
 	Client,
 };
 use atomic::Atomic;
+use codec::{Decode, Encode};
 use dkg_logging::debug_logger::DebugLogger;
 use dkg_primitives::types::{DKGError, SignedDKGMessage};
 use dkg_runtime_primitives::{
 	crypto::{AuthorityId, Public},
-	keccak_256, DKGApi, MaxAuthorities, MaxProposalLength, SessionId, GENESIS_AUTHORITY_SET_ID,
+	keccak_256, DKGApi, DKGProtocol, MaxAuthorities, MaxProposalLength, SessionId,
+	GENESIS_AUTHORITY_SET_ID,
 };
 use sc_client_api::Backend;
+use serde::{Deserialize, Serialize};
 use sp_arithmetic::traits::SaturatedConversion;
 use sp_runtime::traits::{Block, Header, NumberFor};
 use std::{
@@ -41,6 +47,8 @@ pub struct KeygenManager<B: Block, BE, C, GE> {
 	keygen_state: Arc<Atomic<KeygenState>>,
 	latest_executed_session_id: Arc<Atomic<Option<SessionId>>>,
 	pub finished_count: Arc<AtomicUsize>,
+	db: Arc<dyn DKGDbBackend>,
+	logger: DebugLogger,
 	_pd: PhantomData<(B, BE, C, GE)>,
 }
 
@@ -53,11 +61,13 @@ impl<B: Block, BE, C, GE> Clone for KeygenManager<B, BE, C, GE> {
 			keygen_state: self.keygen_state.clone(),
 			latest_executed_session_id: self.latest_executed_session_id.clone(),
 			finished_count: self.finished_count.clone(),
+			db: self.db.clone(),
+			logger: self.logger.clone(),
 		}
 	}
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
 /// State of the KeygenManager
 pub enum KeygenState {
 	Uninitialized,
@@ -77,19 +87,45 @@ where
 	C: Client<B, BE> + 'static,
 	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
 {
-	pub fn new(logger: DebugLogger, clock: impl HasLatestHeader<B>) -> Self {
+	pub fn new(
+		logger: DebugLogger,
+		clock: impl HasLatestHeader<B>,
+		db: Arc<dyn DKGDbBackend>,
+	) -> Self {
+		// Resume from the state persisted before the node was restarted, if any
+		let stored = match db.get_keygen_state() {
+			Ok(stored) => stored.map(restore_keygen_state),
+			Err(err) => {
+				logger.error(format!("Failed to load the persisted keygen state: {err:?}"));
+				None
+			},
+		};
+		if let Some(stored) = stored.as_ref() {
+			logger.info(format!("Resuming the keygen manager from the persisted state {stored:?}"));
+		}
+		let stored = stored.unwrap_or(StoredKeygenState {
+			state: KeygenState::Uninitialized,
+			latest_executed_session_id: None,
+			finished_count: 0,
+		});
+
 		Self {
 			work_manager: WorkManager::<B>::new(
-				logger,
+				logger.clone(),
 				clock,
-				MAX_RUNNING_TASKS,
-				MAX_ENQUEUED_TASKS,
-				PollMethod::Manual,
+				WorkManagerConfig {
+					max_running_tasks: MAX_RUNNING_TASKS,
+					max_enqueued_tasks: MAX_ENQUEUED_TASKS,
+					poll_method: PollMethod::Manual,
+					adaptive_concurrency: false,
+				},
 			),
 			active_keygen_retry_id: Arc::new(AtomicUsize::new(0)),
-			keygen_state: Arc::new(Atomic::new(KeygenState::Uninitialized)),
-			latest_executed_session_id: Arc::new(Atomic::new(None)),
-			finished_count: Arc::new(AtomicUsize::new(0)),
+			keygen_state: Arc::new(Atomic::new(stored.state)),
+			latest_executed_session_id: Arc::new(Atomic::new(stored.latest_executed_session_id)),
+			finished_count: Arc::new(AtomicUsize::new(stored.finished_count as usize)),
+			db,
+			logger,
 			_pd: Default::default(),
 		}
 	}
@@ -104,16 +140,40 @@ where
 		self.work_manager.get_active_sessions_metadata(now).pop()
 	}
 
+	/// Returns the metadata of all the keygen jobs held by the work manager
+	pub fn jobs_metadata(&self, now: NumberFor<B>) -> Vec<JobMetadata> {
+		self.work_manager.get_active_sessions_metadata(now)
+	}
+
 	pub fn get_latest_executed_session_id(&self) -> Option<SessionId> {
 		self.latest_executed_session_id.load(Ordering::SeqCst)
 	}
 
-	fn state(&self) -> KeygenState {
+	pub fn state(&self) -> KeygenState {
 		self.keygen_state.load(Ordering::SeqCst)
 	}
 
 	pub fn set_state(&self, state: KeygenState) {
 		self.keygen_state.store(state, Ordering::SeqCst);
+		self.persist_state();
+	}
+
+	/// Marks the keygen executed by the session `session_id` as completed
+	pub fn keygen_completed(&self, session_id: SessionId) {
+		let _ = self.finished_count.fetch_add(1, Ordering::SeqCst);
+		self.set_state(KeygenState::KeygenCompleted { session_completed: session_id });
+	}
+
+	/// Persists the current state, so that it can be restored after a restart
+	fn persist_state(&self) {
+		let stored = StoredKeygenState {
+			state: self.state(),
+			latest_executed_session_id: self.get_latest_executed_session_id(),
+			finished_count: self.finished_count.load(Ordering::SeqCst) as u64,
+		};
+		if let Err(err) = self.db.store_keygen_state(stored) {
+			self.logger.error(format!("Failed to persist the keygen state: {err:?}"));
+		}
 	}
 
 	/// GENERAL WORKFLOW for Keygen
@@ -377,16 +437,56 @@ where
 			}
 		}
 
-		let party_idx = match stage {
-			KeygenRound::Genesis => dkg_worker.get_party_index(header).await,
-			KeygenRound::Next => dkg_worker.get_next_party_index(header).await,
-		};
-
 		let threshold = match stage {
 			KeygenRound::Genesis => dkg_worker.get_signature_threshold(header).await,
 			KeygenRound::Next => dkg_worker.get_next_signature_threshold(header).await,
 		};
 
+		let dkg_protocol = match stage {
+			KeygenRound::Genesis => dkg_worker.get_dkg_protocol(header).await,
+			KeygenRound::Next => dkg_worker.get_next_dkg_protocol(header).await,
+		};
+
+		let best_authorities = match stage {
+			KeygenRound::Genesis => dkg_worker.get_best_authorities(header).await,
+			KeygenRound::Next => dkg_worker.get_next_best_authorities(header).await,
+		};
+
+		let authority_public_key = dkg_worker.get_authority_public_key();
+		// When the runtime allows it, the outgoing keygen set hands off the shares of the active
+		// key to the next keygen set, so the parties leaving the keygen set may take part as well.
+		// An emergency keygen always generates a new key, in case the resharing cannot complete.
+		let reshare_source = if stage == KeygenRound::Next &&
+			dkg_protocol == DKGProtocol::WTFrost &&
+			!anticipated_execution_status.force_execute &&
+			dkg_worker.should_reshare_key(header).await
+		{
+			let outgoing = dkg_worker.get_best_authorities(header).await;
+			let old_threshold = dkg_worker.get_signature_threshold(header).await;
+			let (session_id, public_key) = dkg_worker.get_dkg_pub_key(header).await;
+			let (participants, sets) =
+				reshare_participants(&best_authorities, &outgoing, threshold, old_threshold);
+			Some((participants, KeyReshareSource { session_id, sets, public_key }))
+		} else {
+			None
+		};
+
+		let (best_authorities, party_idx) = match reshare_source.as_ref() {
+			Some((participants, _)) => {
+				let party_idx = participants
+					.iter()
+					.find_map(|(i, p)| (p == &authority_public_key).then_some(*i));
+				(participants.clone(), party_idx)
+			},
+			None => {
+				let party_idx = match stage {
+					KeygenRound::Genesis => dkg_worker.get_party_index(header).await,
+					KeygenRound::Next => dkg_worker.get_next_party_index(header).await,
+				};
+				(best_authorities, party_idx)
+			},
+		};
+
 		// Check whether the worker is in the best set or return
 		let party_i = match party_idx {
 			Some(party_index) => {
@@ -405,19 +505,15 @@ where
 			},
 		};
 
-		let best_authorities = match stage {
-			KeygenRound::Genesis => dkg_worker.get_best_authorities(header).await,
-			KeygenRound::Next => dkg_worker.get_next_best_authorities(header).await,
-		};
-
-		let best_authorities = best_authorities
+		let best_authorities: Vec<_> = best_authorities
 			.into_iter()
 			.flat_map(|(i, p)| KeygenPartyId::try_from(i).map(|i| (i, p)))
 			.collect();
 
-		let authority_public_key = dkg_worker.get_authority_public_key();
 		let proto_stage_ty = if stage == KeygenRound::Genesis {
 			ProtoStageType::KeygenGenesis
+		} else if reshare_source.is_some() {
+			ProtoStageType::KeyReshare
 		} else {
 			ProtoStageType::KeygenStandard
 		};
@@ -429,18 +525,49 @@ where
 			self.active_keygen_retry_id.load(Ordering::SeqCst),
 		);
 
-		// For now, always use the MpEcdsa variant
-		let params = KeygenProtocolSetupParameters::MpEcdsa {
-			best_authorities,
-			authority_public_key,
-			party_i,
-			session_id,
-			associated_block: *header.number(),
-			threshold,
-			stage: proto_stage_ty,
-			keygen_protocol_hash,
+		let params = match dkg_protocol {
+			DKGProtocol::MpEcdsa => KeygenProtocolSetupParameters::MpEcdsa {
+				best_authorities,
+				authority_public_key,
+				party_i,
+				session_id,
+				associated_block: *header.number(),
+				threshold,
+				stage: proto_stage_ty,
+				keygen_protocol_hash,
+			},
+			DKGProtocol::WTFrost => {
+				let reshare_source = reshare_source.map(|(_, source)| source);
+				let weights = match reshare_source.as_ref() {
+					Some(source) => source.sets.new_weights.clone(),
+					// Every authority in the best set holds a single key share for now
+					None => vec![1; best_authorities.len()],
+				};
+				// When the runtime allows it, the next authority set refreshes the shares of the
+				// active key instead of generating a new one
+				let refresh_session = if stage == KeygenRound::Next &&
+					reshare_source.is_none() &&
+					dkg_worker.should_refresh_key_shares(header).await
+				{
+					Some(dkg_worker.get_dkg_pub_key(header).await.0)
+				} else {
+					None
+				};
+				KeygenProtocolSetupParameters::WTFrost {
+					best_authorities,
+					authority_public_key,
+					party_i,
+					session_id,
+					associated_block: *header.number(),
+					threshold,
+					weights,
+					stage: proto_stage_ty,
+					keygen_protocol_hash,
+					refresh_session,
+					reshare_source,
+				}
+			},
 		};
-
 		let dkg = dkg_worker
 			.dkg_modules
 			.get_keygen_protocol(&params)
@@ -464,12 +591,11 @@ where
 				dkg_worker.handle_dkg_error(err).await;
 			} else {
 				// update states
+				self.latest_executed_session_id.store(Some(session_id), Ordering::SeqCst);
 				match stage {
 					KeygenRound::Genesis => self.set_state(KeygenState::RunningGenesisKeygen),
 					KeygenRound::Next => self.set_state(KeygenState::RunningKeygen),
 				}
-
-				self.latest_executed_session_id.store(Some(session_id), Ordering::Relaxed);
 			}
 		}
 	}
@@ -491,6 +617,93 @@ where
 	}
 }
 
+/// Maps a persisted state to the state to resume from. A keygen that was running when the node
+/// stopped cannot be resumed, since the protocol messages are lost, so it is treated as failed
+/// (genesis) or as if the previous keygen just completed (next), which makes the keygen manager
+/// start it again.
+fn restore_keygen_state(stored: StoredKeygenState) -> StoredKeygenState {
+	let state = match stored.state {
+		KeygenState::RunningGenesisKeygen =>
+			KeygenState::Failed { session_id: GENESIS_AUTHORITY_SET_ID },
+		KeygenState::RunningKeygen => KeygenState::KeygenCompleted {
+			session_completed: stored
+				.latest_executed_session_id
+				.unwrap_or_default()
+				.saturating_sub(1),
+		},
+		state => state,
+	};
+	StoredKeygenState { state, ..stored }
+}
+
+/// Returns the participants of a resharing from the `outgoing` to the `incoming` keygen set,
+/// along with both sets indexed by participant. The incoming parties keep their party index, and
+/// the parties leaving the keygen set are appended after them.
+///
+/// Only `old_threshold + 1` outgoing parties deal the key, the parties staying in the keygen set
+/// first, since the resharing needs every incoming party anyway. The parties leaving the keygen
+/// set only take part if they deal, so they cannot stall the resharing otherwise.
+fn reshare_participants(
+	incoming: &[(u16, Public)],
+	outgoing: &[(u16, Public)],
+	new_threshold: u16,
+	old_threshold: u16,
+) -> (Vec<(u16, Public)>, ReshareSets) {
+	let mut incoming = incoming.to_vec();
+	incoming.sort_by_key(|(i, _)| *i);
+	let new_parties: Vec<u16> = incoming.iter().map(|(i, _)| *i).collect();
+
+	let mut outgoing = outgoing.to_vec();
+	outgoing.sort_by_key(|(i, _)| *i);
+	let staying = |public: &Public| incoming.iter().find(|(_, p)| p == public).map(|(i, _)| *i);
+	let (staying_parties, leaving_parties): (Vec<_>, Vec<_>) = outgoing
+		.iter()
+		.map(|(_, public)| public)
+		.partition(|public| staying(public).is_some());
+	let dealing_leaving_parties =
+		(old_threshold as usize + 1).saturating_sub(staying_parties.len());
+
+	let mut participants = incoming.clone();
+	let mut next_index = new_parties.iter().max().copied().unwrap_or_default();
+	// the leaving dealers get the lowest indices, so the participant indices are contiguous
+	let leaving: Vec<(u16, Public)> = leaving_parties
+		.into_iter()
+		.map(|public| {
+			next_index += 1;
+			(next_index, public.clone())
+		})
+		.collect();
+	participants.extend(leaving.iter().take(dealing_leaving_parties).cloned());
+
+	let old_parties: Vec<u16> = outgoing
+		.iter()
+		.map(|(_, public)| {
+			staying(public)
+				.or_else(|| leaving.iter().find(|(_, p)| p == public).map(|(i, _)| *i))
+				.expect("every outgoing party is either staying or leaving; qed")
+		})
+		.collect();
+	let mut dealers: Vec<u16> = staying_parties
+		.iter()
+		.filter_map(|public| incoming.iter().find(|(_, p)| p == *public).map(|(i, _)| *i))
+		.chain(leaving.iter().map(|(i, _)| *i))
+		.take(old_threshold as usize + 1)
+		.collect();
+	dealers.sort_unstable();
+
+	// Every authority in the best set holds a single key share for now
+	let sets = ReshareSets {
+		old_weights: vec![1; old_parties.len()],
+		old_parties,
+		old_threshold,
+		dealers,
+		new_weights: vec![1; new_parties.len()],
+		new_parties,
+		new_threshold,
+	};
+	(participants, sets)
+}
+
 /// Computes keccak_256(session ID || retry_id)
 fn get_keygen_protocol_hash(session_id: u64, active_keygen_retry_id: usize) -> [u8; 32] {
 	let mut session_id_bytes = session_id.to_be_bytes().to_vec();
@@ -498,3 +711,70 @@ fn get_keygen_protocol_hash(session_id: u64, active_keygen_retry_id: usize) -> [
 	session_id_bytes.extend_from_slice(&retry_id_bytes);
 	keccak_256(&session_id_bytes)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn interrupted_keygens_are_restarted_after_a_restart() {
+		let stored = |state, latest_executed_session_id| StoredKeygenState {
+			state,
+			latest_executed_session_id,
+			finished_count: 3,
+		};
+
+		assert_eq!(
+			restore_keygen_state(stored(KeygenState::RunningGenesisKeygen, Some(0))),
+			stored(KeygenState::Failed { session_id: GENESIS_AUTHORITY_SET_ID }, Some(0))
+		);
+		assert_eq!(
+			restore_keygen_state(stored(KeygenState::RunningKeygen, Some(4))),
+			stored(KeygenState::KeygenCompleted { session_completed: 3 }, Some(4))
+		);
+		let completed = stored(KeygenState::KeygenCompleted { session_completed: 2 }, Some(3));
+		assert_eq!(restore_keygen_state(completed), completed);
+		let failed = stored(KeygenState::Failed { session_id: 3 }, Some(3));
+		assert_eq!(restore_keygen_state(failed), failed);
+	}
+
+	#[test]
+	fn leaving_parties_are_appended_to_the_reshare_participants() {
+		let public = |i: u8| Public::from(sp_core::ecdsa::Public::from_raw([i; 33]));
+		let outgoing = vec![(1, public(1)), (2, public(2)), (3, public(3))];
+		let incoming = vec![(1, public(3)), (2, public(4)), (3, public(2))];
+
+		let (participants, sets) = reshare_participants(&incoming, &outgoing, 1, 2);
+		assert_eq!(
+			participants,
+			vec![(1, public(3)), (2, public(4)), (3, public(2)), (4, public(1))]
+		);
+		assert_eq!(sets.old_parties, vec![4, 3, 1]);
+		assert_eq!(sets.dealers, vec![1, 3, 4]);
+		assert_eq!(sets.new_parties, vec![1, 2, 3]);
+		assert_eq!(sets.old_party(4), Some(1));
+		assert_eq!(sets.new_party(4), None);
+		assert_eq!(sets.new_party(2), Some(2));
+	}
+
+	#[test]
+	fn staying_parties_deal_the_key_before_the_leaving_ones() {
+		let public = |i: u8| Public::from(sp_core::ecdsa::Public::from_raw([i; 33]));
+		let outgoing = vec![(1, public(1)), (2, public(2)), (3, public(3)), (4, public(4))];
+		let incoming = vec![(1, public(3)), (2, public(5)), (3, public(2))];
+
+		// the staying parties hold enough shares, the leaving ones do not take part
+		let (participants, sets) = reshare_participants(&incoming, &outgoing, 1, 1);
+		assert_eq!(participants, incoming);
+		assert_eq!(sets.old_parties, vec![4, 3, 1, 5]);
+		assert_eq!(sets.dealers, vec![1, 3]);
+		assert_eq!(sets.participants(), vec![1, 2, 3]);
+
+		// one leaving party is needed to reach the threshold
+		let (participants, sets) = reshare_participants(&incoming, &outgoing, 1, 2);
+		assert_eq!(participants.last(), Some(&(4, public(1))));
+		assert_eq!(participants.len(), 4);
+		assert_eq!(sets.dealers, vec![1, 3, 4]);
+		assert_eq!(sets.participants(), vec![1, 2, 3, 4]);
+	}
+}
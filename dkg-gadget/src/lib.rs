@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{marker::PhantomData, sync::Arc};
+use std::{marker::PhantomData, path::PathBuf, sync::Arc};
 
 use debug_logger::DebugLogger;
+use dkg_primitives::types::DKGError;
 use dkg_runtime_primitives::{crypto::AuthorityId, DKGApi, MaxAuthorities, MaxProposalLength};
 use parking_lot::RwLock;
 use prometheus::Registry;
@@ -38,6 +39,7 @@ pub mod signing_manager;
 // mod meta_async_rounds;
 pub mod db;
 mod metrics;
+pub mod rpc_links;
 mod utils;
 pub mod worker;
 
@@ -52,6 +54,8 @@ pub use constants::{DKG_KEYGEN_PROTOCOL_NAME, DKG_SIGNING_PROTOCOL_NAME};
 pub use debug_logger::RoundsEventType;
 use gossip_engine::NetworkGossipEngineBuilder;
 pub use keystore::DKGKeystore;
+pub use rpc_links::DKGRpcLinks;
+pub use signing_manager::work_manager::{PollMethod, WorkManagerConfig};
 
 /// Returns the configuration value to put in
 /// [`sc_network::config::NetworkConfiguration::extra_sets`].
@@ -107,6 +111,13 @@ where
 	pub prometheus_registry: Option<Registry>,
 	/// For logging
 	pub debug_logger: DebugLogger,
+	/// Path of a standalone database for the DKG key shares. If not set, the key shares are
+	/// stored in the offchain storage of the chain database.
+	pub db_path: Option<PathBuf>,
+	/// Links shared with the `dkg_` RPC namespace
+	pub rpc_links: DKGRpcLinks,
+	/// Limits of the signing protocols run at the same time
+	pub signing_work_config: WorkManagerConfig,
 	/// Phantom block type
 	pub _block: PhantomData<B>,
 }
@@ -114,7 +125,7 @@ where
 /// Start the DKG gadget.
 ///
 /// This is a thin shim around running and awaiting a DKG worker.
-pub async fn start_dkg_gadget<B, BE, C>(dkg_params: DKGParams<B, BE, C>)
+pub async fn start_dkg_gadget<B, BE, C>(dkg_params: DKGParams<B, BE, C>) -> Result<(), DKGError>
 where
 	B: Block,
 	BE: Backend<B> + Unpin + 'static,
@@ -134,9 +145,24 @@ where
 		local_keystore,
 		_block,
 		debug_logger,
+		db_path,
+		rpc_links,
+		signing_work_config,
 	} = dkg_params;
 
 	let dkg_keystore: DKGKeystore = DKGKeystore::new(key_store, debug_logger.clone());
+
+	// In memory backend, not used for now
+	// let db_backend = Arc::new(db::DKGInMemoryDb::new());
+	let db_backend: Arc<dyn db::DKGDbBackend> = match db_path {
+		Some(db_path) => Arc::new(db::DKGSledDb::open(db_path, debug_logger.clone())?),
+		None => Arc::new(db::DKGOffchainStorageDb::new(
+			backend.clone(),
+			dkg_keystore.clone(),
+			local_keystore.clone(),
+			debug_logger.clone(),
+		)),
+	};
 	let keygen_gossip_protocol = NetworkGossipEngineBuilder::new(
 		DKG_KEYGEN_PROTOCOL_NAME.to_string().into(),
 		dkg_keystore.clone(),
@@ -197,15 +223,6 @@ where
 	let signing_handle =
 		crate::utils::ExplicitPanicFuture::new(tokio::spawn(signing_gossip_handler.run()));
 
-	// In memory backend, not used for now
-	// let db_backend = Arc::new(db::DKGInMemoryDb::new());
-	let offchain_db_backend = db::DKGOffchainStorageDb::new(
-		backend.clone(),
-		dkg_keystore.clone(),
-		local_keystore.clone(),
-		debug_logger.clone(),
-	);
-	let db_backend = Arc::new(offchain_db_backend);
 	let worker_params = worker::WorkerParams {
 		latest_header,
 		client,
@@ -219,6 +236,8 @@ where
 		network: Some(network),
 		sync_service: Some(sync_service),
 		test_bundle: None,
+		rpc_links,
+		signing_work_config,
 		_marker: PhantomData,
 	};
 
@@ -227,6 +246,7 @@ where
 	worker.run().await;
 	keygen_handle.abort();
 	signing_handle.abort();
+	Ok(())
 }
 
 pub mod deadlock_detection {
@@ -50,6 +50,12 @@ pub struct Metrics {
 	pub dkg_keygen_timeout_error: Counter<U64>,
 	/// The total number of sign misbehaviour errors seen
 	pub dkg_sign_misbehaviour_error: Counter<U64>,
+	/// Number of signed proposal batches waiting to be confirmed on chain
+	pub dkg_pending_signed_proposals: Gauge<U64>,
+	/// Number of extra submissions of the pending signed proposal batches
+	pub dkg_signed_proposal_resubmissions: Gauge<U64>,
+	/// Blocks the oldest pending signed proposal batch has waited for a submitter
+	pub dkg_signed_proposal_wait_blocks: Gauge<U64>,
 }
 
 impl Metrics {
@@ -134,6 +140,27 @@ impl Metrics {
 				Counter::new("dkg_sign_misbehaviour_error", "Number of SignMisbehaviour reports")?,
 				registry,
 			)?,
+			dkg_pending_signed_proposals: register(
+				Gauge::new(
+					"dkg_pending_signed_proposals",
+					"Number of signed proposal batches waiting to be confirmed on chain",
+				)?,
+				registry,
+			)?,
+			dkg_signed_proposal_resubmissions: register(
+				Gauge::new(
+					"dkg_signed_proposal_resubmissions",
+					"Number of extra submissions of the pending signed proposal batches",
+				)?,
+				registry,
+			)?,
+			dkg_signed_proposal_wait_blocks: register(
+				Gauge::new(
+					"dkg_signed_proposal_wait_blocks",
+					"Blocks the oldest pending signed proposal batch has waited for a submitter",
+				)?,
+				registry,
+			)?,
 		})
 	}
 
@@ -0,0 +1,121 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Links between the DKG worker and the `dkg_` RPC namespace of the node.
+
+use crate::{
+	constants::rpc::SIGNED_PROPOSALS_CHANNEL_CAPACITY, signing_manager::work_manager::JobMetadata,
+};
+use dkg_primitives::types::SessionId;
+use dkg_runtime_primitives::{
+	BatchId, MaxProposalLength, MaxProposalsInBatch, MaxSignatureLength, SignedProposalBatch,
+	TypedChainId,
+};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+pub use crate::keygen_manager::KeygenState;
+
+/// Snapshot of the keygen and signing jobs of the local DKG worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerStatus {
+	/// Number of the latest block processed by the worker
+	pub block_number: u64,
+	/// State of the keygen manager
+	pub keygen_state: KeygenState,
+	/// The session that executed the latest keygen of this worker
+	pub latest_executed_keygen_session: Option<SessionId>,
+	/// Whether signing is paused while a keygen is running
+	pub signing_locked: bool,
+	/// The keygen jobs held by the keygen work manager
+	pub keygen_jobs: Vec<JobMetadata>,
+	/// The signing jobs held by the signing work manager
+	pub signing_jobs: Vec<JobMetadata>,
+}
+
+/// A batch of proposals freshly signed by the local worker, and the chain it is destined to
+pub type SignedProposalBatchNotification = (
+	TypedChainId,
+	SignedProposalBatch<BatchId, MaxProposalLength, MaxProposalsInBatch, MaxSignatureLength>,
+);
+
+/// Handles shared by the DKG worker and the RPC server.
+///
+/// The links are created by the node before the gadget is started, so the RPC server can
+/// be built even if the node does not run a DKG worker.
+#[derive(Clone)]
+pub struct DKGRpcLinks {
+	worker_status: Arc<RwLock<Option<WorkerStatus>>>,
+	signed_proposals: broadcast::Sender<SignedProposalBatchNotification>,
+}
+
+impl Default for DKGRpcLinks {
+	fn default() -> Self {
+		let (signed_proposals, _) = broadcast::channel(SIGNED_PROPOSALS_CHANNEL_CAPACITY);
+		Self { worker_status: Default::default(), signed_proposals }
+	}
+}
+
+impl DKGRpcLinks {
+	/// Returns the latest status published by the worker, if any
+	pub fn worker_status(&self) -> Option<WorkerStatus> {
+		self.worker_status.read().clone()
+	}
+
+	pub(crate) fn set_worker_status(&self, status: WorkerStatus) {
+		*self.worker_status.write() = Some(status);
+	}
+
+	/// Subscribes to the proposal batches signed by the local worker, before they are submitted
+	/// on-chain
+	pub fn subscribe_signed_proposals(
+		&self,
+	) -> broadcast::Receiver<SignedProposalBatchNotification> {
+		self.signed_proposals.subscribe()
+	}
+
+	pub(crate) fn notify_signed_proposals(&self, notification: SignedProposalBatchNotification) {
+		// sending only fails if there are no subscribers
+		let _ = self.signed_proposals.send(notification);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn signed_proposals_are_sent_to_every_subscriber() {
+		let links = DKGRpcLinks::default();
+		// notifying without subscribers must not fail
+		let batch = SignedProposalBatch {
+			batch_id: 1,
+			proposals: Default::default(),
+			signature: vec![1, 2, 3].try_into().unwrap(),
+			encoding: Default::default(),
+		};
+		links.notify_signed_proposals((TypedChainId::Evm(1), batch.clone()));
+
+		let mut first = links.subscribe_signed_proposals();
+		let mut second = links.clone().subscribe_signed_proposals();
+		links.notify_signed_proposals((TypedChainId::Evm(2), batch.clone()));
+
+		assert_eq!(first.try_recv().unwrap(), (TypedChainId::Evm(2), batch.clone()));
+		assert_eq!(second.try_recv().unwrap(), (TypedChainId::Evm(2), batch));
+		assert!(first.try_recv().is_err());
+	}
+}
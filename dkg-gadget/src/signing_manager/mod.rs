@@ -1,30 +1,56 @@
-use std::marker::PhantomData;
+use std::{
+	collections::{HashMap, HashSet},
+	marker::PhantomData,
+};
 
 use dkg_primitives::{
 	types::{DKGError, SignedDKGMessage},
-	MaxProposalLength,
+	MaxProposalLength, SessionId,
 };
 
 use self::work_manager::WorkManager;
 use crate::{
 	async_protocols::KeygenPartyId,
 	constants::signing_manager::*,
+	db::PendingSigningJob,
 	dkg_modules::SigningProtocolSetupParameters,
 	gossip_engine::GossipEngineIface,
 	metric_inc,
-	signing_manager::work_manager::PollMethod,
+	signing_manager::{
+		nonce_ordering::filter_nonce_ordered_batches,
+		scheduling::{batch_chain, schedule_batches_resuming},
+		work_manager::{JobMetadata, WorkManagerConfig},
+	},
+	storage::proposals::save_signed_proposals_in_storage,
+	utils::has_dkg_api_version,
 	worker::{DKGWorker, HasLatestHeader, KeystoreExt, ProtoStageType},
 	*,
 };
 use codec::Encode;
 use dkg_primitives::utils::select_random_set;
-use dkg_runtime_primitives::crypto::Public;
+use dkg_runtime_primitives::{
+	crypto::Public, BatchId, DKGProtocol, MaxProposalsInBatch, MaxSignatureLength,
+	DKG_API_VERSION_2,
+};
 use sp_api::HeaderT;
 use std::sync::atomic::{AtomicBool, Ordering};
 use webb_proposals::TypedChainId;
 /// For balancing the amount of work done by each node
+mod nonce_ordering;
+mod scheduling;
+#[cfg(test)]
+mod test_utils;
 pub mod work_manager;
 
+/// The persisted signing state which still applies to the unsigned batches on-chain
+#[derive(Default)]
+struct PersistedSigningState {
+	/// The unsigned batches this node already signed
+	already_signed: HashSet<[u8; 32]>,
+	/// The unsigned batches whose signing job was interrupted by a restart
+	interrupted: HashSet<[u8; 32]>,
+}
+
 /// The signing manager is triggered each time a new block is finalized.
 /// It will then start a signing process for each of the proposals. SigningManagerV2 uses
 /// 1 signing set per proposal for simplicity.
@@ -42,12 +68,19 @@ pub struct SigningManager<B: Block, BE, C, GE> {
 	// governs the workload for each node
 	work_manager: WorkManager<B>,
 	lock: Arc<AtomicBool>,
+	// whether the persisted signing jobs and signed proposals were restored since startup
+	restored_persisted_state: Arc<AtomicBool>,
 	_pd: PhantomData<(B, BE, C, GE)>,
 }
 
 impl<B: Block, BE, C, GE> Clone for SigningManager<B, BE, C, GE> {
 	fn clone(&self) -> Self {
-		Self { work_manager: self.work_manager.clone(), _pd: self._pd, lock: self.lock.clone() }
+		Self {
+			work_manager: self.work_manager.clone(),
+			_pd: self._pd,
+			lock: self.lock.clone(),
+			restored_persisted_state: self.restored_persisted_state.clone(),
+		}
 	}
 }
 
@@ -59,16 +92,15 @@ where
 	C: Client<B, BE> + 'static,
 	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
 {
-	pub fn new(logger: DebugLogger, clock: impl HasLatestHeader<B>) -> Self {
+	pub fn new(
+		logger: DebugLogger,
+		clock: impl HasLatestHeader<B>,
+		config: WorkManagerConfig,
+	) -> Self {
 		Self {
-			work_manager: WorkManager::<B>::new(
-				logger,
-				clock,
-				MAX_RUNNING_TASKS,
-				MAX_ENQUEUED_TASKS,
-				PollMethod::Interval { millis: JOB_POLL_INTERVAL_IN_MILLISECONDS },
-			),
+			work_manager: WorkManager::<B>::new(logger, clock, config),
 			lock: Arc::new(AtomicBool::new(false)),
+			restored_persisted_state: Arc::new(AtomicBool::new(false)),
 			_pd: Default::default(),
 		}
 	}
@@ -89,6 +121,16 @@ where
 		self.lock.store(false, Ordering::SeqCst);
 	}
 
+	/// Whether signing is paused while a keygen is running
+	pub fn is_keygen_locked(&self) -> bool {
+		self.lock.load(Ordering::SeqCst)
+	}
+
+	/// Returns the metadata of all the signing jobs held by the work manager
+	pub fn jobs_metadata(&self, now: NumberFor<B>) -> Vec<JobMetadata> {
+		self.work_manager.get_active_sessions_metadata(now)
+	}
+
 	/// This function is called each time a new block is finalized.
 	/// It will then start a signing process for each of the proposals.
 	#[allow(clippy::let_underscore_future)]
@@ -128,8 +170,50 @@ where
 
 		dkg_worker.logger.info_signing("About to get unsigned proposals ...");
 
+		let last_signed_nonces = match dkg_worker
+			.exec_client_function(move |client| {
+				if !has_dkg_api_version::<B, _>(client, at, DKG_API_VERSION_2) {
+					return Ok(Vec::new())
+				}
+				client.runtime_api().get_last_signed_nonces(at)
+			})
+			.await
+		{
+			Ok(nonces) => nonces.into_iter().collect::<HashMap<_, _>>(),
+			Err(e) => {
+				dkg_worker.logger.warn(format!(
+					"🕸️  PARTY {party_i} | Failed to get the last signed nonces: {e:?}"
+				));
+				HashMap::new()
+			},
+		};
+
+		let scheduling_configs = match dkg_worker
+			.exec_client_function(move |client| {
+				if !has_dkg_api_version::<B, _>(client, at, DKG_API_VERSION_2) {
+					return Ok(Vec::new())
+				}
+				client.runtime_api().get_chain_scheduling_configs(at)
+			})
+			.await
+		{
+			Ok(configs) => configs.into_iter().collect::<HashMap<_, _>>(),
+			Err(e) => {
+				dkg_worker.logger.warn(format!(
+					"🕸️  PARTY {party_i} | Failed to get the chain scheduling configs: {e:?}"
+				));
+				HashMap::new()
+			},
+		};
+
 		let unsigned_proposals = match dkg_worker
 			.exec_client_function(move |client| {
+				if !has_dkg_api_version::<B, _>(client, at, DKG_API_VERSION_2) {
+					// the batches of an older runtime are all concatenated
+					#[allow(deprecated)]
+					let batches = client.runtime_api().get_unsigned_proposal_batches_before_version_2(at);
+					return batches.map(|batches| batches.into_iter().map(Into::into).collect())
+				}
 				client.runtime_api().get_unsigned_proposal_batches(at)
 			})
 			.await
@@ -137,18 +221,35 @@ where
 			Ok(mut res) => {
 				// sort proposals by timestamp, we want to pick the oldest proposal to sign
 				res.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+				let on_chain_hashes = res.iter().filter_map(|batch| batch.hash()).collect();
+				let persisted =
+					self.sync_persisted_proposals(&on_chain_hashes, session_id, dkg_worker);
+				// proposals of a resource are signed in nonce order, so a batch waits until the
+				// lower nonces of its resources are signed
+				let ready = filter_nonce_ordered_batches(res, &last_signed_nonces);
 				let mut filtered_unsigned_proposals = Vec::new();
-				for proposal in res {
+				let mut in_flight = HashMap::new();
+				for proposal in ready {
 					if let Some(hash) = proposal.hash() {
-						// only submit the job if it isn't already running
-						if !self.work_manager.job_exists(&hash) {
+						// only submit the job if it isn't already running, and we did not
+						// already sign it before a restart
+						if self.work_manager.job_exists(&hash) {
+							*in_flight.entry(batch_chain(&proposal)).or_default() += 1;
+						} else if !persisted.already_signed.contains(&hash) {
 							// update unsigned proposal counter
 							metric_inc!(dkg_worker, dkg_unsigned_proposal_counter);
 							filtered_unsigned_proposals.push(proposal);
 						}
 					}
 				}
-				filtered_unsigned_proposals
+				// resume the jobs interrupted by a restart, then sign the refresh proposals and the
+				// critical chains first, and let the chains of the same priority take turns
+				schedule_batches_resuming(
+					filtered_unsigned_proposals,
+					&persisted.interrupted,
+					&scheduling_configs,
+					&in_flight,
+				)
 			},
 			Err(e) => {
 				dkg_worker
@@ -175,6 +276,7 @@ where
 			.flat_map(|(i, p)| KeygenPartyId::try_from(i).map(|i| (i, p)))
 			.collect();
 		let threshold = dkg_worker.get_signature_threshold(header).await;
+		let dkg_protocol = dkg_worker.get_dkg_protocol(header).await;
 		let authority_public_key = dkg_worker.get_authority_public_key();
 
 		for batch in unsigned_proposals {
@@ -220,17 +322,32 @@ where
 							signing_set,
 						));
 
-						let params = SigningProtocolSetupParameters::MpEcdsa {
-							best_authorities: best_authorities.clone(),
-							authority_public_key: authority_public_key.clone(),
-							party_i,
-							session_id,
-							threshold,
-							stage: ProtoStageType::Signing { unsigned_proposal_hash },
-							unsigned_proposal_batch: batch.clone(),
-							signing_set,
-							associated_block_id: *header.number(),
-							ssid,
+						// The signing protocol must match the protocol that generated the
+						// current key
+						let params = match dkg_protocol {
+							DKGProtocol::MpEcdsa => SigningProtocolSetupParameters::MpEcdsa {
+								best_authorities: best_authorities.clone(),
+								authority_public_key: authority_public_key.clone(),
+								party_i,
+								session_id,
+								threshold,
+								stage: ProtoStageType::Signing { unsigned_proposal_hash },
+								unsigned_proposal_batch: batch.clone(),
+								signing_set,
+								associated_block_id: *header.number(),
+								ssid,
+							},
+							DKGProtocol::WTFrost => SigningProtocolSetupParameters::WTFrost {
+								best_authorities: best_authorities.clone(),
+								authority_public_key: authority_public_key.clone(),
+								party_i,
+								session_id,
+								stage: ProtoStageType::Signing { unsigned_proposal_hash },
+								unsigned_proposal_batch: batch.clone(),
+								signing_set,
+								associated_block_id: *header.number(),
+								ssid,
+							},
 						};
 
 						let signing_protocol = dkg_worker
@@ -249,6 +366,17 @@ where
 									handle,
 									task,
 								)?;
+								let job = PendingSigningJob {
+									unsigned_proposal_hash,
+									session_id,
+									batch_id: batch.batch_id,
+									ssid,
+								};
+								if let Err(err) = dkg_worker.db.store_pending_signing_job(job) {
+									dkg_worker.logger.error(format!(
+										"Failed to persist the signing job: {err:?}"
+									));
+								}
 							},
 							Err(err) => {
 								dkg_worker
@@ -266,6 +394,102 @@ where
 		Ok(())
 	}
 
+	/// Prunes the persisted signing jobs and signed proposal batches whose unsigned batch is no
+	/// longer on-chain, or that belong to a previous session. On the first call after startup,
+	/// the remaining signed batches are submitted again, since they may have been lost when the
+	/// node stopped, and the remaining signing jobs are reported as interrupted so they are
+	/// enqueued again before any new batch.
+	fn sync_persisted_proposals(
+		&self,
+		on_chain_hashes: &HashSet<[u8; 32]>,
+		session_id: SessionId,
+		dkg_worker: &DKGWorker<B, BE, C, GE>,
+	) -> PersistedSigningState {
+		let restoring = !self.restored_persisted_state.swap(true, Ordering::SeqCst);
+		let mut persisted = PersistedSigningState::default();
+		let is_stale = |unsigned_proposal_hash: &[u8; 32], job_session_id: SessionId| {
+			job_session_id != session_id || !on_chain_hashes.contains(unsigned_proposal_hash)
+		};
+
+		match dkg_worker.db.get_pending_signing_jobs() {
+			Ok(jobs) =>
+				for job in jobs {
+					if is_stale(&job.unsigned_proposal_hash, job.session_id) {
+						if let Err(err) =
+							dkg_worker.db.remove_pending_signing_job(job.unsigned_proposal_hash)
+						{
+							dkg_worker.logger.error(format!(
+								"Failed to remove the stale signing job {job:?}: {err:?}"
+							));
+						}
+					} else if restoring &&
+						!self.work_manager.job_exists(&job.unsigned_proposal_hash)
+					{
+						dkg_worker.logger.info(format!(
+							"Restarting the signing job {job:?} interrupted by a restart"
+						));
+						persisted.interrupted.insert(job.unsigned_proposal_hash);
+					}
+				},
+			Err(err) => dkg_worker
+				.logger
+				.error(format!("Failed to load the persisted signing jobs: {err:?}")),
+		}
+
+		let signed_batches = match dkg_worker.db.get_unsubmitted_signed_proposals() {
+			Ok(signed_batches) => signed_batches,
+			Err(err) => {
+				dkg_worker
+					.logger
+					.error(format!("Failed to load the persisted signed proposals: {err:?}"));
+				return persisted
+			},
+		};
+
+		let (stale, signed_batches): (Vec<_>, Vec<_>) = signed_batches
+			.into_iter()
+			.partition(|signed| is_stale(&signed.unsigned_proposal_hash, signed.session_id));
+		for signed in stale {
+			if let Err(err) =
+				dkg_worker.db.remove_unsubmitted_signed_proposal(signed.unsigned_proposal_hash)
+			{
+				dkg_worker
+					.logger
+					.error(format!("Failed to remove the submitted signed proposal: {err:?}"));
+			}
+		}
+
+		if !signed_batches.is_empty() && restoring {
+			dkg_worker.logger.info(format!(
+				"Submitting {} signed proposal batches persisted before the restart",
+				signed_batches.len()
+			));
+			save_signed_proposals_in_storage::<
+				B,
+				C,
+				BE,
+				MaxProposalLength,
+				MaxAuthorities,
+				BatchId,
+				MaxProposalsInBatch,
+				MaxSignatureLength,
+			>(
+				&dkg_worker.get_authority_public_key(),
+				&dkg_worker.current_validator_set,
+				&dkg_worker.latest_header,
+				&dkg_worker.backend,
+				signed_batches.iter().map(|signed| signed.batch.clone()).collect(),
+				&dkg_worker.logger,
+			);
+		}
+
+		persisted.already_signed =
+			signed_batches.into_iter().map(|signed| signed.unsigned_proposal_hash).collect();
+		// a job whose batch was signed before the restart does not need to run again
+		persisted.interrupted.retain(|hash| !persisted.already_signed.contains(hash));
+		persisted
+	}
+
 	/// After keygen, this should be called to generate a random set of signers
 	/// NOTE: since the random set is called using a deterministic seed to and RNG,
 	/// the resulting set is deterministic
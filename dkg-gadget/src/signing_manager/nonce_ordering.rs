@@ -0,0 +1,179 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ordering of the unsigned proposal batches by the nonces of their resources.
+//!
+//! The target contracts of a resource only execute its proposals in nonce order, so a batch is
+//! only signed once every proposal of the same resource with a lower nonce has been signed,
+//! unless the pending batches interleave the nonces of their resources.
+
+use dkg_runtime_primitives::{
+	handlers::decode_proposals::decode_proposal_resource_nonce, ProposalNonce, ResourceId,
+	StoredUnsignedProposalBatch,
+};
+use sp_core::Get;
+use std::collections::HashMap;
+
+/// Returns the lowest and the highest nonce of each resource of a batch
+fn resource_nonces<BatchId, MaxLength, MaxProposals, Timestamp>(
+	batch: &StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>,
+) -> HashMap<ResourceId, (u32, u32)>
+where
+	MaxLength: Get<u32> + Clone,
+	MaxProposals: Get<u32>,
+{
+	let mut nonces = HashMap::new();
+	for (resource_id, nonce) in batch
+		.proposals
+		.iter()
+		.filter_map(|unsigned| decode_proposal_resource_nonce(&unsigned.proposal))
+	{
+		nonces
+			.entry(resource_id)
+			.and_modify(|(lowest, highest): &mut (u32, u32)| {
+				*lowest = (*lowest).min(nonce.0);
+				*highest = (*highest).max(nonce.0);
+			})
+			.or_insert((nonce.0, nonce.0));
+	}
+	nonces
+}
+
+/// Keeps the batches whose proposals can be signed without skipping a nonce of their resources,
+/// preserving the order of the batches.
+///
+/// For each resource of a batch, the lowest nonce of the batch must directly follow the last
+/// signed nonce of the resource, if one is known, and be the lowest pending nonce of the resource.
+/// Batches may interleave the nonces of their resources, so when no batch is ready this way, the
+/// first batch holding the lowest pending nonce of one of its resources is signed instead, as the
+/// pending batches would otherwise wait on each other forever.
+pub(crate) fn filter_nonce_ordered_batches<BatchId, MaxLength, MaxProposals, Timestamp>(
+	batches: Vec<StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>>,
+	last_signed_nonces: &HashMap<ResourceId, ProposalNonce>,
+) -> Vec<StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>>
+where
+	MaxLength: Get<u32> + Clone,
+	MaxProposals: Get<u32>,
+{
+	let batch_nonces = batches.iter().map(resource_nonces).collect::<Vec<_>>();
+	let mut lowest_pending_nonces = HashMap::<ResourceId, u32>::new();
+	for (resource_id, (lowest, _)) in batch_nonces.iter().flatten() {
+		lowest_pending_nonces
+			.entry(*resource_id)
+			.and_modify(|pending| *pending = (*pending).min(*lowest))
+			.or_insert(*lowest);
+	}
+	let is_next = |resource_id: &ResourceId, lowest: &u32| {
+		let follows_last_signed = last_signed_nonces
+			.get(resource_id)
+			.map_or(true, |last| *lowest <= last.0.saturating_add(1));
+		follows_last_signed && lowest_pending_nonces.get(resource_id) == Some(lowest)
+	};
+
+	let mut ready = batch_nonces
+		.iter()
+		.map(|nonces| nonces.iter().all(|(resource_id, (lowest, _))| is_next(resource_id, lowest)))
+		.collect::<Vec<_>>();
+	let any_ordered_batch_ready = ready
+		.iter()
+		.zip(&batch_nonces)
+		.any(|(ready, nonces)| *ready && !nonces.is_empty());
+	if !any_ordered_batch_ready {
+		if let Some(index) = batch_nonces.iter().position(|nonces| {
+			nonces.iter().any(|(resource_id, (lowest, _))| is_next(resource_id, lowest))
+		}) {
+			ready[index] = true;
+		}
+	}
+
+	batches
+		.into_iter()
+		.zip(ready)
+		.filter(|(_, ready)| *ready)
+		.map(|(batch, _)| batch)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::signing_manager::test_utils::{
+		batch, batch_ids, evm_proposal, proposal, resource, Batch,
+	};
+	use dkg_runtime_primitives::TypedChainId;
+
+	fn ready_batch_ids(batches: Vec<Batch>, last_signed: &[(u8, u32)]) -> Vec<u32> {
+		let last_signed_nonces = last_signed
+			.iter()
+			.map(|(resource_id, nonce)| (resource(*resource_id), ProposalNonce::from(*nonce)))
+			.collect();
+		batch_ids(filter_nonce_ordered_batches(batches, &last_signed_nonces))
+	}
+
+	#[test]
+	fn batches_wait_for_lower_nonces_of_their_resources() {
+		let batches = vec![
+			batch(0, vec![proposal(1, 2)]),
+			batch(1, vec![proposal(1, 1)]),
+			batch(2, vec![proposal(2, 5), proposal(1, 3)]),
+			batch(3, vec![proposal(2, 4)]),
+		];
+		assert_eq!(ready_batch_ids(batches, &[]), vec![1, 3]);
+	}
+
+	#[test]
+	fn batches_wait_for_the_nonce_following_the_last_signed_nonce() {
+		let batches = vec![batch(0, vec![proposal(1, 3)]), batch(1, vec![proposal(2, 8)])];
+		assert_eq!(ready_batch_ids(batches.clone(), &[(1, 1), (2, 7)]), vec![1]);
+		assert_eq!(ready_batch_ids(batches, &[(1, 2), (2, 7)]), vec![0, 1]);
+	}
+
+	#[test]
+	fn batches_holding_consecutive_nonces_are_ready() {
+		let batches = vec![
+			batch(0, vec![proposal(1, 2), proposal(1, 3), proposal(1, 4)]),
+			batch(1, vec![proposal(1, 5)]),
+		];
+		assert_eq!(ready_batch_ids(batches, &[(1, 1)]), vec![0]);
+	}
+
+	#[test]
+	fn batches_interleaving_the_nonces_of_a_resource_are_signed_in_turn() {
+		let batches =
+			vec![batch(0, vec![proposal(1, 2), proposal(1, 4)]), batch(1, vec![proposal(1, 3)])];
+		assert_eq!(ready_batch_ids(batches.clone(), &[(1, 1)]), vec![0]);
+		assert_eq!(ready_batch_ids(batches[1..].to_vec(), &[(1, 4)]), vec![1]);
+	}
+
+	#[test]
+	fn batches_waiting_on_each_other_across_resources_are_signed_in_turn() {
+		let batches = vec![
+			batch(0, vec![proposal(1, 5), proposal(2, 3)]),
+			batch(1, vec![proposal(1, 4), proposal(2, 6)]),
+			batch(2, vec![evm_proposal(0, TypedChainId::Evm(1))]),
+		];
+		assert_eq!(ready_batch_ids(batches.clone(), &[]), vec![0, 2]);
+		assert_eq!(ready_batch_ids(batches[..2].to_vec(), &[(1, 3), (2, 2)]), vec![0]);
+		assert_eq!(ready_batch_ids(batches[1..2].to_vec(), &[(1, 5), (2, 3)]), vec![1]);
+	}
+
+	#[test]
+	fn batches_without_resource_nonces_are_always_ready() {
+		let batches = vec![
+			batch(0, vec![proposal(1, 5)]),
+			batch(1, vec![evm_proposal(0, TypedChainId::Evm(1))]),
+		];
+		assert_eq!(ready_batch_ids(batches, &[(1, 1)]), vec![1]);
+	}
+}
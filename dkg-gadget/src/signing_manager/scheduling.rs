@@ -0,0 +1,219 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fair scheduling of the unsigned proposal batches across their target chains.
+//!
+//! The work manager only runs a limited number of signing jobs, so the order in which the batches
+//! are submitted to it decides which chains get signed first. The refresh proposals always come
+//! first, then the chains of the highest priority class. The chains of the same class take turns,
+//! so a chain with many pending batches cannot starve the others.
+
+use dkg_runtime_primitives::{ChainSchedulingConfig, StoredUnsignedProposalBatch, TypedChainId};
+use sp_core::Get;
+use std::{
+	cmp::Reverse,
+	collections::{HashMap, HashSet, VecDeque},
+};
+
+/// Returns the target chain of a batch
+pub(crate) fn batch_chain<BatchId, MaxLength, MaxProposals, Timestamp>(
+	batch: &StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>,
+) -> TypedChainId
+where
+	MaxLength: Get<u32> + Clone,
+	MaxProposals: Get<u32>,
+{
+	batch
+		.proposals
+		.first()
+		.map_or(TypedChainId::None, |unsigned| unsigned.typed_chain_id)
+}
+
+/// Orders the batches in which they should be signed, dropping the batches of the chains which
+/// already have `max_batches_in_flight` signing jobs running.
+///
+/// The batches of a chain keep their relative order, so they must be sorted oldest first.
+pub(crate) fn schedule_batches<BatchId, MaxLength, MaxProposals, Timestamp>(
+	batches: Vec<StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>>,
+	configs: &HashMap<TypedChainId, ChainSchedulingConfig>,
+	in_flight: &HashMap<TypedChainId, usize>,
+) -> Vec<StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>>
+where
+	MaxLength: Get<u32> + Clone,
+	MaxProposals: Get<u32>,
+{
+	let config = |chain: &TypedChainId| configs.get(chain).copied().unwrap_or_default();
+
+	// group the batches by chain, in the order of the oldest batch of each chain
+	let mut chains: Vec<(TypedChainId, VecDeque<_>)> = Vec::new();
+	for batch in batches {
+		let chain = batch_chain(&batch);
+		match chains.iter_mut().find(|(other, _)| *other == chain) {
+			Some((_, chain_batches)) => chain_batches.push_back(batch),
+			None => chains.push((chain, VecDeque::from([batch]))),
+		}
+	}
+
+	// the refresh proposals are never limited
+	for (chain, chain_batches) in
+		chains.iter_mut().filter(|(chain, _)| *chain != TypedChainId::None)
+	{
+		let running = in_flight.get(chain).copied().unwrap_or_default();
+		if let Some(max) = config(chain).max_batches_in_flight {
+			chain_batches.truncate((max as usize).saturating_sub(running));
+		}
+	}
+
+	let rank =
+		|chain: &TypedChainId| (*chain != TypedChainId::None, Reverse(config(chain).priority));
+	chains.sort_by_key(|(chain, _)| rank(chain));
+
+	// the chains of the same rank take turns
+	let mut scheduled = Vec::new();
+	let mut class_start = 0;
+	while class_start < chains.len() {
+		let class_rank = rank(&chains[class_start].0);
+		let class_end = chains[class_start..]
+			.iter()
+			.position(|(chain, _)| rank(chain) != class_rank)
+			.map_or(chains.len(), |len| class_start + len);
+		let class = &mut chains[class_start..class_end];
+		while class.iter().any(|(_, chain_batches)| !chain_batches.is_empty()) {
+			scheduled.extend(
+				class.iter_mut().filter_map(|(_, chain_batches)| chain_batches.pop_front()),
+			);
+		}
+		class_start = class_end;
+	}
+
+	scheduled
+}
+
+/// Like [`schedule_batches`], but the batches whose signing job was interrupted by a restart come
+/// first, so they are signed again before any new batch. They count towards the batches in flight
+/// of their chain.
+pub(crate) fn schedule_batches_resuming<BatchId, MaxLength, MaxProposals, Timestamp>(
+	batches: Vec<StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>>,
+	interrupted: &HashSet<[u8; 32]>,
+	configs: &HashMap<TypedChainId, ChainSchedulingConfig>,
+	in_flight: &HashMap<TypedChainId, usize>,
+) -> Vec<StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>>
+where
+	MaxLength: Get<u32> + Clone,
+	MaxProposals: Get<u32>,
+{
+	let (mut resumed, batches): (Vec<_>, Vec<_>) = batches
+		.into_iter()
+		.partition(|batch| batch.hash().map_or(false, |hash| interrupted.contains(&hash)));
+	let mut in_flight = in_flight.clone();
+	for batch in &resumed {
+		*in_flight.entry(batch_chain(batch)).or_default() += 1;
+	}
+	resumed.extend(schedule_batches(batches, configs, &in_flight));
+	resumed
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::signing_manager::test_utils::{batch_ids, chain_batch, Batch};
+	use dkg_runtime_primitives::PriorityClass;
+
+	fn config(
+		priority: PriorityClass,
+		max_batches_in_flight: Option<u32>,
+	) -> ChainSchedulingConfig {
+		ChainSchedulingConfig { priority, max_batches_in_flight }
+	}
+
+	fn scheduled_ids(
+		batches: Vec<Batch>,
+		configs: &[(TypedChainId, ChainSchedulingConfig)],
+		in_flight: &[(TypedChainId, usize)],
+	) -> Vec<u32> {
+		let configs = configs.iter().copied().collect();
+		let in_flight = in_flight.iter().copied().collect();
+		batch_ids(schedule_batches(batches, &configs, &in_flight))
+	}
+
+	#[test]
+	fn chains_of_the_same_class_take_turns() {
+		let spammy = TypedChainId::Evm(1);
+		let other = TypedChainId::Evm(2);
+		let batches = vec![
+			chain_batch(0, spammy),
+			chain_batch(1, spammy),
+			chain_batch(2, spammy),
+			chain_batch(3, other),
+			chain_batch(4, spammy),
+			chain_batch(5, other),
+		];
+		assert_eq!(scheduled_ids(batches, &[], &[]), vec![0, 3, 1, 5, 2, 4]);
+	}
+
+	#[test]
+	fn refresh_and_higher_priority_batches_come_first() {
+		let low = TypedChainId::Evm(1);
+		let normal = TypedChainId::Evm(2);
+		let critical = TypedChainId::Evm(3);
+		let batches = vec![
+			chain_batch(0, low),
+			chain_batch(1, normal),
+			chain_batch(2, critical),
+			chain_batch(3, TypedChainId::None),
+			chain_batch(4, critical),
+		];
+		let configs = [
+			(low, config(PriorityClass::Low, None)),
+			(critical, config(PriorityClass::Critical, None)),
+		];
+		assert_eq!(scheduled_ids(batches, &configs, &[]), vec![3, 2, 4, 1, 0]);
+	}
+
+	#[test]
+	fn chains_are_limited_to_their_batches_in_flight() {
+		let limited = TypedChainId::Evm(1);
+		let batches = vec![
+			chain_batch(0, limited),
+			chain_batch(1, limited),
+			chain_batch(2, limited),
+			chain_batch(3, TypedChainId::None),
+		];
+		let configs = [
+			(limited, config(PriorityClass::Normal, Some(2))),
+			(TypedChainId::None, config(PriorityClass::Normal, Some(0))),
+		];
+		assert_eq!(scheduled_ids(batches.clone(), &configs, &[]), vec![3, 0, 1]);
+		assert_eq!(scheduled_ids(batches.clone(), &configs, &[(limited, 1)]), vec![3, 0]);
+		assert_eq!(scheduled_ids(batches, &configs, &[(limited, 3)]), vec![3]);
+	}
+
+	#[test]
+	fn interrupted_batches_are_resumed_first() {
+		let limited = TypedChainId::Evm(1);
+		let other = TypedChainId::Evm(2);
+		let batches = vec![
+			chain_batch(0, other),
+			chain_batch(1, limited),
+			chain_batch(2, limited),
+			chain_batch(3, TypedChainId::None),
+		];
+		let interrupted = [batches[2].hash().unwrap()].into_iter().collect();
+		let configs = [(limited, config(PriorityClass::Normal, Some(1)))].into_iter().collect();
+		let scheduled =
+			batch_ids(schedule_batches_resuming(batches, &interrupted, &configs, &HashMap::new()));
+		// the resumed batch takes the only slot of its chain
+		assert_eq!(scheduled, vec![2, 3, 0]);
+	}
+}
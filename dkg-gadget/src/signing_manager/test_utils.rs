@@ -0,0 +1,74 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![allow(clippy::unwrap_used)] // allow unwraps in tests
+use codec::Encode;
+use dkg_runtime_primitives::{
+	DKGPayloadKey, MaxProposalLength, MaxProposalsInBatch, Proposal, ProposalHeader, ProposalKind,
+	ResourceId, StoredUnsignedProposalBatch, TypedChainId, UnsignedProposal,
+};
+
+pub(crate) type Batch =
+	StoredUnsignedProposalBatch<u32, MaxProposalLength, MaxProposalsInBatch, u64>;
+
+pub(crate) fn resource(id: u8) -> ResourceId {
+	ResourceId::from([id; 32])
+}
+
+/// An unsigned proposal holding the `nonce` of a resource
+pub(crate) fn proposal(resource_id: u8, nonce: u32) -> UnsignedProposal<MaxProposalLength> {
+	let mut data =
+		ProposalHeader::new(resource(resource_id), [0u8; 4].into(), nonce.into()).encode();
+	data.extend_from_slice(&[0u8; 20]);
+	UnsignedProposal {
+		typed_chain_id: TypedChainId::Evm(1),
+		key: DKGPayloadKey::TokenAddProposal(nonce.into()),
+		proposal: Proposal::Unsigned {
+			kind: ProposalKind::TokenAdd,
+			data: data.try_into().unwrap(),
+		},
+	}
+}
+
+/// An unsigned proposal without a resource nonce, unique for each `id`
+pub(crate) fn evm_proposal(
+	id: u32,
+	typed_chain_id: TypedChainId,
+) -> UnsignedProposal<MaxProposalLength> {
+	UnsignedProposal {
+		typed_chain_id,
+		key: DKGPayloadKey::EVMProposal(id.into()),
+		proposal: Proposal::Unsigned {
+			kind: ProposalKind::EVM,
+			data: id.to_le_bytes().to_vec().try_into().unwrap(),
+		},
+	}
+}
+
+pub(crate) fn batch(batch_id: u32, proposals: Vec<UnsignedProposal<MaxProposalLength>>) -> Batch {
+	Batch {
+		batch_id,
+		proposals: proposals.try_into().unwrap(),
+		timestamp: batch_id.into(),
+		encoding: Default::default(),
+	}
+}
+
+/// A batch of a single proposal targeting `typed_chain_id`
+pub(crate) fn chain_batch(batch_id: u32, typed_chain_id: TypedChainId) -> Batch {
+	batch(batch_id, vec![evm_proposal(batch_id, typed_chain_id)])
+}
+
+pub(crate) fn batch_ids(batches: Vec<Batch>) -> Vec<u32> {
+	batches.into_iter().map(|batch| batch.batch_id).collect()
+}
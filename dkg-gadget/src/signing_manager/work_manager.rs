@@ -1,5 +1,6 @@
 use crate::{
 	async_protocols::remote::{AsyncProtocolRemote, ShutdownReason},
+	constants::signing_manager::*,
 	debug_logger::DebugLogger,
 	utils::SendFuture,
 	worker::HasLatestHeader,
@@ -11,8 +12,9 @@ use dkg_primitives::{
 };
 use dkg_runtime_primitives::{associated_block_id_acceptable, SessionId};
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use sp_api::BlockT;
-use sp_arithmetic::traits::SaturatedConversion;
+use sp_arithmetic::traits::{SaturatedConversion, Saturating};
 use std::{
 	collections::{HashMap, HashSet, VecDeque},
 	hash::{Hash, Hasher},
@@ -21,18 +23,75 @@ use std::{
 };
 use sync_wrapper::SyncWrapper;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum PollMethod {
 	Interval { millis: u64 },
 	Manual,
 }
 
+/// The limits of a work manager
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WorkManagerConfig {
+	/// The maximum number of tasks running at the same time
+	pub max_running_tasks: usize,
+	/// The maximum number of tasks waiting to be started
+	pub max_enqueued_tasks: usize,
+	pub poll_method: PollMethod,
+	/// Whether the number of running tasks starts at one and adapts to how quickly the jobs
+	/// complete, up to `max_running_tasks`
+	pub adaptive_concurrency: bool,
+}
+
+impl Default for WorkManagerConfig {
+	/// The default limits of the signing manager
+	fn default() -> Self {
+		Self {
+			max_running_tasks: MAX_RUNNING_TASKS,
+			max_enqueued_tasks: MAX_ENQUEUED_TASKS,
+			poll_method: PollMethod::Interval { millis: JOB_POLL_INTERVAL_IN_MILLISECONDS },
+			adaptive_concurrency: false,
+		}
+	}
+}
+
+/// The number of tasks a work manager runs at the same time.
+///
+/// In adaptive mode, the limit starts at one, grows by one each time a job completes quickly,
+/// and is halved each time a job stalls, staying between one and the configured maximum.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConcurrencyLimit {
+	current: usize,
+	max: usize,
+	adaptive: bool,
+}
+
+impl ConcurrencyLimit {
+	pub fn new(max: usize, adaptive: bool) -> Self {
+		let max = max.max(1);
+		Self { current: if adaptive { 1 } else { max }, max, adaptive }
+	}
+
+	pub fn current(&self) -> usize {
+		self.current
+	}
+
+	pub fn on_fast_completion(&mut self) {
+		if self.adaptive {
+			self.current = (self.current + 1).min(self.max);
+		}
+	}
+
+	pub fn on_stall(&mut self) {
+		if self.adaptive {
+			self.current = (self.current / 2).max(1);
+		}
+	}
+}
+
 #[derive(Clone)]
 pub struct WorkManager<B: BlockT> {
 	inner: Arc<RwLock<WorkManagerInner<B>>>,
 	clock: Arc<dyn HasLatestHeader<B>>,
-	// for now, use a hard-coded value for the number of tasks
-	max_tasks: Arc<usize>,
 	max_enqueued_tasks: Arc<usize>,
 	logger: DebugLogger,
 	poll_method: Arc<PollMethod>,
@@ -44,9 +103,11 @@ pub struct WorkManagerInner<B: BlockT> {
 	pub enqueued_tasks: VecDeque<Job<B>>,
 	// task hash => SSID => enqueued messages
 	pub enqueued_messages: HashMap<[u8; 32], HashMap<u8, VecDeque<SignedDKGMessage<Public>>>>,
+	pub concurrency: ConcurrencyLimit,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct JobMetadata {
 	pub session_id: SessionId,
 	pub is_stalled: bool,
@@ -59,19 +120,23 @@ impl<B: BlockT> WorkManager<B> {
 	pub fn new(
 		logger: DebugLogger,
 		clock: impl HasLatestHeader<B>,
-		max_tasks: usize,
-		max_enqueued_tasks: usize,
-		poll_method: PollMethod,
+		config: WorkManagerConfig,
 	) -> Self {
+		let WorkManagerConfig {
+			max_running_tasks,
+			max_enqueued_tasks,
+			poll_method,
+			adaptive_concurrency,
+		} = config;
 		let (to_handler, mut rx) = tokio::sync::mpsc::unbounded_channel();
 		let this = Self {
 			inner: Arc::new(RwLock::new(WorkManagerInner {
 				active_tasks: HashSet::new(),
 				enqueued_tasks: VecDeque::new(),
 				enqueued_messages: HashMap::new(),
+				concurrency: ConcurrencyLimit::new(max_running_tasks, adaptive_concurrency),
 			})),
 			clock: Arc::new(clock),
-			max_tasks: Arc::new(max_tasks),
 			max_enqueued_tasks: Arc::new(max_enqueued_tasks),
 			logger,
 			to_handler,
@@ -178,9 +243,11 @@ impl<B: BlockT> WorkManager<B> {
 		let now = self.clock.get_latest_block_number();
 		let mut lock = self.inner.write();
 		let cur_count = lock.active_tasks.len();
+		let (mut fast_completions, mut stalls) = (0, 0);
 		lock.active_tasks.retain(|job| {
 			let is_stalled = job.handle.signing_has_stalled(now);
 			if is_stalled {
+				stalls += 1;
 				// If stalled, lets log the start and now blocks for logging purposes
 				self.logger.info(format!(
 					"[worker] Job {:?} | Started at {:?} | Now {:?} | is stalled, shutting down",
@@ -197,6 +264,11 @@ impl<B: BlockT> WorkManager<B> {
 			}
 
 			let is_done = job.handle.is_done();
+			if job.handle.is_completed() &&
+				now.saturating_sub(job.handle.started_at) <= FAST_JOB_COMPLETION_IN_BLOCKS.into()
+			{
+				fast_completions += 1;
+			}
 
 			!is_done
 		});
@@ -206,8 +278,18 @@ impl<B: BlockT> WorkManager<B> {
 			self.logger.info(format!("[worker] {} jobs dropped", cur_count - new_count));
 		}
 
+		let previous_limit = lock.concurrency.current();
+		(0..fast_completions).for_each(|_| lock.concurrency.on_fast_completion());
+		(0..stalls).for_each(|_| lock.concurrency.on_stall());
+		if lock.concurrency.current() != previous_limit {
+			self.logger.info(format!(
+				"[worker] Running up to {} tasks at the same time",
+				lock.concurrency.current()
+			));
+		}
+
 		// Now, check to see if there is room to start a new task
-		let tasks_to_start = self.max_tasks.saturating_sub(lock.active_tasks.len());
+		let tasks_to_start = lock.concurrency.current().saturating_sub(lock.active_tasks.len());
 		for _ in 0..tasks_to_start {
 			if let Some(job) = lock.enqueued_tasks.pop_front() {
 				self.start_job_unconditional(job, &mut *lock);
@@ -431,3 +513,30 @@ fn should_deliver<B: BlockT>(
 			msg.msg.associated_block_id,
 		)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fixed_concurrency_runs_the_maximum_number_of_tasks() {
+		let mut limit = ConcurrencyLimit::new(4, false);
+		assert_eq!(limit.current(), 4);
+		limit.on_stall();
+		limit.on_fast_completion();
+		assert_eq!(limit.current(), 4);
+		assert_eq!(ConcurrencyLimit::new(0, false).current(), 1);
+	}
+
+	#[test]
+	fn adaptive_concurrency_grows_on_fast_jobs_and_backs_off_on_stalls() {
+		let mut limit = ConcurrencyLimit::new(4, true);
+		assert_eq!(limit.current(), 1);
+		(0..5).for_each(|_| limit.on_fast_completion());
+		assert_eq!(limit.current(), 4);
+		limit.on_stall();
+		assert_eq!(limit.current(), 2);
+		(0..3).for_each(|_| limit.on_stall());
+		assert_eq!(limit.current(), 1);
+	}
+}
@@ -11,18 +11,24 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use crate::{debug_logger::DebugLogger, utils::find_index, worker::STORAGE_SET_RETRY_NUM, Client};
+use crate::{
+	debug_logger::DebugLogger,
+	metrics::Metrics,
+	utils::find_index,
+	worker::{MAX_SUBMISSION_DELAY, STORAGE_SET_RETRY_NUM},
+	Client,
+};
 use codec::Encode;
 use dkg_runtime_primitives::{
 	crypto::{AuthorityId, Public},
 	offchain::storage_keys::OFFCHAIN_SIGNED_PROPOSALS,
-	AuthoritySet, DKGApi, SignedProposalBatch,
+	AuthoritySet, DKGApi, OffchainSignedProposalBatches, SignedProposalBatch,
 };
 use parking_lot::RwLock;
 use rand::Rng;
 use sc_client_api::Backend;
 use sp_application_crypto::sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
-use sp_runtime::traits::{Block, Get, Header, NumberFor};
+use sp_runtime::traits::{Block, Get, Header, NumberFor, Saturating, UniqueSaturatedInto};
 use std::sync::Arc;
 
 /// processes signed proposals and puts them in storage
@@ -86,65 +92,109 @@ pub(crate) fn save_signed_proposals_in_storage<
 		return
 	}
 
-	let _current_block_number = {
+	let current_block_number = {
 		let header = latest_header.as_ref().expect("Should not happen, checked above");
-		header.number()
+		*header.number()
 	};
 
 	if let Some(mut offchain) = backend.offchain_storage() {
-		let old_val = offchain.get(STORAGE_PREFIX, OFFCHAIN_SIGNED_PROPOSALS);
-
-		// TODO :: Cleanup
-
-		// let mut prop_wrapper = match old_val.clone() {
-		// 	Some(ser_props) => OffchainSignedProposalBatches::<
-		// 		BatchId,
-		// 		MaxProposalLength,
-		// 		MaxProposalsInBatch,
-		// 		MaxSignatureLength,
-		// 	>::decode(&mut &ser_props[..])
-		// 	.expect("Unable to decode offchain signed proposal!"),
-		// 	None => Default::default(),
-		// };
-
-		// // The signed proposals are submitted in batches, since we want to try and limit
-		// // duplicate submissions as much as we can, we add a random submission delay to each
-		// // batch stored in offchain storage
-		// let submit_at =
-		// 	generate_delayed_submit_at::<B>(*current_block_number, MAX_SUBMISSION_DELAY);
-
-		// // lets create a vector of the data of all the proposals currently in offchain storage
-		// let current_list_of_saved_signed_proposals_data: Vec<Vec<u8>> = prop_wrapper
-		// 	.clone()
-		// 	.batches
-		// 	.into_iter()
-		// 	.map(|prop| prop.data().clone())
-		// 	.collect::<Vec<_>>();
+		// The signed proposals are submitted in batches, since we want to try and limit
+		// duplicate submissions as much as we can, we add a random submission delay to each
+		// batch stored in offchain storage
+		let submit_at = generate_delayed_submit_at::<B>(current_block_number, MAX_SUBMISSION_DELAY)
+			.unwrap_or(current_block_number);
 
-		// lets remove any duplicates
-		// we need to compare the data to ensure that the proposal is a duplicate, otherwise the
-		// signatures can be different for a same proposal
-		// signed_proposals
-		// 	.retain(|prop| !current_list_of_saved_signed_proposals_data.contains(&prop.data()));
+		for _i in 1..STORAGE_SET_RETRY_NUM {
+			let old_val = offchain.get(STORAGE_PREFIX, OFFCHAIN_SIGNED_PROPOSALS);
+			let mut outbox = match old_val.as_deref().map(|bytes| {
+				OffchainSignedProposalBatches::<
+					BatchId,
+					MaxProposalLength,
+					MaxProposalsInBatch,
+					MaxSignatureLength,
+					NumberFor<B>,
+				>::decode_versioned(bytes, submit_at)
+			}) {
+				Some((outbox, true)) => outbox,
+				Some((outbox, false)) => {
+					logger.warn(format!(
+						"🕸️  Keeping the first {} decodable signed proposals in offchain storage",
+						outbox.batches.len()
+					));
+					outbox
+				},
+				None => Default::default(),
+			};
 
-		// prop_wrapper.batches.push(old_val)
+			// lets remove any duplicates
+			// we need to compare the data to ensure that the proposal is a duplicate, otherwise
+			// the signatures can be different for a same proposal
+			let appended = outbox.append(signed_proposals.iter().cloned(), submit_at);
+			if appended == 0 {
+				logger.debug("🕸️  Signed proposals already in offchain storage".to_string());
+				break
+			}
 
-		for _i in 1..STORAGE_SET_RETRY_NUM {
 			if offchain.compare_and_set(
 				STORAGE_PREFIX,
 				OFFCHAIN_SIGNED_PROPOSALS,
 				old_val.as_deref(),
-				&signed_proposals.encode(),
+				&outbox.encode(),
 			) {
-				logger.debug(
-					"🕸️  Successfully saved signed proposals in offchain storage".to_string(),
-				);
+				logger.debug(format!(
+					"🕸️  Successfully saved {appended} signed proposals in offchain storage, {} pending",
+					outbox.batches.len()
+				));
 				break
 			}
 		}
 	}
 }
 
+/// Reports the signed proposal batches waiting in the offchain outbox. The batches which wait long
+/// or are submitted several times reveal the submitters which are offline, and which the backup
+/// submitters stand in for
+pub(crate) fn report_pending_signed_proposals<B, BE>(
+	backend: &Arc<BE>,
+	current_block_number: NumberFor<B>,
+	metrics: &Metrics,
+) where
+	B: Block,
+	BE: Backend<B>,
+{
+	let Some((outbox, _)) = backend
+		.offchain_storage()
+		.and_then(|offchain| offchain.get(STORAGE_PREFIX, OFFCHAIN_SIGNED_PROPOSALS))
+		.map(|bytes| {
+			OffchainSignedProposalBatches::<
+				dkg_runtime_primitives::BatchId,
+				dkg_runtime_primitives::MaxProposalLength,
+				dkg_runtime_primitives::MaxProposalsInBatch,
+				dkg_runtime_primitives::MaxSignatureLength,
+				NumberFor<B>,
+			>::decode_versioned(&bytes, current_block_number)
+		})
+	else {
+		return
+	};
+
+	let resubmissions: u64 = outbox
+		.batches
+		.iter()
+		.map(|pending| pending.retries.saturating_sub(1) as u64)
+		.sum();
+	let wait_blocks = outbox
+		.batches
+		.iter()
+		.map(|pending| current_block_number.saturating_sub(pending.submit_at))
+		.max()
+		.unwrap_or_default();
+
+	metrics.dkg_pending_signed_proposals.set(outbox.batches.len() as u64);
+	metrics.dkg_signed_proposal_resubmissions.set(resubmissions);
+	metrics.dkg_signed_proposal_wait_blocks.set(wait_blocks.unique_saturated_into());
+}
+
 /// Generate a random delay to wait before taking an action.
 /// The delay is generated from a random number between 0 and `max_delay`.
 pub fn generate_delayed_submit_at<B: Block>(
@@ -14,10 +14,11 @@
 //
 use crate::worker::ENGINE_ID;
 use dkg_primitives::{
-	crypto::AuthorityId, types::DKGError, AuthoritySet, ConsensusLog, MaxAuthorities,
+	crypto::AuthorityId, types::DKGError, AuthoritySet, ConsensusLog, DKGApi, MaxAuthorities,
+	MaxProposalLength,
 };
-use sp_api::{BlockT as Block, HeaderT};
-use sp_runtime::generic::OpaqueDigestItemId;
+use sp_api::{ApiExt, BlockT as Block, HeaderT, ProvideRuntimeApi};
+use sp_runtime::{generic::OpaqueDigestItemId, traits::NumberFor};
 use std::{fmt::Debug, future::Future};
 
 pub trait SendFuture<'a, Out: 'a>: Future<Output = Result<Out, DKGError>> + Send + 'a {}
@@ -44,6 +45,24 @@ where
 	header.digest().convert_first(|l| l.try_to(id).and_then(match_consensus_log))
 }
 
+/// Whether the runtime at `at` implements at least the given version of the [`DKGApi`]. The
+/// methods added in a later version fail when called on an older runtime.
+pub fn has_dkg_api_version<B, C>(client: &C, at: B::Hash, version: u32) -> bool
+where
+	B: Block,
+	C: ProvideRuntimeApi<B>,
+	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
+{
+	client
+		.runtime_api()
+		.api_version::<dyn DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>>(
+			at,
+		)
+		.ok()
+		.flatten()
+		.map_or(false, |version_at| version_at >= version)
+}
+
 /// Matches a `ConsensusLog` for a DKG validator set change.
 fn match_consensus_log(
 	log: ConsensusLog<AuthorityId, MaxAuthorities>,
@@ -24,7 +24,7 @@ use sc_network::NetworkService;
 use sc_network_sync::SyncingService;
 use sp_consensus::SyncOracle;
 
-use crate::signing_manager::SigningManager;
+use crate::signing_manager::{work_manager::WorkManagerConfig, SigningManager};
 use futures::StreamExt;
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
 use parking_lot::{Mutex, RwLock};
@@ -49,8 +49,8 @@ use dkg_runtime_primitives::{
 	gossip_messages::MisbehaviourMessage,
 	utils::to_slice_33,
 	AggregatedMisbehaviourReports, AggregatedPublicKeys, AuthoritySet, BatchId, DKGApi,
-	MaxAuthorities, MaxProposalLength, MaxProposalsInBatch, MaxReporters, MaxSignatureLength,
-	GENESIS_AUTHORITY_SET_ID,
+	DKGProtocol, MaxAuthorities, MaxProposalLength, MaxProposalsInBatch, MaxReporters,
+	MaxSignatureLength, DKG_API_VERSION_2, GENESIS_AUTHORITY_SET_ID,
 };
 
 pub use crate::constants::worker::*;
@@ -60,14 +60,19 @@ use crate::{
 	error,
 	gossip_engine::GossipEngineIface,
 	gossip_messages::{
-		misbehaviour_report::{gossip_misbehaviour_report, handle_misbehaviour_report},
+		misbehaviour_report::{
+			gossip_misbehaviour_report, handle_misbehaviour_report, handle_vote_equivocation,
+			SeenVoteStore,
+		},
 		public_key_gossip::handle_public_key_broadcast,
 	},
 	keygen_manager::KeygenManager,
 	keystore::DKGKeystore,
 	metric_inc, metric_set,
 	metrics::Metrics,
-	utils::find_authorities_change,
+	rpc_links::{DKGRpcLinks, WorkerStatus},
+	storage::proposals::report_pending_signed_proposals,
+	utils::{find_authorities_change, has_dkg_api_version},
 	Client,
 };
 
@@ -90,6 +95,8 @@ where
 	pub network: Option<Arc<NetworkService<B, B::Hash>>>,
 	pub sync_service: Option<Arc<SyncingService<B>>>,
 	pub test_bundle: Option<TestBundle>,
+	pub rpc_links: DKGRpcLinks,
+	pub signing_work_config: WorkManagerConfig,
 	pub _marker: PhantomData<B>,
 }
 
@@ -122,6 +129,8 @@ where
 	pub aggregated_public_keys: Shared<AggregatedPublicKeysAndSigs>,
 	/// Tracking for the misbehaviour reports
 	pub aggregated_misbehaviour_reports: Shared<AggregatedMisbehaviourReportStore>,
+	/// Tracking for the votes of the signing rounds, to detect equivocations
+	pub seen_votes: Shared<SeenVoteStore>,
 	/// Concrete type that points to the actual local keystore if it exists
 	pub local_keystore: Shared<Option<Arc<LocalKeystore>>>,
 	/// Used to keep track of network status
@@ -129,6 +138,8 @@ where
 	/// Used to keep track of sync status
 	pub sync_service: Option<Arc<SyncingService<B>>>,
 	pub test_bundle: Option<TestBundle>,
+	/// Used to publish the worker status to the RPC server
+	pub rpc_links: DKGRpcLinks,
 	pub logger: DebugLogger,
 	pub dkg_modules: DKGModules<B, BE, C, GE>,
 	pub signing_manager: SigningManager<B, BE, C, GE>,
@@ -177,8 +188,10 @@ where
 			queued_validator_set: self.queued_validator_set.clone(),
 			aggregated_public_keys: self.aggregated_public_keys.clone(),
 			aggregated_misbehaviour_reports: self.aggregated_misbehaviour_reports.clone(),
+			seen_votes: self.seen_votes.clone(),
 			local_keystore: self.local_keystore.clone(),
 			test_bundle: self.test_bundle.clone(),
+			rpc_links: self.rpc_links.clone(),
 			network: self.network.clone(),
 			sync_service: self.sync_service.clone(),
 			logger: self.logger.clone(),
@@ -226,13 +239,16 @@ where
 			network,
 			sync_service,
 			test_bundle,
+			rpc_links,
+			signing_work_config,
 			..
 		} = worker_params;
 
 		let clock = Clock { latest_header: latest_header.clone() };
-		let signing_manager = SigningManager::<B, BE, C, GE>::new(logger.clone(), clock.clone());
+		let signing_manager =
+			SigningManager::<B, BE, C, GE>::new(logger.clone(), clock.clone(), signing_work_config);
 		// 2 tasks max: 1 for current, 1 for queued
-		let keygen_manager = KeygenManager::new(logger.clone(), clock);
+		let keygen_manager = KeygenManager::new(logger.clone(), clock, db_backend.clone());
 		let dkg_modules = DKGModules::default();
 
 		let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
@@ -254,8 +270,10 @@ where
 			latest_header,
 			aggregated_public_keys: Arc::new(RwLock::new(HashMap::new())),
 			aggregated_misbehaviour_reports: Arc::new(RwLock::new(HashMap::new())),
+			seen_votes: Arc::new(RwLock::new(HashMap::new())),
 			local_keystore: Arc::new(RwLock::new(local_keystore)),
 			test_bundle,
+			rpc_links,
 			error_handler_channel,
 			logger,
 			network,
@@ -275,7 +293,11 @@ where
 pub enum ProtoStageType {
 	KeygenGenesis,
 	KeygenStandard,
-	Signing { unsigned_proposal_hash: [u8; 32] },
+	/// The outgoing keygen set hands off the shares of the active key to the next keygen set
+	KeyReshare,
+	Signing {
+		unsigned_proposal_hash: [u8; 32],
+	},
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -340,6 +362,7 @@ where
 		let active_local_key = match stage {
 			ProtoStageType::KeygenGenesis => None,
 			ProtoStageType::KeygenStandard => None,
+			ProtoStageType::KeyReshare => None,
 			ProtoStageType::Signing { .. } => {
 				let (active_local_key, _) = self.fetch_local_keys(session_id);
 				active_local_key
@@ -368,6 +391,7 @@ where
 				is_genesis: stage == ProtoStageType::KeygenGenesis,
 				metrics: self.metrics.clone(),
 				test_bundle: self.test_bundle.clone(),
+				rpc_links: self.rpc_links.clone(),
 				logger: self.logger.clone(),
 				_pd: Default::default(),
 			}),
@@ -391,7 +415,9 @@ where
 				Ok(params)
 			},
 
-			ProtoStageType::KeygenGenesis | ProtoStageType::KeygenStandard => {
+			ProtoStageType::KeygenGenesis |
+			ProtoStageType::KeygenStandard |
+			ProtoStageType::KeyReshare => {
 				self.logger.debug(format!(
 					"Protocol for stage {stage:?} will start later in the keygen manager"
 				));
@@ -468,6 +494,56 @@ where
 		.await
 	}
 
+	/// Get the DKG protocol of the current authority set at a specific block
+	pub async fn get_dkg_protocol(&self, header: &B::Header) -> DKGProtocol {
+		let at = header.hash();
+		self.exec_client_function(move |client| {
+			if !has_dkg_api_version::<B, _>(client, at, DKG_API_VERSION_2) {
+				return Default::default()
+			}
+			client.runtime_api().dkg_protocol(at).unwrap_or_default()
+		})
+		.await
+	}
+
+	/// Get the DKG protocol of the next authority set at a specific block
+	pub async fn get_next_dkg_protocol(&self, header: &B::Header) -> DKGProtocol {
+		let at = header.hash();
+		self.exec_client_function(move |client| {
+			if !has_dkg_api_version::<B, _>(client, at, DKG_API_VERSION_2) {
+				return Default::default()
+			}
+			client.runtime_api().next_dkg_protocol(at).unwrap_or_default()
+		})
+		.await
+	}
+
+	/// Whether the next authority set should refresh the shares of the active key rather than
+	/// generating a new one, at a specific block
+	pub async fn should_refresh_key_shares(&self, header: &B::Header) -> bool {
+		let at = header.hash();
+		self.exec_client_function(move |client| {
+			if !has_dkg_api_version::<B, _>(client, at, DKG_API_VERSION_2) {
+				return Default::default()
+			}
+			client.runtime_api().should_refresh_key_shares(at).unwrap_or_default()
+		})
+		.await
+	}
+
+	/// Whether the outgoing keygen set should reshare the active key to the next keygen set
+	/// rather than the next set generating a new one, at a specific block
+	pub async fn should_reshare_key(&self, header: &B::Header) -> bool {
+		let at = header.hash();
+		self.exec_client_function(move |client| {
+			if !has_dkg_api_version::<B, _>(client, at, DKG_API_VERSION_2) {
+				return Default::default()
+			}
+			client.runtime_api().should_reshare_key(at).unwrap_or_default()
+		})
+		.await
+	}
+
 	/// Get the active DKG public key
 	pub async fn get_dkg_pub_key(&self, header: &B::Header) -> (AuthoritySetId, Vec<u8>) {
 		let at = header.hash();
@@ -617,6 +693,9 @@ where
 		self.logger
 			.debug(format!("🕸️  Processing block notification for block {}", header.number()));
 		metric_set!(self, dkg_latest_block_height, header.number());
+		if let Some(metrics) = self.metrics.as_ref() {
+			report_pending_signed_proposals::<B, BE>(&self.backend, *header.number(), metrics);
+		}
 		*self.latest_header.write() = Some(header.clone());
 		self.logger.debug(format!("🕸️  Latest header is now: {:?}", header.number()));
 
@@ -648,6 +727,35 @@ where
 					.error(format!("🕸️  Error running signing_manager.on_block_finalized: {e:?}"));
 			}
 		}
+
+		self.update_gossip_authorities();
+		self.publish_worker_status(header);
+	}
+
+	/// Routes the gossiped messages to the current and next best authorities
+	fn update_gossip_authorities(&self) {
+		let authorities = self
+			.best_authorities
+			.read()
+			.iter()
+			.chain(self.next_best_authorities.read().iter())
+			.map(|(_, authority_id)| authority_id.clone())
+			.collect::<Vec<_>>();
+		self.keygen_gossip_engine.set_authorities(authorities.clone());
+		self.signing_gossip_engine.set_authorities(authorities);
+	}
+
+	/// Publishes the state of the keygen and signing jobs to the RPC server
+	fn publish_worker_status(&self, header: &B::Header) {
+		let now = *header.number();
+		self.rpc_links.set_worker_status(WorkerStatus {
+			block_number: now.saturated_into(),
+			keygen_state: self.keygen_manager.state(),
+			latest_executed_keygen_session: self.keygen_manager.get_latest_executed_session_id(),
+			signing_locked: self.signing_manager.is_keygen_locked(),
+			keygen_jobs: self.keygen_manager.jobs_metadata(now),
+			signing_jobs: self.signing_manager.jobs_metadata(now),
+		});
 	}
 
 	async fn maybe_enact_genesis_authorities(&self, header: &B::Header) {
@@ -678,7 +786,7 @@ where
 			// verify the new validator set
 			let _ = self.verify_validator_set(header.number(), active.clone());
 			// Check if the on chain authority_set_id is the same as the queued_authority_set_id.
-			let (set_id, _) = self.get_dkg_pub_key(header).await;
+			let (set_id, dkg_pub_key) = self.get_dkg_pub_key(header).await;
 			let queued_authority_set_id = self.queued_validator_set.read().id;
 			self.logger.debug(format!("🕸️  CURRENT SET ID: {set_id:?}"));
 			self.logger
@@ -699,6 +807,7 @@ where
 			}
 			// Delete logs from old sessions to preserve disk space
 			self.logger.clear_local_logs();
+			self.remove_replaced_key_shares(set_id, &dkg_pub_key);
 		} else {
 			self.logger.info(
 				"🕸️  No update to local session found, not rotating local sessions".to_string(),
@@ -706,6 +815,28 @@ where
 		}
 	}
 
+	/// Removes the WT-FROST key shares of the previous session once the active session kept its
+	/// public key, since they were replaced by the refreshed or reshared shares.
+	fn remove_replaced_key_shares(&self, session_id: SessionId, dkg_pub_key: &[u8]) {
+		let Some(previous_session_id) = session_id.checked_sub(1) else { return };
+		let replaced = match self.db.get_frost_local_key(previous_session_id) {
+			Ok(Some(key)) => key.public_key().to_bytes(true).as_ref() == dkg_pub_key,
+			_ => false,
+		};
+		if !replaced {
+			return
+		}
+
+		match self.db.remove_frost_local_key(previous_session_id) {
+			Ok(()) => self.logger.info(format!(
+				"🕸️  Removed the replaced WT-FROST key shares of session {previous_session_id}"
+			)),
+			Err(err) => self.logger.error(format!(
+				"🕸️  Failed to remove the replaced WT-FROST key shares of session {previous_session_id}: {err:?}"
+			)),
+		}
+	}
+
 	async fn handle_finality_notification(&self, notification: FinalityNotification<B>) {
 		self.logger.trace(format!("🕸️  Finality notification: {notification:?}"));
 		// Handle finality notifications
@@ -855,7 +986,14 @@ where
 				self.keygen_manager.deliver_message(dkg_msg);
 				Ok(())
 			},
-			NetworkMsgPayload::Offline(..) | NetworkMsgPayload::Vote(..) => {
+			NetworkMsgPayload::Offline(..) => {
+				self.signing_manager.deliver_message(dkg_msg);
+				Ok(())
+			},
+			NetworkMsgPayload::Vote(..) => {
+				if let Err(err) = handle_vote_equivocation(self, &dkg_msg).await {
+					self.logger.error(format!("🕸️  Error while checking the DKG vote {err:?}"));
+				}
 				self.signing_manager.deliver_message(dkg_msg);
 				Ok(())
 			},
@@ -917,7 +1055,7 @@ where
 
 		let misbehaviour_msg =
 			MisbehaviourMessage { misbehaviour_type, session_id, offender, signature: vec![] };
-		let gossip = gossip_misbehaviour_report(self, misbehaviour_msg).await;
+		let gossip = gossip_misbehaviour_report(self, misbehaviour_msg, None).await;
 		if gossip.is_err() {
 			self.logger.info("🕸️  DKG gossip_misbehaviour_report failed!");
 		}
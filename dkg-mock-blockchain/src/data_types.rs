@@ -1,5 +1,6 @@
 use crate::{mock_blockchain_config::ErrorCase, FinalityNotification, ImportNotification};
 use codec::{Decode, Encode};
+use dkg_runtime_primitives::MisbehaviourType;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -23,12 +24,49 @@ pub struct AttachedCommandMetadata {
 }
 
 /// A command given to the receiving MockClient
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 pub enum AttachedCommand {
 	// Tells the client to process the requests as normal
+	#[default]
 	ProcessAsNormal,
 	// Tells the client to not process the request (for this round)
 	ErrorDontProcessRequest,
+	// Tells the client to send truncated keygen, offline and vote messages, properly signed
+	SendMalformedMessages,
+	// Tells the client to send a different payload to half of its peers
+	Equivocate,
+	// Tells the client to claim the identity of another authority in its messages
+	ForgeSignature,
+	// Tells the client to send its messages of a previous session instead of the current ones
+	ReplayOldSessionMessages,
+}
+
+impl AttachedCommand {
+	/// Whether the receiving client actively misbehaves towards its peers
+	pub fn is_byzantine(&self) -> bool {
+		matches!(
+			self,
+			Self::SendMalformedMessages |
+				Self::Equivocate | Self::ForgeSignature |
+				Self::ReplayOldSessionMessages
+		)
+	}
+}
+
+/// The kind of misbehaviour a client reports another client for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReportedMisbehaviour {
+	Keygen,
+	Sign,
+}
+
+impl From<MisbehaviourType> for ReportedMisbehaviour {
+	fn from(misbehaviour: MisbehaviourType) -> Self {
+		match misbehaviour {
+			MisbehaviourType::Keygen => Self::Keygen,
+			MisbehaviourType::Sign => Self::Sign,
+		}
+	}
 }
 
 /// When a MockClient receives a message, it should attempt to send information
@@ -36,8 +74,25 @@ pub enum AttachedCommand {
 /// introspection
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum MockClientResponse {
-	Keygen { result: Result<(), String>, trace_id: Uuid, pub_key: Vec<u8> },
-	Sign { result: Result<(), String>, trace_id: Uuid },
+	Keygen {
+		result: Result<(), String>,
+		trace_id: Uuid,
+		pub_key: Vec<u8>,
+	},
+	Sign {
+		result: Result<(), String>,
+		trace_id: Uuid,
+	},
+	// The client gossiped a misbehaviour report against the `offender`
+	MisbehaviourReport {
+		trace_id: Uuid,
+		#[serde(
+			serialize_with = "crate::data_types::serialize_peer_id",
+			deserialize_with = "crate::data_types::deserialize_peer_id"
+		)]
+		offender: crate::server::PeerId,
+		misbehaviour: ReportedMisbehaviour,
+	},
 }
 
 /// For keeping track of various events sent to subscribing clients
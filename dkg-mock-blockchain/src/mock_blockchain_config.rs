@@ -20,6 +20,113 @@ pub struct MockBlockchainConfig {
 	pub error_cases: Option<Vec<ErrorCase>>,
 	// the number of unsigned proposals to send per session
 	pub unsigned_proposals_per_session: Option<usize>,
+	// the faults injected in the gossip network between the clients
+	#[serde(default)]
+	pub network_faults: Option<NetworkFaultsConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+/// Faults injected in the in-memory gossip network of the clients. The clients are referred to by
+/// their index, in the order they are spawned by the orchestrator
+pub struct NetworkFaultsConfig {
+	// the probability, in [0, 1], that a message is lost
+	#[serde(default)]
+	pub loss_probability: f64,
+	// the probability, in [0, 1], that a message is delivered twice
+	#[serde(default)]
+	pub duplication_probability: f64,
+	// the probability, in [0, 1], that a message is held back for a random duration up to
+	// `reorder_window`, so that the next messages of the same link overtake it
+	#[serde(default)]
+	pub reorder_probability: f64,
+	#[serde(default)]
+	#[serde(with = "humantime_serde")]
+	pub reorder_window: Option<Duration>,
+	#[serde(default)]
+	#[serde(with = "humantime_serde")]
+	// the latency of every link, unless overridden in `link_latencies`
+	pub latency: Option<Duration>,
+	#[serde(default)]
+	#[serde(with = "humantime_serde")]
+	// a random delay up to `jitter` is added to the latency of each message
+	pub jitter: Option<Duration>,
+	// the latencies of specific links
+	#[serde(default)]
+	pub link_latencies: Vec<LinkLatency>,
+	// the partitions of the network over time
+	#[serde(default)]
+	pub partitions: Vec<NetworkPartition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The latency of the messages sent by the client `from` to the client `to`
+pub struct LinkLatency {
+	pub from: usize,
+	pub to: usize,
+	#[serde(with = "humantime_serde")]
+	pub latency: Duration,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A partition of the network, during which the clients only reach the clients of their group.
+/// The clients which are not listed in any group form a group of their own
+pub struct NetworkPartition {
+	pub groups: Vec<Vec<usize>>,
+	#[serde(with = "humantime_serde")]
+	// the time after the start of the orchestrator at which the partition begins
+	pub start: Duration,
+	#[serde(default)]
+	#[serde(with = "humantime_serde")]
+	// the time after the start of the orchestrator at which the partition heals, if ever
+	pub end: Option<Duration>,
+}
+
+impl NetworkFaultsConfig {
+	pub fn validate(&self, n_clients: usize) -> Result<(), String> {
+		let probabilities = [
+			("loss_probability", self.loss_probability),
+			("duplication_probability", self.duplication_probability),
+			("reorder_probability", self.reorder_probability),
+		];
+		for (name, probability) in probabilities {
+			if !(0.0..=1.0).contains(&probability) {
+				return Err(format!("{name} must be between 0 and 1, got {probability}"))
+			}
+		}
+
+		let links = self.link_latencies.iter().flat_map(|link| [link.from, link.to]);
+		let partitioned = self.partitions.iter().flat_map(|partition| partition.groups.concat());
+		if let Some(client) = links.chain(partitioned).find(|client| *client >= n_clients) {
+			return Err(format!("Client {client} does not exist, there are {n_clients} clients"))
+		}
+
+		Ok(())
+	}
+
+	/// Whether the messages from the client `from` reach the client `to` at `elapsed` after the
+	/// start of the orchestrator
+	pub fn is_reachable(&self, from: usize, to: usize, elapsed: Duration) -> bool {
+		self.partitions
+			.iter()
+			.filter(|partition| {
+				partition.start <= elapsed && partition.end.map_or(true, |end| elapsed < end)
+			})
+			.all(|partition| {
+				let group_of =
+					|client| partition.groups.iter().position(|group| group.contains(&client));
+				group_of(from) == group_of(to)
+			})
+	}
+
+	/// The latency of the messages from the client `from` to the client `to`, before jitter
+	pub fn link_latency(&self, from: usize, to: usize) -> Duration {
+		self.link_latencies
+			.iter()
+			.find(|link| link.from == from && link.to == to)
+			.map(|link| link.latency)
+			.or(self.latency)
+			.unwrap_or_default()
+	}
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -38,6 +145,9 @@ pub struct ErrorCase {
 	pub n_clients: usize,
 	// the specific command that each receiving client should cause
 	pub command: crate::AttachedCommand,
+	// the misbehaviour each honest client is expected to report each byzantine client for, if any
+	#[serde(default)]
+	pub expected_report: Option<crate::ReportedMisbehaviour>,
 	// the number of times to run this test case
 	pub count: usize,
 }
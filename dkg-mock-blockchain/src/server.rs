@@ -1,6 +1,8 @@
 use crate::{
-	mock_blockchain_config::MockBlockchainConfig, transport::*, FinalityNotification,
-	MockBlockchainEvent, MockClientResponse, TestBlock, TestCase,
+	mock_blockchain_config::{ErrorCase, MockBlockchainConfig},
+	transport::*,
+	FinalityNotification, MockBlockchainEvent, MockClientResponse, ReportedMisbehaviour, TestBlock,
+	TestCase,
 };
 use atomic::Atomic;
 use dkg_logging::debug_logger::DebugLogger;
@@ -46,6 +48,8 @@ enum ClientToOrchestratorEvent {
 	// running the test cases once "n" peers send this status
 	ClientReady,
 	TestResult { peer_id: PeerId, trace_id: Uuid, result: TestResult },
+	// The `reporter` gossiped a misbehaviour report against the `offender`
+	MisbehaviourReport { reporter: PeerId, offender: PeerId, misbehaviour: ReportedMisbehaviour },
 }
 
 #[derive(Debug)]
@@ -59,7 +63,11 @@ enum OrchestratorToClientEvent {
 	// Tells the client subtask to halt
 	Halt,
 	// Tells the client subtask to send a mock event
-	BlockChainEvent { trace_id: Uuid, event: MockBlockchainEvent<TestBlock> },
+	BlockChainEvent {
+		trace_id: Uuid,
+		event: MockBlockchainEvent<TestBlock>,
+		command: crate::AttachedCommand,
+	},
 }
 
 #[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
@@ -79,9 +87,21 @@ struct ConnectedClientState {
 	// for the specific client, the test case will be removed from the list
 	outstanding_tasks_keygen: HashMap<Uuid, crate::TestCase>,
 	outstanding_tasks_signing: HashMap<Uuid, Vec<crate::TestCase>>,
+	// the misbehaviour reports this client is expected to gossip. Unlike the tasks, they are
+	// kept across rounds, since the reports may be gossiped after the round completes
+	outstanding_reports: Vec<ExpectedReport>,
+	// whether the client misbehaves in the current test case. Its test results are ignored
+	byzantine: bool,
 	orchestrator_to_client_subtask: mpsc::UnboundedSender<OrchestratorToClientEvent>,
 }
 
+#[derive(Debug)]
+struct ExpectedReport {
+	trace_id: Uuid,
+	offender: PeerId,
+	misbehaviour: ReportedMisbehaviour,
+}
+
 impl<T: MutableBlockchain> MockBlockchain<T> {
 	pub async fn new(
 		config: MockBlockchainConfig,
@@ -152,6 +172,8 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 			let state = ConnectedClientState {
 				outstanding_tasks_keygen: Default::default(),
 				outstanding_tasks_signing: Default::default(),
+				outstanding_reports: Default::default(),
+				byzantine: false,
 				orchestrator_to_client_subtask: orchestrator_to_this_task,
 			};
 
@@ -179,20 +201,31 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 							panic!("Received invalid packet {pkt:?} inside to_orchestrator for {peer_id:?}")
 						},
 						ProtocolPacket::ClientToBlockchain { event } => {
-							let (result, trace_id) = match event {
+							let event = match event {
 								MockClientResponse::Keygen { result, trace_id, pub_key } =>
-									(TestResult::Keygen { result, pub_key }, trace_id),
+									ClientToOrchestratorEvent::TestResult {
+										peer_id: *peer_id,
+										trace_id,
+										result: TestResult::Keygen { result, pub_key },
+									},
 								MockClientResponse::Sign { result, trace_id } =>
-									(TestResult::Sign { result }, trace_id),
+									ClientToOrchestratorEvent::TestResult {
+										peer_id: *peer_id,
+										trace_id,
+										result: TestResult::Sign { result },
+									},
+								MockClientResponse::MisbehaviourReport {
+									offender,
+									misbehaviour,
+									..
+								} => ClientToOrchestratorEvent::MisbehaviourReport {
+									reporter: *peer_id,
+									offender,
+									misbehaviour,
+								},
 							};
 
-							self.to_orchestrator
-								.send(ClientToOrchestratorEvent::TestResult {
-									peer_id: *peer_id,
-									trace_id,
-									result,
-								})
-								.unwrap();
+							self.to_orchestrator.send(event).unwrap();
 						},
 					}
 				}
@@ -209,10 +242,14 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 							tx.send(ProtocolPacket::Halt).await.unwrap();
 							return
 						},
-						OrchestratorToClientEvent::BlockChainEvent { trace_id, event } => {
-							tx.send(ProtocolPacket::BlockchainToClient { trace_id, event })
-								.await
-								.unwrap();
+						OrchestratorToClientEvent::BlockChainEvent { trace_id, event, command } => {
+							tx.send(ProtocolPacket::BlockchainToClient {
+								trace_id,
+								event,
+								command,
+							})
+							.await
+							.unwrap();
 						},
 					}
 				}
@@ -255,11 +292,26 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 					if !client.outstanding_tasks_signing.is_empty() {
 						log::warn!(target: "dkg", "Client {id:?} has {tasks:?} outstanding SIGNING task(s)", tasks = client.outstanding_tasks_signing.values().map(|r| r.len()).sum::<usize>());
 					}
+
+					if !client.outstanding_reports.is_empty() {
+						log::warn!(target: "dkg", "Client {id:?} has {reports:?} outstanding misbehaviour report(s)", reports = client.outstanding_reports.len());
+					}
 				}
 			}
 		});
 
 		while let Some(client_update) = client_to_orchestrator_rx.recv().await {
+			// the misbehaviour reports are not tied to the rounds
+			if let ClientToOrchestratorEvent::MisbehaviourReport {
+				reporter,
+				offender,
+				misbehaviour,
+			} = &client_update
+			{
+				self.handle_misbehaviour_report(reporter, offender, *misbehaviour).await;
+				continue
+			}
+
 			match self.orchestrator_state.load(Ordering::SeqCst) {
 				o_state @ OrchestratorState::WaitingForInit => match client_update {
 					ClientToOrchestratorEvent::ClientReady => {
@@ -279,9 +331,16 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 
 				o_state @ OrchestratorState::AwaitingRoundCompletion => {
 					match &client_update {
-						ClientToOrchestratorEvent::ClientReady =>
+						ClientToOrchestratorEvent::ClientReady |
+						ClientToOrchestratorEvent::MisbehaviourReport { .. } =>
 							log_invalid_signal(&o_state, &client_update),
 						ClientToOrchestratorEvent::TestResult { peer_id, trace_id, result } => {
+							if self.clients.read().await.get(peer_id).map_or(false, |c| c.byzantine)
+							{
+								log::info!(target: "dkg", "Ignoring the result of test {trace_id:?} from byzantine peer {peer_id:?}");
+								continue
+							}
+
 							let res = match result {
 								TestResult::Keygen { result, pub_key } => {
 									// set the public key that way other nodes can verify that
@@ -349,9 +408,11 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 						},
 					}
 
-					// at the end, check if the round is complete
-					let keygen_complete =
-						current_round_completed_count_keygen == self.config.n_clients;
+					// at the end, check if the round is complete. Only the honest clients are
+					// expected to complete it
+					let honest_clients =
+						self.clients.read().await.values().filter(|c| !c.byzantine).count();
+					let keygen_complete = current_round_completed_count_keygen == honest_clients;
 
 					if keygen_complete && matches!(intra_test_phase, IntraTestPhase::Keygen { .. })
 					{
@@ -401,6 +462,30 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 		Err(generic_error("client_to_orchestrator_tx's all dropped"))
 	}
 
+	async fn handle_misbehaviour_report(
+		&self,
+		reporter: &PeerId,
+		offender: &PeerId,
+		misbehaviour: ReportedMisbehaviour,
+	) {
+		let mut clients = self.clients.write().await;
+		let Some(client) = clients.get_mut(reporter) else {
+			log::warn!(target: "dkg", "Received a misbehaviour report from unknown peer {reporter:?}");
+			return
+		};
+
+		if let Some(idx) = client
+			.outstanding_reports
+			.iter()
+			.position(|report| report.offender == *offender && report.misbehaviour == misbehaviour)
+		{
+			let report = client.outstanding_reports.remove(idx);
+			log::info!(target: "dkg", "Peer {reporter:?} reported the expected {misbehaviour:?} misbehaviour of peer {offender:?} for test {trace_id:?}", trace_id = report.trace_id);
+		} else {
+			log::warn!(target: "dkg", "Peer {reporter:?} reported an unexpected {misbehaviour:?} misbehaviour of peer {offender:?}");
+		}
+	}
+
 	async fn clear_tasks(&self) {
 		let mut clients = self.clients.write().await;
 		clients.values_mut().for_each(|client| client.outstanding_tasks_signing.clear());
@@ -475,12 +560,14 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 			for (peer_id, client_state) in &*read {
 				let outstanding_tasks_keygen = &client_state.outstanding_tasks_keygen;
 				let outstanding_tasks_signing = &client_state.outstanding_tasks_signing;
-				// the client should have no outstanding tasks if successful
-				let success =
-					outstanding_tasks_keygen.is_empty() && outstanding_tasks_signing.is_empty();
+				let outstanding_reports = &client_state.outstanding_reports;
+				// the client should have no outstanding tasks nor reports if successful
+				let success = outstanding_tasks_keygen.is_empty() &&
+					outstanding_tasks_signing.is_empty() &&
+					outstanding_reports.is_empty();
 				if !success {
 					exit_code = 1;
-					log::info!(target: "dkg", "Peer {peer_id:?} final state FAILURE | Failed tasks: KEYGEN: {outstanding_tasks_keygen:?}, SIGNING: {outstanding_tasks_signing:?}")
+					log::info!(target: "dkg", "Peer {peer_id:?} final state FAILURE | Failed tasks: KEYGEN: {outstanding_tasks_keygen:?}, SIGNING: {outstanding_tasks_signing:?}, REPORTS: {outstanding_reports:?}")
 				} else {
 					log::info!(target: "dkg", "Peer {peer_id:?} SUCCESS!")
 				}
@@ -524,24 +611,50 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 
 		let mut write = self.clients.write().await;
 		let next_finality_notification = create_mocked_finality_blockchain_event(round_number);
-		for (_id, client) in write.iter_mut() {
+		let byzantine_peers = byzantine_peers(&write, &next_case);
+		for (id, client) in write.iter_mut() {
+			client.byzantine = byzantine_peers.contains(id);
+			let command = match &next_case {
+				TestCase::Invalid(error_case) if client.byzantine => error_case.command.clone(),
+				_ => Default::default(),
+			};
+
 			match test_phase {
 				IntraTestPhase::Keygen { trace_id, .. } => {
-					client.outstanding_tasks_keygen.insert(*trace_id, next_case.clone());
+					if !client.byzantine {
+						client.outstanding_tasks_keygen.insert(*trace_id, next_case.clone());
+						// the reports are expected once per test case
+						if let TestCase::Invalid(ErrorCase {
+							expected_report: Some(misbehaviour),
+							..
+						}) = &next_case
+						{
+							client.outstanding_reports.extend(byzantine_peers.iter().map(
+								|offender| ExpectedReport {
+									trace_id: *trace_id,
+									offender: *offender,
+									misbehaviour: *misbehaviour,
+								},
+							));
+						}
+					}
 					// always set the unsigned props to empty to ensure no signing protocol executes
 					self.blockchain.set_should_execute_keygen(true);
 					self.blockchain.set_unsigned_proposals(vec![]);
 				},
 				IntraTestPhase::Signing { trace_id, queued_unsigned_proposals, .. } => {
 					if let Some(unsigned_propos) = queued_unsigned_proposals.clone() {
-						for _ in 0..unsigned_propos.len() {
-							for _ in 0..*self.max_signing_sets_per_proposal {
-								client
-									.outstanding_tasks_signing
-									.entry(*trace_id)
-									.or_default()
-									.push(next_case.clone());
-							}
+						let n_tasks = if client.byzantine {
+							0
+						} else {
+							unsigned_propos.len() * *self.max_signing_sets_per_proposal
+						};
+						for _ in 0..n_tasks {
+							client
+								.outstanding_tasks_signing
+								.entry(*trace_id)
+								.or_default()
+								.push(next_case.clone());
 						}
 						self.blockchain.set_should_execute_keygen(false);
 						self.blockchain.set_unsigned_proposals(unsigned_propos);
@@ -555,6 +668,7 @@ impl<T: MutableBlockchain> MockBlockchain<T> {
 				.send(OrchestratorToClientEvent::BlockChainEvent {
 					trace_id,
 					event: next_finality_notification.clone(),
+					command,
 				})
 				.unwrap();
 		}
@@ -565,6 +679,23 @@ fn generic_error<T: Into<String>>(err: T) -> std::io::Error {
 	std::io::Error::new(std::io::ErrorKind::Other, err.into())
 }
 
+/// The clients which misbehave in a test case: the first `n_clients` of the error case, ordered by
+/// peer ID
+fn byzantine_peers(
+	clients: &HashMap<PeerId, ConnectedClientState>,
+	test_case: &TestCase,
+) -> Vec<PeerId> {
+	match test_case {
+		TestCase::Invalid(error_case) if error_case.command.is_byzantine() => {
+			let mut peers = clients.keys().copied().collect::<Vec<_>>();
+			peers.sort();
+			peers.truncate(error_case.n_clients);
+			peers
+		},
+		_ => Vec::new(),
+	}
+}
+
 fn log_invalid_signal(o_state: &OrchestratorState, c_update: &ClientToOrchestratorEvent) {
 	log::error!(target: "dkg", "Orchestrator state is {o_state:?}, yet, the client's update state is {c_update:?}")
 }
@@ -29,6 +29,8 @@ pub enum ProtocolPacket<B: crate::BlockTraitForTest> {
 		trace_id: Uuid,
 		#[serde(bound = "")]
 		event: crate::MockBlockchainEvent<B>,
+		// how the client should behave towards its peers for this event
+		command: crate::AttachedCommand,
 	},
 	ClientToBlockchain {
 		event: crate::MockClientResponse,
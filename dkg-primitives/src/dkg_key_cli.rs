@@ -1,41 +1,539 @@
-use std::path::PathBuf;
+use std::{
+	fmt,
+	path::{Path, PathBuf},
+};
 
-use sc_cli::{Error, SubstrateCli};
+use curv::elliptic::curves::{Point, Secp256k1};
+use dkg_runtime_primitives::DKGProtocol;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use rand::RngCore;
+use sc_cli::{CliConfiguration, Error, KeystoreParams, SharedParams, SubstrateCli};
+use sc_service::Configuration;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+	types::{FE, GE},
+	utils::{decrypt_data, encrypt_data},
+	SessionId,
+};
 
 /// Key utilities for the cli.
 #[derive(Debug, clap::Subcommand)]
 pub enum DKGKeySubcommand {
 	/// Prints all the information sotred int the DKG LocalKey.
 	Inspect(InspectKeyCmd),
+	/// Exports the DKG LocalKey of a session to a password encrypted file.
+	Export(ExportKeyCmd),
+	/// Imports a DKG LocalKey from a password encrypted file.
+	Import(ImportKeyCmd),
 }
 
 impl DKGKeySubcommand {
 	/// run the key subcommands
-	pub fn run<C: SubstrateCli>(&self, _cli: &C) -> Result<(), Error> {
+	///
+	/// `open_db` opens the DKG database of the node for the subcommands reading or writing its
+	/// keys, given the node configuration and the `--dkg-db-path` if any.
+	pub fn run<C, D, F>(&self, cli: &C, open_db: F) -> Result<(), Error>
+	where
+		C: SubstrateCli,
+		D: LocalKeyDb,
+		F: FnOnce(&Configuration, Option<&Path>) -> Result<D, Error>,
+	{
+		match self {
+			// an exported key file is inspected without the node
+			DKGKeySubcommand::Inspect(cmd) if cmd.input.is_some() => cmd.run(None::<&D>),
+			DKGKeySubcommand::Inspect(cmd) => {
+				let runner = cli.create_runner(cmd)?;
+				runner.sync_run(|config| {
+					cmd.run(Some(&open_db(&config, cmd.dkg_db_path.as_deref())?))
+				})
+			},
+			DKGKeySubcommand::Export(cmd) => {
+				let runner = cli.create_runner(cmd)?;
+				runner.sync_run(|config| cmd.run(&open_db(&config, cmd.dkg_db_path.as_deref())?))
+			},
+			DKGKeySubcommand::Import(cmd) => {
+				let runner = cli.create_runner(cmd)?;
+				runner.sync_run(|config| cmd.run(&open_db(&config, cmd.dkg_db_path.as_deref())?))
+			},
+		}
+	}
+}
+
+/// The DKG keys stored by a node, as needed by the key subcommands.
+pub trait LocalKeyDb {
+	/// Returns the [`DKGLocalKey`] at specific session, of whichever protocol, if any.
+	fn get_local_key(&self, session_id: SessionId) -> Result<Option<DKGLocalKey>, Error>;
+	/// Stores the [`DKGLocalKey`] at a specified session.
+	fn store_local_key(&self, session_id: SessionId, local_key: DKGLocalKey) -> Result<(), Error>;
+}
+
+impl<T: LocalKeyDb + ?Sized> LocalKeyDb for Box<T> {
+	fn get_local_key(&self, session_id: SessionId) -> Result<Option<DKGLocalKey>, Error> {
+		(**self).get_local_key(session_id)
+	}
+
+	fn store_local_key(&self, session_id: SessionId, local_key: DKGLocalKey) -> Result<(), Error> {
+		(**self).store_local_key(session_id, local_key)
+	}
+}
+
+/// The secret shares of a WT-FROST key, with the same fields as the gadget's `FrostLocalKey`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrostKeyShares {
+	/// The party index in the range `[1, n]`
+	pub party_i: u16,
+	/// Signing requires a combined weight of at least `threshold + 1`
+	pub threshold: u16,
+	/// The number of key ids held by each party
+	pub weights: Vec<u16>,
+	/// The secret shares for every key id owned by this party
+	pub secret_shares: Vec<(u32, FE)>,
+	/// The coefficient-wise sum of every party's polynomial commitments
+	pub group_commitments: Vec<GE>,
+}
+
+/// A DKG key of either protocol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "protocol", content = "key")]
+pub enum DKGLocalKey {
+	MpEcdsa(LocalKey<Secp256k1>),
+	WTFrost(FrostKeyShares),
+}
+
+impl DKGLocalKey {
+	/// The protocol that generated the key
+	pub fn protocol(&self) -> DKGProtocol {
 		match self {
-			DKGKeySubcommand::Inspect(cmd) => cmd.run(),
+			DKGLocalKey::MpEcdsa(_) => DKGProtocol::MpEcdsa,
+			DKGLocalKey::WTFrost(_) => DKGProtocol::WTFrost,
 		}
 	}
 }
 
+/// A [`DKGLocalKey`] together with the session it was generated for, as stored in an exported
+/// key file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredLocalKey {
+	pub session_id: SessionId,
+	pub local_key: DKGLocalKey,
+}
+
+/// The content of the version 1 key files, which only held ECDSA keys.
+#[derive(Deserialize)]
+struct StoredLocalKeyV1 {
+	session_id: SessionId,
+	local_key: LocalKey<Secp256k1>,
+}
+
 /// Prints all the information sotred int the DKG LocalKey.
 #[derive(Debug, clap::Parser)]
 #[clap(
 	name = "inspect-dkg-key",
-	about = "Load an encrypted DKG key from a file and the secret used to encrypt it from the secret-file"
+	about = "Print the DKG key of a session stored by the node, or held by an exported key file"
 )]
 pub struct InspectKeyCmd {
-	/// The path to the local key file.
+	/// The path of a key file written by the export subcommand.
+	#[clap(long, conflicts_with = "session_id", required_unless_present = "session_id")]
+	pub input: Option<PathBuf>,
+	/// The session of the key stored by the node to inspect.
 	#[clap(long)]
-	pub dkg_file: PathBuf,
-	/// The path to the secret file.
-	#[clap(long)]
-	pub secret_file: PathBuf,
+	pub session_id: Option<SessionId>,
+	/// The path to the DKG database, if the node uses one instead of the offchain storage.
+	#[clap(long, requires = "session_id")]
+	pub dkg_db_path: Option<PathBuf>,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub password_params: PasswordParams,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
 }
 
 impl InspectKeyCmd {
-	/// run the key subcommands
-	pub fn run(&self) -> Result<(), Error> {
-		Err(Error::Io(std::io::Error::new(std::io::ErrorKind::Other, "not implemented")))
+	/// run the inspect subcommand against the key file, or else the given database
+	pub fn run(&self, db: Option<&impl LocalKeyDb>) -> Result<(), Error> {
+		let stored = match (&self.input, self.session_id, db) {
+			(Some(input), ..) => PortableKeyFile::read(input, &self.password_params.read()?)?,
+			(None, Some(session_id), Some(db)) => StoredLocalKey {
+				session_id,
+				local_key: db.get_local_key(session_id)?.ok_or_else(|| {
+					Error::Input(format!("No DKG key stored for session {session_id}"))
+				})?,
+			},
+			_ => return Err(Error::Input("Either --input or --session-id is required".into())),
+		};
+
+		print!("{}", KeyInfo::new(&stored)?);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InspectKeyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+/// The password used to encrypt an exported DKG key.
+#[derive(Debug, Clone, clap::Args)]
+pub struct PasswordParams {
+	/// The password used to encrypt the key file.
+	#[clap(long, conflicts_with = "password_file")]
+	pub password: Option<String>,
+	/// The path to a file containing the password used to encrypt the key file.
+	#[clap(long)]
+	pub password_file: Option<PathBuf>,
+}
+
+impl PasswordParams {
+	fn read(&self) -> Result<String, Error> {
+		match (&self.password, &self.password_file) {
+			(Some(password), _) => Ok(password.clone()),
+			(None, Some(path)) => Ok(std::fs::read_to_string(path)?.trim_end().to_string()),
+			(None, None) =>
+				Err(Error::Input("Either --password or --password-file is required".into())),
+		}
+	}
+}
+
+/// Exports the DKG LocalKey of a session to a password encrypted file.
+#[derive(Debug, clap::Parser)]
+#[clap(
+	name = "export-dkg-key",
+	about = "Export the DKG key of a session from the node database to a password encrypted file"
+)]
+pub struct ExportKeyCmd {
+	/// The session of the key to export.
+	#[clap(long)]
+	pub session_id: SessionId,
+	/// The path of the file to write the key to.
+	#[clap(long)]
+	pub output: PathBuf,
+	/// The path to the DKG database, if the node uses one instead of the offchain storage.
+	#[clap(long)]
+	pub dkg_db_path: Option<PathBuf>,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub password_params: PasswordParams,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl ExportKeyCmd {
+	/// run the export subcommand against the given database
+	pub fn run(&self, db: &impl LocalKeyDb) -> Result<(), Error> {
+		let local_key = db.get_local_key(self.session_id)?.ok_or_else(|| {
+			Error::Input(format!("No DKG key stored for session {}", self.session_id))
+		})?;
+		let stored = StoredLocalKey { session_id: self.session_id, local_key };
+		let file = PortableKeyFile::encrypt(&stored, &self.password_params.read()?)?;
+		let content = serde_json::to_vec_pretty(&file)
+			.map_err(|e| Error::Input(format!("Failed to encode the key file: {e}")))?;
+		std::fs::write(&self.output, content)?;
+
+		println!(
+			"Exported the DKG key of session {} to {}",
+			self.session_id,
+			self.output.display()
+		);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportKeyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+/// Imports a DKG LocalKey from a password encrypted file.
+#[derive(Debug, clap::Parser)]
+#[clap(
+	name = "import-dkg-key",
+	about = "Import a DKG key from a password encrypted file into the node database"
+)]
+pub struct ImportKeyCmd {
+	/// The path of the exported key file.
+	#[clap(long)]
+	pub input: PathBuf,
+	/// The path to the DKG database, if the node uses one instead of the offchain storage.
+	#[clap(long)]
+	pub dkg_db_path: Option<PathBuf>,
+	/// Replace the key already stored for the session, if any.
+	#[clap(long)]
+	pub force: bool,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub password_params: PasswordParams,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl ImportKeyCmd {
+	/// run the import subcommand against the given database
+	pub fn run(&self, db: &impl LocalKeyDb) -> Result<(), Error> {
+		let stored = PortableKeyFile::read(&self.input, &self.password_params.read()?)?;
+		let session_id = stored.session_id;
+		if !self.force && db.get_local_key(session_id)?.is_some() {
+			return Err(Error::Input(format!(
+				"A DKG key is already stored for session {session_id}, use --force to replace it"
+			)))
+		}
+		db.store_local_key(session_id, stored.local_key)?;
+
+		println!("Imported the DKG key of session {session_id}");
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportKeyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+/// The format version of newly exported key files. Version 1 files, holding an ECDSA key, can
+/// still be read.
+const PORTABLE_KEY_FILE_VERSION: u16 = 2;
+/// The PBKDF2 rounds used for newly exported key files, and the fewest accepted when importing.
+const PBKDF2_ROUNDS: u32 = 210_000;
+/// The most PBKDF2 rounds accepted when importing a key file, so a crafted file cannot stall the
+/// import.
+const MAX_PBKDF2_ROUNDS: u32 = 10 * PBKDF2_ROUNDS;
+
+/// A [`StoredLocalKey`] encrypted with a key derived from a password, which unlike the node
+/// database does not depend on the node's keystore, so it can be moved to another machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PortableKeyFile {
+	version: u16,
+	/// PBKDF2-HMAC-SHA512 rounds
+	rounds: u32,
+	/// Hex encoded PBKDF2 salt
+	salt: String,
+	/// Hex encoded [`encrypt_data`] output
+	ciphertext: String,
+}
+
+impl PortableKeyFile {
+	fn encrypt(stored: &StoredLocalKey, password: &str) -> Result<Self, Error> {
+		let mut salt = [0u8; 32];
+		rand::thread_rng().fill_bytes(&mut salt);
+		let plaintext = serde_json::to_vec(stored)
+			.map_err(|e| Error::Input(format!("Failed to encode the DKG key: {e}")))?;
+		let ciphertext = encrypt_data(plaintext, derive_secret(password, &salt, PBKDF2_ROUNDS))
+			.map_err(|e| Error::Input(format!("Failed to encrypt the DKG key: {e}")))?;
+		Ok(Self {
+			version: PORTABLE_KEY_FILE_VERSION,
+			rounds: PBKDF2_ROUNDS,
+			salt: hex::encode(salt),
+			ciphertext: hex::encode(ciphertext),
+		})
+	}
+
+	/// Reads and decrypts the key file at `path`
+	fn read(path: &Path, password: &str) -> Result<StoredLocalKey, Error> {
+		let file: Self = serde_json::from_slice(&std::fs::read(path)?)
+			.map_err(|e| Error::Input(format!("Invalid key file: {e}")))?;
+		file.decrypt(password)
+	}
+
+	fn decrypt(&self, password: &str) -> Result<StoredLocalKey, Error> {
+		if !(1..=PORTABLE_KEY_FILE_VERSION).contains(&self.version) {
+			return Err(Error::Input(format!("Unsupported key file version {}", self.version)))
+		}
+		if !(PBKDF2_ROUNDS..=MAX_PBKDF2_ROUNDS).contains(&self.rounds) {
+			return Err(Error::Input(format!(
+				"Invalid key file: {} PBKDF2 rounds, expected between {PBKDF2_ROUNDS} and \
+				 {MAX_PBKDF2_ROUNDS}",
+				self.rounds
+			)))
+		}
+		let decode_hex =
+			|s: &str| hex::decode(s).map_err(|e| Error::Input(format!("Invalid key file: {e}")));
+		let secret = derive_secret(password, &decode_hex(&self.salt)?, self.rounds);
+		let plaintext = decrypt_data(decode_hex(&self.ciphertext)?, secret).map_err(|_| {
+			Error::Input("Failed to decrypt the key file, is the password correct?".into())
+		})?;
+		let decode_error = |e| Error::Input(format!("Failed to decode the DKG key: {e}"));
+		if self.version == 1 {
+			let v1: StoredLocalKeyV1 = serde_json::from_slice(&plaintext).map_err(decode_error)?;
+			return Ok(StoredLocalKey {
+				session_id: v1.session_id,
+				local_key: DKGLocalKey::MpEcdsa(v1.local_key),
+			})
+		}
+		serde_json::from_slice(&plaintext).map_err(decode_error)
+	}
+}
+
+/// Derives the 64 bytes secret expected by [`encrypt_data`] from the password. The salt being
+/// random, the nonce part of the secret is unique for each file.
+fn derive_secret(password: &str, salt: &[u8], rounds: u32) -> Vec<u8> {
+	let mut secret = vec![0u8; 64];
+	pbkdf2::pbkdf2_hmac::<sha2::Sha512>(password.as_bytes(), salt, rounds, &mut secret);
+	secret
+}
+
+/// The information about a DKG key that is printed by [`InspectKeyCmd`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+	pub session_id: SessionId,
+	pub protocol: DKGProtocol,
+	pub party_index: u16,
+	pub threshold: u16,
+	pub participants: u16,
+	pub public_key_compressed: Vec<u8>,
+	pub public_key_uncompressed: Vec<u8>,
+	pub ethereum_address: [u8; 20],
+}
+
+impl KeyInfo {
+	pub fn new(stored: &StoredLocalKey) -> Result<Self, Error> {
+		let (party_index, threshold, participants, public_key) = match &stored.local_key {
+			DKGLocalKey::MpEcdsa(key) => (key.i, key.t, key.n, key.public_key()),
+			DKGLocalKey::WTFrost(key) => {
+				let public_key = key.group_commitments.first().cloned().ok_or_else(|| {
+					Error::Input("The WT-FROST key has no group commitments".into())
+				})?;
+				(key.party_i, key.threshold, key.weights.len() as u16, public_key)
+			},
+		};
+		let (public_key_compressed, public_key_uncompressed, ethereum_address) =
+			public_key_info(&public_key);
+		Ok(Self {
+			session_id: stored.session_id,
+			protocol: stored.local_key.protocol(),
+			party_index,
+			threshold,
+			participants,
+			public_key_compressed,
+			public_key_uncompressed,
+			ethereum_address,
+		})
+	}
+}
+
+impl fmt::Display for KeyInfo {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "Session ID:                {}", self.session_id)?;
+		writeln!(f, "Protocol:                  {:?}", self.protocol)?;
+		writeln!(f, "Party index:               {}", self.party_index)?;
+		writeln!(f, "Threshold:                 {}", self.threshold)?;
+		writeln!(f, "Participants:              {}", self.participants)?;
+		writeln!(f, "Public key (compressed):   0x{}", hex::encode(&self.public_key_compressed))?;
+		writeln!(f, "Public key (uncompressed): 0x{}", hex::encode(&self.public_key_uncompressed))?;
+		writeln!(f, "Ethereum address:          0x{}", hex::encode(self.ethereum_address))
+	}
+}
+
+/// Returns the compressed and uncompressed encodings of the public key, and its Ethereum address.
+fn public_key_info(public_key: &Point<Secp256k1>) -> (Vec<u8>, Vec<u8>, [u8; 20]) {
+	let compressed = public_key.to_bytes(true).to_vec();
+	let uncompressed = public_key.to_bytes(false).to_vec();
+	// The address is the last 20 bytes of the hash of the key, without the 0x04 prefix
+	let hash = Keccak256::digest(&uncompressed[1..]);
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&hash[12..]);
+	(compressed, uncompressed, address)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_derive_the_ethereum_address_of_the_public_key() {
+		// The public key of the secret key `1`
+		let (compressed, uncompressed, address) = public_key_info(&Point::generator().to_point());
+		assert_eq!(
+			hex::encode(compressed),
+			"0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+		);
+		assert_eq!(uncompressed.len(), 65);
+		assert_eq!(hex::encode(address), "7e5f4552091a69125d5dfcb7b8c2659029395bdf");
+	}
+
+	#[test]
+	fn should_derive_a_different_secret_for_each_salt() {
+		let secret = derive_secret("password", &[1u8; 32], 1);
+		assert_eq!(secret.len(), 64);
+		assert_eq!(secret, derive_secret("password", &[1u8; 32], 1));
+		assert_ne!(secret, derive_secret("password", &[2u8; 32], 1));
+		assert_ne!(secret, derive_secret("other password", &[1u8; 32], 1));
+	}
+
+	#[test]
+	fn should_read_back_an_exported_frost_key() {
+		let secret = FE::from(5u64);
+		let stored = StoredLocalKey {
+			session_id: 3,
+			local_key: DKGLocalKey::WTFrost(FrostKeyShares {
+				party_i: 1,
+				threshold: 0,
+				weights: vec![1],
+				secret_shares: vec![(1, secret.clone())],
+				group_commitments: vec![GE::generator() * secret],
+			}),
+		};
+		let file = PortableKeyFile::encrypt(&stored, "password").unwrap();
+		assert_eq!(file.version, PORTABLE_KEY_FILE_VERSION);
+		assert!(file.decrypt("other password").is_err());
+
+		let decrypted = file.decrypt("password").unwrap();
+		let info = KeyInfo::new(&decrypted).unwrap();
+		assert_eq!(info, KeyInfo::new(&stored).unwrap());
+		assert_eq!(info.protocol, DKGProtocol::WTFrost);
+		assert_eq!(info.participants, 1);
+		match decrypted.local_key {
+			DKGLocalKey::WTFrost(key) => assert_eq!(key.secret_shares, vec![(1, FE::from(5u64))]),
+			other => panic!("Unexpected key {other:?}"),
+		}
+	}
+
+	#[test]
+	fn should_reject_key_files_with_out_of_range_rounds() {
+		let stored = StoredLocalKey {
+			session_id: 3,
+			local_key: DKGLocalKey::WTFrost(FrostKeyShares {
+				party_i: 1,
+				threshold: 0,
+				weights: vec![1],
+				secret_shares: vec![(1, FE::from(5u64))],
+				group_commitments: vec![GE::generator() * FE::from(5u64)],
+			}),
+		};
+		let file = PortableKeyFile::encrypt(&stored, "password").unwrap();
+		for rounds in [0, PBKDF2_ROUNDS - 1, MAX_PBKDF2_ROUNDS + 1, u32::MAX] {
+			let tampered = PortableKeyFile { rounds, ..file.clone() };
+			match tampered.decrypt("password") {
+				Err(Error::Input(reason)) => assert!(reason.contains("PBKDF2 rounds")),
+				other => panic!("Unexpected result {other:?}"),
+			}
+		}
 	}
 }
@@ -0,0 +1,290 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! RPC methods of the `dkg_` namespace, exposing the on-chain DKG state and the status of the
+//! local DKG worker.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use dkg_gadget::{rpc_links::WorkerStatus, DKGRpcLinks};
+use dkg_runtime_primitives::{
+	crypto::AuthorityId, DKGApi, MaxAuthorities, MaxProposalLength, StoredUnsignedProposalBatch,
+	DKG_API_VERSION_2,
+};
+use futures::{future, FutureExt, StreamExt};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, types::SubscriptionResult, SubscriptionSink};
+use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+use tokio_stream::wrappers::BroadcastStream;
+
+pub mod error;
+pub mod types;
+
+use error::Error;
+use types::*;
+
+/// DKG RPC methods.
+#[rpc(client, server)]
+pub trait DKGRpcApi<BlockHash> {
+	/// Returns the active and queued DKG public keys with their signatures.
+	#[method(name = "dkg_publicKeys")]
+	fn public_keys(&self, at: Option<BlockHash>) -> RpcResult<DKGPublicKeys>;
+
+	/// Returns the active and queued authority sets.
+	#[method(name = "dkg_authoritySets")]
+	fn authority_sets(&self, at: Option<BlockHash>) -> RpcResult<AuthoritySets>;
+
+	/// Returns the current and next keygen sets with their thresholds.
+	#[method(name = "dkg_bestAuthorities")]
+	fn best_authorities(&self, at: Option<BlockHash>) -> RpcResult<BestAuthorities>;
+
+	/// Returns the jailed authorities among the active and queued authority sets.
+	#[method(name = "dkg_jailedAuthorities")]
+	fn jailed_authorities(&self, at: Option<BlockHash>) -> RpcResult<JailedAuthorities>;
+
+	/// Returns the reputations of the active and queued authorities.
+	#[method(name = "dkg_reputations")]
+	fn reputations(&self, at: Option<BlockHash>) -> RpcResult<Vec<AuthorityReputation>>;
+
+	/// Returns the proposal batches waiting to be signed by the DKG.
+	#[method(name = "dkg_unsignedProposals")]
+	fn unsigned_proposals(
+		&self,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<UnsignedProposalBatchInfo>>;
+
+	/// Returns the proposal batches signed by the DKG.
+	#[method(name = "dkg_signedProposals")]
+	fn signed_proposals(&self, at: Option<BlockHash>) -> RpcResult<Vec<SignedProposalBatchInfo>>;
+
+	/// Returns the keygen and signing jobs of the local DKG worker, if the node runs one.
+	///
+	/// This method is unsafe, as it exposes the state of the local worker.
+	#[method(name = "dkg_workerStatus")]
+	fn worker_status(&self) -> RpcResult<Option<WorkerStatus>>;
+
+	/// Subscribes to the proposal batches signed by the local DKG worker, as soon as they are
+	/// signed and before they are submitted on-chain.
+	///
+	/// If `typed_chain_ids` is given, only the batches destined to one of these chains are sent.
+	#[subscription(
+		name = "dkg_subscribeSignedProposals" => "dkg_signedProposalBatch",
+		unsubscribe = "dkg_unsubscribeSignedProposals",
+		item = SignedProposalBatchInfo,
+	)]
+	fn subscribe_signed_proposals(&self, typed_chain_ids: Option<Vec<u64>>);
+}
+
+/// Implements the [`DKGRpcApiServer`] RPC trait for interacting with the DKG.
+pub struct DKGRpc<C, B> {
+	client: Arc<C>,
+	links: DKGRpcLinks,
+	executor: SubscriptionTaskExecutor,
+	deny_unsafe: DenyUnsafe,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> DKGRpc<C, B> {
+	/// Creates a new DKG RPC handler.
+	pub fn new(
+		client: Arc<C>,
+		links: DKGRpcLinks,
+		executor: SubscriptionTaskExecutor,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self { client, links, executor, deny_unsafe, _marker: PhantomData }
+	}
+}
+
+impl<C, B> DKGRpc<C, B>
+where
+	B: BlockT,
+	C: HeaderBackend<B> + ProvideRuntimeApi<B>,
+	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
+{
+	fn at(&self, at: Option<B::Hash>) -> B::Hash {
+		at.unwrap_or_else(|| self.client.info().best_hash)
+	}
+
+	/// Whether the runtime at `at` implements the methods added in version 2 of the [`DKGApi`]
+	fn has_api_v2(&self, at: B::Hash) -> Result<bool, Error> {
+		let version = self.client.runtime_api().api_version::<dyn DKGApi<
+			B,
+			AuthorityId,
+			NumberFor<B>,
+			MaxProposalLength,
+			MaxAuthorities,
+		>>(at)?;
+		Ok(version.map_or(false, |version| version >= DKG_API_VERSION_2))
+	}
+
+	/// Returns the authorities of the active and queued authority sets, without duplicates
+	fn authorities(&self, at: B::Hash) -> Result<Vec<AuthorityId>, Error> {
+		let api = self.client.runtime_api();
+		let mut authorities = api.authority_set(at)?.authorities.into_inner();
+		for authority in api.queued_authority_set(at)?.authorities {
+			if !authorities.contains(&authority) {
+				authorities.push(authority);
+			}
+		}
+		Ok(authorities)
+	}
+}
+
+impl<C, B> DKGRpcApiServer<B::Hash> for DKGRpc<C, B>
+where
+	B: BlockT,
+	C: HeaderBackend<B> + ProvideRuntimeApi<B> + Send + Sync + 'static,
+	C::Api: DKGApi<B, AuthorityId, NumberFor<B>, MaxProposalLength, MaxAuthorities>,
+{
+	fn public_keys(&self, at: Option<B::Hash>) -> RpcResult<DKGPublicKeys> {
+		let at = self.at(at);
+		let api = self.client.runtime_api();
+		let (authority_set_id, public_key) = api.dkg_pub_key(at).map_err(Error::from)?;
+		let signature = if self.has_api_v2(at)? {
+			api.dkg_pub_key_sig(at).map_err(Error::from)?
+		} else {
+			Vec::new()
+		};
+		let current = DKGPublicKey {
+			authority_set_id,
+			public_key: Bytes(public_key),
+			signature: (!signature.is_empty()).then_some(Bytes(signature)),
+		};
+		let next_signature = api.next_pub_key_sig(at).map_err(Error::from)?;
+		let next =
+			api.next_dkg_pub_key(at)
+				.map_err(Error::from)?
+				.map(|(authority_set_id, public_key)| DKGPublicKey {
+					authority_set_id,
+					public_key: Bytes(public_key),
+					signature: next_signature.map(Bytes),
+				});
+
+		Ok(DKGPublicKeys { current, next })
+	}
+
+	fn authority_sets(&self, at: Option<B::Hash>) -> RpcResult<AuthoritySets> {
+		let at = self.at(at);
+		let api = self.client.runtime_api();
+		Ok(AuthoritySets {
+			current: api.authority_set(at).map_err(Error::from)?.into(),
+			queued: api.queued_authority_set(at).map_err(Error::from)?.into(),
+		})
+	}
+
+	fn best_authorities(&self, at: Option<B::Hash>) -> RpcResult<BestAuthorities> {
+		let at = self.at(at);
+		let api = self.client.runtime_api();
+		let into_best = |authorities: Vec<(u16, AuthorityId)>| {
+			authorities.into_iter().map(Into::into).collect::<Vec<BestAuthority>>()
+		};
+		Ok(BestAuthorities {
+			current: into_best(api.get_best_authorities(at).map_err(Error::from)?),
+			next: into_best(api.get_next_best_authorities(at).map_err(Error::from)?),
+			signature_threshold: api.signature_threshold(at).map_err(Error::from)?,
+			keygen_threshold: api.keygen_threshold(at).map_err(Error::from)?,
+			next_signature_threshold: api.next_signature_threshold(at).map_err(Error::from)?,
+			next_keygen_threshold: api.next_keygen_threshold(at).map_err(Error::from)?,
+		})
+	}
+
+	fn jailed_authorities(&self, at: Option<B::Hash>) -> RpcResult<JailedAuthorities> {
+		let at = self.at(at);
+		let authorities = self.authorities(at)?;
+		let api = self.client.runtime_api();
+		let keygen = api.get_keygen_jailed(at, authorities.clone()).map_err(Error::from)?;
+		let signing = api.get_signing_jailed(at, authorities).map_err(Error::from)?;
+		Ok(JailedAuthorities {
+			keygen: keygen.iter().map(authority_bytes).collect(),
+			signing: signing.iter().map(authority_bytes).collect(),
+		})
+	}
+
+	fn reputations(&self, at: Option<B::Hash>) -> RpcResult<Vec<AuthorityReputation>> {
+		let at = self.at(at);
+		let authorities = self.authorities(at)?;
+		let reputations = self
+			.client
+			.runtime_api()
+			.get_reputations(at, authorities)
+			.map_err(Error::from)?;
+		Ok(reputations
+			.into_iter()
+			.map(|(authority, reputation)| AuthorityReputation {
+				authority: authority_bytes(&authority),
+				reputation,
+			})
+			.collect())
+	}
+
+	fn unsigned_proposals(&self, at: Option<B::Hash>) -> RpcResult<Vec<UnsignedProposalBatchInfo>> {
+		let at = self.at(at);
+		let api = self.client.runtime_api();
+		if !self.has_api_v2(at)? {
+			// the batches of an older runtime are all concatenated
+			#[allow(deprecated)]
+			let batches = api.get_unsigned_proposal_batches_before_version_2(at).map_err(Error::from)?;
+			return Ok(batches
+				.into_iter()
+				.map(|batch| StoredUnsignedProposalBatch::from(batch).into())
+				.collect())
+		}
+		let batches = api.get_unsigned_proposal_batches(at).map_err(Error::from)?;
+		Ok(batches.into_iter().map(Into::into).collect())
+	}
+
+	fn signed_proposals(&self, at: Option<B::Hash>) -> RpcResult<Vec<SignedProposalBatchInfo>> {
+		let at = self.at(at);
+		if !self.has_api_v2(at)? {
+			return Ok(Vec::new())
+		}
+		let batches =
+			self.client.runtime_api().get_signed_proposal_batches(at).map_err(Error::from)?;
+		Ok(batches.into_iter().map(Into::into).collect())
+	}
+
+	fn worker_status(&self) -> RpcResult<Option<WorkerStatus>> {
+		self.deny_unsafe.check_if_safe()?;
+		Ok(self.links.worker_status())
+	}
+
+	fn subscribe_signed_proposals(
+		&self,
+		mut sink: SubscriptionSink,
+		typed_chain_ids: Option<Vec<u64>>,
+	) -> SubscriptionResult {
+		// batches missed by a lagging subscriber are skipped
+		let stream = BroadcastStream::new(self.links.subscribe_signed_proposals())
+			.filter_map(|notification| future::ready(notification.ok()))
+			.map(SignedProposalBatchInfo::from)
+			.filter(move |batch| {
+				future::ready(
+					typed_chain_ids
+						.as_ref()
+						.map_or(true, |chains| chains.contains(&batch.typed_chain_id)),
+				)
+			});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("dkg-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
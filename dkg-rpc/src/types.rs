@@ -0,0 +1,246 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON representations of the DKG state returned by the `dkg_` RPC namespace.
+
+use codec::Encode;
+use dkg_runtime_primitives::{
+	crypto::AuthorityId, keccak_256, AuthoritySet, Proposal, SignedProposalBatch,
+	StoredUnsignedProposalBatch, TypedChainId, UnsignedProposal,
+};
+use serde::{Deserialize, Serialize};
+use sp_core::{Bytes, Get, H256};
+use sp_runtime::traits::UniqueSaturatedInto;
+
+/// Encodes an authority id as its raw compressed public key
+pub fn authority_bytes(authority: &AuthorityId) -> Bytes {
+	Bytes(authority.encode())
+}
+
+/// A DKG public key together with its signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DKGPublicKey {
+	/// The authority set which generated the key
+	pub authority_set_id: u64,
+	/// The compressed public key
+	pub public_key: Bytes,
+	/// The signature of the key by the previous key, if it was submitted
+	pub signature: Option<Bytes>,
+}
+
+/// The active and the queued DKG public keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DKGPublicKeys {
+	pub current: DKGPublicKey,
+	pub next: Option<DKGPublicKey>,
+}
+
+/// An authority set and its id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthoritySetInfo {
+	pub id: u64,
+	pub authorities: Vec<Bytes>,
+}
+
+impl<MaxAuthorities: Get<u32>> From<AuthoritySet<AuthorityId, MaxAuthorities>>
+	for AuthoritySetInfo
+{
+	fn from(set: AuthoritySet<AuthorityId, MaxAuthorities>) -> Self {
+		Self { id: set.id, authorities: set.authorities.iter().map(authority_bytes).collect() }
+	}
+}
+
+/// The active and the queued authority sets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthoritySets {
+	pub current: AuthoritySetInfo,
+	pub queued: AuthoritySetInfo,
+}
+
+/// An authority chosen for keygen and its party index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BestAuthority {
+	pub party_index: u16,
+	pub authority: Bytes,
+}
+
+impl From<(u16, AuthorityId)> for BestAuthority {
+	fn from((party_index, authority): (u16, AuthorityId)) -> Self {
+		Self { party_index, authority: authority_bytes(&authority) }
+	}
+}
+
+/// The current and next keygen sets with their thresholds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BestAuthorities {
+	pub current: Vec<BestAuthority>,
+	pub next: Vec<BestAuthority>,
+	pub signature_threshold: u16,
+	pub keygen_threshold: u16,
+	pub next_signature_threshold: u16,
+	pub next_keygen_threshold: u16,
+}
+
+/// The jailed authorities among the active and queued authority sets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JailedAuthorities {
+	pub keygen: Vec<Bytes>,
+	pub signing: Vec<Bytes>,
+}
+
+/// The reputation of an authority
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorityReputation {
+	pub authority: Bytes,
+	pub reputation: u128,
+}
+
+/// A proposal and its kind
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalInfo {
+	pub kind: String,
+	pub data: Bytes,
+}
+
+impl<MaxLength: Get<u32>> From<&Proposal<MaxLength>> for ProposalInfo {
+	fn from(proposal: &Proposal<MaxLength>) -> Self {
+		Self { kind: format!("{:?}", proposal.kind()), data: Bytes(proposal.data().clone()) }
+	}
+}
+
+/// An unsigned proposal waiting to be signed by the DKG
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedProposalInfo {
+	pub typed_chain_id: u64,
+	pub key: String,
+	#[serde(flatten)]
+	pub proposal: ProposalInfo,
+}
+
+impl<MaxLength: Get<u32> + Clone> From<&UnsignedProposal<MaxLength>> for UnsignedProposalInfo {
+	fn from(unsigned: &UnsignedProposal<MaxLength>) -> Self {
+		Self {
+			typed_chain_id: unsigned.typed_chain_id.chain_id(),
+			key: format!("{:?}", unsigned.key),
+			proposal: (&unsigned.proposal).into(),
+		}
+	}
+}
+
+/// A batch of unsigned proposals waiting to be signed by the DKG
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedProposalBatchInfo {
+	pub batch_id: u32,
+	/// Block number at which the batch was created
+	pub timestamp: u64,
+	/// Hash of the data signed for this batch
+	pub hash: H256,
+	pub proposals: Vec<UnsignedProposalInfo>,
+}
+
+impl<MaxLength, MaxProposals, Timestamp>
+	From<StoredUnsignedProposalBatch<u32, MaxLength, MaxProposals, Timestamp>>
+	for UnsignedProposalBatchInfo
+where
+	MaxLength: Get<u32> + Clone,
+	MaxProposals: Get<u32>,
+	Timestamp: UniqueSaturatedInto<u64>,
+{
+	fn from(batch: StoredUnsignedProposalBatch<u32, MaxLength, MaxProposals, Timestamp>) -> Self {
+		Self {
+			batch_id: batch.batch_id,
+			hash: H256(keccak_256(&batch.data())),
+			timestamp: batch.timestamp.unique_saturated_into(),
+			proposals: batch.proposals.iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// A batch of proposals signed by the DKG
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedProposalBatchInfo {
+	pub typed_chain_id: u64,
+	pub batch_id: u32,
+	pub proposals: Vec<ProposalInfo>,
+	pub signature: Bytes,
+}
+
+impl<MaxLength, MaxProposals, MaxSignatureLen>
+	From<(TypedChainId, SignedProposalBatch<u32, MaxLength, MaxProposals, MaxSignatureLen>)>
+	for SignedProposalBatchInfo
+where
+	MaxLength: Get<u32>,
+	MaxProposals: Get<u32>,
+	MaxSignatureLen: Get<u32>,
+{
+	fn from(
+		(typed_chain_id, batch): (
+			TypedChainId,
+			SignedProposalBatch<u32, MaxLength, MaxProposals, MaxSignatureLen>,
+		),
+	) -> Self {
+		Self {
+			typed_chain_id: typed_chain_id.chain_id(),
+			batch_id: batch.batch_id,
+			proposals: batch.proposals.iter().map(Into::into).collect(),
+			signature: Bytes(batch.signature.into_inner()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dkg_runtime_primitives::{
+		MaxProposalLength, MaxProposalsInBatch, MaxSignatureLength, ProposalKind,
+	};
+
+	#[test]
+	fn signed_batch_is_serialized_with_its_chain() {
+		let proposal = Proposal::<MaxProposalLength>::Unsigned {
+			kind: ProposalKind::EVM,
+			data: vec![1, 2, 3].try_into().unwrap(),
+		};
+		let batch = SignedProposalBatch::<u32, _, MaxProposalsInBatch, MaxSignatureLength> {
+			batch_id: 7,
+			proposals: vec![proposal].try_into().unwrap(),
+			signature: vec![4, 5].try_into().unwrap(),
+			encoding: Default::default(),
+		};
+
+		let info = SignedProposalBatchInfo::from((TypedChainId::Evm(1), batch));
+		assert_eq!(info.typed_chain_id, TypedChainId::Evm(1).chain_id());
+		assert_eq!(
+			serde_json::to_value(&info).unwrap(),
+			serde_json::json!({
+				"typedChainId": TypedChainId::Evm(1).chain_id(),
+				"batchId": 7,
+				"proposals": [{ "kind": "EVM", "data": "0x010203" }],
+				"signature": "0x0405",
+			})
+		);
+	}
+}
@@ -74,3 +74,95 @@ pub struct MisbehaviourMessage {
 	/// Authority's signature for this report
 	pub signature: Vec<u8>,
 }
+
+/// Gossip message struct for all DKG + Webb Protocol messages.
+///
+/// A message wrapper intended to be passed between the nodes
+#[derive(Debug, Clone, Decode, Encode)]
+#[cfg_attr(feature = "scale-info", derive(scale_info::TypeInfo))]
+pub struct DKGMessage<AuthorityId> {
+	/// Node authority id
+	pub sender_id: AuthorityId,
+	/// Authority id of the recipient.
+	///
+	/// If None, the message is broadcasted to all nodes.
+	pub recipient_id: Option<AuthorityId>,
+	/// DKG message contents
+	pub payload: NetworkMsgPayload,
+	/// Identifier for the message
+	pub session_id: SessionId,
+	/// The round ID
+	pub associated_block_id: u64,
+	/// The signing set ID
+	pub ssid: u8,
+}
+
+impl<ID> sp_std::fmt::Display for DKGMessage<ID> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		let label = match self.payload {
+			NetworkMsgPayload::Keygen(_) => "Keygen",
+			NetworkMsgPayload::Offline(_) => "Offline",
+			NetworkMsgPayload::Vote(_) => "Vote",
+			NetworkMsgPayload::PublicKeyBroadcast(_) => "PublicKeyBroadcast",
+			NetworkMsgPayload::MisbehaviourBroadcast(_) => "MisbehaviourBroadcast",
+		};
+		write!(f, "DKGMessage of type {label}")
+	}
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[cfg_attr(feature = "scale-info", derive(scale_info::TypeInfo))]
+pub enum NetworkMsgPayload {
+	Keygen(DKGKeygenMessage),
+	Offline(DKGOfflineMessage),
+	Vote(DKGVoteMessage),
+	PublicKeyBroadcast(PublicKeyMessage),
+	MisbehaviourBroadcast(MisbehaviourMessage),
+}
+
+impl NetworkMsgPayload {
+	pub fn payload(&self) -> &Vec<u8> {
+		match self {
+			NetworkMsgPayload::Offline(msg) => &msg.offline_msg,
+			NetworkMsgPayload::Vote(msg) => &msg.partial_signature,
+			NetworkMsgPayload::Keygen(msg) => &msg.keygen_msg,
+			NetworkMsgPayload::PublicKeyBroadcast(msg) => &msg.pub_key,
+			NetworkMsgPayload::MisbehaviourBroadcast(msg) => &msg.signature,
+		}
+	}
+	pub fn unsigned_proposal_hash(&self) -> Option<&[u8; 32]> {
+		match self {
+			NetworkMsgPayload::Offline(msg) => Some(&msg.unsigned_proposal_hash),
+			NetworkMsgPayload::Vote(msg) => Some(&msg.unsigned_proposal_hash),
+			_ => None,
+		}
+	}
+
+	pub fn keygen_protocol_hash(&self) -> Option<&[u8; 32]> {
+		if let NetworkMsgPayload::Keygen(msg) = self {
+			Some(&msg.keygen_protocol_hash)
+		} else {
+			None
+		}
+	}
+	/// NOTE: this is hacky
+	/// TODO: Change enums for keygen, offline, vote
+	pub fn async_proto_only_get_sender_id(&self) -> Option<u16> {
+		match self {
+			NetworkMsgPayload::Keygen(kg) => Some(kg.sender_id),
+			NetworkMsgPayload::Offline(offline) => Some(offline.signer_set_id as u16),
+			NetworkMsgPayload::Vote(vote) => Some(vote.party_ind),
+			_ => None,
+		}
+	}
+
+	pub fn get_type(&self) -> &'static str {
+		match self {
+			NetworkMsgPayload::Keygen(_) => "keygen",
+			NetworkMsgPayload::Offline(_) => "offline",
+			NetworkMsgPayload::Vote(_) => "vote",
+			NetworkMsgPayload::PublicKeyBroadcast(_) => "pub_key_broadcast",
+			NetworkMsgPayload::MisbehaviourBroadcast(_) => "misbehaviour",
+		}
+	}
+}
@@ -13,11 +13,11 @@
 // limitations under the License.
 
 use crate::{
-	handlers::{evm, validate_proposals::ValidationError},
+	handlers::{evm, substrate, validate_proposals::ValidationError},
 	DKGPayloadKey,
 };
 use frame_support::pallet_prelude::Get;
-use webb_proposals::{Proposal, ProposalKind};
+use webb_proposals::{Nonce as ProposalNonce, Proposal, ProposalKind, ResourceId};
 
 pub fn decode_proposal_header(
 	data: &[u8],
@@ -37,6 +37,19 @@ pub struct ProposalIdentifier {
 	pub typed_chain_id: webb_proposals::TypedChainId,
 }
 
+fn matches_kind(
+	prop_kind: ProposalKind,
+	expected_kind: ProposalKind,
+) -> impl Fn(ProposalIdentifier) -> Result<ProposalIdentifier, ValidationError> {
+	move |out| {
+		if prop_kind == expected_kind {
+			Ok(out)
+		} else {
+			Err(ValidationError::UnimplementedProposalKind)
+		}
+	}
+}
+
 pub fn decode_proposal_identifier<MaxLength: Get<u32>>(
 	proposal: &Proposal<MaxLength>,
 ) -> Result<ProposalIdentifier, ValidationError> {
@@ -49,25 +62,19 @@ pub fn decode_proposal_identifier<MaxLength: Get<u32>>(
 	}
 
 	// Otherwise, begin parsing DKG proposal header
-	fn matches_kind(
-		prop_kind: ProposalKind,
-		expected_kind: ProposalKind,
-	) -> impl Fn(ProposalIdentifier) -> Result<ProposalIdentifier, ValidationError> {
-		move |out| {
-			if prop_kind == expected_kind {
-				Ok(out)
-			} else {
-				Err(ValidationError::UnimplementedProposalKind)
-			}
-		}
-	}
-
 	let header = decode_proposal_header(proposal.data())?;
 	let mut identifier = ProposalIdentifier {
 		key: DKGPayloadKey::EVMProposal(header.nonce()), // placeholder
 		typed_chain_id: header.resource_id().typed_chain_id(),
 	};
 
+	// Substrate chains execute SCALE-encoded pallet calls rather than EVM calldata
+	if proposal.kind() != ProposalKind::Refresh &&
+		matches!(identifier.typed_chain_id, webb_proposals::TypedChainId::Substrate(_))
+	{
+		return decode_substrate_proposal_identifier(proposal, identifier)
+	}
+
 	// we then create a lazy identifier.
 	let maybe_refresh_vote = evm::refresh::create(proposal.data())
 		.map(|p| {
@@ -170,3 +177,65 @@ pub fn decode_proposal_identifier<MaxLength: Get<u32>>(
 		.or(maybe_fee_recipient_update)
 		.map_err(|_| ValidationError::UnimplementedProposalKind)
 }
+
+fn decode_substrate_proposal_identifier<MaxLength: Get<u32>>(
+	proposal: &Proposal<MaxLength>,
+	mut identifier: ProposalIdentifier,
+) -> Result<ProposalIdentifier, ValidationError> {
+	let maybe_anchor_update = substrate::anchor_update::create(proposal.data())
+		.map(|p| {
+			identifier.key = DKGPayloadKey::SubstrateAnchorUpdateProposal(p.header().nonce());
+			identifier
+		})
+		.and_then(matches_kind(proposal.kind(), ProposalKind::AnchorUpdate));
+
+	let maybe_resource_id_update = substrate::resource_id_update::create(proposal.data())
+		.map(|p| {
+			identifier.key = DKGPayloadKey::SubstrateResourceIdUpdateProposal(p.header().nonce());
+			identifier
+		})
+		.and_then(matches_kind(proposal.kind(), ProposalKind::ResourceIdUpdate));
+
+	let maybe_fee_update = substrate::fee_update::create(proposal.data())
+		.map(|p| {
+			identifier.key = DKGPayloadKey::SubstrateWrappingFeeUpdateProposal(p.header().nonce());
+			identifier
+		})
+		.and_then(matches_kind(proposal.kind(), ProposalKind::WrappingFeeUpdate));
+
+	let maybe_token_add = substrate::add_token_to_set::create(proposal.data())
+		.map(|p| {
+			identifier.key = DKGPayloadKey::SubstrateTokenAddProposal(p.header().nonce());
+			identifier
+		})
+		.and_then(matches_kind(proposal.kind(), ProposalKind::TokenAdd));
+
+	let maybe_token_remove = substrate::remove_token_from_set::create(proposal.data())
+		.map(|p| {
+			identifier.key = DKGPayloadKey::SubstrateTokenRemoveProposal(p.header().nonce());
+			identifier
+		})
+		.and_then(matches_kind(proposal.kind(), ProposalKind::TokenRemove));
+
+	// Switch on all cases
+	maybe_anchor_update
+		.or(maybe_resource_id_update)
+		.or(maybe_fee_update)
+		.or(maybe_token_add)
+		.or(maybe_token_remove)
+		.map_err(|_| ValidationError::UnimplementedProposalKind)
+}
+
+/// Returns the resource and the nonce of a proposal executed by a target resource, which must be
+/// signed in nonce order. EVM transaction and refresh proposals are not bound to a resource, so
+/// `None` is returned for them.
+pub fn decode_proposal_resource_nonce<MaxLength: Get<u32>>(
+	proposal: &Proposal<MaxLength>,
+) -> Option<(ResourceId, ProposalNonce)> {
+	match proposal.kind() {
+		ProposalKind::EVM | ProposalKind::Refresh => None,
+		_ => decode_proposal_header(proposal.data())
+			.ok()
+			.map(|header| (header.resource_id(), header.nonce())),
+	}
+}
@@ -14,6 +14,7 @@
 //
 pub mod decode_proposals;
 pub mod evm;
+pub mod substrate;
 pub mod validate_proposals;
 
 #[cfg(test)]
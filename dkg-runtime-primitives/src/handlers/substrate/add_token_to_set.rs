@@ -0,0 +1,55 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::{Decode, Encode};
+
+use crate::handlers::{substrate::SubstrateProposal, validate_proposals::ValidationError};
+
+/// Adds an asset to the pool of the target token wrapper
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct TokenAddCall {
+	/// The pool share asset of the token wrapper
+	pub pool_share_id: u32,
+	/// The asset to add
+	pub asset_id: u32,
+}
+
+pub type TokenAddProposal = SubstrateProposal<TokenAddCall>;
+
+pub fn create(data: &[u8]) -> Result<TokenAddProposal, ValidationError> {
+	super::decode(data)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::handlers::substrate::tests::{make_header, make_proposal};
+	use webb_proposals::TypedChainId;
+
+	#[test]
+	fn should_create_token_add() {
+		let call = TokenAddCall { pool_share_id: 1, asset_id: 2 };
+		let header = make_header(TypedChainId::Substrate(1), 1);
+		let proposal = create(&make_proposal(header, call.clone())).unwrap();
+		assert_eq!(proposal.header(), header);
+		assert_eq!(proposal.call(), &call);
+	}
+
+	#[test]
+	fn should_reject_token_add_for_evm() {
+		let call = TokenAddCall { pool_share_id: 1, asset_id: 2 };
+		let data = make_proposal(make_header(TypedChainId::Evm(1), 1), call);
+		assert!(matches!(create(&data), Err(ValidationError::InvalidParameter(_))));
+	}
+}
@@ -0,0 +1,68 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::{Decode, Encode};
+use webb_proposals::ResourceId;
+
+use crate::handlers::{substrate::SubstrateProposal, validate_proposals::ValidationError};
+
+/// Updates the root of a linked anchor of the target anchor
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct AnchorUpdateCall {
+	/// The new merkle root of the linked anchor
+	pub merkle_root: [u8; 32],
+	/// The index of the latest leaf inserted in the linked anchor
+	pub latest_leaf_index: u32,
+	/// The resource id of the linked anchor
+	pub src_resource_id: ResourceId,
+}
+
+pub type AnchorUpdateProposal = SubstrateProposal<AnchorUpdateCall>;
+
+pub fn create(data: &[u8]) -> Result<AnchorUpdateProposal, ValidationError> {
+	super::decode(data)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::handlers::substrate::tests::{make_header, make_proposal};
+	use webb_proposals::{TargetSystem, TypedChainId};
+
+	fn make_call() -> AnchorUpdateCall {
+		AnchorUpdateCall {
+			merkle_root: [2u8; 32],
+			latest_leaf_index: 10,
+			src_resource_id: ResourceId::new(
+				TargetSystem::new_contract_address([3u8; 20]),
+				TypedChainId::Substrate(2),
+			),
+		}
+	}
+
+	#[test]
+	fn should_create_anchor_update() {
+		let header = make_header(TypedChainId::Substrate(1), 1);
+		let proposal = create(&make_proposal(header, make_call())).unwrap();
+		assert_eq!(proposal.header(), header);
+		assert_eq!(proposal.call(), &make_call());
+	}
+
+	#[test]
+	fn should_reject_truncated_anchor_update() {
+		let mut data = make_proposal(make_header(TypedChainId::Substrate(1), 1), make_call());
+		data.pop();
+		assert!(matches!(create(&data), Err(ValidationError::InvalidDecoding(_))));
+	}
+}
@@ -0,0 +1,65 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::{alloc::string::ToString, Decode, Encode};
+
+use crate::handlers::{substrate::SubstrateProposal, validate_proposals::ValidationError};
+
+/// The highest wrapping fee, in percent
+pub const MAX_WRAPPING_FEE_PERCENT: u16 = 100;
+
+/// Sets the wrapping fee of the target token wrapper
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct WrappingFeeUpdateCall {
+	/// The pool share asset the fee applies to
+	pub pool_share_id: u32,
+	/// The new wrapping fee, in percent
+	pub wrapping_fee_percent: u16,
+}
+
+pub type WrappingFeeUpdateProposal = SubstrateProposal<WrappingFeeUpdateCall>;
+
+pub fn create(data: &[u8]) -> Result<WrappingFeeUpdateProposal, ValidationError> {
+	let proposal: WrappingFeeUpdateProposal = super::decode(data)?;
+	if proposal.call().wrapping_fee_percent > MAX_WRAPPING_FEE_PERCENT {
+		return Err(ValidationError::InvalidParameter("Wrapping fee is above 100%".to_string()))
+	}
+	Ok(proposal)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::handlers::substrate::tests::{make_header, make_proposal};
+	use webb_proposals::TypedChainId;
+
+	#[test]
+	fn should_create_fee_update() {
+		let call = WrappingFeeUpdateCall { pool_share_id: 1, wrapping_fee_percent: 5 };
+		let header = make_header(TypedChainId::Substrate(1), 1);
+		let proposal = create(&make_proposal(header, call.clone())).unwrap();
+		assert_eq!(proposal.header(), header);
+		assert_eq!(proposal.call(), &call);
+	}
+
+	#[test]
+	fn should_reject_fee_above_max() {
+		let call = WrappingFeeUpdateCall {
+			pool_share_id: 1,
+			wrapping_fee_percent: MAX_WRAPPING_FEE_PERCENT + 1,
+		};
+		let data = make_proposal(make_header(TypedChainId::Substrate(1), 1), call);
+		assert!(matches!(create(&data), Err(ValidationError::InvalidParameter(_))));
+	}
+}
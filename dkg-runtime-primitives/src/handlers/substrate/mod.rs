@@ -0,0 +1,119 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proposals targeting Substrate chains.
+//!
+//! A Substrate proposal is the 40 bytes [`ProposalHeader`] followed by the SCALE-encoded
+//! arguments of the pallet call it executes on the target chain.
+
+use codec::{alloc::string::ToString, Decode, DecodeAll};
+use webb_proposals::{ProposalHeader, TypedChainId};
+
+use crate::handlers::{
+	decode_proposals::decode_proposal_header, validate_proposals::ValidationError,
+};
+
+/// Bridge proposals
+pub mod resource_id_update;
+
+/// Anchor proposals
+pub mod anchor_update;
+
+/// Token update proposals
+pub mod add_token_to_set;
+pub mod fee_update;
+pub mod remove_token_from_set;
+
+/// A decoded proposal for a Substrate chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstrateProposal<Call> {
+	header: ProposalHeader,
+	call: Call,
+}
+
+impl<Call> SubstrateProposal<Call> {
+	/// The header of the proposal
+	pub fn header(&self) -> ProposalHeader {
+		self.header
+	}
+
+	/// The decoded arguments of the pallet call
+	pub fn call(&self) -> &Call {
+		&self.call
+	}
+}
+
+/// Decodes a proposal targeting a Substrate chain, the call must use all the remaining bytes
+pub fn decode<Call: Decode>(data: &[u8]) -> Result<SubstrateProposal<Call>, ValidationError> {
+	let header = decode_proposal_header(data)?;
+	if !matches!(header.resource_id().typed_chain_id(), TypedChainId::Substrate(_)) {
+		return Err(ValidationError::InvalidParameter(
+			"Proposal does not target a Substrate chain".to_string(),
+		))
+	}
+
+	let mut call_bytes = &data[ProposalHeader::LENGTH..];
+	let call = Call::decode_all(&mut call_bytes)
+		.map_err(|_| ValidationError::InvalidDecoding("Invalid pallet call".to_string()))?;
+	Ok(SubstrateProposal { header, call })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use codec::Encode;
+	use sp_std::vec::Vec;
+	use webb_proposals::{FunctionSignature, Nonce, ResourceId, TargetSystem};
+
+	pub(crate) fn make_header(typed_chain_id: TypedChainId, nonce: u32) -> ProposalHeader {
+		let resource_id =
+			ResourceId::new(TargetSystem::new_contract_address([1u8; 20]), typed_chain_id);
+		ProposalHeader::new(resource_id, FunctionSignature::from([0u8; 4]), Nonce(nonce))
+	}
+
+	pub(crate) fn make_proposal<Call: Encode>(header: ProposalHeader, call: Call) -> Vec<u8> {
+		let mut data = header.encode();
+		call.encode_to(&mut data);
+		data
+	}
+
+	#[test]
+	fn should_reject_short_header() {
+		assert_eq!(
+			decode::<u32>(&[0u8; ProposalHeader::LENGTH - 1]),
+			Err(ValidationError::InvalidProposalBytesLength)
+		);
+	}
+
+	#[test]
+	fn should_reject_evm_target() {
+		let data = make_proposal(make_header(TypedChainId::Evm(1), 1), 7u32);
+		assert!(matches!(decode::<u32>(&data), Err(ValidationError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn should_reject_trailing_bytes() {
+		let mut data = make_proposal(make_header(TypedChainId::Substrate(1), 1), 7u32);
+		data.push(0);
+		assert!(matches!(decode::<u32>(&data), Err(ValidationError::InvalidDecoding(_))));
+	}
+
+	#[test]
+	fn should_decode_substrate_proposal() {
+		let header = make_header(TypedChainId::Substrate(1), 3);
+		let proposal = decode::<u32>(&make_proposal(header, 7u32)).unwrap();
+		assert_eq!(proposal.header(), header);
+		assert_eq!(proposal.call(), &7);
+	}
+}
@@ -0,0 +1,58 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::{Decode, Encode};
+use webb_proposals::ResourceId;
+
+use crate::handlers::{substrate::SubstrateProposal, validate_proposals::ValidationError};
+
+/// Replaces the resource id the target bridge pallet is registered under
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ResourceIdUpdateCall {
+	/// The new resource id
+	pub new_resource_id: ResourceId,
+}
+
+pub type ResourceIdUpdateProposal = SubstrateProposal<ResourceIdUpdateCall>;
+
+pub fn create(data: &[u8]) -> Result<ResourceIdUpdateProposal, ValidationError> {
+	super::decode(data)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::handlers::substrate::tests::{make_header, make_proposal};
+	use webb_proposals::{TargetSystem, TypedChainId};
+
+	#[test]
+	fn should_create_resource_id_update() {
+		let call = ResourceIdUpdateCall {
+			new_resource_id: ResourceId::new(
+				TargetSystem::new_contract_address([3u8; 20]),
+				TypedChainId::Substrate(1),
+			),
+		};
+		let header = make_header(TypedChainId::Substrate(1), 1);
+		let proposal = create(&make_proposal(header, call.clone())).unwrap();
+		assert_eq!(proposal.header(), header);
+		assert_eq!(proposal.call(), &call);
+	}
+
+	#[test]
+	fn should_reject_empty_resource_id_update() {
+		let data = make_proposal(make_header(TypedChainId::Substrate(1), 1), ());
+		assert!(matches!(create(&data), Err(ValidationError::InvalidDecoding(_))));
+	}
+}
@@ -19,6 +19,7 @@
 pub mod ethereum_abi;
 pub mod gossip_messages;
 pub mod handlers;
+pub mod merkle;
 pub mod offchain;
 pub mod proposal;
 pub mod traits;
@@ -88,6 +89,14 @@ pub const KEYGEN_TIMEOUT: u32 = 10;
 /// The sign timeout limit in blocks before we consider proposal as stalled
 pub const SIGN_TIMEOUT: u32 = 10;
 
+/// The number of blocks to wait for a submitted signed proposal batch to be confirmed on chain
+/// before submitting it again
+pub const SIGNED_PROPOSAL_RESUBMIT_DELAY: u32 = 5;
+
+/// The number of times a signed proposal batch is submitted on chain before it is dropped from
+/// the offchain outbox
+pub const MAX_SIGNED_PROPOSAL_SUBMISSIONS: u32 = 10;
+
 /// So long as the associated block id is within this tolerance, we consider the message as
 /// deliverable. This should be less than the SIGN_TIMEOUT
 pub const ASSOCIATED_BLOCK_ID_MESSAGE_DELIVERY_TOLERANCE: u64 = (SIGN_TIMEOUT - 2) as u64;
@@ -127,6 +136,9 @@ pub type MaxProposalsInBatch = CustomU32Getter<10>;
 /// Max size for signatures
 pub type MaxSignatureLength = CustomU32Getter<512>;
 
+/// Max size for the encoded round messages carried as misbehaviour evidence
+pub type MaxRoundMessageLength = CustomU32Getter<65_536>;
+
 /// Max size for keys
 pub type MaxKeyLength = CustomU32Getter<512>;
 
@@ -164,6 +176,32 @@ pub enum MisbehaviourType {
 	Sign,
 }
 
+/// The threshold signature scheme run by an authority set
+#[derive(
+	Debug, Default, Clone, Copy, Decode, Encode, PartialEq, Eq, TypeInfo, Hash, MaxEncodedLen,
+)]
+pub enum DKGProtocol {
+	/// Multi-party threshold ECDSA (GG20)
+	#[default]
+	MpEcdsa,
+	/// Weighted threshold FROST (Schnorr)
+	WTFrost,
+}
+
+/// How the key of an authority set was obtained
+#[derive(
+	Debug, Default, Clone, Copy, Decode, Encode, PartialEq, Eq, TypeInfo, Hash, MaxEncodedLen,
+)]
+pub enum DKGKeyOrigin {
+	/// A new key was generated by the keygen set
+	#[default]
+	Generated,
+	/// The shares of the previous key were refreshed by the same keygen set
+	Refreshed,
+	/// The previous key was reshared by the outgoing keygen set to a new keygen set
+	Reshared,
+}
+
 #[derive(Eq, PartialEq, Clone, Encode, Decode, Debug, TypeInfo, codec::MaxEncodedLen)]
 pub struct AggregatedMisbehaviourReports<
 	DKGId: AsRef<[u8]>,
@@ -180,6 +218,28 @@ pub struct AggregatedMisbehaviourReports<
 	pub reporters: BoundedVec<DKGId, MaxReporters>,
 	/// A list of signed reports
 	pub signatures: BoundedVec<BoundedVec<u8, MaxSignatureLength>, MaxReporters>,
+	/// The evidence of the misbehaviour, if any
+	pub evidence: Option<MisbehaviourEvidence<MaxSignatureLength>>,
+}
+
+/// A gossiped DKG message, encoded as it was signed by its sender
+#[derive(Eq, PartialEq, Clone, Encode, Decode, Debug, TypeInfo, codec::MaxEncodedLen)]
+pub struct SignedRoundMessage<MaxSignatureLength: Get<u32> + Debug + Clone + TypeInfo> {
+	/// The encoded [`gossip_messages::DKGMessage`]
+	pub message: BoundedVec<u8, MaxRoundMessageLength>,
+	/// The signature of the sender over the encoded message
+	pub signature: BoundedVec<u8, MaxSignatureLength>,
+}
+
+/// Evidence of a misbehaviour, checked by the runtime against the signatures of the offender
+#[derive(Eq, PartialEq, Clone, Encode, Decode, Debug, TypeInfo, codec::MaxEncodedLen)]
+pub enum MisbehaviourEvidence<MaxSignatureLength: Get<u32> + Debug + Clone + TypeInfo> {
+	/// Two different votes the offender signed for the same signing round. This proves the
+	/// misbehaviour on its own.
+	Equivocation {
+		first: SignedRoundMessage<MaxSignatureLength>,
+		second: SignedRoundMessage<MaxSignatureLength>,
+	},
 }
 
 #[derive(Eq, PartialEq, Clone, Encode, Decode, Debug, TypeInfo, codec::MaxEncodedLen)]
@@ -304,7 +364,13 @@ impl<MaxProposalLength: Get<u32> + Clone> UnsignedProposal<MaxProposalLength> {
 	}
 }
 
+/// The version of the [`DKGApi`] which added the DKG protocols, the key refresh and resharing
+/// modes, the signature of the active key, the signed proposal batches, the last signed nonces and
+/// the chain scheduling configs, and the encoding of the unsigned proposal batches.
+pub const DKG_API_VERSION_2: u32 = 2;
+
 sp_api::decl_runtime_apis! {
+	#[api_version(2)]
 	pub trait DKGApi<AuthorityId, N, MaxProposalLength, MaxAuthorities> where
 		AuthorityId: Codec + PartialEq,
 		MaxProposalLength: Get<u32> + Clone,
@@ -327,6 +393,16 @@ sp_api::decl_runtime_apis! {
 		fn next_signature_threshold() -> u16;
 		/// Return the next keygen threshold for the DKG
 		fn next_keygen_threshold() -> u16;
+		/// Return the DKG protocol used by the current authority set
+		fn dkg_protocol() -> DKGProtocol;
+		/// Return the DKG protocol used by the queued authority set
+		fn next_dkg_protocol() -> DKGProtocol;
+		/// Check if the queued authority set should refresh the shares of the active key
+		/// instead of generating a new key
+		fn should_refresh_key_shares() -> bool;
+		/// Check if the current keygen set should reshare the active key to the queued keygen
+		/// set instead of the queued set generating a new key
+		fn should_reshare_key() -> bool;
 		/// Return the next authorities active authority set
 		fn queued_authority_set() -> AuthoritySet<AuthorityId, MaxAuthorities>;
 		/// Check if refresh process should start
@@ -335,8 +411,19 @@ sp_api::decl_runtime_apis! {
 		fn next_dkg_pub_key() -> Option<(AuthoritySetId, Vec<u8>)>;
 		/// Fetch DKG public key for current authorities
 		fn dkg_pub_key() -> (AuthoritySetId, Vec<u8>);
+		/// Fetch the signature of the DKG public key for current authorities
+		fn dkg_pub_key_sig() -> Vec<u8>;
+		/// Get list of unsigned proposals, without their encoding
+		#[changed_in(2)]
+		fn get_unsigned_proposal_batches() -> Vec<StoredUnsignedProposalBatchV0<BatchId, MaxProposalLength, MaxProposalsInBatch, N>>;
 		/// Get list of unsigned proposals
 		fn get_unsigned_proposal_batches() -> Vec<StoredUnsignedProposalBatch<BatchId, MaxProposalLength, MaxProposalsInBatch, N>>;
+		/// Get list of signed proposals with the chain they are destined to
+		fn get_signed_proposal_batches() -> Vec<(webb_proposals::TypedChainId, SignedProposalBatch<BatchId, MaxProposalLength, MaxProposalsInBatch, MaxSignatureLength>)>;
+		/// Get the nonce of the latest signed proposal of each resource
+		fn get_last_signed_nonces() -> Vec<(ResourceId, ProposalNonce)>;
+		/// Get the scheduling configuration of the chains which are not scheduled by default
+		fn get_chain_scheduling_configs() -> Vec<(webb_proposals::TypedChainId, ChainSchedulingConfig)>;
 		/// Current and Queued Authority Account Ids [/current_authorities/, /next_authorities/]
 		fn get_authority_accounts() -> (Vec<AccountId>, Vec<AccountId>);
 		/// Reputations for authorities
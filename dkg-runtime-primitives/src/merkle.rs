@@ -0,0 +1,248 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keccak256 binary merkle trees, used to commit to the DKG voter set and to the proposals of a
+//! batch.
+//!
+//! The leaves are padded with `keccak256(0x00)` up to a power of two, and each node of the voter
+//! set tree is the hash of the concatenation of its two children. The proposal batch trees separate
+//! the leaves from the inner nodes instead: a leaf is the hash of its data prefixed with `0x00`,
+//! and an inner node the hash of its children prefixed with `0x01`, so an inner node cannot be
+//! proven as a proposal.
+
+use crate::keccak_256;
+use frame_support::{pallet_prelude::ConstU32, BoundedVec};
+use sp_std::vec::Vec;
+
+/// Maximum number of nodes in a merkle proof, which is the height of the tree
+pub type MaxMerkleProofLength = ConstU32<32>;
+
+/// The sibling nodes on the path from a leaf to the root of a merkle tree
+pub type MerkleProof = BoundedVec<[u8; 32], MaxMerkleProofLength>;
+
+/// Returns the minimal height of a merkle tree with `leaf_count` leaves
+pub fn tree_height(leaf_count: usize) -> u32 {
+	if leaf_count == 1 {
+		1
+	} else {
+		let two: u32 = 2;
+		let mut h = 0;
+		while two.saturating_pow(h) < leaf_count as u32 {
+			h += 1;
+		}
+		h
+	}
+}
+
+/// Pads the leaves of a merkle tree with the hash of a zero byte, so that their number is
+/// `2^tree_height`
+pub fn pad_leaves(mut leaves: Vec<[u8; 32]>) -> Vec<[u8; 32]> {
+	let width = 2usize.saturating_pow(tree_height(leaves.len()));
+	leaves.resize(width, keccak_256(&[0u8]));
+	leaves
+}
+
+/// Computes the next layer of a merkle tree by hashing the pairs of nodes of a layer
+pub fn next_layer(layer: Vec<[u8; 32]>) -> Vec<[u8; 32]> {
+	hash_layer(layer, hash_pair)
+}
+
+fn hash_layer(layer: Vec<[u8; 32]>, hash: NodeHash) -> Vec<[u8; 32]> {
+	layer.chunks_exact(2).map(|pair| hash(&pair[0], &pair[1])).collect()
+}
+
+/// Hashes two sibling nodes into their parent
+type NodeHash = fn(&[u8; 32], &[u8; 32]) -> [u8; 32];
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+	let mut input = [0u8; 64];
+	input[..32].copy_from_slice(left);
+	input[32..].copy_from_slice(right);
+	keccak_256(&input)
+}
+
+fn hash_inner_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+	let mut input = [0u8; 65];
+	input[0] = INNER_NODE_PREFIX;
+	input[1..33].copy_from_slice(left);
+	input[33..].copy_from_slice(right);
+	keccak_256(&input)
+}
+
+const LEAF_PREFIX: u8 = 0x00;
+const INNER_NODE_PREFIX: u8 = 0x01;
+
+/// Hashes the data of a proposal into a leaf of a proposal batch tree
+pub fn hash_leaf(data: &[u8]) -> [u8; 32] {
+	let mut input = Vec::with_capacity(data.len() + 1);
+	input.push(LEAF_PREFIX);
+	input.extend_from_slice(data);
+	keccak_256(&input)
+}
+
+/// Returns all the layers of a merkle tree, from the padded leaves up to the root
+fn layers(leaves: Vec<[u8; 32]>, hash: NodeHash) -> Vec<Vec<[u8; 32]>> {
+	let height = tree_height(leaves.len());
+	let mut layers = Vec::with_capacity(height as usize + 1);
+	layers.push(pad_leaves(leaves));
+	for _ in 0..height {
+		let layer = hash_layer(layers.last().cloned().unwrap_or_default(), hash);
+		layers.push(layer);
+	}
+	layers
+}
+
+fn root(leaves: Vec<[u8; 32]>, hash: NodeHash) -> [u8; 32] {
+	layers(leaves, hash)
+		.last()
+		.and_then(|root| root.first().copied())
+		.unwrap_or_default()
+}
+
+/// Computes the merkle root of a list of leaves
+pub fn merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
+	root(leaves, hash_pair)
+}
+
+/// Computes the merkle root of the leaves of a proposal batch, see [`hash_leaf`]
+pub fn batch_merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
+	root(leaves, hash_inner_node)
+}
+
+fn proofs(leaves: Vec<[u8; 32]>, hash: NodeHash) -> Vec<Vec<[u8; 32]>> {
+	let leaf_count = leaves.len();
+	let layers = layers(leaves, hash);
+	(0..leaf_count)
+		.map(|leaf_index| {
+			let height = layers.len() - 1;
+			layers[..height]
+				.iter()
+				.enumerate()
+				.map(|(level, layer)| layer[(leaf_index >> level) ^ 1])
+				.collect()
+		})
+		.collect()
+}
+
+/// Returns the inclusion proof of each leaf, in the order of the leaves
+pub fn merkle_proofs(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+	proofs(leaves, hash_pair)
+}
+
+/// Returns the inclusion proof of each leaf of a proposal batch, in the order of the leaves
+pub fn batch_merkle_proofs(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+	proofs(leaves, hash_inner_node)
+}
+
+fn verify_proof(
+	root: &[u8; 32],
+	leaf: [u8; 32],
+	mut index: usize,
+	proof: &[[u8; 32]],
+	hash: NodeHash,
+) -> bool {
+	let node = proof.iter().fold(leaf, |node, sibling| {
+		let parent = if index % 2 == 0 { hash(&node, sibling) } else { hash(sibling, &node) };
+		index /= 2;
+		parent
+	});
+	index == 0 && node == *root
+}
+
+/// Checks that `proof` proves the inclusion of `leaf` at `index` in the merkle tree of `root`
+pub fn verify_merkle_proof(
+	root: &[u8; 32],
+	leaf: [u8; 32],
+	index: usize,
+	proof: &[[u8; 32]],
+) -> bool {
+	verify_proof(root, leaf, index, proof, hash_pair)
+}
+
+/// Checks that `proof` proves the inclusion of the proposal `data` at `index` in the proposal
+/// batch tree of `root`
+pub fn verify_batch_merkle_proof(
+	root: &[u8; 32],
+	data: &[u8],
+	index: usize,
+	proof: &[[u8; 32]],
+) -> bool {
+	verify_proof(root, hash_leaf(data), index, proof, hash_inner_node)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn leaves(count: u8) -> Vec<[u8; 32]> {
+		(0..count).map(|i| keccak_256(&[i])).collect()
+	}
+
+	#[test]
+	fn leaves_are_padded_to_a_power_of_two() {
+		assert_eq!(pad_leaves(leaves(1)).len(), 2);
+		assert_eq!(pad_leaves(leaves(4)).len(), 4);
+		assert_eq!(pad_leaves(leaves(5)).len(), 8);
+		assert_eq!(pad_leaves(leaves(5))[7], keccak_256(&[0u8]));
+	}
+
+	#[test]
+	fn root_of_a_single_leaf_includes_the_padding() {
+		let leaf = keccak_256(b"leaf");
+		assert_eq!(merkle_root(vec![leaf]), hash_pair(&leaf, &keccak_256(&[0u8])));
+	}
+
+	#[test]
+	fn every_leaf_is_proven_against_the_root() {
+		for count in 1..=9 {
+			let leaves = leaves(count);
+			let root = merkle_root(leaves.clone());
+			let proofs = merkle_proofs(leaves.clone());
+			assert_eq!(proofs.len(), leaves.len());
+			for (index, (leaf, proof)) in leaves.iter().zip(proofs.iter()).enumerate() {
+				assert_eq!(proof.len(), tree_height(leaves.len()) as usize);
+				assert!(verify_merkle_proof(&root, *leaf, index, proof));
+			}
+		}
+	}
+
+	#[test]
+	fn proofs_are_bound_to_their_leaf_and_index() {
+		let leaves = leaves(5);
+		let root = merkle_root(leaves.clone());
+		let proofs = merkle_proofs(leaves.clone());
+
+		assert!(!verify_merkle_proof(&root, leaves[1], 2, &proofs[2]));
+		assert!(!verify_merkle_proof(&root, leaves[2], 3, &proofs[2]));
+		assert!(!verify_merkle_proof(&root, leaves[2], 2 + 8, &proofs[2]));
+		assert!(!verify_merkle_proof(&root, leaves[2], 2, &proofs[2][1..]));
+	}
+
+	#[test]
+	fn inner_nodes_of_a_batch_tree_are_not_proven_as_proposals() {
+		let data: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 8]).collect();
+		let leaves: Vec<_> = data.iter().map(|data| hash_leaf(data)).collect();
+		let root = batch_merkle_root(leaves.clone());
+		let proofs = batch_merkle_proofs(leaves.clone());
+		for (index, (data, proof)) in data.iter().zip(proofs.iter()).enumerate() {
+			assert!(verify_batch_merkle_proof(&root, data, index, proof));
+		}
+
+		// the concatenation of two leaves hashes to their parent in an undomained tree
+		let mut inner = leaves[0].to_vec();
+		inner.extend_from_slice(&leaves[1]);
+		assert!(!verify_batch_merkle_proof(&root, &inner, 0, &proofs[0][1..]));
+		assert_ne!(batch_merkle_root(leaves.clone()), merkle_root(leaves));
+	}
+}
@@ -18,7 +18,7 @@ use frame_support::{
 };
 use sp_std::hash::{Hash, Hasher};
 
-use codec::{Decode, Encode, EncodeLike, MaxEncodedLen};
+use codec::{Decode, DecodeAll, Encode, EncodeLike, MaxEncodedLen};
 use sp_std::{vec, vec::Vec};
 
 pub const PROPOSAL_SIGNATURE_LENGTH: usize = 65;
@@ -145,6 +145,11 @@ pub enum DKGPayloadKey {
 	SetVerifierProposal(ProposalNonce),
 	SetTreasuryHandlerProposal(ProposalNonce),
 	FeeRecipientUpdateProposal(ProposalNonce),
+	SubstrateAnchorUpdateProposal(ProposalNonce),
+	SubstrateResourceIdUpdateProposal(ProposalNonce),
+	SubstrateWrappingFeeUpdateProposal(ProposalNonce),
+	SubstrateTokenAddProposal(ProposalNonce),
+	SubstrateTokenRemoveProposal(ProposalNonce),
 }
 
 impl PartialEq for DKGPayloadKey {
@@ -170,6 +175,19 @@ impl PartialEq for DKGPayloadKey {
 				l0 == r0,
 			(Self::FeeRecipientUpdateProposal(l0), Self::FeeRecipientUpdateProposal(r0)) =>
 				l0 == r0,
+			(Self::SubstrateAnchorUpdateProposal(l0), Self::SubstrateAnchorUpdateProposal(r0)) =>
+				l0 == r0,
+			(
+				Self::SubstrateResourceIdUpdateProposal(l0),
+				Self::SubstrateResourceIdUpdateProposal(r0),
+			) => l0 == r0,
+			(
+				Self::SubstrateWrappingFeeUpdateProposal(l0),
+				Self::SubstrateWrappingFeeUpdateProposal(r0),
+			) => l0 == r0,
+			(Self::SubstrateTokenAddProposal(l0), Self::SubstrateTokenAddProposal(r0)) => l0 == r0,
+			(Self::SubstrateTokenRemoveProposal(l0), Self::SubstrateTokenRemoveProposal(r0)) =>
+				l0 == r0,
 			_ => false,
 		}
 	}
@@ -216,6 +234,109 @@ impl ProposalHandlerTrait for () {
 	type MaxSignatureLen = ConstU32<0>;
 }
 
+/// The priority class of the proposals of a chain, which orders their signing by the DKG
+#[derive(
+	Debug,
+	Default,
+	Encode,
+	Decode,
+	Clone,
+	Copy,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Hash,
+	scale_info::TypeInfo,
+	codec::MaxEncodedLen,
+)]
+pub enum PriorityClass {
+	Low,
+	#[default]
+	Normal,
+	High,
+	Critical,
+}
+
+/// How the proposal batches of a chain are scheduled for signing
+#[derive(
+	Debug,
+	Default,
+	Encode,
+	Decode,
+	Clone,
+	Copy,
+	Eq,
+	PartialEq,
+	scale_info::TypeInfo,
+	codec::MaxEncodedLen,
+)]
+pub struct ChainSchedulingConfig {
+	/// Batches of a higher priority class are signed first
+	pub priority: PriorityClass,
+	/// Maximum number of batches of the chain waiting to be signed at the same time, if any
+	pub max_batches_in_flight: Option<u32>,
+}
+
+impl ChainSchedulingConfig {
+	/// Whether another batch can be put in flight while `in_flight` batches are
+	pub fn allows_more_batches(&self, in_flight: usize) -> bool {
+		self.max_batches_in_flight.map_or(true, |max| in_flight < max as usize)
+	}
+}
+
+/// How the proposals of a batch are encoded into the data signed by the DKG
+#[derive(
+	Debug,
+	Default,
+	Encode,
+	Decode,
+	Clone,
+	Copy,
+	Eq,
+	PartialEq,
+	scale_info::TypeInfo,
+	codec::MaxEncodedLen,
+)]
+pub enum BatchEncoding {
+	/// The DKG signs the ABI encoding of the data of all the proposals, so the whole batch must
+	/// be submitted to verify any of its proposals
+	#[default]
+	Concatenated,
+	/// The DKG signs the merkle root of the data of the proposals, so each proposal can be
+	/// verified on its own with its inclusion proof, see [`crate::merkle::hash_leaf`]
+	MerkleRoot,
+}
+
+/// Encodes the data of the proposals of a batch into the data signed by the DKG
+fn encode_batch_data<'a>(
+	encoding: BatchEncoding,
+	mut proposals_data: impl ExactSizeIterator<Item = &'a Vec<u8>>,
+) -> Vec<u8> {
+	use ethabi::token::Token;
+
+	if encoding == BatchEncoding::MerkleRoot {
+		let leaves = proposals_data.map(|data| crate::merkle::hash_leaf(data)).collect();
+		return crate::merkle::batch_merkle_root(leaves).to_vec()
+	}
+
+	// We generate the data to sign for a proposal batch by doing ethabi::encode
+	// on the data of all proposals in the batch.
+	// If the proposal batch has just one proposal, the data is the encoded data of
+	// the proposal, this allows us to quickly verify batches with just one proposal
+	if proposals_data.len() == 1 {
+		return proposals_data.next().expect("checked above that len = 1").clone()
+	}
+
+	let mut vec_proposal_data: Vec<Token> = Vec::new();
+	for data in proposals_data {
+		let data_as_token = Token::FixedBytes(data.to_vec());
+		vec_proposal_data.push(data_as_token);
+	}
+
+	ethabi::encode(&[Token::Array(vec_proposal_data)])
+}
+
 /// An unsigned proposal represented in pallet storage
 /// We store the creation timestamp to purge expired proposals
 #[derive(
@@ -233,29 +354,16 @@ pub struct StoredUnsignedProposalBatch<
 	pub proposals: BoundedVec<crate::UnsignedProposal<MaxLength>, MaxProposals>,
 	/// Creation timestamp
 	pub timestamp: Timestamp,
+	/// How the proposals are encoded into the signed data
+	pub encoding: BatchEncoding,
 }
 
 impl<BatchId, MaxLength: Get<u32> + Clone, MaxProposals: Get<u32>, Timestamp>
 	StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>
 {
-	// We generate the data to sign for a proposal batch by doing ethabi::encode
-	// on the data of all proposals in the batch
+	/// The data signed by the DKG for this batch, see [`BatchEncoding`]
 	pub fn data(&self) -> Vec<u8> {
-		use ethabi::token::Token;
-
-		// if the proposal batch has just one proposal, the data is the encoded data of
-		// the proposal, this allows us to quickly verify batches with just one proposal
-		if self.proposals.len() == 1 {
-			return self.proposals.first().expect("checked above that len = 1").data().clone()
-		}
-
-		let mut vec_proposal_data: Vec<Token> = Vec::new();
-		for proposal in self.proposals.iter() {
-			let data_as_token = Token::FixedBytes(proposal.data().to_vec());
-			vec_proposal_data.push(data_as_token);
-		}
-
-		ethabi::encode(&[Token::Array(vec_proposal_data)])
+		encode_batch_data(self.encoding, self.proposals.iter().map(|proposal| proposal.data()))
 	}
 
 	pub fn hash(&self) -> Option<[u8; 32]> {
@@ -263,6 +371,36 @@ impl<BatchId, MaxLength: Get<u32> + Clone, MaxProposals: Get<u32>, Timestamp>
 	}
 }
 
+/// The layout of a [`StoredUnsignedProposalBatch`] stored before the batches had an encoding,
+/// when they were all [`BatchEncoding::Concatenated`]
+#[derive(Debug, Encode, Decode, Clone, Eq, PartialEq)]
+pub struct StoredUnsignedProposalBatchV0<
+	BatchId,
+	MaxLength: Get<u32> + Clone,
+	MaxProposals: Get<u32>,
+	Timestamp,
+> {
+	pub batch_id: BatchId,
+	pub proposals: BoundedVec<crate::UnsignedProposal<MaxLength>, MaxProposals>,
+	pub timestamp: Timestamp,
+}
+
+impl<BatchId, MaxLength: Get<u32> + Clone, MaxProposals: Get<u32>, Timestamp>
+	From<StoredUnsignedProposalBatchV0<BatchId, MaxLength, MaxProposals, Timestamp>>
+	for StoredUnsignedProposalBatch<BatchId, MaxLength, MaxProposals, Timestamp>
+{
+	fn from(
+		batch: StoredUnsignedProposalBatchV0<BatchId, MaxLength, MaxProposals, Timestamp>,
+	) -> Self {
+		StoredUnsignedProposalBatch {
+			batch_id: batch.batch_id,
+			proposals: batch.proposals,
+			timestamp: batch.timestamp,
+			encoding: BatchEncoding::Concatenated,
+		}
+	}
+}
+
 /// An unsigned proposal represented in pallet storage
 /// We store the creation timestamp to purge expired proposals
 #[derive(
@@ -280,6 +418,38 @@ pub struct SignedProposalBatch<
 	pub proposals: BoundedVec<Proposal<MaxLength>, MaxProposals>,
 	/// Signature for proposals
 	pub signature: BoundedVec<u8, MaxSignatureLen>,
+	/// How the proposals are encoded into the signed data
+	pub encoding: BatchEncoding,
+}
+
+/// The layout of a [`SignedProposalBatch`] stored before the batches had an encoding, when they
+/// were all [`BatchEncoding::Concatenated`]
+#[derive(Debug, Encode, Decode, Clone, Eq, PartialEq)]
+pub struct SignedProposalBatchV0<
+	BatchId,
+	MaxLength: Get<u32>,
+	MaxProposals: Get<u32>,
+	MaxSignatureLen: Get<u32>,
+> {
+	pub batch_id: BatchId,
+	pub proposals: BoundedVec<Proposal<MaxLength>, MaxProposals>,
+	pub signature: BoundedVec<u8, MaxSignatureLen>,
+}
+
+impl<BatchId, MaxLength: Get<u32>, MaxProposals: Get<u32>, MaxSignatureLen: Get<u32>>
+	From<SignedProposalBatchV0<BatchId, MaxLength, MaxProposals, MaxSignatureLen>>
+	for SignedProposalBatch<BatchId, MaxLength, MaxProposals, MaxSignatureLen>
+{
+	fn from(
+		batch: SignedProposalBatchV0<BatchId, MaxLength, MaxProposals, MaxSignatureLen>,
+	) -> Self {
+		SignedProposalBatch {
+			batch_id: batch.batch_id,
+			proposals: batch.proposals,
+			signature: batch.signature,
+			encoding: BatchEncoding::Concatenated,
+		}
+	}
 }
 
 impl<BatchId, MaxLength: Get<u32>, MaxProposals: Get<u32>, MaxSignatureLen: Get<u32>>
@@ -291,6 +461,7 @@ impl<BatchId, MaxLength: Get<u32>, MaxProposals: Get<u32>, MaxSignatureLen: Get<
 			batch_id: payload.batch_id,
 			proposals: payload.payload,
 			signature: payload.signature,
+			encoding: payload.encoding,
 		}
 	}
 }
@@ -298,24 +469,23 @@ impl<BatchId, MaxLength: Get<u32>, MaxProposals: Get<u32>, MaxSignatureLen: Get<
 impl<BatchId, MaxLength: Get<u32>, MaxProposals: Get<u32>, MaxSignatureLen: Get<u32>>
 	SignedProposalBatch<BatchId, MaxLength, MaxProposals, MaxSignatureLen>
 {
-	// We generate the data to sign for a proposal batch by doing ethabi::encode
-	// on the data of all proposals in the batch
+	/// The data signed by the DKG for this batch, see [`BatchEncoding`]
 	pub fn data(&self) -> Vec<u8> {
-		use ethabi::token::Token;
-
-		// if the proposal batch has just one proposal, the data is the encoded data of
-		// the proposal, this allows us to quickly verify batches with just one proposal
-		if self.proposals.len() == 1 {
-			return self.proposals.first().expect("checked above that len = 1").data().clone()
-		}
+		encode_batch_data(self.encoding, self.proposals.iter().map(|proposal| proposal.data()))
+	}
 
-		let mut vec_proposal_data: Vec<Token> = Vec::new();
-		for proposal in self.proposals.iter() {
-			let data_as_token = Token::FixedBytes(proposal.data().to_vec());
-			vec_proposal_data.push(data_as_token);
+	/// Returns the proof of inclusion of each proposal in the signed merkle root, in the order of
+	/// the proposals, if the batch is encoded as a merkle root
+	pub fn inclusion_proofs(&self) -> Option<Vec<Vec<[u8; 32]>>> {
+		if self.encoding != BatchEncoding::MerkleRoot {
+			return None
 		}
-
-		ethabi::encode(&[Token::Array(vec_proposal_data)])
+		let leaves = self
+			.proposals
+			.iter()
+			.map(|proposal| crate::merkle::hash_leaf(proposal.data()))
+			.collect();
+		Some(crate::merkle::batch_merkle_proofs(leaves))
 	}
 }
 
@@ -333,22 +503,133 @@ pub struct DKGSignedPayload<
 	pub payload: BoundedVec<Proposal<MaxLength>, MaxProposals>,
 	/// Runtime compatible signature for the payload
 	pub signature: BoundedVec<u8, MaxSignatureLen>,
+	/// How the payload is encoded into the signed data
+	pub encoding: BatchEncoding,
 }
 
+/// A signed proposal batch in the offchain outbox, waiting to be confirmed on chain
+#[derive(Clone, Debug, PartialEq, Eq, codec::Encode, codec::Decode)]
+pub struct OffchainSignedProposalBatch<
+	BatchId,
+	MaxLength: Get<u32>,
+	MaxProposals: Get<u32>,
+	MaxSignatureLen: Get<u32>,
+	BlockNumber,
+> {
+	pub batch: SignedProposalBatch<BatchId, MaxLength, MaxProposals, MaxSignatureLen>,
+	/// The block from which the expected submitter can submit the batch on chain
+	pub submit_at: BlockNumber,
+	/// The number of times the batch was submitted on chain
+	pub retries: u32,
+	/// The block at which the batch was last submitted on chain
+	pub last_submitted_at: Option<BlockNumber>,
+}
+
+/// The outbox of the signed proposal batches, shared by the DKG worker which appends the batches
+/// and the offchain worker which submits them on chain
 #[derive(Clone, Debug, PartialEq, Eq, codec::Encode, codec::Decode)]
 pub struct OffchainSignedProposalBatches<
 	BatchId,
 	MaxLength: Get<u32>,
 	MaxProposals: Get<u32>,
 	MaxSignatureLen: Get<u32>,
+	BlockNumber,
 > {
-	pub batches: Vec<SignedProposalBatch<BatchId, MaxLength, MaxProposals, MaxSignatureLen>>,
+	pub batches: Vec<
+		OffchainSignedProposalBatch<BatchId, MaxLength, MaxProposals, MaxSignatureLen, BlockNumber>,
+	>,
 }
 
-impl<BatchId, MaxLength: Get<u32>, MaxProposals: Get<u32>, MaxSignatureLen: Get<u32>> Default
-	for OffchainSignedProposalBatches<BatchId, MaxLength, MaxProposals, MaxSignatureLen>
+impl<
+		BatchId,
+		MaxLength: Get<u32>,
+		MaxProposals: Get<u32>,
+		MaxSignatureLen: Get<u32>,
+		BlockNumber,
+	> Default
+	for OffchainSignedProposalBatches<BatchId, MaxLength, MaxProposals, MaxSignatureLen, BlockNumber>
 {
 	fn default() -> Self {
 		Self { batches: Default::default() }
 	}
 }
+
+impl<
+		BatchId,
+		MaxLength: Get<u32>,
+		MaxProposals: Get<u32>,
+		MaxSignatureLen: Get<u32>,
+		BlockNumber: Clone,
+	> OffchainSignedProposalBatches<BatchId, MaxLength, MaxProposals, MaxSignatureLen, BlockNumber>
+{
+	/// Appends the batches which are not in the outbox yet, to be submitted from `submit_at`.
+	/// The batches are compared by their signed data, since the signatures of the same batch can
+	/// differ. Returns the number of appended batches.
+	pub fn append(
+		&mut self,
+		batches: impl IntoIterator<
+			Item = SignedProposalBatch<BatchId, MaxLength, MaxProposals, MaxSignatureLen>,
+		>,
+		submit_at: BlockNumber,
+	) -> usize {
+		let mut saved_data: Vec<Vec<u8>> =
+			self.batches.iter().map(|pending| pending.batch.data()).collect();
+		let len_before = self.batches.len();
+		for batch in batches {
+			let data = batch.data();
+			if saved_data.contains(&data) {
+				continue
+			}
+			saved_data.push(data);
+			self.batches.push(OffchainSignedProposalBatch {
+				batch,
+				submit_at: submit_at.clone(),
+				retries: 0,
+				last_submitted_at: None,
+			});
+		}
+		self.batches.len() - len_before
+	}
+}
+
+impl<
+		BatchId: Decode,
+		MaxLength: Get<u32>,
+		MaxProposals: Get<u32>,
+		MaxSignatureLen: Get<u32>,
+		BlockNumber: Decode + Clone,
+	> OffchainSignedProposalBatches<BatchId, MaxLength, MaxProposals, MaxSignatureLen, BlockNumber>
+{
+	/// Decodes the outbox, or the plain list of signed proposal batches stored before the outbox
+	/// and the batch encodings, whose batches are then submitted from `submit_at`. Anything else
+	/// keeps the batches of the outbox up to the first one which cannot be decoded.
+	///
+	/// Returns whether the whole outbox was decoded.
+	pub fn decode_versioned(mut bytes: &[u8], submit_at: BlockNumber) -> (Self, bool) {
+		if let Ok(outbox) = Self::decode_all(&mut &bytes[..]) {
+			return (outbox, true)
+		}
+
+		let legacy = Vec::<
+			SignedProposalBatchV0<BatchId, MaxLength, MaxProposals, MaxSignatureLen>,
+		>::decode_all(&mut &bytes[..]);
+		if let Ok(batches) = legacy {
+			let batches = batches
+				.into_iter()
+				.map(|batch| OffchainSignedProposalBatch {
+					batch: batch.into(),
+					submit_at: submit_at.clone(),
+					retries: 0,
+					last_submitted_at: None,
+				})
+				.collect();
+			return (Self { batches }, true)
+		}
+
+		let len = codec::Compact::<u32>::decode(&mut bytes).map_or(0, |len| len.0);
+		let batches = (0..len)
+			.map_while(|_| OffchainSignedProposalBatch::decode(&mut bytes).ok())
+			.collect();
+		(Self { batches }, false)
+	}
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 //
+use crate::DKGProtocol;
 use frame_support::dispatch::DispatchResultWithPostInfo;
 use sp_core::Get;
 use sp_runtime::{BoundedVec, DispatchError};
@@ -33,6 +34,9 @@ impl<AccountId, AuthoritySetId, AuthorityId>
 pub trait GetDKGPublicKey {
 	fn dkg_key() -> Vec<u8>;
 	fn previous_dkg_key() -> Vec<u8>;
+	/// The protocol which produced the current DKG key, and so the signature scheme
+	/// signatures made by it must be verified with.
+	fn dkg_protocol() -> DKGProtocol;
 }
 
 /// A trait for fetching the current proposer set.
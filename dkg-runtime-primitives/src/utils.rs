@@ -17,7 +17,7 @@ use sp_io::{hashing::keccak_256, EcdsaVerifyError};
 use sp_runtime::traits::BadOrigin;
 use sp_std::vec::Vec;
 
-use crate::traits::GetDKGPublicKey;
+use crate::{traits::GetDKGPublicKey, DKGProtocol};
 
 pub const SIGNATURE_LENGTH: usize = 65;
 const SR25519_KEY_LENGTH: usize = 32;
@@ -49,6 +49,59 @@ pub fn recover_ecdsa_pub_key(data: &[u8], signature: &[u8]) -> Result<Vec<u8>, E
 	Err(EcdsaVerifyError::BadSignature)
 }
 
+/// Verifies a WT-FROST Schnorr signature `R || z` over the keccak256 hash of `data`, that is,
+/// checks that `z * G == R + c * Y` where `c = keccak256(R || Y || keccak256(data))`.
+pub fn verify_schnorr_signature(data: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+	use libsecp256k1::{
+		curve::{Affine, Jacobian, Scalar},
+		PublicKey, PublicKeyFormat, ECMULT_CONTEXT,
+	};
+
+	if signature.len() != SIGNATURE_LENGTH || public_key.len() != ECDSA_KEY_LENGTH {
+		return false
+	}
+
+	let parse_point = |bytes: &[u8]| -> Option<Affine> {
+		let mut point: Affine =
+			PublicKey::parse_slice(bytes, Some(PublicKeyFormat::Compressed)).ok()?.into();
+		point.x.normalize();
+		point.y.normalize();
+		Some(point)
+	};
+	let (r, y) = match (parse_point(&signature[..ECDSA_KEY_LENGTH]), parse_point(public_key)) {
+		(Some(r), Some(y)) => (r, y),
+		_ => return false,
+	};
+
+	let mut z_bytes = [0u8; 32];
+	z_bytes.copy_from_slice(&signature[ECDSA_KEY_LENGTH..]);
+	let mut z = Scalar::default();
+	let overflow: bool = z.set_b32(&z_bytes).into();
+	if overflow {
+		return false
+	}
+
+	let mut challenge_data = Vec::with_capacity(2 * ECDSA_KEY_LENGTH + 32);
+	challenge_data.extend_from_slice(&signature[..ECDSA_KEY_LENGTH]);
+	challenge_data.extend_from_slice(public_key);
+	challenge_data.extend_from_slice(&keccak_256(data));
+	let mut c = Scalar::default();
+	// the challenge is reduced modulo the group order, as the signer does
+	let _ = c.set_b32(&keccak_256(&challenge_data));
+
+	// z * G - c * Y must equal R
+	let mut computed = Jacobian::default();
+	ECMULT_CONTEXT.ecmult(&mut computed, &Jacobian::from_ge(&y), &-c, &z);
+	if computed.is_infinity() {
+		return false
+	}
+
+	let mut computed = Affine::from_gej(&computed);
+	computed.x.normalize();
+	computed.y.normalize();
+	computed.x.b32() == r.x.b32() && computed.y.b32() == r.y.b32()
+}
+
 pub fn verify_signer_from_set_ecdsa(
 	maybe_signers: Vec<ecdsa::Public>,
 	msg: &[u8],
@@ -128,6 +181,7 @@ pub enum SignatureError {
 	InvalidDKGKey(BadOrigin),
 	InvalidRecovery(SignatureResult),
 	InvalidECDSASignature(BadOrigin),
+	InvalidSchnorrSignature(BadOrigin),
 }
 
 impl SignatureError {
@@ -136,6 +190,7 @@ impl SignatureError {
 			Self::InvalidDKGKey(_) => "InvalidDKGKey",
 			Self::InvalidRecovery(_) => "InvalidRecovery",
 			Self::InvalidECDSASignature(_) => "InvalidECDSASignature",
+			Self::InvalidSchnorrSignature(_) => "InvalidSchnorrSignature",
 		}
 	}
 	pub fn expected_public_key(&self) -> Option<Vec<u8>> {
@@ -153,13 +208,44 @@ impl SignatureError {
 	}
 }
 
-/// This function takes the ecdsa signature and the unhashed data
+/// This function takes the signature and the unhashed data, and verifies it against the current
+/// DKG key using the signature scheme of the protocol that generated the key
 pub fn ensure_signed_by_dkg<T: GetDKGPublicKey>(
 	signature: &[u8],
 	data: &[u8],
 ) -> Result<(), SignatureError> {
-	let dkg_key = T::dkg_key();
+	ensure_signed_by_key(T::dkg_protocol(), &T::dkg_key(), signature, data)
+}
 
+/// This function takes the signature and the unhashed data, and verifies it against the given
+/// DKG key using the signature scheme of `protocol`
+pub fn ensure_signed_by_key(
+	protocol: DKGProtocol,
+	dkg_key: &[u8],
+	signature: &[u8],
+	data: &[u8],
+) -> Result<(), SignatureError> {
+	match protocol {
+		DKGProtocol::MpEcdsa => ensure_ecdsa_signed_by_key(dkg_key, signature, data),
+		DKGProtocol::WTFrost => {
+			if dkg_key.len() != ECDSA_KEY_LENGTH {
+				return Err(SignatureError::InvalidDKGKey(BadOrigin))
+			}
+
+			if verify_schnorr_signature(data, signature, dkg_key) {
+				Ok(())
+			} else {
+				Err(SignatureError::InvalidSchnorrSignature(BadOrigin))
+			}
+		},
+	}
+}
+
+fn ensure_ecdsa_signed_by_key(
+	dkg_key: &[u8],
+	signature: &[u8],
+	data: &[u8],
+) -> Result<(), SignatureError> {
 	let recovered_key = recover_ecdsa_pub_key(data, signature)
 		.map_err(|_| SignatureError::InvalidECDSASignature(BadOrigin))?;
 
@@ -0,0 +1,156 @@
+//! The byzantine behaviours of the clients. As commanded by the MockBlockchain, a client tampers
+//! with the keygen, offline and vote messages it sends through the in-memory gossip engine. The
+//! misbehaviour reports gossiped by the client are relayed to the MockBlockchain, which checks that
+//! the honest clients blame the byzantine ones.
+use dkg_gadget::keyring::Keyring;
+use dkg_mock_blockchain::AttachedCommand;
+use dkg_primitives::types::{NetworkMsgPayload, SignedDKGMessage};
+use dkg_runtime_primitives::{crypto::AuthorityId, MisbehaviourType};
+use parking_lot::{Mutex, RwLock};
+use sp_runtime::codec::Encode;
+use std::{collections::VecDeque, sync::Arc};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::in_memory_gossip_engine::PeerId;
+
+// the number of sent messages kept to be replayed in the later sessions
+const MAX_RECORDED_MESSAGES: usize = 1024;
+
+pub type MisbehaviourReport = (PeerId, MisbehaviourType);
+
+#[derive(Clone)]
+pub struct ByzantineBehaviour {
+	command: Arc<RwLock<AttachedCommand>>,
+	keyring: Keyring,
+	// the authority this client claims to be when forging signatures, and its party index
+	victim: (AuthorityId, u16),
+	recorded_messages: Arc<Mutex<VecDeque<SignedDKGMessage<AuthorityId>>>>,
+	reports_tx: UnboundedSender<MisbehaviourReport>,
+	reports_rx: Arc<Mutex<Option<UnboundedReceiver<MisbehaviourReport>>>>,
+}
+
+impl ByzantineBehaviour {
+	/// Creates the behaviour of the client of index `idx`, which impersonates the next client when
+	/// forging signatures. The party indexes follow the order in which the clients are spawned
+	pub fn new(idx: usize, n_clients: usize) -> Self {
+		let victim = (idx + 1) % n_clients;
+		let (reports_tx, reports_rx) = tokio::sync::mpsc::unbounded_channel();
+		Self {
+			command: Arc::new(RwLock::new(AttachedCommand::default())),
+			keyring: Keyring::Custom(idx as _),
+			victim: (Keyring::Custom(victim as _).public(), victim as u16 + 1),
+			recorded_messages: Arc::new(Mutex::new(VecDeque::new())),
+			reports_tx,
+			reports_rx: Arc::new(Mutex::new(Some(reports_rx))),
+		}
+	}
+
+	pub fn set_command(&self, command: AttachedCommand) {
+		*self.command.write() = command;
+	}
+
+	/// Applies the commanded misbehaviour to a message sent by the client. Returns `None` if the
+	/// message is withheld
+	pub fn tamper(
+		&self,
+		mut message: SignedDKGMessage<AuthorityId>,
+	) -> Option<SignedDKGMessage<AuthorityId>> {
+		if round_message_bytes(&mut message.msg.payload).is_none() {
+			return Some(message)
+		}
+
+		let command = self.command.read().clone();
+		match command {
+			AttachedCommand::SendMalformedMessages => {
+				let bytes = round_message_bytes(&mut message.msg.payload)?;
+				bytes.truncate(bytes.len() / 2);
+				Some(self.resign(message))
+			},
+			AttachedCommand::ForgeSignature => {
+				let (victim, party_index) = self.victim.clone();
+				message.msg.sender_id = victim;
+				set_party_index(&mut message.msg.payload, party_index);
+				Some(self.resign(message))
+			},
+			AttachedCommand::ReplayOldSessionMessages => {
+				// the old messages take turns, since there may be less of them than new ones
+				let mut recorded = self.recorded_messages.lock();
+				let idx = recorded.iter().position(|old| {
+					old.msg.session_id < message.msg.session_id &&
+						old.msg.payload.get_type() == message.msg.payload.get_type()
+				})?;
+				let old = recorded.remove(idx)?;
+				recorded.push_back(old.clone());
+				Some(old)
+			},
+			AttachedCommand::ProcessAsNormal |
+			AttachedCommand::ErrorDontProcessRequest |
+			AttachedCommand::Equivocate => {
+				let mut recorded = self.recorded_messages.lock();
+				if recorded.len() == MAX_RECORDED_MESSAGES {
+					recorded.pop_front();
+				}
+				recorded.push_back(message.clone());
+				Some(message)
+			},
+		}
+	}
+
+	/// The version of a message received by the client of index `to`. When equivocating, the
+	/// clients of odd index receive a different payload than the others
+	pub fn equivocate(
+		&self,
+		to: usize,
+		mut message: SignedDKGMessage<AuthorityId>,
+	) -> SignedDKGMessage<AuthorityId> {
+		if *self.command.read() != AttachedCommand::Equivocate || to % 2 == 0 {
+			return message
+		}
+
+		let Some(bytes) = round_message_bytes(&mut message.msg.payload) else {
+			return message
+		};
+		if let Some(last) = bytes.last_mut() {
+			*last ^= 0xff;
+		}
+		self.resign(message)
+	}
+
+	/// Relays a misbehaviour report gossiped by the client
+	pub fn report(&self, offender: PeerId, misbehaviour: MisbehaviourType) {
+		// the client may have stopped listening to the reports
+		let _ = self.reports_tx.send((offender, misbehaviour));
+	}
+
+	pub fn take_reports(&self) -> Option<UnboundedReceiver<MisbehaviourReport>> {
+		self.reports_rx.lock().take()
+	}
+
+	// signs the tampered message with the key of the client, so that it reaches the protocols of
+	// the recipients
+	fn resign(&self, mut message: SignedDKGMessage<AuthorityId>) -> SignedDKGMessage<AuthorityId> {
+		message.signature = Some(self.keyring.sign(&message.msg.encode()).encode());
+		message
+	}
+}
+
+// the serialized protocol message of the keygen, offline and vote messages
+fn round_message_bytes(payload: &mut NetworkMsgPayload) -> Option<&mut Vec<u8>> {
+	match payload {
+		NetworkMsgPayload::Keygen(msg) => Some(&mut msg.keygen_msg),
+		NetworkMsgPayload::Offline(msg) => Some(&mut msg.offline_msg),
+		NetworkMsgPayload::Vote(msg) => Some(&mut msg.partial_signature),
+		NetworkMsgPayload::PublicKeyBroadcast(_) | NetworkMsgPayload::MisbehaviourBroadcast(_) =>
+			None,
+	}
+}
+
+fn set_party_index(payload: &mut NetworkMsgPayload, party_index: u16) {
+	match payload {
+		NetworkMsgPayload::Keygen(msg) => msg.sender_id = party_index,
+		NetworkMsgPayload::Offline(msg) => msg.signer_set_id = party_index.into(),
+		NetworkMsgPayload::Vote(msg) => msg.party_ind = party_index,
+		NetworkMsgPayload::PublicKeyBroadcast(_) | NetworkMsgPayload::MisbehaviourBroadcast(_) => {
+		},
+	}
+}
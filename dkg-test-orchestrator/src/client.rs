@@ -11,7 +11,7 @@ use sc_network::PeerId;
 use sc_utils::mpsc::*;
 use sp_api::{offchain::storage::InMemOffchainStorage, BlockT, ProvideRuntimeApi};
 
-use crate::dummy_api::*;
+use crate::{byzantine::ByzantineBehaviour, dummy_api::*};
 use dkg_gadget::worker::TestClientPayload;
 use sp_runtime::testing::H256;
 use std::{collections::HashMap, sync::Arc};
@@ -50,6 +50,7 @@ impl TestClient {
 		api: DummyApi,
 		mut from_dkg_worker: UnboundedReceiver<TestClientPayload>,
 		latest_test_uuid: Arc<RwLock<Option<Uuid>>>,
+		byzantine: ByzantineBehaviour,
 		logger: DebugLogger,
 	) -> std::io::Result<Self> {
 		logger
@@ -58,6 +59,7 @@ impl TestClient {
 		let (tx, mut rx) = dkg_mock_blockchain::transport::bind_transport::<TestBlock>(socket);
 		let tx0 = Arc::new(tokio::sync::Mutex::new(tx));
 		let tx1 = tx0.clone();
+		let tx2 = tx0.clone();
 
 		let this = TestClient {
 			inner: TestClientState {
@@ -96,6 +98,29 @@ impl TestClient {
 			panic!("DKG worker listener ended prematurely")
 		};
 
+		let mut misbehaviour_reports =
+			byzantine.take_reports().expect("The reports should only be taken once");
+		let latest_test_uuid0 = latest_test_uuid.clone();
+		let logger1 = logger.clone();
+		let misbehaviour_reports_listener = async move {
+			while let Some((offender, misbehaviour)) = misbehaviour_reports.recv().await {
+				let Some(trace_id) = *latest_test_uuid0.read() else {
+					continue
+				};
+				logger1.info(format!(
+					"The client {peer_id:?} reported the {misbehaviour:?} misbehaviour of {offender:?} during test {trace_id:?}"
+				));
+
+				let event = MockClientResponse::MisbehaviourReport {
+					trace_id,
+					offender,
+					misbehaviour: misbehaviour.into(),
+				};
+				let packet = ProtocolPacket::ClientToBlockchain { event };
+				tx2.lock().await.send(packet).await.unwrap();
+			}
+		};
+
 		let this_for_orchestrator_rx = this.clone();
 		let orchestrator_coms = async move {
 			logger
@@ -111,8 +136,9 @@ impl TestClient {
 							.await
 							.unwrap();
 					},
-					ProtocolPacket::BlockchainToClient { trace_id, event } => {
+					ProtocolPacket::BlockchainToClient { trace_id, event, command } => {
 						*latest_test_uuid.write() = Some(trace_id);
+						byzantine.set_command(command);
 
 						match event {
 							MockBlockchainEvent::FinalityNotification { notification } => {
@@ -150,6 +176,7 @@ impl TestClient {
 		};
 
 		tokio::task::spawn(dkg_worker_listener);
+		tokio::task::spawn(misbehaviour_reports_listener);
 		tokio::task::spawn(orchestrator_coms);
 
 		Ok(this)
@@ -1,8 +1,8 @@
 use dkg_gadget::debug_logger::DebugLogger;
 use dkg_mock_blockchain::{MutableBlockchain, TestBlock};
 use dkg_runtime_primitives::{
-	crypto::AuthorityId, MaxAuthorities, MaxProposalLength, StoredUnsignedProposalBatch,
-	UnsignedProposal,
+	crypto::AuthorityId, DKGProtocol, MaxAuthorities, MaxProposalLength,
+	StoredUnsignedProposalBatch, UnsignedProposal,
 };
 use hash_db::HashDB;
 use parking_lot::RwLock;
@@ -40,6 +40,7 @@ pub struct DummyApiInner {
 	pub should_execute_keygen: bool,
 	pub blocks_per_session: u64,
 	pub incrementing_batch_id: u32,
+	pub dkg_protocol: DKGProtocol,
 }
 
 impl MutableBlockchain for DummyApi {
@@ -58,6 +59,7 @@ impl MutableBlockchain for DummyApi {
 				proposals: vec![prop.clone().0].try_into().unwrap(),
 				batch_id,
 				timestamp: 0,
+				encoding: Default::default(),
 			})
 			.collect::<Vec<_>>();
 
@@ -101,6 +103,7 @@ impl DummyApi {
 				should_execute_keygen: false,
 				blocks_per_session,
 				incrementing_batch_id: 0,
+				dkg_protocol: DKGProtocol::default(),
 			})),
 			logger,
 		}
@@ -293,7 +296,7 @@ impl ApiExt<TestBlock> for DummyApi {
 	where
 		Self: Sized,
 	{
-		todo!()
+		Ok(Some(A::VERSION))
 	}
 
 	fn record_proof(&mut self) {
@@ -576,6 +579,22 @@ impl
 		Ok(self.inner.read().keygen_t)
 	}
 
+	fn dkg_protocol(&self, _: H256) -> ApiResult<DKGProtocol> {
+		Ok(self.inner.read().dkg_protocol)
+	}
+
+	fn next_dkg_protocol(&self, _block: H256) -> ApiResult<DKGProtocol> {
+		Ok(self.inner.read().dkg_protocol)
+	}
+
+	fn should_refresh_key_shares(&self, _: H256) -> ApiResult<bool> {
+		Ok(false)
+	}
+
+	fn should_reshare_key(&self, _: H256) -> ApiResult<bool> {
+		Ok(false)
+	}
+
 	fn should_refresh(&self, _: H256, _block_number: BlockNumber) -> ApiResult<bool> {
 		Ok(true)
 	}
@@ -610,6 +629,10 @@ impl
 		Ok((authority_set_id, pub_key))
 	}
 
+	fn dkg_pub_key_sig(&self, _: H256) -> ApiResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
 	fn get_best_authorities(&self, id: H256) -> ApiResult<Vec<(u16, AuthorityId)>> {
 		let id = self.block_id_to_session_id(&id);
 		let read = self.inner.read();
@@ -660,6 +683,40 @@ impl
 		Ok(self.inner.read().unsigned_proposals.clone())
 	}
 
+	fn get_signed_proposal_batches(
+		&self,
+		_hash: H256,
+	) -> ApiResult<
+		Vec<(
+			dkg_runtime_primitives::TypedChainId,
+			dkg_runtime_primitives::SignedProposalBatch<
+				dkg_runtime_primitives::BatchId,
+				dkg_runtime_primitives::MaxProposalLength,
+				dkg_runtime_primitives::MaxProposalsInBatch,
+				dkg_runtime_primitives::MaxSignatureLength,
+			>,
+		)>,
+	> {
+		Ok(vec![])
+	}
+
+	fn get_last_signed_nonces(
+		&self,
+		_hash: H256,
+	) -> ApiResult<Vec<(dkg_runtime_primitives::ResourceId, dkg_runtime_primitives::ProposalNonce)>>
+	{
+		Ok(vec![])
+	}
+
+	fn get_chain_scheduling_configs(
+		&self,
+		_hash: H256,
+	) -> ApiResult<
+		Vec<(dkg_runtime_primitives::TypedChainId, dkg_runtime_primitives::ChainSchedulingConfig)>,
+	> {
+		Ok(vec![])
+	}
+
 	fn get_authority_accounts(&self, _: H256) -> ApiResult<(Vec<AccountId>, Vec<AccountId>)> {
 		self.logger.error("unimplemented get_authority_accounts".to_string());
 		todo!()
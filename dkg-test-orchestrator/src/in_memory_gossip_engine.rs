@@ -1,17 +1,26 @@
 use dkg_gadget::gossip_engine::GossipEngineIface;
-use dkg_primitives::types::{DKGError, SignedDKGMessage};
+use dkg_mock_blockchain::NetworkFaultsConfig;
+use dkg_primitives::types::{DKGError, NetworkMsgPayload, SignedDKGMessage};
 use dkg_runtime_primitives::crypto::AuthorityId;
 use parking_lot::Mutex;
-use std::{collections::HashMap, sync::Arc};
+use rand::Rng;
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-use crate::dummy_api::DummyApi;
+use crate::{byzantine::ByzantineBehaviour, dummy_api::DummyApi};
 use dkg_gadget::debug_logger::DebugLogger;
 use dkg_runtime_primitives::crypto;
 
 pub type PeerId = sc_network::PeerId;
 
+// the maximum delay of the reordered messages, if no reorder window is configured
+const DEFAULT_REORDER_WINDOW: Duration = Duration::from_millis(100);
+
 #[derive(Clone)]
 pub struct InMemoryGossipEngine {
 	clients: Arc<Mutex<HashMap<PeerId, UnboundedSender<SignedDKGMessage<AuthorityId>>>>>,
@@ -20,6 +29,11 @@ pub struct InMemoryGossipEngine {
 	this_peer_public_key: Option<AuthorityId>,
 	// Maps Peer IDs to public keys
 	mapping: Arc<Mutex<HashMap<PeerId, AuthorityId>>>,
+	// Maps Peer IDs to the client indexes used by the network faults
+	peer_indexes: Arc<Mutex<HashMap<PeerId, usize>>>,
+	network_faults: Option<Arc<NetworkFaultsConfig>>,
+	started_at: Instant,
+	byzantine: Option<ByzantineBehaviour>,
 	logger: Option<DebugLogger>,
 }
 
@@ -31,17 +45,26 @@ impl Default for InMemoryGossipEngine {
 
 impl InMemoryGossipEngine {
 	pub fn new() -> Self {
+		Self::with_network_faults(None)
+	}
+
+	/// Creates a gossip engine which loses, duplicates, reorders, delays and partitions the
+	/// messages of its peers as configured
+	pub fn with_network_faults(network_faults: Option<NetworkFaultsConfig>) -> Self {
 		Self {
 			clients: Arc::new(Mutex::new(Default::default())),
 			message_deliver_rx: Arc::new(Mutex::new(None)),
 			this_peer: None,
 			this_peer_public_key: None,
 			mapping: Arc::new(Mutex::new(Default::default())),
+			peer_indexes: Arc::new(Mutex::new(Default::default())),
+			network_faults: network_faults.map(Arc::new),
+			started_at: Instant::now(),
+			byzantine: None,
 			logger: None,
 		}
 	}
 
-	#[allow(dead_code)]
 	fn public_to_peer_id(&self, public: AuthorityId) -> Option<PeerId> {
 		let mapping = self.mapping.lock();
 		for (peer_id, public_key) in mapping.iter() {
@@ -60,9 +83,15 @@ impl InMemoryGossipEngine {
 		n_blocks: u64,
 		this_peer: PeerId,
 		public_key: crypto::Public,
+		byzantine: &ByzantineBehaviour,
 		logger: &DebugLogger,
 	) -> Self {
 		self.mapping.lock().insert(this_peer, public_key.clone());
+		{
+			let mut peer_indexes = self.peer_indexes.lock();
+			let index = peer_indexes.len();
+			peer_indexes.entry(this_peer).or_insert(index);
+		}
 
 		// by default, add this peer to the best authorities
 		// TODO: make the configurable
@@ -85,6 +114,10 @@ impl InMemoryGossipEngine {
 			this_peer: Some(this_peer),
 			this_peer_public_key: Some(public_key),
 			mapping: self.mapping.clone(),
+			peer_indexes: self.peer_indexes.clone(),
+			network_faults: self.network_faults.clone(),
+			started_at: self.started_at,
+			byzantine: Some(byzantine.clone()),
 			logger: Some(logger.clone()),
 		}
 	}
@@ -92,6 +125,95 @@ impl InMemoryGossipEngine {
 	pub fn peer_id(&self) -> (&PeerId, &AuthorityId) {
 		(self.this_peer.as_ref().unwrap(), self.this_peer_public_key.as_ref().unwrap())
 	}
+
+	/// Relays the misbehaviour reports sent by this peer, and applies its byzantine behaviour to
+	/// the message. Returns `None` if the message is withheld
+	fn outgoing(
+		&self,
+		message: SignedDKGMessage<AuthorityId>,
+	) -> Option<SignedDKGMessage<AuthorityId>> {
+		let Some(byzantine) = self.byzantine.as_ref() else {
+			return Some(message)
+		};
+
+		if let NetworkMsgPayload::MisbehaviourBroadcast(report) = &message.msg.payload {
+			if let Some(offender) = self.public_to_peer_id(report.offender.clone()) {
+				byzantine.report(offender, report.misbehaviour_type);
+			}
+		}
+
+		byzantine.tamper(message)
+	}
+
+	/// Delivers a message to a peer through the faults of the network, if any
+	fn deliver(
+		&self,
+		recipient: &PeerId,
+		tx: &UnboundedSender<SignedDKGMessage<AuthorityId>>,
+		message: SignedDKGMessage<AuthorityId>,
+	) -> Result<(), DKGError> {
+		let (from, to) = {
+			let peer_indexes = self.peer_indexes.lock();
+			(peer_indexes[self.peer_id().0], peer_indexes[recipient])
+		};
+
+		let message = match self.byzantine.as_ref() {
+			Some(byzantine) => byzantine.equivocate(to, message),
+			None => message,
+		};
+
+		let Some(faults) = self.network_faults.as_ref() else {
+			return tx.send(message).map_err(|_| error("Failed to send message"))
+		};
+		let log = |reason: &str| {
+			if let Some(logger) = self.logger.as_ref() {
+				logger.debug(format!("[network faults] {reason} message from {from} to {to}"));
+			}
+		};
+
+		if !faults.is_reachable(from, to, self.started_at.elapsed()) {
+			log("Partition dropped");
+			return Ok(())
+		}
+
+		let mut rng = rand::thread_rng();
+		if rng.gen_bool(faults.loss_probability) {
+			log("Lost");
+			return Ok(())
+		}
+
+		let copies = if rng.gen_bool(faults.duplication_probability) {
+			log("Duplicated");
+			2
+		} else {
+			1
+		};
+
+		for _ in 0..copies {
+			let mut delay = faults.link_latency(from, to);
+			if let Some(jitter) = faults.jitter {
+				delay += jitter.mul_f64(rng.gen());
+			}
+			if rng.gen_bool(faults.reorder_probability) {
+				log("Reordered");
+				delay += faults.reorder_window.unwrap_or(DEFAULT_REORDER_WINDOW).mul_f64(rng.gen());
+			}
+
+			if delay.is_zero() {
+				tx.send(message.clone()).map_err(|_| error("Failed to send message"))?;
+			} else {
+				let tx = tx.clone();
+				let message = message.clone();
+				tokio::spawn(async move {
+					tokio::time::sleep(delay).await;
+					// the recipient may have stopped in the meantime
+					let _ = tx.send(message);
+				});
+			}
+		}
+
+		Ok(())
+	}
 }
 
 impl GossipEngineIface for InMemoryGossipEngine {
@@ -103,24 +225,29 @@ impl GossipEngineIface for InMemoryGossipEngine {
 		recipient: PeerId,
 		message: SignedDKGMessage<AuthorityId>,
 	) -> Result<(), DKGError> {
+		let Some(message) = self.outgoing(message) else {
+			return Ok(())
+		};
+
 		let mut clients = self.clients.lock();
 		let tx = clients
 			.get_mut(&recipient)
 			.ok_or_else(|| error(format!("Peer {recipient:?} does not exist")))?;
-		tx.send(message).map_err(|_| error("Failed to send message"))?;
-
-		Ok(())
+		self.deliver(&recipient, tx, message)
 	}
 
 	/// Send a DKG message to all peers.
 	fn gossip(&self, message: SignedDKGMessage<AuthorityId>) -> Result<(), DKGError> {
+		let Some(message) = self.outgoing(message) else {
+			return Ok(())
+		};
+
 		let mut clients = self.clients.lock();
 		let (this_peer, _) = self.peer_id();
 
 		for (peer_id, tx) in clients.iter_mut() {
 			if peer_id != this_peer {
-				tx.send(message.clone())
-					.map_err(|_| error("Failed to send broadcast message"))?;
+				self.deliver(peer_id, tx, message.clone())?;
 			}
 		}
 
@@ -9,7 +9,7 @@
 extern crate core;
 
 // allow unwraps in tests
-use crate::in_memory_gossip_engine::InMemoryGossipEngine;
+use crate::{byzantine::ByzantineBehaviour, in_memory_gossip_engine::InMemoryGossipEngine};
 use dkg_gadget::worker::TestBundle;
 use dkg_mock_blockchain::*;
 use dkg_runtime_primitives::{crypto, KEY_TYPE};
@@ -19,6 +19,7 @@ use sp_keystore::Keystore;
 use std::{path::PathBuf, sync::Arc};
 use structopt::StructOpt;
 
+mod byzantine;
 mod client;
 mod dummy_api;
 mod in_memory_gossip_engine;
@@ -59,6 +60,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 	dkg_logging::setup_simple_log();
 
 	let config = args_to_config(&args)?;
+	if let Some(network_faults) = config.network_faults.as_ref() {
+		network_faults.validate(config.n_clients)?;
+	}
 	let n_clients = config.n_clients;
 	let t = config.threshold;
 	// set the number of blocks to the sum of the number of positive and negative cases
@@ -70,8 +74,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 	// the gossip engine and the dummy api share a state between ALL clients in this process
 	// we will use the SAME gossip engine for both keygen and signing
-	let keygen_gossip_engine = &InMemoryGossipEngine::new();
-	let signing_gossip_engine = &InMemoryGossipEngine::new();
+	let keygen_gossip_engine =
+		&InMemoryGossipEngine::with_network_faults(config.network_faults.clone());
+	let signing_gossip_engine =
+		&InMemoryGossipEngine::with_network_faults(config.network_faults.clone());
 	let keygen_t = t as u16;
 	let keygen_n = n_clients as u16;
 	let signing_t = t as u16;
@@ -134,11 +140,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 		// output the logs for this specific peer to a file
 		let output = args.tmp_path.join(format!("{peer_id}.log"));
 		let logger = dkg_gadget::debug_logger::DebugLogger::new(peer_id, Some(output))?;
+		// the keygen and signing engines misbehave together, as commanded to the client
+		let byzantine = ByzantineBehaviour::new(idx, n_clients);
 		let keygen_gossip_engine = keygen_gossip_engine.clone_for_new_peer(
 			api,
 			n_blocks as _,
 			peer_id,
 			public_key.clone(),
+			&byzantine,
 			&logger,
 		);
 		let signing_gossip_engine = signing_gossip_engine.clone_for_new_peer(
@@ -146,6 +155,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 			n_blocks as _,
 			peer_id,
 			public_key,
+			&byzantine,
 			&logger,
 		);
 
@@ -158,6 +168,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 				api.clone(),
 				rx,
 				current_test_id.clone(),
+				byzantine,
 				logger.clone(),
 			)
 			.await?,
@@ -185,6 +196,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 				metrics,
 				local_keystore,
 				test_bundle: Some(test_bundle),
+				rpc_links: Default::default(),
+				signing_work_config: Default::default(),
 				_marker: Default::default(),
 			};
 
@@ -275,6 +288,7 @@ fn args_to_config(args: &Args) -> Result<MockBlockchainConfig, String> {
 			bind,
 			n_clients: n,
 			unsigned_proposals_per_session: Some(proposals_per_test),
+			network_faults: None,
 		};
 		Ok(config)
 	}
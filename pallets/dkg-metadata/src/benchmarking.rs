@@ -201,6 +201,7 @@ benchmarks! {
 													offender : offender.clone(),
 													reporters:reporters.clone().try_into().unwrap(),
 													signatures : signatures.try_into().unwrap(),
+													evidence: None,
 												};
 		let caller = T::AccountId::from(sr25519::Public::from_raw([1u8; 32]));
 	}: _(RawOrigin::None, aggregated_misbehaviour_reports)
@@ -255,6 +256,30 @@ benchmarks! {
 	verify {
 		assert!(JailedKeygenAuthorities::<T>::contains_key(offender.clone()) == false);
 	}
+
+	set_dkg_protocol {
+	}: _(RawOrigin::Root, DKGProtocol::WTFrost)
+	verify {
+		assert!(Pallet::<T>::pending_dkg_protocol() == DKGProtocol::WTFrost);
+	}
+
+	set_key_refresh_enabled {
+	}: _(RawOrigin::Root, true)
+	verify {
+		assert!(Pallet::<T>::key_refresh_enabled());
+	}
+
+	set_key_resharing_enabled {
+	}: _(RawOrigin::Root, true)
+	verify {
+		assert!(Pallet::<T>::key_resharing_enabled());
+	}
+
+	set_misbehaviour_evidence_required {
+	}: _(RawOrigin::Root, true)
+	verify {
+		assert!(Pallet::<T>::misbehaviour_evidence_required());
+	}
 }
 
 impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(vec![1, 2, 3, 4]), crate::mock::Test);
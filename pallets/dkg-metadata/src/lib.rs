@@ -65,6 +65,8 @@
 //!   is initiated.
 //! - `submit_misbehaviour_reports`: Allows authorities to submit misbehaviour reports. Once the
 //!   `threshold` number of reports is submitted, the offending authority will lose reputation.
+//! - `set_misbehaviour_evidence_required`: Allows a root-origin to reject the misbehaviour reports
+//!   which carry no evidence.
 //! - `submit_public_key`: Allows submitting of the genesis public key by the initial authorities of
 //!   the DKG protocol.
 //! - `submit_next_public_key`: Allows submitting of the next public key by the next authorities of
@@ -82,6 +84,12 @@
 //! deterministic signing set for the threshold signing protocols. The signing set is taken to
 //! initially be the top `t` DKG authorities by reputation.
 //!
+//! The reports may carry evidence of the misbehaviour, signed by the offender, which the runtime
+//! checks. Two different votes signed by the offender for the same signing round prove an
+//! equivocation on their own, so a single reporter suffices. Once the evidence is required, a
+//! colluding `threshold` of reporters can no longer jail an authority with reports alone, and only
+//! the equivocations are punished.
+//!
 //! ## Related Modules
 //!
 //! * [`System`](https://github.com/paritytech/substrate/tree/master/frame/system)
@@ -91,8 +99,10 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::Encode;
+use codec::{Decode, Encode};
 use dkg_runtime_primitives::{
+	gossip_messages::{DKGMessage, NetworkMsgPayload},
+	merkle,
 	offchain::storage_keys::{
 		AGGREGATED_MISBEHAVIOUR_REPORTS, AGGREGATED_MISBEHAVIOUR_REPORTS_LOCK,
 		AGGREGATED_PUBLIC_KEYS, AGGREGATED_PUBLIC_KEYS_AT_GENESIS,
@@ -103,8 +113,8 @@ use dkg_runtime_primitives::{
 	traits::{GetDKGPublicKey, OnAuthoritySetChangeHandler},
 	utils::{ecdsa, to_slice_33, verify_signer_from_set_ecdsa},
 	AggregatedMisbehaviourReports, AggregatedPublicKeys, AuthorityIndex, AuthoritySet,
-	ConsensusLog, MisbehaviourType, ProposalHandlerTrait, ProposalNonce, RefreshProposal,
-	DKG_ENGINE_ID,
+	ConsensusLog, DKGKeyOrigin, DKGProtocol, MisbehaviourEvidence, MisbehaviourType,
+	ProposalHandlerTrait, ProposalNonce, RefreshProposal, SignedRoundMessage, DKG_ENGINE_ID,
 };
 use frame_support::{
 	dispatch::DispatchResultWithPostInfo,
@@ -535,6 +545,48 @@ pub mod pallet {
 	#[pallet::getter(fn pending_keygen_threshold)]
 	pub(super) type PendingKeygenThreshold<T: Config> = StorageValue<_, u16, ValueQuery>;
 
+	/// The DKG protocol run by the current authority set
+	#[pallet::storage]
+	#[pallet::getter(fn dkg_protocol)]
+	pub(super) type ActiveDKGProtocol<T: Config> = StorageValue<_, DKGProtocol, ValueQuery>;
+
+	/// The DKG protocol run by the next authority set
+	#[pallet::storage]
+	#[pallet::getter(fn next_dkg_protocol)]
+	pub(super) type NextDKGProtocol<T: Config> = StorageValue<_, DKGProtocol, ValueQuery>;
+
+	/// The DKG protocol that will be run by the authority set following the next one
+	#[pallet::storage]
+	#[pallet::getter(fn pending_dkg_protocol)]
+	pub(super) type PendingDKGProtocol<T: Config> = StorageValue<_, DKGProtocol, ValueQuery>;
+
+	/// Whether an unchanged WT-FROST authority set refreshes the shares of the active key
+	/// instead of generating a new key
+	#[pallet::storage]
+	#[pallet::getter(fn key_refresh_enabled)]
+	pub(super) type KeyRefreshEnabled<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Whether the active key is reshared to a changed WT-FROST keygen set instead of the next
+	/// keygen set generating a new key
+	#[pallet::storage]
+	#[pallet::getter(fn key_resharing_enabled)]
+	pub(super) type KeyResharingEnabled<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Whether the misbehaviour reports must carry evidence of the misbehaviour
+	#[pallet::storage]
+	#[pallet::getter(fn misbehaviour_evidence_required)]
+	pub(super) type MisbehaviourEvidenceRequired<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// How the current DKG public key was obtained
+	#[pallet::storage]
+	#[pallet::getter(fn key_origin)]
+	pub(super) type KeyOrigin<T: Config> = StorageValue<_, DKGKeyOrigin, ValueQuery>;
+
+	/// How the next DKG public key was obtained
+	#[pallet::storage]
+	#[pallet::getter(fn next_key_origin)]
+	pub(super) type NextKeyOrigin<T: Config> = StorageValue<_, DKGKeyOrigin, OptionQuery>;
+
 	/// The current authorities set
 	#[pallet::storage]
 	#[pallet::getter(fn authorities)]
@@ -696,6 +748,12 @@ pub mod pallet {
 		AlreadyJailed,
 		/// We do not have authorities to jail
 		NotEnoughAuthoritiesToJail,
+		/// The evidence of the misbehaviour reports is invalid
+		InvalidMisbehaviourEvidence,
+		/// The misbehaviour reports must carry evidence
+		MisbehaviourEvidenceRequired,
+		/// The next public key is the current key, which is neither refreshed nor reshared
+		UnchangedPublicKey,
 	}
 
 	// Pallets use events to inform users when important changes are made.
@@ -723,6 +781,8 @@ pub mod pallet {
 		},
 		/// Current Public Key Changed.
 		PublicKeyChanged { compressed_pub_key: Vec<u8> },
+		/// The authority set rotated with refreshed key shares of the current public key.
+		PublicKeyRetained { compressed_pub_key: Vec<u8> },
 		/// Current Public Key Signature Changed.
 		PublicKeySignatureChanged {
 			/// The merkle root of the voters (validators)
@@ -756,6 +816,16 @@ pub mod pallet {
 		PendingKeygenThresholdUpdated { pending_keygen_threshold: u16 },
 		/// PendingSignatureThreshold updated
 		PendingSignatureThresholdUpdated { pending_signature_threshold: u16 },
+		/// NextDKGProtocol updated
+		NextDKGProtocolUpdated { next_dkg_protocol: DKGProtocol },
+		/// PendingDKGProtocol updated
+		PendingDKGProtocolUpdated { pending_dkg_protocol: DKGProtocol },
+		/// KeyRefreshEnabled updated
+		KeyRefreshModeUpdated { enabled: bool },
+		/// KeyResharingEnabled updated
+		KeyResharingModeUpdated { enabled: bool },
+		/// MisbehaviourEvidenceRequired updated
+		MisbehaviourEvidenceModeUpdated { required: bool },
 		/// An Emergency Keygen Protocol was triggered.
 		EmergencyKeygenTriggered,
 		/// An authority has been jailed for misbehaviour
@@ -934,7 +1004,8 @@ pub mod pallet {
 		///
 		/// Can only be submitted by the next authorities. It is also required that a
 		/// `NextSignatureThreshold` of submissions is reached in order to successfully
-		/// store the public key on-chain.
+		/// store the public key on-chain. The current key is only accepted if the next authorities
+		/// refresh or reshare its shares.
 		///
 		/// * `origin` - The account origin.
 		/// * `keys_and_signatures` - The aggregated public keys and signatures for possible next
@@ -953,6 +1024,12 @@ pub mod pallet {
 				Self::next_best_authorities().iter().map(|id| id.1.clone()).collect();
 			let dict = Self::process_public_key_submissions(keys_and_signatures, next_authorities)?;
 			let threshold = Self::next_signature_threshold();
+			// The next authorities only hold shares of the current key if they refresh or reshare
+			// it, otherwise they must generate a new key.
+			let current_key = Self::dkg_public_key().1;
+			let key_may_be_retained =
+				Self::should_refresh_key_shares() || Self::should_reshare_key();
+			let mut unchanged_key_rejected = false;
 
 			// Loop through the keys, and if we find one that has enough signatures, store it.
 			//
@@ -962,9 +1039,14 @@ pub mod pallet {
 			let accepted_key = loop {
 				if let Some((key, accounts)) = keys.next() {
 					if accounts.len() >= threshold.into() {
+						if !key_may_be_retained && key[..] == current_key[..] {
+							unchanged_key_rejected = true;
+							continue
+						}
 						let bounded_key: BoundedVec<_, _> =
 							key.clone().try_into().map_err(|_| Error::<T>::OutOfBounds)?;
 						NextDKGPublicKey::<T>::put((Self::next_authority_set_id(), bounded_key));
+						NextKeyOrigin::<T>::put(Self::origin_of_next_key(key));
 						Self::deposit_event(Event::NextPublicKeySubmitted {
 							compressed_pub_key: key.clone(),
 						});
@@ -989,6 +1071,8 @@ pub mod pallet {
 					set_id,
 				);
 				Ok(().into())
+			} else if unchanged_key_rejected {
+				Err(Error::<T>::UnchangedPublicKey.into())
 			} else {
 				Err(Error::<T>::InvalidPublicKeys.into())
 			}
@@ -1009,6 +1093,9 @@ pub mod pallet {
 		/// session, then we deduct the pending keygen threshold (and pending signing threshold)
 		/// accordingly.
 		///
+		/// If the reports carry evidence which proves the misbehaviour on its own, a single valid
+		/// reporter is enough. Invalid evidence rejects the reports.
+		///
 		/// * `origin` - The account origin.
 		/// * `reports` - The aggregated misbehaviour reports containing signatures of an offending
 		///   authority
@@ -1047,6 +1134,12 @@ pub mod pallet {
 			// sanity check, is the offender an authority?
 			ensure!(authorities.contains(&offender), Error::<T>::OffenderNotAuthority);
 
+			let conclusive_evidence = Self::verify_misbehaviour_evidence(&reports)?;
+			ensure!(
+				conclusive_evidence || !Self::misbehaviour_evidence_required(),
+				Error::<T>::MisbehaviourEvidenceRequired
+			);
+
 			let valid_reporters = Self::process_misbehaviour_reports(reports, authorities.into());
 			// Get the threshold for the misbehaviour type
 			let signature_threshold = match misbehaviour_type {
@@ -1056,7 +1149,9 @@ pub mod pallet {
 				MisbehaviourType::Sign => Self::signature_threshold(),
 			};
 
-			if valid_reporters.len() > signature_threshold.into() {
+			if (conclusive_evidence && !valid_reporters.is_empty()) ||
+				valid_reporters.len() > signature_threshold.into()
+			{
 				// Deduct one point for misbehaviour report
 				let reputation = AuthorityReputations::<T>::get(&offender);
 				// Compute reputation impact and apply to the offender
@@ -1361,6 +1456,7 @@ pub mod pallet {
 			// Clear the next public key, if any, to ensure that the keygen protocol runs and we
 			// do not have any invalid state.
 			NextDKGPublicKey::<T>::kill();
+			NextKeyOrigin::<T>::kill();
 			// Clear the next public key signature, if any.
 			NextPublicKeySignature::<T>::kill();
 			// Emit `EmergencyKeygenTriggered` RuntimeEvent so that we can see it on monitoring.
@@ -1369,6 +1465,88 @@ pub mod pallet {
 			<ShouldExecuteNewKeygen<T>>::put((true, true));
 			Ok(().into())
 		}
+
+		/// Set the pending DKG protocol for the session following the next session.
+		///
+		/// As with the thresholds, the next authority set may have already started its keygen
+		/// with the next protocol, so the new protocol only becomes the next protocol after the
+		/// next session update, and the active protocol once that authority set rotates in.
+		///
+		/// * `origin` - The account origin.
+		/// * `protocol` - The DKG protocol to be run by the authorities.
+		#[pallet::weight(<T as Config>::WeightInfo::set_dkg_protocol())]
+		#[pallet::call_index(11)]
+		pub fn set_dkg_protocol(
+			origin: OriginFor<T>,
+			protocol: DKGProtocol,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+			PendingDKGProtocol::<T>::put(protocol);
+			Self::deposit_event(Event::PendingDKGProtocolUpdated {
+				pending_dkg_protocol: protocol,
+			});
+			Ok(().into())
+		}
+
+		/// Enable or disable the proactive refresh of the key shares.
+		///
+		/// When enabled, a WT-FROST authority set which is unchanged across a session rotation
+		/// refreshes the shares of the active key instead of generating a new one. The public
+		/// key is kept, so no refresh proposal is created for the rotation.
+		///
+		/// * `origin` - The account origin.
+		/// * `enabled` - Whether the key shares should be refreshed when possible.
+		#[pallet::weight(<T as Config>::WeightInfo::set_key_refresh_enabled())]
+		#[pallet::call_index(12)]
+		pub fn set_key_refresh_enabled(
+			origin: OriginFor<T>,
+			enabled: bool,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+			KeyRefreshEnabled::<T>::put(enabled);
+			Self::deposit_event(Event::KeyRefreshModeUpdated { enabled });
+			Ok(().into())
+		}
+
+		/// Enable or disable the resharing of the key to a new keygen set.
+		///
+		/// When enabled, the WT-FROST keygen set of the active authorities hands off the shares
+		/// of the active key to a changed next keygen set, instead of the next keygen set
+		/// generating a new key. The public key is kept, so no refresh proposal is created for
+		/// the rotation.
+		///
+		/// * `origin` - The account origin.
+		/// * `enabled` - Whether the key should be reshared when possible.
+		#[pallet::weight(<T as Config>::WeightInfo::set_key_resharing_enabled())]
+		#[pallet::call_index(13)]
+		pub fn set_key_resharing_enabled(
+			origin: OriginFor<T>,
+			enabled: bool,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+			KeyResharingEnabled::<T>::put(enabled);
+			Self::deposit_event(Event::KeyResharingModeUpdated { enabled });
+			Ok(().into())
+		}
+
+		/// Require or not the misbehaviour reports to carry evidence of the misbehaviour.
+		///
+		/// When required, the reports without evidence are rejected, whatever the number of
+		/// reporters.
+		///
+		/// * `origin` - The account origin.
+		/// * `required` - Whether the misbehaviour reports must carry evidence.
+		#[pallet::weight(<T as Config>::WeightInfo::set_misbehaviour_evidence_required())]
+		#[pallet::call_index(14)]
+		pub fn set_misbehaviour_evidence_required(
+			origin: OriginFor<T>,
+			required: bool,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+			MisbehaviourEvidenceRequired::<T>::put(required);
+			Self::deposit_event(Event::MisbehaviourEvidenceModeUpdated { required });
+			Ok(().into())
+		}
 	}
 
 	#[pallet::validate_unsigned]
@@ -1596,62 +1774,30 @@ impl<T: Config> Pallet<T> {
 	///
 	/// It is expected that the size of the returned vector is a power of 2.
 	pub fn pre_process_for_merkleize(voters: &[T::DKGId]) -> Vec<[u8; 32]> {
-		let height = Self::get_voter_set_tree_height(voters.len());
 		// Hash the external accounts into 32 byte chunks to form the base layer of the merkle tree
-		let mut base_layer: Vec<[u8; 32]> = voters
+		let base_layer: Vec<[u8; 32]> = voters
 			.iter()
 			.map(|account| account.to_raw_vec())
 			.map(|account| keccak_256(&account))
 			.collect();
 		// Pad base_layer to have length 2^height
-		let two = 2;
-		while base_layer.len() != two.saturating_pow(height.try_into().unwrap_or_default()) {
-			base_layer.push(keccak_256(&[0u8]));
-		}
-		base_layer
+		merkle::pad_leaves(base_layer)
 	}
 
 	/// Computes the next layer of the merkle tree by hashing the previous layer.
 	pub fn next_layer(curr_layer: Vec<[u8; 32]>) -> Vec<[u8; 32]> {
-		let mut layer_above: Vec<[u8; 32]> = Vec::new();
-		let mut index = 0;
-		while index < curr_layer.len() {
-			let mut input_to_hash_as_vec: Vec<u8> = curr_layer[index].to_vec();
-			input_to_hash_as_vec.extend_from_slice(&curr_layer[index + 1][..]);
-			let input_to_hash_as_slice = &input_to_hash_as_vec[..];
-			layer_above.push(keccak_256(input_to_hash_as_slice));
-			index += 2;
-		}
-		layer_above
+		merkle::next_layer(curr_layer)
 	}
 
 	// Returns the minimal height of the voter set Merkle tree
-	// Right now this takes O(log(size of voter set)) time but can likely be reduced
 	pub fn get_voter_set_tree_height(voter_count: usize) -> u32 {
-		if voter_count == 1 {
-			1
-		} else {
-			let two: u32 = 2;
-			let mut h = 0;
-			while two.saturating_pow(h) < voter_count as u32 {
-				h += 1;
-			}
-			h
-		}
+		merkle::tree_height(voter_count)
 	}
 
 	/// Computes the merkle root of the voter set tree
 	pub fn get_voter_set_tree_root(voters: &[T::DKGId]) -> [u8; 32] {
-		let mut curr_layer = Self::pre_process_for_merkleize(voters);
-		let mut height = Self::get_voter_set_tree_height(voters.len());
-		while height > 0 {
-			curr_layer = Self::next_layer(curr_layer);
-			height -= 1;
-		}
-
-		let mut root = [0u8; 32];
-		root.copy_from_slice(&curr_layer[0][..]);
-		root
+		let leaves = voters.iter().map(|account| keccak_256(&account.to_raw_vec())).collect();
+		merkle::merkle_root(leaves)
 	}
 
 	pub fn process_public_key_submissions(
@@ -1726,6 +1872,59 @@ impl<T: Config> Pallet<T> {
 		valid_reporters
 	}
 
+	/// Checks the evidence of the misbehaviour reports against the signatures of the offender.
+	///
+	/// Returns whether the reports carry evidence, which proves the misbehaviour on its own.
+	pub fn verify_misbehaviour_evidence(
+		reports: &AggregatedMisbehaviourReports<T::DKGId, T::MaxSignatureLength, T::MaxReporters>,
+	) -> Result<bool, Error<T>> {
+		let Some(MisbehaviourEvidence::Equivocation { first, second }) = reports.evidence.as_ref()
+		else {
+			return Ok(false)
+		};
+
+		let (first, second) = (
+			Self::offender_round_message(reports, first)?,
+			Self::offender_round_message(reports, second)?,
+		);
+		// a party votes once per signing round
+		let equivocates = match (&first.payload, &second.payload) {
+			(NetworkMsgPayload::Vote(first_vote), NetworkMsgPayload::Vote(second_vote)) =>
+				first.associated_block_id == second.associated_block_id &&
+					first.ssid == second.ssid &&
+					first_vote.party_ind == second_vote.party_ind &&
+					first_vote.round_key == second_vote.round_key &&
+					first_vote.unsigned_proposal_hash == second_vote.unsigned_proposal_hash &&
+					first_vote.partial_signature != second_vote.partial_signature,
+			_ => false,
+		};
+		ensure!(
+			reports.misbehaviour_type == MisbehaviourType::Sign && equivocates,
+			Error::<T>::InvalidMisbehaviourEvidence
+		);
+		Ok(true)
+	}
+
+	/// Decodes a round message of the reported session, signed by the offender.
+	fn offender_round_message(
+		reports: &AggregatedMisbehaviourReports<T::DKGId, T::MaxSignatureLength, T::MaxReporters>,
+		signed: &SignedRoundMessage<T::MaxSignatureLength>,
+	) -> Result<DKGMessage<T::DKGId>, Error<T>> {
+		let (_, success) = verify_signer_from_set_ecdsa(
+			vec![reports.offender.clone().into()],
+			&signed.message,
+			&signed.signature,
+		);
+		ensure!(success, Error::<T>::InvalidMisbehaviourEvidence);
+		let message = DKGMessage::<T::DKGId>::decode(&mut signed.message.as_slice())
+			.map_err(|_| Error::<T>::InvalidMisbehaviourEvidence)?;
+		ensure!(
+			message.sender_id == reports.offender && message.session_id == reports.session_id,
+			Error::<T>::InvalidMisbehaviourEvidence
+		);
+		Ok(message)
+	}
+
 	pub fn store_consensus_log(
 		authority_ids: BoundedVec<T::DKGId, T::MaxAuthorities>,
 		next_authority_ids: BoundedVec<T::DKGId, T::MaxAuthorities>,
@@ -1773,6 +1972,9 @@ impl<T: Config> Pallet<T> {
 		let new_current_keygen_threshold = NextKeygenThreshold::<T>::get();
 		Self::update_next_signature_threshold(PendingSignatureThreshold::<T>::get());
 		Self::update_next_keygen_threshold(PendingKeygenThreshold::<T>::get());
+		// Update the next protocol for the next session
+		let new_current_dkg_protocol = NextDKGProtocol::<T>::get();
+		Self::update_next_dkg_protocol(PendingDKGProtocol::<T>::get());
 		// Compute next ID for next authorities
 		let next_id = Self::next_authority_set_id();
 		// We continue to rotate the next authority set in case of failure of the previous
@@ -1790,9 +1992,16 @@ impl<T: Config> Pallet<T> {
 		let new_best_authorities = Self::next_best_authorities();
 		// Update the keys for the next authorities
 		let next_pub_key = Self::next_dkg_public_key();
-		let next_pub_key_signature = Self::next_public_key_signature();
 		let dkg_pub_key = Self::dkg_public_key();
 		let pub_key_signature = Self::public_key_signature();
+		// A refreshed key is the current key, which is already signed
+		let key_retained =
+			next_pub_key.as_ref().map_or(false, |(_, next_key)| *next_key == dkg_pub_key.1);
+		let next_pub_key_signature = if key_retained {
+			Some(pub_key_signature.clone())
+		} else {
+			Self::next_public_key_signature()
+		};
 		// Ensure next/pending thresholds remain valid across authority set changes that may
 		// break. We update the pending thresholds because we call `refresh_keys` below, which
 		// rotates all the thresholds into the current / next sets. Pending becomes the next,
@@ -1823,6 +2032,8 @@ impl<T: Config> Pallet<T> {
 			// Update the active thresholds for the next session
 			SignatureThreshold::<T>::put(new_current_signature_threshold);
 			KeygenThreshold::<T>::put(new_current_keygen_threshold);
+			// The next public key was generated with the next protocol, which now becomes active
+			ActiveDKGProtocol::<T>::put(new_current_dkg_protocol);
 			// Update the new and next authorities
 			let bounded_authority_ids: BoundedVec<_, _> = new_authority_ids
 				.try_into()
@@ -1850,6 +2061,15 @@ impl<T: Config> Pallet<T> {
 			DKGPublicKey::<T>::put(next_pub_key.clone());
 			DKGPublicKeySignature::<T>::put(next_pub_key_signature.clone());
 			PreviousPublicKey::<T>::put(dkg_pub_key);
+			KeyOrigin::<T>::put(NextKeyOrigin::<T>::take().unwrap_or_default());
+			if key_retained {
+				// The key and its signature remain in use, and no refresh proposal was signed
+				Self::deposit_event(Event::PublicKeyRetained {
+					compressed_pub_key: next_pub_key.1.into(),
+				});
+				return
+			}
+
 			let _ = UsedSignatures::<T>::try_mutate(|val| {
 				let added = val.try_push(pub_key_signature.clone());
 				if added.is_err() {
@@ -2152,10 +2372,54 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	pub fn update_next_dkg_protocol(next_protocol: DKGProtocol) {
+		if Self::next_dkg_protocol() != next_protocol {
+			NextDKGProtocol::<T>::put(next_protocol);
+			Self::deposit_event(Event::NextDKGProtocolUpdated { next_dkg_protocol: next_protocol });
+		}
+	}
+
 	pub fn should_refresh(_now: T::BlockNumber) -> bool {
 		let next_dkg_public_key = Self::next_dkg_public_key();
 		let next_dkg_public_key_signature = Self::next_public_key_signature();
-		next_dkg_public_key.is_some() && next_dkg_public_key_signature.is_none()
+		// A refreshed key is unchanged, so there is nothing for the bridges to rotate to
+		next_dkg_public_key.map_or(false, |(_, key)| key != Self::dkg_public_key().1) &&
+			next_dkg_public_key_signature.is_none()
+	}
+
+	/// Whether the keygen set of the active authorities should reshare the active key to the
+	/// changed keygen set of the next authorities, instead of the latter generating a new key.
+	pub fn should_reshare_key() -> bool {
+		Self::key_resharing_enabled() &&
+			Self::dkg_protocol() == DKGProtocol::WTFrost &&
+			Self::next_dkg_protocol() == DKGProtocol::WTFrost &&
+			!Self::dkg_public_key().1.is_empty() &&
+			Self::next_best_authorities() != Self::best_authorities()
+	}
+
+	/// How a submitted next key was obtained. The key is only unchanged if the shares of the
+	/// current key were refreshed or reshared.
+	fn origin_of_next_key(next_key: &[u8]) -> DKGKeyOrigin {
+		if next_key != &Self::dkg_public_key().1[..] {
+			DKGKeyOrigin::Generated
+		} else if Self::next_best_authorities() == Self::best_authorities() {
+			DKGKeyOrigin::Refreshed
+		} else {
+			DKGKeyOrigin::Reshared
+		}
+	}
+
+	/// Whether the next authority set should refresh the shares of the active key instead of
+	/// generating a new key. This requires the same WT-FROST keygen set and thresholds as the
+	/// active authority set.
+	pub fn should_refresh_key_shares() -> bool {
+		Self::key_refresh_enabled() &&
+			Self::dkg_protocol() == DKGProtocol::WTFrost &&
+			Self::next_dkg_protocol() == DKGProtocol::WTFrost &&
+			!Self::dkg_public_key().1.is_empty() &&
+			Self::next_best_authorities() == Self::best_authorities() &&
+			Self::next_signature_threshold() == Self::signature_threshold() &&
+			Self::next_keygen_threshold() == Self::keygen_threshold()
 	}
 
 	/// Inserts a successful rotation into the history
@@ -2312,6 +2576,10 @@ impl<T: Config> GetDKGPublicKey for Pallet<T> {
 	fn previous_dkg_key() -> Vec<u8> {
 		Self::previous_public_key().1.into()
 	}
+
+	fn dkg_protocol() -> DKGProtocol {
+		Self::dkg_protocol()
+	}
 }
 
 /// Periodic Session manager for DKGMetadata
@@ -15,12 +15,18 @@
 use std::vec;
 
 use crate::{
-	mock::*, AggregatedMisbehaviourReports, AuthorityReputations, Config, Error, Event,
-	JailedKeygenAuthorities, NextAuthorities, NextBestAuthorities, NextKeygenThreshold,
-	NextSignatureThreshold,
+	mock::*, AggregatedMisbehaviourReports, Authorities, AuthorityReputations, BestAuthorities,
+	Config, Error, Event, JailedKeygenAuthorities, JailedSigningAuthorities, NextAuthorities,
+	NextBestAuthorities, NextKeygenThreshold, NextSignatureThreshold, SignatureThreshold,
 };
 use codec::Encode;
-use dkg_runtime_primitives::{keccak_256, utils::ecdsa, MisbehaviourType, KEY_TYPE};
+use dkg_runtime_primitives::{
+	gossip_messages::{DKGMessage, DKGVoteMessage, NetworkMsgPayload},
+	keccak_256,
+	utils::ecdsa,
+	AggregatedPublicKeys, DKGKeyOrigin, DKGProtocol, MisbehaviourEvidence, MisbehaviourType,
+	SignedRoundMessage, KEY_TYPE,
+};
 use frame_support::{assert_noop, assert_ok, traits::Hooks, weights::Weight, BoundedVec};
 use sp_core::ByteArray;
 use sp_io::crypto::{ecdsa_generate, ecdsa_sign_prehashed};
@@ -53,6 +59,33 @@ fn mock_misbehaviour_report<T: Config>(
 	signature.encode().try_into().unwrap()
 }
 
+fn mock_signed_vote(
+	signer: ecdsa::Public,
+	sender_id: DKGId,
+	partial_signature: Vec<u8>,
+) -> SignedRoundMessage<MaxSignatureLength> {
+	let message = DKGMessage {
+		sender_id,
+		recipient_id: None,
+		payload: NetworkMsgPayload::Vote(DKGVoteMessage {
+			party_ind: 1,
+			round_key: vec![1u8; 32],
+			partial_signature,
+			unsigned_proposal_hash: [2u8; 32],
+		}),
+		session_id: 1,
+		associated_block_id: 10,
+		ssid: 0,
+	}
+	.encode();
+	let signature = ecdsa_sign_prehashed(KEY_TYPE, &signer, &keccak_256(&message)).unwrap();
+
+	SignedRoundMessage {
+		message: message.try_into().unwrap(),
+		signature: signature.encode().try_into().unwrap(),
+	}
+}
+
 fn mock_pub_key() -> ecdsa::Public {
 	ecdsa_generate(KEY_TYPE, None)
 }
@@ -186,6 +219,236 @@ fn refresh_nonce_should_increment_by_one() {
 	});
 }
 
+#[test]
+fn set_dkg_protocol_requires_force_origin() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		assert_noop!(
+			DKGMetadata::set_dkg_protocol(
+				RuntimeOrigin::signed(crate::mock::mock_pub_key(1)),
+				DKGProtocol::WTFrost
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_eq!(DKGMetadata::pending_dkg_protocol(), DKGProtocol::MpEcdsa);
+	});
+}
+
+#[test]
+fn dkg_protocol_rotates_with_authority_sets() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+		assert_eq!(DKGMetadata::dkg_protocol(), DKGProtocol::MpEcdsa);
+		assert_ok!(DKGMetadata::set_dkg_protocol(RuntimeOrigin::root(), DKGProtocol::WTFrost));
+		assert_last_event::<Test>(
+			Event::PendingDKGProtocolUpdated { pending_dkg_protocol: DKGProtocol::WTFrost }.into(),
+		);
+		assert_eq!(DKGMetadata::pending_dkg_protocol(), DKGProtocol::WTFrost);
+		assert_eq!(DKGMetadata::next_dkg_protocol(), DKGProtocol::MpEcdsa);
+
+		// the next authority set may already be running keygen, so the new protocol only
+		// becomes the next protocol after the session change
+		let input: BoundedVec<_, _> = mock_dkg_id(1).to_raw_vec().try_into().unwrap();
+		crate::pallet::NextDKGPublicKey::<Test>::put((1, input));
+		let next_pub_key_signature: BoundedVec<_, _> = vec![1u8; 64].try_into().unwrap();
+		crate::pallet::NextPublicKeySignature::<Test>::put(next_pub_key_signature);
+		init_block(2);
+		assert_has_event::<Test>(
+			Event::NextDKGProtocolUpdated { next_dkg_protocol: DKGProtocol::WTFrost }.into(),
+		);
+		assert_eq!(DKGMetadata::next_dkg_protocol(), DKGProtocol::WTFrost);
+		assert_eq!(DKGMetadata::dkg_protocol(), DKGProtocol::MpEcdsa);
+
+		// once the authority set that ran the new protocol rotates in, it becomes active
+		let input: BoundedVec<_, _> = mock_dkg_id(2).to_raw_vec().try_into().unwrap();
+		crate::pallet::NextDKGPublicKey::<Test>::put((2, input));
+		let next_pub_key_signature: BoundedVec<_, _> = vec![2u8; 64].try_into().unwrap();
+		crate::pallet::NextPublicKeySignature::<Test>::put(next_pub_key_signature);
+		init_block(3);
+		assert_eq!(DKGMetadata::dkg_protocol(), DKGProtocol::WTFrost);
+	});
+}
+
+#[test]
+fn key_shares_are_only_refreshed_for_an_unchanged_frost_set() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+		let input: BoundedVec<_, _> = mock_dkg_id(1).to_raw_vec().try_into().unwrap();
+		crate::pallet::DKGPublicKey::<Test>::put((0, input));
+		crate::pallet::ActiveDKGProtocol::<Test>::put(DKGProtocol::WTFrost);
+		crate::pallet::NextDKGProtocol::<Test>::put(DKGProtocol::WTFrost);
+		assert!(!DKGMetadata::should_refresh_key_shares());
+
+		assert_noop!(
+			DKGMetadata::set_key_refresh_enabled(
+				RuntimeOrigin::signed(crate::mock::mock_pub_key(1)),
+				true
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(DKGMetadata::set_key_refresh_enabled(RuntimeOrigin::root(), true));
+		assert_last_event::<Test>(Event::KeyRefreshModeUpdated { enabled: true }.into());
+		crate::pallet::BestAuthorities::<Test>::put(DKGMetadata::next_best_authorities());
+		assert!(DKGMetadata::should_refresh_key_shares());
+
+		// a new key is required when the keygen set changes
+		crate::pallet::BestAuthorities::<Test>::kill();
+		assert!(!DKGMetadata::should_refresh_key_shares());
+		crate::pallet::BestAuthorities::<Test>::put(DKGMetadata::next_best_authorities());
+		crate::pallet::NextDKGProtocol::<Test>::put(DKGProtocol::MpEcdsa);
+		assert!(!DKGMetadata::should_refresh_key_shares());
+	});
+}
+
+#[test]
+fn refreshed_key_rotates_without_refresh_proposal() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+		let input: BoundedVec<_, _> = mock_dkg_id(1).to_raw_vec().try_into().unwrap();
+		let signature: BoundedVec<_, _> = vec![1u8; 64].try_into().unwrap();
+		crate::pallet::DKGPublicKey::<Test>::put((0, input.clone()));
+		crate::pallet::DKGPublicKeySignature::<Test>::put(signature.clone());
+		crate::pallet::NextDKGPublicKey::<Test>::put((1, input.clone()));
+		assert!(!DKGMetadata::should_refresh(1));
+
+		init_block(2);
+		assert!(crate::pallet::CurrentRefreshProposal::<Test>::get().is_none());
+		assert_has_event::<Test>(
+			Event::PublicKeyRetained { compressed_pub_key: input.clone().into() }.into(),
+		);
+		assert_eq!(DKGMetadata::dkg_public_key(), (1, input));
+		assert_eq!(DKGMetadata::public_key_signature(), signature);
+		assert_eq!(DKGMetadata::authority_set_id(), 1);
+		assert_eq!(DKGMetadata::refresh_nonce(), 0);
+		assert!(DKGMetadata::next_dkg_public_key().is_none());
+	});
+}
+
+#[test]
+fn key_is_reshared_to_a_changed_frost_set() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+		let input: BoundedVec<_, _> = mock_dkg_id(1).to_raw_vec().try_into().unwrap();
+		crate::pallet::DKGPublicKey::<Test>::put((0, input.clone()));
+		crate::pallet::ActiveDKGProtocol::<Test>::put(DKGProtocol::WTFrost);
+		crate::pallet::NextDKGProtocol::<Test>::put(DKGProtocol::WTFrost);
+		crate::pallet::BestAuthorities::<Test>::kill();
+		assert!(!DKGMetadata::should_reshare_key());
+
+		assert_noop!(
+			DKGMetadata::set_key_resharing_enabled(
+				RuntimeOrigin::signed(crate::mock::mock_pub_key(1)),
+				true
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(DKGMetadata::set_key_resharing_enabled(RuntimeOrigin::root(), true));
+		assert_last_event::<Test>(Event::KeyResharingModeUpdated { enabled: true }.into());
+		assert!(DKGMetadata::should_reshare_key());
+		assert_eq!(DKGMetadata::origin_of_next_key(&input), DKGKeyOrigin::Reshared);
+		assert_eq!(
+			DKGMetadata::origin_of_next_key(&mock_dkg_id(2).to_raw_vec()),
+			DKGKeyOrigin::Generated
+		);
+
+		// an unchanged keygen set refreshes its shares instead
+		crate::pallet::BestAuthorities::<Test>::put(DKGMetadata::next_best_authorities());
+		assert!(!DKGMetadata::should_reshare_key());
+		assert_eq!(DKGMetadata::origin_of_next_key(&input), DKGKeyOrigin::Refreshed);
+	});
+}
+
+// sets up three next authorities with a signature threshold of 2, the active keygen set being
+// unchanged, and returns them
+fn setup_next_authorities() -> Vec<ecdsa::Public> {
+	let next_authorities_raw: Vec<_> = (0..3).map(|_| mock_pub_key()).collect();
+	let next_best_authorities: BoundedVec<_, _> = next_authorities_raw
+		.iter()
+		.enumerate()
+		.map(|(i, id)| (i as u16 + 1, DKGId::from(*id)))
+		.collect::<Vec<_>>()
+		.try_into()
+		.unwrap();
+	NextBestAuthorities::<Test>::put(next_best_authorities.clone());
+	BestAuthorities::<Test>::put(next_best_authorities);
+	NextSignatureThreshold::<Test>::put(2);
+	SignatureThreshold::<Test>::put(2);
+	crate::pallet::NextKeygenThreshold::<Test>::put(3);
+	crate::pallet::KeygenThreshold::<Test>::put(3);
+	next_authorities_raw
+}
+
+fn mock_signed_public_key(signers: &[ecdsa::Public], key: &[u8]) -> AggregatedPublicKeys {
+	let keys_and_signatures = signers
+		.iter()
+		.map(|signer| {
+			let signature = ecdsa_sign_prehashed(KEY_TYPE, signer, &keccak_256(key)).unwrap();
+			(key.to_vec(), signature.encode())
+		})
+		.collect();
+	AggregatedPublicKeys { keys_and_signatures }
+}
+
+#[test]
+fn unchanged_key_is_only_accepted_when_refreshed_or_reshared() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+		let next_authorities_raw = setup_next_authorities();
+		let input: BoundedVec<_, _> = mock_dkg_id(1).to_raw_vec().try_into().unwrap();
+		crate::pallet::DKGPublicKey::<Test>::put((0, input.clone()));
+		crate::pallet::ActiveDKGProtocol::<Test>::put(DKGProtocol::WTFrost);
+		crate::pallet::NextDKGProtocol::<Test>::put(DKGProtocol::WTFrost);
+		let keys = mock_signed_public_key(&next_authorities_raw, &input);
+
+		// the next authorities hold no shares of the current key
+		assert_noop!(
+			DKGMetadata::submit_next_public_key(RuntimeOrigin::none(), keys.clone()),
+			Error::<Test>::UnchangedPublicKey
+		);
+
+		// neither do they when the key is generated with MP-ECDSA
+		assert_ok!(DKGMetadata::set_key_refresh_enabled(RuntimeOrigin::root(), true));
+		assert_ok!(DKGMetadata::set_key_resharing_enabled(RuntimeOrigin::root(), true));
+		crate::pallet::NextDKGProtocol::<Test>::put(DKGProtocol::MpEcdsa);
+		assert_noop!(
+			DKGMetadata::submit_next_public_key(RuntimeOrigin::none(), keys.clone()),
+			Error::<Test>::UnchangedPublicKey
+		);
+
+		// a new key is accepted
+		let new_key = mock_dkg_id(2).to_raw_vec();
+		assert_ok!(DKGMetadata::submit_next_public_key(
+			RuntimeOrigin::none(),
+			mock_signed_public_key(&next_authorities_raw, &new_key)
+		));
+		assert_eq!(DKGMetadata::next_key_origin(), Some(DKGKeyOrigin::Generated));
+
+		// the refreshed key is accepted
+		crate::pallet::NextDKGPublicKey::<Test>::kill();
+		crate::pallet::NextDKGProtocol::<Test>::put(DKGProtocol::WTFrost);
+		assert!(DKGMetadata::should_refresh_key_shares());
+		assert_ok!(DKGMetadata::submit_next_public_key(RuntimeOrigin::none(), keys));
+		assert_eq!(DKGMetadata::next_key_origin(), Some(DKGKeyOrigin::Refreshed));
+	});
+}
+
+#[test]
+fn key_origin_rotates_with_the_key() {
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		init_block(1);
+		assert_eq!(DKGMetadata::key_origin(), DKGKeyOrigin::Generated);
+		let input: BoundedVec<_, _> = mock_dkg_id(1).to_raw_vec().try_into().unwrap();
+		let signature: BoundedVec<_, _> = vec![1u8; 64].try_into().unwrap();
+		crate::pallet::DKGPublicKey::<Test>::put((0, input.clone()));
+		crate::pallet::DKGPublicKeySignature::<Test>::put(signature);
+		crate::pallet::NextDKGPublicKey::<Test>::put((1, input));
+		crate::pallet::NextKeyOrigin::<Test>::put(DKGKeyOrigin::Reshared);
+
+		init_block(2);
+		assert_eq!(DKGMetadata::key_origin(), DKGKeyOrigin::Reshared);
+		assert!(DKGMetadata::next_key_origin().is_none());
+	});
+}
+
 #[test]
 fn misbehaviour_reports_submission_rejects_if_offender_not_authority() {
 	new_test_ext(vec![1, 2, 3, 4, 5]).execute_with(|| {
@@ -218,6 +481,7 @@ fn misbehaviour_reports_submission_rejects_if_offender_not_authority() {
 			offender,
 			reporters: reporters.clone().try_into().unwrap(),
 			signatures,
+			evidence: None,
 		};
 
 		assert_noop!(
@@ -295,6 +559,7 @@ fn keygen_misbehaviour_reports_work() {
 			offender: offender.clone(),
 			reporters: reporters.clone().try_into().unwrap(),
 			signatures,
+			evidence: None,
 		};
 
 		assert_ok!(DKGMetadata::submit_misbehaviour_reports(
@@ -419,6 +684,7 @@ fn keygen_misbehaviour_reports_reduce_signature_threshold_if_needed() {
 				offender: offender.clone(),
 				reporters: reporters.clone().try_into().unwrap(),
 				signatures,
+				evidence: None,
 			};
 
 			assert_ok!(DKGMetadata::submit_misbehaviour_reports(
@@ -534,6 +800,7 @@ fn keygen_misbehaviour_reports_fail_if_not_threshold_plus_1() {
 			offender,
 			reporters: reporters.clone().try_into().unwrap(),
 			signatures,
+			evidence: None,
 		};
 
 		// should not be accepted since we did not have t+1 signers
@@ -613,6 +880,7 @@ fn keygen_misbehaviour_reports_does_not_drop_threshold_if_authorities_available(
 			offender: offender.clone(),
 			reporters: reporters.clone().try_into().unwrap(),
 			signatures,
+			evidence: None,
 		};
 
 		assert_ok!(DKGMetadata::submit_misbehaviour_reports(
@@ -717,6 +985,7 @@ fn keygen_misbehaviour_reports_does_not_drop_threshold_below_2() {
 				offender: offender.clone(),
 				reporters: reporters.clone().try_into().unwrap(),
 				signatures,
+				evidence: None,
 			};
 
 			assert_ok!(DKGMetadata::submit_misbehaviour_reports(
@@ -794,6 +1063,7 @@ fn keygen_misbehaviour_reports_does_not_drop_threshold_below_2() {
 			offender,
 			reporters: reporters.clone().try_into().unwrap(),
 			signatures,
+			evidence: None,
 		};
 
 		assert_noop!(
@@ -805,3 +1075,133 @@ fn keygen_misbehaviour_reports_does_not_drop_threshold_below_2() {
 		);
 	});
 }
+
+// sets up five signing authorities, and returns them with the first one as the offender
+fn setup_signing_authorities() -> (Vec<ecdsa::Public>, DKGId) {
+	let mut authorities: BoundedVec<_, _> = Default::default();
+	let mut authorities_raw: Vec<_> = Default::default();
+	for _ in 1..=5 {
+		let authority_id = mock_pub_key();
+		authorities_raw.push(authority_id);
+		authorities.try_push(DKGId::from(authority_id)).unwrap();
+	}
+	Authorities::<Test>::put(&authorities);
+	SignatureThreshold::<Test>::put(3);
+	let best_authorities = DKGMetadata::get_best_authorities(5, &authorities);
+	BestAuthorities::<Test>::put(BoundedVec::<_, _>::try_from(best_authorities).unwrap());
+
+	let offender = authorities[0].clone();
+	(authorities_raw, offender)
+}
+
+fn mock_sign_misbehaviour_reports(
+	reporters_raw: &[ecdsa::Public],
+	offender: DKGId,
+	evidence: Option<MisbehaviourEvidence<MaxSignatureLength>>,
+) -> AggregatedMisbehaviourReports<DKGId, MaxSignatureLength, MaxReporters> {
+	let misbehaviour_type = MisbehaviourType::Sign;
+	let mut signatures: BoundedVec<_, _> = Default::default();
+	for reporter in reporters_raw {
+		let sig = mock_misbehaviour_report::<Test>(*reporter, offender.clone(), misbehaviour_type);
+		signatures.try_push(sig).unwrap();
+	}
+	let reporters: Vec<DKGId> = reporters_raw.iter().map(|id| DKGId::from(*id)).collect();
+
+	AggregatedMisbehaviourReports {
+		misbehaviour_type,
+		session_id: 1,
+		offender,
+		reporters: reporters.try_into().unwrap(),
+		signatures,
+		evidence,
+	}
+}
+
+#[test]
+fn vote_equivocation_evidence_jails_with_a_single_reporter() {
+	new_test_ext(vec![1, 2, 3, 4, 5]).execute_with(|| {
+		let (authorities_raw, offender) = setup_signing_authorities();
+		let evidence = MisbehaviourEvidence::Equivocation {
+			first: mock_signed_vote(authorities_raw[0], offender.clone(), vec![1u8; 32]),
+			second: mock_signed_vote(authorities_raw[0], offender.clone(), vec![3u8; 32]),
+		};
+		let reports = mock_sign_misbehaviour_reports(
+			&authorities_raw[1..2],
+			offender.clone(),
+			Some(evidence),
+		);
+
+		assert_ok!(DKGMetadata::submit_misbehaviour_reports(RuntimeOrigin::none(), reports));
+		assert!(JailedSigningAuthorities::<Test>::contains_key(&offender));
+	});
+}
+
+#[test]
+fn identical_votes_are_not_an_equivocation() {
+	new_test_ext(vec![1, 2, 3, 4, 5]).execute_with(|| {
+		let (authorities_raw, offender) = setup_signing_authorities();
+		let vote = mock_signed_vote(authorities_raw[0], offender.clone(), vec![1u8; 32]);
+		let evidence = MisbehaviourEvidence::Equivocation { first: vote.clone(), second: vote };
+		let reports =
+			mock_sign_misbehaviour_reports(&authorities_raw[1..], offender, Some(evidence));
+
+		assert_noop!(
+			DKGMetadata::submit_misbehaviour_reports(RuntimeOrigin::none(), reports),
+			Error::<Test>::InvalidMisbehaviourEvidence
+		);
+	});
+}
+
+#[test]
+fn misbehaviour_evidence_must_be_signed_by_the_offender() {
+	new_test_ext(vec![1, 2, 3, 4, 5]).execute_with(|| {
+		let (authorities_raw, offender) = setup_signing_authorities();
+		// a reporter forges the votes of the offender
+		let evidence = MisbehaviourEvidence::Equivocation {
+			first: mock_signed_vote(authorities_raw[1], offender.clone(), vec![1u8; 32]),
+			second: mock_signed_vote(authorities_raw[1], offender.clone(), vec![3u8; 32]),
+		};
+		let reports =
+			mock_sign_misbehaviour_reports(&authorities_raw[1..2], offender, Some(evidence));
+
+		assert_noop!(
+			DKGMetadata::submit_misbehaviour_reports(RuntimeOrigin::none(), reports),
+			Error::<Test>::InvalidMisbehaviourEvidence
+		);
+	});
+}
+
+#[test]
+fn misbehaviour_reports_without_evidence_are_rejected_when_required() {
+	new_test_ext(vec![1, 2, 3, 4, 5]).execute_with(|| {
+		let (authorities_raw, offender) = setup_signing_authorities();
+		assert_noop!(
+			DKGMetadata::set_misbehaviour_evidence_required(
+				RuntimeOrigin::signed(crate::mock::mock_pub_key(1)),
+				true
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(DKGMetadata::set_misbehaviour_evidence_required(RuntimeOrigin::root(), true));
+		assert_last_event::<Test>(Event::MisbehaviourEvidenceModeUpdated { required: true }.into());
+
+		let reports = mock_sign_misbehaviour_reports(&authorities_raw[1..], offender.clone(), None);
+		assert_noop!(
+			DKGMetadata::submit_misbehaviour_reports(RuntimeOrigin::none(), reports),
+			Error::<Test>::MisbehaviourEvidenceRequired
+		);
+
+		// an equivocation still jails the offender
+		let evidence = MisbehaviourEvidence::Equivocation {
+			first: mock_signed_vote(authorities_raw[0], offender.clone(), vec![1u8; 32]),
+			second: mock_signed_vote(authorities_raw[0], offender.clone(), vec![3u8; 32]),
+		};
+		let reports = mock_sign_misbehaviour_reports(
+			&authorities_raw[1..2],
+			offender.clone(),
+			Some(evidence),
+		);
+		assert_ok!(DKGMetadata::submit_misbehaviour_reports(RuntimeOrigin::none(), reports));
+		assert!(JailedSigningAuthorities::<Test>::contains_key(&offender));
+	});
+}
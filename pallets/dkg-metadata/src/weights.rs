@@ -39,6 +39,10 @@ pub trait WeightInfo {
 	fn unjail() -> Weight;
 	fn force_unjail_signing() -> Weight;
 	fn force_unjail_keygen() -> Weight;
+	fn set_dkg_protocol() -> Weight;
+	fn set_key_refresh_enabled() -> Weight;
+	fn set_key_resharing_enabled() -> Weight;
+	fn set_misbehaviour_evidence_required() -> Weight;
 }
 
 /// Weights for pallet_dkg_metadata using the Substrate node and recommended hardware.
@@ -184,6 +188,38 @@ impl<T: frame_system::Config> WeightInfo for WebbWeight<T> {
 		Weight::from_parts(10_000_000, 0)
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	/// Storage: DKG PendingDKGProtocol (r:0 w:1)
+	/// Proof: DKG PendingDKGProtocol (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_dkg_protocol() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKG KeyRefreshEnabled (r:0 w:1)
+	/// Proof: DKG KeyRefreshEnabled (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_key_refresh_enabled() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKG KeyResharingEnabled (r:0 w:1)
+	/// Proof: DKG KeyResharingEnabled (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_key_resharing_enabled() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKG MisbehaviourEvidenceRequired (r:0 w:1)
+	/// Proof: DKG MisbehaviourEvidenceRequired (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_misbehaviour_evidence_required() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
 }
 
 // For backwards compatibility and tests
@@ -328,4 +364,36 @@ impl WeightInfo for () {
 		Weight::from_parts(10_000_000, 0)
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
-}
\ No newline at end of file
+	/// Storage: DKG PendingDKGProtocol (r:0 w:1)
+	/// Proof: DKG PendingDKGProtocol (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_dkg_protocol() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKG KeyRefreshEnabled (r:0 w:1)
+	/// Proof: DKG KeyRefreshEnabled (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_key_refresh_enabled() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKG KeyResharingEnabled (r:0 w:1)
+	/// Proof: DKG KeyResharingEnabled (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_key_resharing_enabled() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKG MisbehaviourEvidenceRequired (r:0 w:1)
+	/// Proof: DKG MisbehaviourEvidenceRequired (max_values: Some(1), max_size: Some(1), added: 496, mode: MaxEncodedLen)
+	fn set_misbehaviour_evidence_required() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+}
@@ -17,7 +17,7 @@ use super::*;
 #[allow(unused)]
 use crate::Pallet;
 use codec::Encode;
-use dkg_runtime_primitives::KEY_TYPE;
+use dkg_runtime_primitives::{PriorityClass, KEY_TYPE};
 use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
 use frame_system::RawOrigin;
 use pallet_dkg_metadata::Pallet as DKGPallet;
@@ -116,6 +116,32 @@ benchmarks! {
 		assert!(Pallet::<T>::get_unsigned_proposals().len() == 0);
 	}
 
+	force_set_last_signed_nonce {
+		let resource_id = ResourceId::from([1u8; 32]);
+		let nonce = ProposalNonce::from(5u32);
+	}: _(RawOrigin::Root, resource_id, nonce)
+	verify {
+		assert_eq!(Pallet::<T>::last_signed_nonce(resource_id), Some(nonce));
+	}
+
+	set_batch_encoding {
+		let typed_chain_id = TypedChainId::Evm(1);
+	}: _(RawOrigin::Root, typed_chain_id, BatchEncoding::MerkleRoot)
+	verify {
+		assert_eq!(Pallet::<T>::batch_encoding(typed_chain_id), BatchEncoding::MerkleRoot);
+	}
+
+	set_chain_scheduling_config {
+		let typed_chain_id = TypedChainId::Evm(1);
+		let config = ChainSchedulingConfig {
+			priority: PriorityClass::Critical,
+			max_batches_in_flight: Some(2),
+		};
+	}: _(RawOrigin::Root, typed_chain_id, config)
+	verify {
+		assert_eq!(Pallet::<T>::chain_scheduling_config(typed_chain_id), config);
+	}
+
 }
 
 impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext_benchmarks(), crate::mock::Test,);
@@ -1,6 +1,11 @@
 use super::*;
-use dkg_runtime_primitives::handlers::decode_proposals::ProposalIdentifier;
-use sp_runtime::traits::{CheckedAdd, CheckedSub, One};
+use dkg_runtime_primitives::{
+	handlers::decode_proposals::{decode_proposal_resource_nonce, ProposalIdentifier},
+	utils::{ensure_signed_by_dkg, ensure_signed_by_key},
+	AuthoritySetId, DKGProtocol, MAX_SIGNED_PROPOSAL_SUBMISSIONS, SIGNED_PROPOSAL_RESUBMIT_DELAY,
+};
+use sp_runtime::traits::{CheckedAdd, CheckedSub, One, Saturating};
+use sp_std::{cmp::Reverse, collections::btree_map::BTreeMap};
 
 impl<T: Config> Pallet<T> {
 	// *** API methods ***
@@ -11,6 +16,32 @@ impl<T: Config> Pallet<T> {
 			.collect()
 	}
 
+	pub fn get_signed_proposal_batches() -> Vec<(TypedChainId, SignedProposalBatchOf<T>)> {
+		SignedProposals::<T>::iter()
+			.map(|(typed_chain_id, _batch_id, signed_proposal)| (typed_chain_id, signed_proposal))
+			.collect()
+	}
+
+	pub fn get_last_signed_nonces() -> Vec<(ResourceId, ProposalNonce)> {
+		LastSignedNonce::<T>::iter().collect()
+	}
+
+	pub fn get_chain_scheduling_configs() -> Vec<(TypedChainId, ChainSchedulingConfig)> {
+		ChainSchedulingConfigs::<T>::iter().collect()
+	}
+
+	/// Returns the number of batches of a chain waiting to be signed
+	pub(crate) fn batches_in_flight(typed_chain_id: TypedChainId) -> usize {
+		UnsignedProposalQueue::<T>::iter_prefix_values(typed_chain_id).count()
+	}
+
+	/// Whether a new batch can be created for a chain without exceeding its maximum number of
+	/// batches in flight. The refresh proposals are never limited.
+	pub(crate) fn can_create_batch(typed_chain_id: TypedChainId, in_flight: usize) -> bool {
+		typed_chain_id == TypedChainId::None ||
+			ChainSchedulingConfigs::<T>::get(typed_chain_id).allows_more_batches(in_flight)
+	}
+
 	/// Checks whether a signed proposal exists in the `SignedProposals` storage
 	pub fn is_not_existing_proposal_batch(prop: &SignedProposalBatchOf<T>) -> bool {
 		match decode_proposal_identifier(
@@ -21,6 +52,16 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// Checks whether the unsigned batch of a signed proposal batch is still waiting to be signed,
+	/// so that the signed batch can still be submitted on chain
+	pub fn is_queued_proposal_batch(prop: &SignedProposalBatchOf<T>) -> bool {
+		match prop.proposals.first().map(decode_proposal_identifier) {
+			Some(Ok(v)) =>
+				UnsignedProposalQueue::<T>::contains_key(v.typed_chain_id, prop.batch_id),
+			_ => false,
+		}
+	}
+
 	// ** Calculate the turn of authorities to submit transactions **
 	// we use a simple round robin algorithm to determine who submits the proposal on-chain, this
 	// avoids all the validators trying to submit at the same time.
@@ -39,6 +80,34 @@ impl<T: Config> Pallet<T> {
 		current_authorities.get(submitter_index as usize).cloned()
 	}
 
+	/// Returns the number of authorities before `account` in line to submit the signed proposals
+	/// at `block_number`, or `None` if `account` is not an authority
+	pub(crate) fn submitter_rank(
+		block_number: T::BlockNumber,
+		account: &T::AccountId,
+	) -> Option<u32> {
+		let current_authorities = pallet_dkg_metadata::CurrentAuthoritiesAccounts::<T>::get();
+		let block_as_u32: u32 = block_number.try_into().unwrap_or_default();
+		let authorities_len = current_authorities.len() as u32;
+		let index = current_authorities.iter().position(|authority| authority == account)? as u32;
+
+		let expected_index = block_as_u32 % authorities_len;
+		Some((index + authorities_len - expected_index) % authorities_len)
+	}
+
+	/// Records the nonces of the proposals of a signed batch as the latest signed nonces of their
+	/// resources
+	pub(crate) fn record_signed_nonces(proposals: &[ProposalOf<T>]) {
+		for (resource_id, nonce) in proposals.iter().filter_map(decode_proposal_resource_nonce) {
+			LastSignedNonce::<T>::mutate(resource_id, |last| {
+				if last.as_ref().map_or(true, |last| nonce.0 > last.0) {
+					*last = Some(nonce);
+					StuckNonces::<T>::remove(resource_id);
+				}
+			});
+		}
+	}
+
 	pub(crate) fn generate_next_batch_id() -> Result<T::BatchId, DispatchError> {
 		let batch_id = Self::next_batch_id();
 		let next_batch_id =
@@ -54,8 +123,12 @@ impl<T: Config> Pallet<T> {
 		// create a new batch
 		let current_block = <frame_system::Pallet<T>>::block_number();
 		let batch_id = Self::generate_next_batch_id()?;
-		let batch =
-			StoredUnsignedProposalBatchOf::<T> { proposals, timestamp: current_block, batch_id };
+		let batch = StoredUnsignedProposalBatchOf::<T> {
+			proposals,
+			timestamp: current_block,
+			batch_id,
+			encoding: BatchEncodings::<T>::get(identifier.typed_chain_id),
+		};
 
 		// push the batch to unsigned proposal queue
 		UnsignedProposalQueue::<T>::insert(identifier.typed_chain_id, batch_id, batch);
@@ -74,12 +147,28 @@ impl<T: Config> Pallet<T> {
 			typed_chain_id: identifier.typed_chain_id,
 		};
 
+		// the proposals submitted after a deferred proposal wait behind it
+		let (first, end) = DeferredUnsignedProposalsRange::<T>::get(identifier.typed_chain_id);
+		if first < end {
+			Self::defer_unsigned_proposal(new_unsigned_proposal);
+			return Ok(())
+		}
+
 		UnsignedProposals::<T>::try_mutate(identifier.typed_chain_id, |proposals| {
 			let proposals = proposals.get_or_insert_with(Default::default);
 
 			// if the bounded vec is full, we create a new batch with the current bounded vec
 			// and push it to the UnsignedProposalQueue
 			if proposals.len() == T::MaxProposalsPerBatch::get() as usize {
+				// the proposal waits until a batch of the chain is signed or expires
+				if !Self::can_create_batch(
+					identifier.typed_chain_id,
+					Self::batches_in_flight(identifier.typed_chain_id),
+				) {
+					Self::defer_unsigned_proposal(new_unsigned_proposal);
+					return Ok(())
+				}
+
 				// push the batch to unsigned proposal queue
 				Self::create_batch_and_add_to_storage(proposals.clone(), identifier)?;
 
@@ -102,27 +191,138 @@ impl<T: Config> Pallet<T> {
 		})
 	}
 
-	// report an offence against current active validator set
+	/// Queues an unsigned proposal until the staging queue of its chain can take it
+	fn defer_unsigned_proposal(unsigned_proposal: UnsignedProposalOf<T>) {
+		let typed_chain_id = unsigned_proposal.typed_chain_id;
+		DeferredUnsignedProposalsRange::<T>::mutate(typed_chain_id, |(_, end)| {
+			DeferredUnsignedProposals::<T>::insert(typed_chain_id, *end, unsigned_proposal);
+			*end = end.saturating_add(1);
+		});
+	}
+
+	/// Moves the oldest deferred unsigned proposals of a chain to its empty staging queue, and
+	/// returns how many were moved
+	fn restage_deferred_proposals(typed_chain_id: TypedChainId) -> u64 {
+		let (first, end) = DeferredUnsignedProposalsRange::<T>::get(typed_chain_id);
+		let last = end.min(first.saturating_add(T::MaxProposalsPerBatch::get() as u64));
+		if first == last {
+			return 0
+		}
+
+		let proposals = (first..last)
+			.filter_map(|index| DeferredUnsignedProposals::<T>::take(typed_chain_id, index))
+			.collect::<Vec<_>>();
+		let proposals: BoundedVec<_, T::MaxProposalsPerBatch> = proposals
+			.try_into()
+			.expect("at most MaxProposalsPerBatch proposals are taken; qed");
+		UnsignedProposals::<T>::insert(typed_chain_id, proposals);
+		if last == end {
+			DeferredUnsignedProposalsRange::<T>::remove(typed_chain_id);
+		} else {
+			DeferredUnsignedProposalsRange::<T>::insert(typed_chain_id, (last, end));
+		}
+		last - first
+	}
+
+	/// Records the session and the accounts of the authority set holding the current DKG key, and
+	/// forgets the authority sets whose keys left the `T::SigningOffenceKeyWindow`
+	pub(crate) fn record_dkg_key_holders() -> Weight {
+		let (set_id, _) = pallet_dkg_metadata::Pallet::<T>::dkg_public_key();
+		if DKGKeyHolders::<T>::contains_key(set_id) {
+			return T::DbWeight::get().reads(2)
+		}
+
+		DKGKeyHolders::<T>::insert(
+			set_id,
+			(
+				T::ValidatorSet::session_index(),
+				pallet_dkg_metadata::CurrentAuthoritiesAccounts::<T>::get(),
+			),
+		);
+
+		let window = AuthoritySetId::from(T::SigningOffenceKeyWindow::get());
+		let mut reads = 4;
+		let mut writes = 1;
+		let recorded = DKGKeyHolders::<T>::iter_keys().collect::<Vec<_>>();
+		for recorded_id in recorded {
+			reads += 1;
+			if recorded_id.saturating_add(window) < set_id {
+				DKGKeyHolders::<T>::remove(recorded_id);
+				writes += 1;
+			}
+		}
+
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	/// Returns the authority set whose DKG key signed the batch, which is either the current set
+	/// or a recorded set within the `T::SigningOffenceKeyWindow`
+	pub(crate) fn signing_key_set_id(
+		signed_data: &SignedProposalBatchOf<T>,
+	) -> Option<AuthoritySetId> {
+		let data = signed_data.data();
+		let (current_set_id, _) = pallet_dkg_metadata::Pallet::<T>::dkg_public_key();
+		if ensure_signed_by_dkg::<pallet_dkg_metadata::Pallet<T>>(&signed_data.signature, &data)
+			.is_ok()
+		{
+			return Some(current_set_id)
+		}
+
+		let window = AuthoritySetId::from(T::SigningOffenceKeyWindow::get());
+		let (previous_set_id, previous_key) =
+			pallet_dkg_metadata::Pallet::<T>::previous_public_key();
+		DKGKeyHolders::<T>::iter_keys()
+			.filter(|set_id| {
+				*set_id < current_set_id && set_id.saturating_add(window) >= current_set_id
+			})
+			.find(|set_id| {
+				let key = if *set_id == previous_set_id {
+					previous_key.clone()
+				} else {
+					pallet_dkg_metadata::HistoricalRounds::<T>::get(set_id).next_round_pub_key
+				};
+				// the protocol which generated a previous key is not recorded, so both signature
+				// schemes are tried
+				!key.is_empty() &&
+					[DKGProtocol::MpEcdsa, DKGProtocol::WTFrost].into_iter().any(|protocol| {
+						ensure_signed_by_key(protocol, &key, &signed_data.signature, &data).is_ok()
+					})
+			})
+	}
+
+	// report an offence against the authority set which held the DKG key of `key_set_id`
 	pub fn report_offence(
 		offence_type: DKGMisbehaviorOffenceType,
+		key_set_id: AuthoritySetId,
 	) -> Result<(), sp_staking::offence::OffenceError> {
-		let session_index = T::ValidatorSet::session_index();
-
-		// The current dkg authorities are the same as the current validator set
-		// so we pick the current validator set, this results in easier
-		// conversion to pallet offences index
-		let current_validators = T::ValidatorSet::validators();
-		let offenders = current_validators
+		let (current_set_id, _) = pallet_dkg_metadata::Pallet::<T>::dkg_public_key();
+		let (session_index, validators) = match DKGKeyHolders::<T>::get(key_set_id) {
+			// a previous key is reported against the validators of the session its authority set
+			// was recorded in
+			Some((session_index, accounts)) if key_set_id != current_set_id => (
+				session_index,
+				accounts
+					.into_iter()
+					.filter_map(
+						<T::ValidatorSet as ValidatorSet<T::AccountId>>::ValidatorIdOf::convert,
+					)
+					.collect::<Vec<_>>(),
+			),
+			// The current dkg authorities are the same as the current validator set
+			// so we pick the current validator set, this results in easier
+			// conversion to pallet offences index
+			_ => (T::ValidatorSet::session_index(), T::ValidatorSet::validators()),
+		};
+		let offenders = validators
 			.into_iter()
-			.enumerate()
-			.filter_map(|(_, id)| {
+			.filter_map(|id| {
 				<T::ValidatorSet as ValidatorSetWithIdentification<T::AccountId>>::IdentificationOf::convert(
 					id.clone()
 				).map(|full_id| (id, full_id))
 			})
 			.collect::<Vec<IdentificationTuple<T>>>();
 
-		// we report an offence against the current DKG authorities
+		// we report an offence against the DKG authorities which held the key
 		let offence = DKGMisbehaviourOffence {
 			offence: offence_type,
 			session_index,
@@ -139,7 +339,11 @@ impl<T: Config> Pallet<T> {
 	/// The function submits batches of signed proposals on-chain in batches of
 	/// `T::MaxProposalsPerBatch`. Proposals are stored offchain and target specific block numbers
 	/// for submission. This function polls all relevant proposals ready for submission at the
-	/// current block number
+	/// current block number.
+	///
+	/// The expected submitter of the block can submit the batches right away. The authorities
+	/// after it in the round robin can submit the batches which are still not on chain,
+	/// `T::SubmitterFallbackBlocks` blocks later for each authority before them in line.
 	pub(crate) fn submit_signed_proposal_onchain(
 		block_number: T::BlockNumber,
 	) -> Result<(), &'static str> {
@@ -151,7 +355,8 @@ impl<T: Config> Pallet<T> {
 			)
 		}
 
-		// check if its our turn to submit proposals
+		// check how many authorities are before us in line to submit proposals
+		let mut fallbacks = 0;
 		if let Some(expected_signer_account) = Self::get_expected_signer(block_number) {
 			// the signer does not have a method to read all available public keys, we instead sign
 			// a dummy message and read the current pub key from the signature.
@@ -159,13 +364,16 @@ impl<T: Config> Pallet<T> {
 			let account: &T::AccountId =
 				&signature.first().expect("Unable to retreive signed message").0.id; // the unwrap here is ok since we checked if can_sign() is true above
 
-			if account != &expected_signer_account {
-				log::debug!(
-					target: "runtime::dkg_proposal_handler",
-					"submit_signed_proposal_onchain: Not our turn to sign, selected signer is {:?}",
-					expected_signer_account
-				);
-				return Ok(())
+			match Self::submitter_rank(block_number, account) {
+				Some(rank) => fallbacks = rank,
+				None => {
+					log::debug!(
+						target: "runtime::dkg_proposal_handler",
+						"submit_signed_proposal_onchain: Not an authority, selected signer is {:?}",
+						expected_signer_account
+					);
+					return Ok(())
+				},
 			}
 		}
 
@@ -173,11 +381,11 @@ impl<T: Config> Pallet<T> {
 		{
 			let _guard = lock.lock();
 
-			match Self::get_next_offchain_signed_proposals() {
+			match Self::get_next_offchain_signed_proposals(block_number, fallbacks) {
 				Ok(next_proposals) => {
 					log::debug!(
 						target: "runtime::dkg_proposal_handler",
-						"submit_signed_proposal_onchain: found {} proposal batches to submit before filtering\n {:?}",
+						"submit_signed_proposal_onchain: found {} proposal batches to submit\n {:?}",
 						next_proposals.len(), next_proposals
 					);
 
@@ -186,21 +394,9 @@ impl<T: Config> Pallet<T> {
 						return Ok(())
 					}
 
-					// We filter out all proposals that are already on chain
-					let filtered_proposals = next_proposals
-						.iter()
-						.cloned()
-						.filter(Self::is_not_existing_proposal_batch)
-						.collect::<Vec<_>>();
-					log::debug!(
-						target: "runtime::dkg_proposal_handler",
-						"submit_signed_proposal_onchain: found {} proposals to submit after filtering\n {:?}",
-						filtered_proposals.len(), filtered_proposals
-					);
 					// We split the vector into chunks of `T::MaxProposalsPerBatch` length and
 					// submit those chunks
-					for chunk in filtered_proposals.chunks(T::MaxProposalsPerBatch::get() as usize)
-					{
+					for chunk in next_proposals.chunks(T::MaxProposalsPerBatch::get() as usize) {
 						let call = Call::<T>::submit_signed_proposals { props: chunk.to_vec() };
 						let result = signer
 							.send_signed_transaction(|_| call.clone())
@@ -237,29 +433,50 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
-	/// Returns the list of signed proposals ready for on-chain submission
-	#[allow(clippy::type_complexity)]
+	/// Returns the list of signed proposals ready for on-chain submission by a submitter with
+	/// `fallbacks` authorities before it in line.
+	///
+	/// The batches already on chain are removed from the outbox, as well as the batches which can
+	/// no longer land because their unsigned batch left the queue, or which were submitted
+	/// [`MAX_SIGNED_PROPOSAL_SUBMISSIONS`] times already. The returned batches stay in the outbox,
+	/// to be submitted again after [`SIGNED_PROPOSAL_RESUBMIT_DELAY`] blocks if they are still not
+	/// on chain by then.
 	pub(crate) fn get_next_offchain_signed_proposals(
+		block_number: T::BlockNumber,
+		fallbacks: u32,
 	) -> Result<Vec<SignedProposalBatchOf<T>>, &'static str> {
+		let fallback_delay = T::SubmitterFallbackBlocks::get().saturating_mul(fallbacks.into());
 		let proposals_ref = StorageValueRef::persistent(OFFCHAIN_SIGNED_PROPOSALS);
 
-		let mut all_proposals = Vec::new();
-		let res = proposals_ref.mutate::<OffchainSignedProposalBatches<
-			T::BatchId,
-			T::MaxProposalLength,
-			T::MaxProposalsPerBatch,
-			T::MaxSignatureLength,
-		>, _, _>(|res| {
+		let mut due_proposals = Vec::new();
+		let res = proposals_ref.mutate::<OffchainSignedProposalBatchesOf<T>, _, _>(|res| {
 			match res {
-				Ok(Some(prop_wrapper)) => {
+				Ok(Some(mut outbox)) => {
 					// log the proposals
 					log::debug!(
 						target: "runtime::dkg_proposal_handler",
 						"Offchain signed proposals: {:?}",
-						prop_wrapper
+						outbox
 					);
-					all_proposals = prop_wrapper.clone().batches;
-					Ok(prop_wrapper)
+					outbox.batches.retain(|pending| {
+						pending.retries < MAX_SIGNED_PROPOSAL_SUBMISSIONS &&
+							Self::is_not_existing_proposal_batch(&pending.batch) &&
+							Self::is_queued_proposal_batch(&pending.batch)
+					});
+					for pending in outbox.batches.iter_mut() {
+						let resubmit_at = pending.last_submitted_at.map(|submitted_at| {
+							submitted_at.saturating_add(SIGNED_PROPOSAL_RESUBMIT_DELAY.into())
+						});
+						if pending.submit_at.saturating_add(fallback_delay) > block_number ||
+							resubmit_at.map_or(false, |at| at > block_number)
+						{
+							continue
+						}
+						due_proposals.push(pending.batch.clone());
+						pending.retries = pending.retries.saturating_add(1);
+						pending.last_submitted_at = Some(block_number);
+					}
+					Ok(outbox)
 				},
 				Ok(None) => Err("No signed proposals key stored"),
 				Err(e) => {
@@ -278,13 +495,21 @@ impl<T: Config> Pallet<T> {
 			return Err("Unable to get next proposal batch")
 		}
 
-		Ok(all_proposals)
+		Ok(due_proposals)
 	}
 
 	// *** Validation methods ***
 
 	pub(crate) fn validate_proposal_signature(data: &[u8], signature: &[u8]) -> bool {
-		dkg_runtime_primitives::utils::validate_ecdsa_signature(data, signature)
+		match pallet_dkg_metadata::Pallet::<T>::dkg_protocol() {
+			DKGProtocol::MpEcdsa =>
+				dkg_runtime_primitives::utils::validate_ecdsa_signature(data, signature),
+			DKGProtocol::WTFrost => dkg_runtime_primitives::utils::verify_schnorr_signature(
+				data,
+				signature,
+				&pallet_dkg_metadata::Pallet::<T>::dkg_public_key().1,
+			),
+		}
 	}
 
 	pub(crate) fn handle_validation_error(error: ValidationError) -> Error<T> {
@@ -305,19 +530,33 @@ impl<T: Config> Pallet<T> {
 
 	pub fn on_idle_create_proposal_batches(mut remaining_weight: Weight) -> Weight {
 		// fetch all unsigned proposals
-		let unsigned_proposals: Vec<_> = UnsignedProposals::<T>::iter().collect();
+		let mut unsigned_proposals: Vec<_> = UnsignedProposals::<T>::iter().collect();
 		let unsigned_proposals_len = unsigned_proposals.len() as u64;
 		remaining_weight =
-			remaining_weight.saturating_sub(T::DbWeight::get().reads(unsigned_proposals_len));
+			remaining_weight.saturating_sub(T::DbWeight::get().reads(unsigned_proposals_len * 2));
+
+		// the refresh proposals come first, then the chains of the highest priority class
+		unsigned_proposals.sort_by_cached_key(|(typed_chain_id, _)| {
+			(
+				*typed_chain_id != TypedChainId::None,
+				Reverse(ChainSchedulingConfigs::<T>::get(typed_chain_id).priority),
+			)
+		});
 
 		for (typed_chain_id, unsigned_proposals) in unsigned_proposals {
-			remaining_weight =
-				remaining_weight.saturating_sub(T::DbWeight::get().reads_writes(1, 3));
+			let in_flight = Self::batches_in_flight(typed_chain_id);
+			remaining_weight = remaining_weight
+				.saturating_sub(T::DbWeight::get().reads_writes(2 + in_flight as u64, 3));
 
 			if remaining_weight.is_zero() {
 				break
 			}
 
+			// the proposals wait until a batch of the chain is signed or expires
+			if !Self::can_create_batch(typed_chain_id, in_flight) {
+				continue
+			}
+
 			let batch_id_res = Self::generate_next_batch_id();
 
 			if batch_id_res.is_err() {
@@ -336,12 +575,16 @@ impl<T: Config> Pallet<T> {
 				batch_id,
 				proposals: unsigned_proposals,
 				timestamp: <frame_system::Pallet<T>>::block_number(),
+				encoding: BatchEncodings::<T>::get(typed_chain_id),
 			};
 			// push the batch to unsigned proposal queue
 			UnsignedProposalQueue::<T>::insert(typed_chain_id, batch_id, proposal_batch);
 
-			// remove the batch from the unsigned proposal list
+			// remove the batch from the unsigned proposal list, and stage the deferred proposals
 			UnsignedProposals::<T>::remove(typed_chain_id);
+			let restaged = Self::restage_deferred_proposals(typed_chain_id);
+			remaining_weight = remaining_weight
+				.saturating_sub(T::DbWeight::get().reads_writes(1 + restaged, 2 + restaged));
 		}
 
 		remaining_weight
@@ -396,4 +639,69 @@ impl<T: Config> Pallet<T> {
 
 		remaining_weight
 	}
+
+	/// Reports the resources whose lowest pending nonce has been waiting for longer than
+	/// `T::StuckNonceThreshold` on a nonce that was never signed
+	pub fn on_idle_detect_stuck_nonces(
+		now: T::BlockNumber,
+		mut remaining_weight: Weight,
+	) -> Weight {
+		// early return if we dont have enough weight to perform a read
+		if remaining_weight.is_zero() {
+			return remaining_weight
+		}
+
+		let unsigned_proposals: Vec<_> = UnsignedProposalQueue::<T>::iter_values().collect();
+		remaining_weight = remaining_weight
+			.saturating_sub(T::DbWeight::get().reads(unsigned_proposals.len() as u64));
+
+		// the lowest pending nonce of each resource and the creation of its batch
+		let mut next_nonces = BTreeMap::<ResourceId, (ProposalNonce, T::BlockNumber)>::new();
+		for batch in unsigned_proposals {
+			for unsigned in batch.proposals.iter() {
+				let Some((resource_id, nonce)) = decode_proposal_resource_nonce(&unsigned.proposal)
+				else {
+					continue
+				};
+				next_nonces
+					.entry(resource_id)
+					.and_modify(|(lowest, created)| {
+						if nonce.0 < lowest.0 {
+							*lowest = nonce;
+							*created = batch.timestamp;
+						}
+					})
+					.or_insert((nonce, batch.timestamp));
+			}
+		}
+
+		for (resource_id, (next_nonce, timestamp)) in next_nonces {
+			remaining_weight = remaining_weight.saturating_sub(T::DbWeight::get().reads(2));
+			if remaining_weight.is_zero() {
+				break
+			}
+
+			let time_passed = now.checked_sub(&timestamp).unwrap_or_default();
+			if time_passed < T::StuckNonceThreshold::get() {
+				continue
+			}
+
+			let Some(last_signed_nonce) = LastSignedNonce::<T>::get(resource_id) else { continue };
+			if next_nonce.0 <= last_signed_nonce.0.saturating_add(1) ||
+				StuckNonces::<T>::get(resource_id) == Some(next_nonce)
+			{
+				continue
+			}
+
+			remaining_weight = remaining_weight.saturating_sub(T::DbWeight::get().writes(1));
+			StuckNonces::<T>::insert(resource_id, next_nonce);
+			Self::deposit_event(Event::<T>::StuckNonceDetected {
+				resource_id,
+				last_signed_nonce,
+				next_nonce,
+			});
+		}
+
+		remaining_weight
+	}
 }
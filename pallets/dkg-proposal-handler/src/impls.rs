@@ -46,10 +46,8 @@ impl<T: Config> ProposalHandlerTrait for Pallet<T> {
 			id.key,
 		);
 
-		ensure!(
-			UnsignedProposalQueue::<T>::contains_key(id.typed_chain_id, prop.batch_id),
-			Error::<T>::ProposalDoesNotExists
-		);
+		let unsigned_batch = UnsignedProposalQueue::<T>::get(id.typed_chain_id, prop.batch_id)
+			.ok_or(Error::<T>::ProposalDoesNotExists)?;
 
 		// Log that proposal exist in the unsigned queue
 		log::debug!(
@@ -57,6 +55,9 @@ impl<T: Config> ProposalHandlerTrait for Pallet<T> {
 			"submit_signed_proposal: proposal exist in the unsigned queue"
 		);
 
+		// the signed data depends on the encoding, which is chosen when the batch is created
+		ensure!(unsigned_batch.encoding == prop.encoding, Error::<T>::InvalidBatchEncoding);
+
 		ensure!(
 			Self::validate_proposal_signature(&prop.data(), &prop.signature),
 			Error::<T>::ProposalSignatureInvalid
@@ -76,8 +77,23 @@ impl<T: Config> ProposalHandlerTrait for Pallet<T> {
 		// Update storage
 		SignedProposals::<T>::insert(id.typed_chain_id, prop.batch_id, prop.clone());
 
+		// Store the inclusion proofs of the proposals of merkleized batches
+		if let Some(proofs) = prop.inclusion_proofs() {
+			let proofs: BoundedVec<_, _> = proofs
+				.into_iter()
+				.map(MerkleProof::try_from)
+				.collect::<Result<Vec<_>, _>>()
+				.ok()
+				.and_then(|proofs| proofs.try_into().ok())
+				.ok_or(Error::<T>::ProposalsLengthOverflow)?;
+			SignedProposalProofs::<T>::insert(id.typed_chain_id, prop.batch_id, proofs);
+		}
+
 		UnsignedProposalQueue::<T>::remove(id.typed_chain_id, prop.batch_id);
 
+		// Let the next proposals of each resource be signed
+		Self::record_signed_nonces(&prop.proposals);
+
 		// Emit RuntimeEvent so frontend can react to it.
 		let signed_proposal_events = prop
 			.proposals
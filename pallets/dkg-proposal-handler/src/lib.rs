@@ -72,6 +72,23 @@
 //! Signed proposals are stored in the offchain storage system and polled each block by the offchain
 //! worker system.
 //!
+//! The proposals of a resource are executed in nonce order by their target, so the DKG authorities
+//! only sign a proposal once all the proposals of its resource with a lower nonce are signed. The
+//! nonce of the latest signed proposal of each resource is kept in `LastSignedNonce`, and a
+//! `StuckNonceDetected` event is emitted when the pending proposals of a resource wait on a nonce
+//! that was never signed for longer than `StuckNonceThreshold` blocks. The gap can be skipped with
+//! `force_set_last_signed_nonce`.
+//!
+//! Each chain can be given a priority class and a maximum number of batches in flight with
+//! `set_chain_scheduling_config`. Batches are created for the chains of a higher priority class
+//! first, and the DKG authorities sign them in the same order, so a chain flooding the pallet with
+//! proposals cannot delay the refresh proposals or the proposals of critical chains.
+//!
+//! Data signed by the DKG that was never queued by the pallet can be reported with
+//! `submit_dkg_signing_offence`. Besides the current key, the keys of the authority sets of the
+//! last `SigningOffenceKeyWindow` set ids are accepted, and the offence is reported against the
+//! authorities which held the signing key, in the session recorded for them in `DKGKeyHolders`.
+//!
 //! The types of proposals available for submission is defined in the `ProposalType` enum as well as
 //! the `DKGPayloadKey` enum. The list of currently supported proposals is as follows:
 //! - Refresh: A proposal to refresh the DKG key across authority changes.
@@ -107,10 +124,11 @@
 
 use dkg_runtime_primitives::{
 	handlers::{decode_proposals::decode_proposal_identifier, validate_proposals::ValidationError},
+	merkle::MerkleProof,
 	offchain::storage_keys::{OFFCHAIN_SIGNED_PROPOSALS, SUBMIT_SIGNED_PROPOSAL_ON_CHAIN_LOCK},
 	traits::OnSignedProposal,
-	OffchainSignedProposalBatches, ProposalHandlerTrait, ProposalKind, SignedProposalBatch,
-	TypedChainId,
+	BatchEncoding, ChainSchedulingConfig, OffchainSignedProposalBatches, ProposalHandlerTrait,
+	ProposalKind, ProposalNonce, ResourceId, SignedProposalBatch, TypedChainId,
 };
 use frame_support::{
 	dispatch::fmt::Debug,
@@ -124,7 +142,7 @@ use sp_runtime::{
 		storage::StorageValueRef,
 		storage_lock::{StorageLock, Time},
 	},
-	traits::{AtLeast32BitUnsigned, Convert, Zero},
+	traits::{AtLeast32BitUnsigned, Convert, One, Saturating, Zero},
 };
 use sp_staking::{
 	offence::{DisableStrategy, Kind, Offence, ReportOffence},
@@ -142,6 +160,8 @@ pub use offences::*;
 mod functions;
 pub use functions::*;
 
+pub mod migrations;
+
 #[cfg(test)]
 mod mock;
 
@@ -184,6 +204,14 @@ pub mod pallet {
 		<T as pallet_dkg_metadata::Config>::MaxSignatureLength,
 	>;
 
+	pub type OffchainSignedProposalBatchesOf<T> = OffchainSignedProposalBatches<
+		<T as Config>::BatchId,
+		<T as pallet_dkg_metadata::Config>::MaxProposalLength,
+		<T as Config>::MaxProposalsPerBatch,
+		<T as pallet_dkg_metadata::Config>::MaxSignatureLength,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+
 	/// A type for representing the validator id in a session.
 	pub type ValidatorId<T> = <<T as Config>::ValidatorSet as ValidatorSet<
 		<T as frame_system::Config>::AccountId,
@@ -242,6 +270,21 @@ pub mod pallet {
 		#[pallet::constant]
 		type UnsignedProposalExpiry: Get<Self::BlockNumber>;
 
+		/// Blocks after which a proposal batch waiting on a missing nonce of its resource is
+		/// reported as stuck
+		#[pallet::constant]
+		type StuckNonceThreshold: Get<Self::BlockNumber>;
+
+		/// The number of blocks a signed proposal batch waits for the expected submitter, before
+		/// the next authority in line can submit it as well
+		#[pallet::constant]
+		type SubmitterFallbackBlocks: Get<Self::BlockNumber>;
+
+		/// The number of authority set ids after a key rotation during which the signatures of a
+		/// previous DKG key can still be reported as signing offences
+		#[pallet::constant]
+		type SigningOffenceKeyWindow: Get<u32>;
+
 		/// The origin which may forcibly reset parameters or otherwise alter
 		/// privileged attributes.
 		type ForceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
@@ -260,7 +303,11 @@ pub mod pallet {
 		type WeightInfo: WeightInfo;
 	}
 
+	/// The current storage version
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// All unsigned proposals.
@@ -290,6 +337,26 @@ pub mod pallet {
 		BoundedVec<UnsignedProposalOf<T>, T::MaxProposalsPerBatch>,
 	>;
 
+	/// Unsigned proposals waiting for the staging queue of their chain, which is full while the
+	/// chain has its maximum number of batches in flight
+	#[pallet::storage]
+	#[pallet::getter(fn deferred_unsigned_proposals)]
+	pub type DeferredUnsignedProposals<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		TypedChainId,
+		Blake2_128Concat,
+		u64,
+		UnsignedProposalOf<T>,
+	>;
+
+	/// The range of the indices of the deferred unsigned proposals of each chain, in the order
+	/// they were submitted
+	#[pallet::storage]
+	#[pallet::getter(fn deferred_unsigned_proposals_range)]
+	pub type DeferredUnsignedProposalsRange<T: Config> =
+		StorageMap<_, Blake2_128Concat, TypedChainId, (u64, u64), ValueQuery>;
+
 	/// All signed proposals.
 	#[pallet::storage]
 	#[pallet::getter(fn signed_proposals)]
@@ -302,6 +369,67 @@ pub mod pallet {
 		SignedProposalBatchOf<T>,
 	>;
 
+	/// The encoding of the proposal batches created for each chain
+	#[pallet::storage]
+	#[pallet::getter(fn batch_encoding)]
+	pub type BatchEncodings<T: Config> =
+		StorageMap<_, Blake2_128Concat, TypedChainId, BatchEncoding, ValueQuery>;
+
+	/// The inclusion proof of each proposal of the signed batches encoded as a merkle root, in the
+	/// order of the proposals of the batch.
+	///
+	/// Relayers use them to submit the proposals of a batch to their target individually.
+	#[pallet::storage]
+	#[pallet::getter(fn signed_proposal_proofs)]
+	pub type SignedProposalProofs<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		TypedChainId,
+		Blake2_128Concat,
+		T::BatchId,
+		BoundedVec<MerkleProof, T::MaxProposalsPerBatch>,
+	>;
+
+	/// The scheduling configuration of the chains which are not scheduled by default.
+	///
+	/// Batches are created for the chains of a higher priority class first, and no batch is
+	/// created for a chain which already has its maximum number of batches waiting to be signed.
+	#[pallet::storage]
+	#[pallet::getter(fn chain_scheduling_config)]
+	pub type ChainSchedulingConfigs<T: Config> =
+		StorageMap<_, Blake2_128Concat, TypedChainId, ChainSchedulingConfig, ValueQuery>;
+
+	/// The nonce of the latest signed proposal of each resource.
+	///
+	/// The DKG signs the proposals of a resource in nonce order, so a proposal is only signed once
+	/// all the proposals of its resource with a lower nonce are signed.
+	#[pallet::storage]
+	#[pallet::getter(fn last_signed_nonce)]
+	pub type LastSignedNonce<T: Config> =
+		StorageMap<_, Blake2_128Concat, ResourceId, ProposalNonce, OptionQuery>;
+
+	/// The next pending nonce of each resource that was reported as stuck, so the same gap is
+	/// only reported once
+	#[pallet::storage]
+	#[pallet::getter(fn stuck_nonce)]
+	pub type StuckNonces<T: Config> =
+		StorageMap<_, Blake2_128Concat, ResourceId, ProposalNonce, OptionQuery>;
+
+	/// The session and the accounts of the authority sets which held the recent DKG keys, so the
+	/// signing offences of a previous key are reported against the authorities which held it
+	#[pallet::storage]
+	#[pallet::getter(fn dkg_key_holders)]
+	pub type DKGKeyHolders<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		dkg_runtime_primitives::AuthoritySetId,
+		(
+			SessionIndex,
+			BoundedVec<T::AccountId, <T as pallet_dkg_metadata::Config>::MaxAuthorities>,
+		),
+		OptionQuery,
+	>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -341,6 +469,14 @@ pub mod pallet {
 			/// The batch ID of the proposal
 			batch_id: T::BatchId,
 		},
+		/// RuntimeEvent When an authority submits signed proposal batches.
+		SignedProposalsSubmitted {
+			/// The account which submitted the batches
+			submitter: T::AccountId,
+			/// The number of authorities in line before the submitter, 0 when the expected
+			/// submitter submitted the batches
+			fallbacks: u32,
+		},
 		/// RuntimeEvent When a Proposal Gets Signed by DKG.
 		ProposalBatchSigned {
 			/// The Target Chain.
@@ -352,13 +488,45 @@ pub mod pallet {
 			/// Signature of the hash of the proposal data.
 			signature: Vec<u8>,
 		},
-		/// Offence reported against current DKG
+		/// Offence reported against the current DKG or a recent one
 		SigningOffenceReported {
 			/// the type of offence reported
 			offence: DKGMisbehaviorOffenceType,
+			/// the authority set which held the DKG key that signed the data
+			authority_set_id: dkg_runtime_primitives::AuthoritySetId,
 			/// the signed data that is the source of the report
 			signed_data: SignedProposalBatchOf<T>,
 		},
+		/// The pending proposals of a resource cannot be signed because a nonce is missing.
+		StuckNonceDetected {
+			/// The resource of the proposals
+			resource_id: ResourceId,
+			/// The nonce of the latest signed proposal of the resource
+			last_signed_nonce: ProposalNonce,
+			/// The lowest nonce waiting to be signed
+			next_nonce: ProposalNonce,
+		},
+		/// The encoding of the proposal batches of a chain was updated.
+		BatchEncodingUpdated {
+			/// The Target Chain.
+			target_chain: TypedChainId,
+			/// The encoding of the next batches
+			encoding: BatchEncoding,
+		},
+		/// The scheduling configuration of a chain was updated.
+		ChainSchedulingConfigUpdated {
+			/// The Target Chain.
+			target_chain: TypedChainId,
+			/// The configuration of the chain
+			config: ChainSchedulingConfig,
+		},
+		/// The nonce of the latest signed proposal of a resource was forcefully set.
+		LastSignedNonceUpdated {
+			/// The resource of the proposals
+			resource_id: ResourceId,
+			/// The new nonce
+			nonce: ProposalNonce,
+		},
 	}
 
 	// Errors inform users that something went wrong.
@@ -394,7 +562,7 @@ pub mod pallet {
 		ArithmeticOverflow,
 		/// Batch does not contain proposals
 		EmptyBatch,
-		/// The signature does not match current active key
+		/// The signature matches neither the current DKG key nor a recent one
 		NotSignedByCurrentDKG,
 		/// the signed data is invalid
 		InvalidSignedData,
@@ -402,10 +570,18 @@ pub mod pallet {
 		ProposalExistsAndIsValid,
 		/// Proposal batch does not exist
 		ProposalBatchNotFound,
+		/// The encoding of the signed batch does not match the queued batch
+		InvalidBatchEncoding,
+		/// The scheduling of the refresh proposals cannot be configured
+		RefreshSchedulingNotConfigurable,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			Self::record_dkg_key_holders()
+		}
+
 		fn offchain_worker(block_number: T::BlockNumber) {
 			let res = Self::submit_signed_proposal_onchain(block_number);
 			log::debug!(
@@ -428,7 +604,10 @@ pub mod pallet {
 			remaining_weight = Self::on_idle_create_proposal_batches(remaining_weight);
 
 			// remove expired proposals with remaining weight
-			Self::on_idle_remove_expired_batches(now, remaining_weight)
+			remaining_weight = Self::on_idle_remove_expired_batches(now, remaining_weight);
+
+			// report the resources whose proposals wait on a missing nonce
+			Self::on_idle_detect_stuck_nonces(now, remaining_weight)
 		}
 	}
 
@@ -441,7 +620,7 @@ pub mod pallet {
 		/// >
 		#[frame_support::transactional]
 		pub fn submit_signed_proposals(
-			_origin: OriginFor<T>,
+			origin: OriginFor<T>,
 			props: Vec<SignedProposalBatchOf<T>>,
 		) -> DispatchResultWithPostInfo {
 			ensure!(
@@ -449,6 +628,14 @@ pub mod pallet {
 				Error::<T>::ProposalsLengthOverflow
 			);
 
+			// the offchain worker submitting the batches ran on the parent block
+			let submitted_at = <frame_system::Pallet<T>>::block_number().saturating_sub(One::one());
+			if let Some((submitter, fallbacks)) = ensure_signed(origin).ok().and_then(|submitter| {
+				Self::submitter_rank(submitted_at, &submitter).map(|rank| (submitter, rank))
+			}) {
+				Self::deposit_event(Event::SignedProposalsSubmitted { submitter, fallbacks });
+			}
+
 			// log the caller, and the props.
 			log::debug!(
 				target: "runtime::dkg_proposal_handler",
@@ -552,16 +739,10 @@ pub mod pallet {
 			// sanity check
 			ensure!(!signed_data.proposals.is_empty(), Error::<T>::InvalidSignedData);
 
-			// is the signature valid
-			let result = ensure_signed_by_dkg::<pallet_dkg_metadata::Pallet<T>>(
-				&signed_data.signature,
-				&signed_data.data(),
-			);
-
-			// sanity check, does the signature match current DKG
-			// we can only report the current DKG, this maybe a valid signature
-			// from a previous DKG, but that is not considered here
-			ensure!(result.is_ok(), Error::<T>::NotSignedByCurrentDKG);
+			// sanity check, does the signature match the current DKG or a recent one
+			// the offence is reported against the authority set which held the signing key
+			let key_set_id =
+				Self::signing_key_set_id(&signed_data).ok_or(Error::<T>::NotSignedByCurrentDKG)?;
 
 			// retreive the typed chain id
 			let common_typed_chain_id = match decode_proposal_identifier(
@@ -584,11 +765,14 @@ pub mod pallet {
 					// this means that the signature happened outside of pallet, pallet will never
 					// create a mixed typed_chain proposal
 					// report an offence
-					let _ =
-						Self::report_offence(DKGMisbehaviorOffenceType::SignedMalformedProposal);
+					let _ = Self::report_offence(
+						DKGMisbehaviorOffenceType::SignedMalformedProposal,
+						key_set_id,
+					);
 
 					Self::deposit_event(Event::SigningOffenceReported {
 						offence: DKGMisbehaviorOffenceType::SignedMalformedProposal,
+						authority_set_id: key_set_id,
 						signed_data,
 					});
 					return Ok(())
@@ -624,9 +808,13 @@ pub mod pallet {
 				}
 
 				// the batch was never part of unsigned proposal queue, report an offence
-				let _ = Self::report_offence(DKGMisbehaviorOffenceType::SignedProposalNotInQueue);
+				let _ = Self::report_offence(
+					DKGMisbehaviorOffenceType::SignedProposalNotInQueue,
+					key_set_id,
+				);
 				Self::deposit_event(Event::SigningOffenceReported {
 					offence: DKGMisbehaviorOffenceType::SignedProposalNotInQueue,
+					authority_set_id: key_set_id,
 					signed_data,
 				});
 				Ok(())
@@ -655,6 +843,76 @@ pub mod pallet {
 			});
 			Ok(().into())
 		}
+
+		/// Force set the nonce of the latest signed proposal of a resource
+		///
+		/// This unblocks the signing of the pending proposals of a resource when a nonce will never
+		/// be signed, for instance because its proposal expired or was removed.
+		#[pallet::weight(<T as Config>::WeightInfo::force_set_last_signed_nonce())]
+		#[pallet::call_index(4)]
+		pub fn force_set_last_signed_nonce(
+			origin: OriginFor<T>,
+			resource_id: ResourceId,
+			nonce: ProposalNonce,
+		) -> DispatchResultWithPostInfo {
+			// Call must come from root (likely from a democracy proposal passing)
+			<T as pallet::Config>::ForceOrigin::ensure_origin(origin)?;
+			LastSignedNonce::<T>::insert(resource_id, nonce);
+			StuckNonces::<T>::remove(resource_id);
+			Self::deposit_event(Event::LastSignedNonceUpdated { resource_id, nonce });
+			Ok(().into())
+		}
+
+		/// Set the encoding of the proposal batches created for a chain
+		///
+		/// Batches encoded as a merkle root let relayers submit their proposals individually, if
+		/// the target chain verifies the inclusion proofs. Batches already created keep their
+		/// encoding.
+		#[pallet::weight(<T as Config>::WeightInfo::set_batch_encoding())]
+		#[pallet::call_index(5)]
+		pub fn set_batch_encoding(
+			origin: OriginFor<T>,
+			typed_chain_id: TypedChainId,
+			encoding: BatchEncoding,
+		) -> DispatchResultWithPostInfo {
+			// Call must come from root (likely from a democracy proposal passing)
+			<T as pallet::Config>::ForceOrigin::ensure_origin(origin)?;
+			BatchEncodings::<T>::insert(typed_chain_id, encoding);
+			Self::deposit_event(Event::BatchEncodingUpdated {
+				target_chain: typed_chain_id,
+				encoding,
+			});
+			Ok(().into())
+		}
+
+		/// Set the priority class and the maximum number of batches in flight of a chain
+		///
+		/// The proposals of the refresh of the DKG key are always scheduled first and are never
+		/// limited, so their configuration cannot be changed.
+		#[pallet::weight(<T as Config>::WeightInfo::set_chain_scheduling_config())]
+		#[pallet::call_index(6)]
+		pub fn set_chain_scheduling_config(
+			origin: OriginFor<T>,
+			typed_chain_id: TypedChainId,
+			config: ChainSchedulingConfig,
+		) -> DispatchResultWithPostInfo {
+			// Call must come from root (likely from a democracy proposal passing)
+			<T as pallet::Config>::ForceOrigin::ensure_origin(origin)?;
+			ensure!(
+				typed_chain_id != TypedChainId::None,
+				Error::<T>::RefreshSchedulingNotConfigurable
+			);
+			if config == ChainSchedulingConfig::default() {
+				ChainSchedulingConfigs::<T>::remove(typed_chain_id);
+			} else {
+				ChainSchedulingConfigs::<T>::insert(typed_chain_id, config);
+			}
+			Self::deposit_event(Event::ChainSchedulingConfigUpdated {
+				target_chain: typed_chain_id,
+				config,
+			});
+			Ok(().into())
+		}
 	}
 
 	#[pallet::validate_unsigned]
@@ -0,0 +1,72 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations of the DKG proposal handler pallet.
+
+use super::*;
+use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+use sp_std::marker::PhantomData;
+
+/// Adds the encoding of the queued and signed proposal batches, which were all concatenated
+pub mod v1 {
+	use super::*;
+	use dkg_runtime_primitives::{SignedProposalBatchV0, StoredUnsignedProposalBatchV0};
+
+	type StoredUnsignedProposalBatchV0Of<T> = StoredUnsignedProposalBatchV0<
+		<T as Config>::BatchId,
+		<T as pallet_dkg_metadata::Config>::MaxProposalLength,
+		<T as Config>::MaxProposalsPerBatch,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+
+	type SignedProposalBatchV0Of<T> = SignedProposalBatchV0<
+		<T as Config>::BatchId,
+		<T as pallet_dkg_metadata::Config>::MaxProposalLength,
+		<T as Config>::MaxProposalsPerBatch,
+		<T as pallet_dkg_metadata::Config>::MaxSignatureLength,
+	>;
+
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() >= 1 {
+				log::info!(
+					target: "runtime::dkg_proposal_handler",
+					"MigrateToV1 should be removed, the storage is already at version 1"
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			UnsignedProposalQueue::<T>::translate::<StoredUnsignedProposalBatchV0Of<T>, _>(
+				|_, _, batch| {
+					translated += 1;
+					Some(batch.into())
+				},
+			);
+			SignedProposals::<T>::translate::<SignedProposalBatchV0Of<T>, _>(|_, _, batch| {
+				translated += 1;
+				Some(batch.into())
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::dkg_proposal_handler",
+				"MigrateToV1 added the encoding of {translated} proposal batches"
+			);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+	}
+}
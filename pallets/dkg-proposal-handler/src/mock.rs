@@ -206,6 +206,9 @@ impl pallet_dkg_proposal_handler::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type OffChainAuthId = dkg_runtime_primitives::offchain::crypto::OffchainAuthId;
 	type UnsignedProposalExpiry = frame_support::traits::ConstU64<10>;
+	type StuckNonceThreshold = frame_support::traits::ConstU64<5>;
+	type SubmitterFallbackBlocks = frame_support::traits::ConstU64<3>;
+	type SigningOffenceKeyWindow = frame_support::traits::ConstU32<2>;
 	type SignedProposalHandler = ();
 	type BatchId = u32;
 	type MaxProposalsPerBatch = MaxProposalsPerBatch;
@@ -224,7 +227,9 @@ impl pallet_dkg_proposals::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
 	type ProposalLifetime = ProposalLifetime;
+	type ArchiveWindow = frame_support::traits::ConstU64<100>;
 	type ProposalHandler = DKGProposalHandler;
+	type VoteWeight = ();
 	type Period = Period;
 	type MaxVotes = MaxVotes;
 	type MaxResources = MaxResources;
@@ -413,5 +418,6 @@ pub fn mock_signed_proposal_batch(eth_tx: TransactionV2) -> SignedProposalBatchO
 		proposals: vec![unsigned_proposal].try_into().unwrap(),
 		batch_id: 0_u32,
 		signature: sig_vec.try_into().unwrap(),
+		encoding: Default::default(),
 	}
 }
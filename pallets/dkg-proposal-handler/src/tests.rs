@@ -15,16 +15,24 @@
 #![allow(clippy::unwrap_used)]
 use super::mock::DKGProposalHandler;
 
-use crate::{mock::*, Error, SignedProposalBatchOf};
+use crate::{mock::*, Error, OffchainSignedProposalBatchesOf, SignedProposalBatchOf};
 use codec::Encode;
 use dkg_runtime_primitives::{
-	offchain::storage_keys::OFFCHAIN_SIGNED_PROPOSALS, ProposalHandlerTrait, TransactionV2,
-	TypedChainId,
+	handlers::substrate::{
+		add_token_to_set::TokenAddCall, anchor_update::AnchorUpdateCall,
+		fee_update::WrappingFeeUpdateCall, remove_token_from_set::TokenRemoveCall,
+		resource_id_update::ResourceIdUpdateCall,
+	},
+	merkle::verify_batch_merkle_proof,
+	offchain::storage_keys::OFFCHAIN_SIGNED_PROPOSALS,
+	BatchEncoding, ChainSchedulingConfig, PriorityClass, ProposalHandlerTrait, TransactionV2,
+	TypedChainId, MAX_SIGNED_PROPOSAL_SUBMISSIONS, SIGNED_PROPOSAL_RESUBMIT_DELAY,
 };
 use frame_support::{
 	assert_err, assert_noop, assert_ok,
-	traits::{Hooks, OnFinalize},
+	traits::{Get, Hooks, OnFinalize},
 	weights::constants::RocksDbWeight,
+	BoundedVec,
 };
 use sp_core::sr25519;
 use sp_runtime::offchain::storage::{StorageRetrievalError, StorageValueRef};
@@ -39,18 +47,12 @@ use webb_proposals::{Proposal, ProposalKind};
 fn add_proposal_to_offchain_storage(prop: SignedProposalBatchOf<Test>) {
 	let proposals_ref = StorageValueRef::persistent(OFFCHAIN_SIGNED_PROPOSALS);
 
-	let update_res: Result<Vec<SignedProposalBatchOf<Test>>, MutateStorageError<_, ()>> =
+	let update_res: Result<OffchainSignedProposalBatchesOf<Test>, MutateStorageError<_, ()>> =
 		proposals_ref.mutate(
-			|val: Result<Option<Vec<SignedProposalBatchOf<Test>>>, StorageRetrievalError>| match val
-			{
-				Ok(Some(mut ser_props)) => {
-					ser_props.push(prop);
-					Ok(ser_props)
-				},
-				_ => {
-					let prop_wrapper: Vec<SignedProposalBatchOf<Test>> = vec![prop];
-					Ok(prop_wrapper)
-				},
+			|val: Result<Option<OffchainSignedProposalBatchesOf<Test>>, StorageRetrievalError>| {
+				let mut outbox = val.ok().flatten().unwrap_or_default();
+				outbox.append(vec![prop], System::block_number());
+				Ok(outbox)
 			},
 		);
 
@@ -59,10 +61,10 @@ fn add_proposal_to_offchain_storage(prop: SignedProposalBatchOf<Test>) {
 
 fn check_offchain_proposals_num_eq(num: usize) {
 	let proposals_ref = StorageValueRef::persistent(OFFCHAIN_SIGNED_PROPOSALS);
-	let stored_props: Option<Vec<SignedProposalBatchOf<Test>>> =
-		proposals_ref.get::<Vec<SignedProposalBatchOf<Test>>>().unwrap();
+	let stored_props: Option<OffchainSignedProposalBatchesOf<Test>> =
+		proposals_ref.get::<OffchainSignedProposalBatchesOf<Test>>().unwrap();
 	assert!(stored_props.is_some(), "{}", true);
-	assert_eq!(stored_props.unwrap().len(), num);
+	assert_eq!(stored_props.unwrap().batches.len(), num);
 }
 
 // helper function to skip blocks
@@ -169,6 +171,181 @@ fn store_signed_proposal_offchain() {
 	})
 }
 
+#[test]
+fn offchain_signed_proposals_stay_in_outbox_until_on_chain() {
+	execute_test_with(|| {
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			Proposal::Unsigned {
+				kind: ProposalKind::EVM,
+				data: tx_v_2.encode().try_into().unwrap()
+			},
+		));
+		let now = run_n_blocks(5);
+
+		// the same batch signed twice is only stored once
+		let signed_proposal = mock_signed_proposal_batch(tx_v_2);
+		add_proposal_to_offchain_storage(signed_proposal.clone());
+		add_proposal_to_offchain_storage(signed_proposal.clone());
+		check_offchain_proposals_num_eq(1);
+
+		assert_eq!(
+			DKGProposalHandler::get_next_offchain_signed_proposals(now, 0).unwrap(),
+			vec![signed_proposal.clone()]
+		);
+		// the submitted batch waits to be confirmed before being submitted again
+		assert!(DKGProposalHandler::get_next_offchain_signed_proposals(now + 1, 0)
+			.unwrap()
+			.is_empty());
+		let resubmit_at = now + SIGNED_PROPOSAL_RESUBMIT_DELAY as u64;
+		assert_eq!(
+			DKGProposalHandler::get_next_offchain_signed_proposals(resubmit_at, 0).unwrap(),
+			vec![signed_proposal.clone()]
+		);
+		check_offchain_proposals_num_eq(1);
+
+		// the batch leaves the outbox once on chain
+		assert_ok!(DKGProposalHandler::submit_signed_proposals(
+			RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+			vec![signed_proposal]
+		));
+		assert!(DKGProposalHandler::get_next_offchain_signed_proposals(resubmit_at * 2, 0)
+			.unwrap()
+			.is_empty());
+		check_offchain_proposals_num_eq(0);
+	});
+}
+
+#[test]
+fn offchain_signed_proposals_which_cannot_land_are_evicted() {
+	execute_test_with(|| {
+		let evm_proposal = |tx: &TransactionV2| Proposal::Unsigned {
+			kind: ProposalKind::EVM,
+			data: tx.encode().try_into().unwrap(),
+		};
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			evm_proposal(&tx_v_2),
+		));
+		let now = run_n_blocks(5);
+
+		// the batch is submitted until it reaches the maximum number of submissions
+		add_proposal_to_offchain_storage(mock_signed_proposal_batch(tx_v_2));
+		let resubmit_at = |n: u32| now + (n * SIGNED_PROPOSAL_RESUBMIT_DELAY) as u64;
+		for n in 0..MAX_SIGNED_PROPOSAL_SUBMISSIONS {
+			assert_eq!(
+				DKGProposalHandler::get_next_offchain_signed_proposals(resubmit_at(n), 0)
+					.unwrap()
+					.len(),
+				1
+			);
+		}
+		assert!(DKGProposalHandler::get_next_offchain_signed_proposals(
+			resubmit_at(MAX_SIGNED_PROPOSAL_SUBMISSIONS),
+			0
+		)
+		.unwrap()
+		.is_empty());
+		check_offchain_proposals_num_eq(0);
+
+		// the batch is dropped once its unsigned batch leaves the queue
+		add_proposal_to_offchain_storage(mock_signed_proposal_batch(tx_v_2));
+		assert_ok!(DKGProposalHandler::force_remove_unsigned_proposal_batch(
+			RuntimeOrigin::root(),
+			TypedChainId::Evm(0),
+			0,
+		));
+		assert!(DKGProposalHandler::get_next_offchain_signed_proposals(now, 0)
+			.unwrap()
+			.is_empty());
+		check_offchain_proposals_num_eq(0);
+	});
+}
+
+#[test]
+fn undecodable_offchain_outboxes_keep_their_signed_proposals() {
+	let batch =
+		|nonce| mock_signed_proposal_batch(TransactionV2::EIP2930(mock_eth_tx_eip2930(nonce)));
+
+	// the signed proposals stored before the outbox are submitted from the given block
+	let (outbox, whole) =
+		OffchainSignedProposalBatchesOf::<Test>::decode_versioned(&vec![batch(0)].encode(), 7);
+	assert!(whole);
+	assert_eq!(outbox.batches.len(), 1);
+	assert_eq!(outbox.batches[0].submit_at, 7);
+
+	// a truncated outbox keeps the batches before the truncated one
+	let mut outbox = OffchainSignedProposalBatchesOf::<Test>::default();
+	outbox.append(vec![batch(0), batch(1)], 3);
+	let bytes = outbox.encode();
+	let (truncated, whole) =
+		OffchainSignedProposalBatchesOf::<Test>::decode_versioned(&bytes[..bytes.len() - 1], 7);
+	assert!(!whole);
+	assert_eq!(truncated.batches, outbox.batches[..1].to_vec());
+}
+
+#[test]
+fn backup_submitters_wait_for_the_fallback_blocks() {
+	execute_test_with(|| {
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			Proposal::Unsigned {
+				kind: ProposalKind::EVM,
+				data: tx_v_2.encode().try_into().unwrap()
+			},
+		));
+		let now = run_n_blocks(5);
+		add_proposal_to_offchain_storage(mock_signed_proposal_batch(tx_v_2));
+
+		// the second authority in line waits twice the fallback blocks
+		let fallback_at = now + 2 * <Test as crate::Config>::SubmitterFallbackBlocks::get();
+		assert!(DKGProposalHandler::get_next_offchain_signed_proposals(fallback_at - 1, 2)
+			.unwrap()
+			.is_empty());
+		assert_eq!(
+			DKGProposalHandler::get_next_offchain_signed_proposals(fallback_at, 2)
+				.unwrap()
+				.len(),
+			1
+		);
+	});
+}
+
+#[test]
+fn signed_proposals_submission_records_the_submitter_fallbacks() {
+	execute_test_with(|| {
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			Proposal::Unsigned {
+				kind: ProposalKind::EVM,
+				data: tx_v_2.encode().try_into().unwrap()
+			},
+		));
+		let now = run_n_blocks(5);
+
+		let accounts: Vec<_> = (1..=3).map(|i| sr25519::Public::from_raw([i; 32])).collect();
+		pallet_dkg_metadata::CurrentAuthoritiesAccounts::<Test>::put(
+			BoundedVec::try_from(accounts.clone()).unwrap(),
+		);
+		// the offchain worker of the parent block expects the authority after it
+		let expected_index = ((now - 1) % 3) as usize;
+		let submitter = accounts[(expected_index + 1) % 3];
+
+		assert_ok!(DKGProposalHandler::submit_signed_proposals(
+			RuntimeOrigin::signed(submitter),
+			vec![mock_signed_proposal_batch(tx_v_2)]
+		));
+		System::assert_has_event(
+			crate::Event::SignedProposalsSubmitted { submitter, fallbacks: 1 }.into(),
+		);
+	});
+}
+
 #[test]
 fn submit_signed_proposal_success() {
 	execute_test_with(|| {
@@ -379,6 +556,37 @@ pub fn make_proposal<const N: usize>(
 	panic!("Invalid proposal type")
 }
 
+pub fn make_substrate_proposal<Call: Encode>(
+	kind: ProposalKind,
+	call: Call,
+) -> Proposal<<Test as pallet_dkg_metadata::Config>::MaxProposalLength> {
+	let mut buf = make_header(TypedChainId::Substrate(1)).encode();
+	call.encode_to(&mut buf);
+	Proposal::Unsigned { kind, data: buf.try_into().unwrap() }
+}
+
+/// A token add proposal for the substrate resource of `make_header` with the given nonce
+pub fn make_nonced_proposal(
+	nonce: u32,
+) -> Proposal<<Test as pallet_dkg_metadata::Config>::MaxProposalLength> {
+	let resource_id = make_header(TypedChainId::Substrate(1)).resource_id();
+	let mut buf =
+		ProposalHeader::new(resource_id, [0x0, 0x0, 0x0, 0x0].into(), nonce.into()).encode();
+	TokenAddCall { pool_share_id: 1, asset_id: nonce }.encode_to(&mut buf);
+	Proposal::Unsigned { kind: ProposalKind::TokenAdd, data: buf.try_into().unwrap() }
+}
+
+fn stuck_nonce_events() -> Vec<crate::Event<Test>> {
+	System::events()
+		.into_iter()
+		.filter_map(|record| match record.event {
+			RuntimeEvent::DKGProposalHandler(event @ crate::Event::StuckNonceDetected { .. }) =>
+				Some(event),
+			_ => None,
+		})
+		.collect()
+}
+
 #[test]
 fn force_submit_should_fail_with_invalid_proposal_header_bytes() {
 	execute_test_with(|| {
@@ -553,12 +761,9 @@ fn force_submit_should_work_with_valid_proposals() {
 		// Substrate Tests
 		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
 			RuntimeOrigin::root(),
-			make_proposal::<20>(
-				Proposal::Unsigned {
-					kind: ProposalKind::TokenAdd,
-					data: vec![].try_into().unwrap()
-				},
-				TypedChainId::Substrate(0)
+			make_substrate_proposal(
+				ProposalKind::TokenAdd,
+				TokenAddCall { pool_share_id: 1, asset_id: 2 }
 			)
 		));
 
@@ -569,14 +774,95 @@ fn force_submit_should_work_with_valid_proposals() {
 		);
 		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
 			RuntimeOrigin::root(),
-			make_proposal::<20>(
-				Proposal::Unsigned {
-					kind: ProposalKind::TokenRemove,
-					data: vec![].try_into().unwrap()
-				},
-				TypedChainId::Substrate(0)
+			make_substrate_proposal(
+				ProposalKind::TokenRemove,
+				TokenRemoveCall { pool_share_id: 1, asset_id: 2 }
+			)
+		));
+	});
+}
+
+#[test]
+fn force_submit_should_work_with_valid_substrate_proposals() {
+	execute_test_with(|| {
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			make_substrate_proposal(
+				ProposalKind::AnchorUpdate,
+				AnchorUpdateCall {
+					merkle_root: [1u8; 32],
+					latest_leaf_index: 1,
+					src_resource_id: make_header(TypedChainId::Substrate(2)).resource_id(),
+				}
+			)
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			make_substrate_proposal(
+				ProposalKind::ResourceIdUpdate,
+				ResourceIdUpdateCall {
+					new_resource_id: make_header(TypedChainId::Substrate(1)).resource_id()
+				}
+			)
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			make_substrate_proposal(
+				ProposalKind::WrappingFeeUpdate,
+				WrappingFeeUpdateCall { pool_share_id: 1, wrapping_fee_percent: 10 }
 			)
 		));
+
+		assert_eq!(
+			DKGProposalHandler::unsigned_proposals(TypedChainId::Substrate(1))
+				.unwrap()
+				.len(),
+			3
+		);
+	});
+}
+
+#[test]
+fn force_submit_should_fail_with_invalid_substrate_proposals() {
+	execute_test_with(|| {
+		// EVM-style calldata is not a valid pallet call
+		assert_noop!(
+			DKGProposalHandler::force_submit_unsigned_proposal(
+				RuntimeOrigin::root(),
+				make_proposal::<20>(
+					Proposal::Unsigned {
+						kind: ProposalKind::TokenAdd,
+						data: vec![].try_into().unwrap()
+					},
+					TypedChainId::Substrate(0)
+				)
+			),
+			Error::<Test>::ProposalFormatInvalid
+		);
+
+		// the wrapping fee must not be above 100%
+		assert_noop!(
+			DKGProposalHandler::force_submit_unsigned_proposal(
+				RuntimeOrigin::root(),
+				make_substrate_proposal(
+					ProposalKind::WrappingFeeUpdate,
+					WrappingFeeUpdateCall { pool_share_id: 1, wrapping_fee_percent: 101 }
+				)
+			),
+			Error::<Test>::ProposalFormatInvalid
+		);
+
+		// the call does not match the proposal kind
+		assert_noop!(
+			DKGProposalHandler::force_submit_unsigned_proposal(
+				RuntimeOrigin::root(),
+				make_substrate_proposal(
+					ProposalKind::AnchorUpdate,
+					TokenAddCall { pool_share_id: 1, asset_id: 2 }
+				)
+			),
+			Error::<Test>::ProposalFormatInvalid
+		);
 	});
 }
 
@@ -769,3 +1055,449 @@ fn offence_reporting_accepts_proposal_signed_not_in_queue() {
 		);
 	});
 }
+
+#[test]
+fn offence_reporting_accepts_proposal_signed_by_a_previous_dkg_key() {
+	execute_test_with(|| {
+		System::set_block_number(1);
+		let previous_authorities: Vec<_> =
+			(1..=2).map(|i| sr25519::Public::from_raw([i; 32])).collect();
+		pallet_dkg_metadata::CurrentAuthoritiesAccounts::<Test>::put(
+			BoundedVec::try_from(previous_authorities.clone()).unwrap(),
+		);
+		DKGProposalHandler::on_initialize(1);
+
+		// the key rotates in the next session
+		let (_, previous_key) = pallet_dkg_metadata::DKGPublicKey::<Test>::get();
+		pallet_session::CurrentIndex::<Test>::put(1);
+		pallet_dkg_metadata::PreviousPublicKey::<Test>::put((0, previous_key));
+		pallet_dkg_metadata::DKGPublicKey::<Test>::put((
+			1,
+			BoundedVec::try_from(vec![2u8; 33]).unwrap(),
+		));
+		pallet_dkg_metadata::CurrentAuthoritiesAccounts::<Test>::put(
+			BoundedVec::try_from(vec![sr25519::Public::from_raw([3; 32])]).unwrap(),
+		);
+		DKGProposalHandler::on_initialize(2);
+		assert_eq!(
+			DKGProposalHandler::dkg_key_holders(0),
+			Some((0, BoundedVec::try_from(previous_authorities.clone()).unwrap()))
+		);
+
+		// the batch signed by the previous key was never part of the queue
+		let signed_proposal =
+			mock_signed_proposal_batch(TransactionV2::EIP2930(mock_eth_tx_eip2930(0)));
+		assert_ok!(DKGProposalHandler::submit_dkg_signing_offence(
+			RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+			signed_proposal.clone()
+		));
+		assert_eq!(
+			Offences::get(),
+			vec![(
+				vec![],
+				crate::DKGMisbehaviourOffence {
+					session_index: 0,
+					validator_set_count: 2,
+					offence: crate::DKGMisbehaviorOffenceType::SignedProposalNotInQueue,
+					offenders: previous_authorities
+						.iter()
+						.map(|authority| (*authority, *authority))
+						.collect()
+				}
+			)]
+		);
+		System::assert_last_event(
+			crate::Event::SigningOffenceReported {
+				offence: crate::DKGMisbehaviorOffenceType::SignedProposalNotInQueue,
+				authority_set_id: 0,
+				signed_data: signed_proposal.clone(),
+			}
+			.into(),
+		);
+
+		// the previous key leaves the window
+		pallet_dkg_metadata::DKGPublicKey::<Test>::put((
+			3,
+			BoundedVec::try_from(vec![3u8; 33]).unwrap(),
+		));
+		DKGProposalHandler::on_initialize(3);
+		assert_eq!(DKGProposalHandler::dkg_key_holders(0), None);
+		assert_noop!(
+			DKGProposalHandler::submit_dkg_signing_offence(
+				RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+				signed_proposal
+			),
+			Error::<Test>::NotSignedByCurrentDKG
+		);
+	});
+}
+
+#[test]
+fn signed_batches_record_the_last_signed_nonce_of_each_resource() {
+	execute_test_with(|| {
+		let resource_id = make_header(TypedChainId::Substrate(1)).resource_id();
+		let evm_proposal = Proposal::Unsigned {
+			kind: ProposalKind::EVM,
+			data: TransactionV2::EIP2930(mock_eth_tx_eip2930(0)).encode().try_into().unwrap(),
+		};
+
+		DKGProposalHandler::record_signed_nonces(&[
+			make_nonced_proposal(2),
+			make_nonced_proposal(1),
+			evm_proposal,
+		]);
+		assert_eq!(DKGProposalHandler::last_signed_nonce(resource_id), Some(2.into()));
+
+		// a lower nonce never moves the last signed nonce back
+		DKGProposalHandler::record_signed_nonces(&[make_nonced_proposal(1)]);
+		assert_eq!(DKGProposalHandler::get_last_signed_nonces(), vec![(resource_id, 2.into())]);
+
+		DKGProposalHandler::record_signed_nonces(&[make_nonced_proposal(3)]);
+		assert_eq!(DKGProposalHandler::last_signed_nonce(resource_id), Some(3.into()));
+	});
+}
+
+#[test]
+fn stuck_nonce_is_reported_once_after_the_threshold() {
+	execute_test_with(|| {
+		let resource_id = make_header(TypedChainId::Substrate(1)).resource_id();
+		DKGProposalHandler::record_signed_nonces(&[make_nonced_proposal(1)]);
+
+		// nonce 2 is missing, so nonce 3 cannot be signed
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			make_nonced_proposal(3),
+		));
+
+		// the batch is created at block 2 and is not stuck yet
+		run_n_blocks(4);
+		assert!(stuck_nonce_events().is_empty());
+
+		run_n_blocks(10);
+		assert_eq!(
+			stuck_nonce_events(),
+			vec![crate::Event::StuckNonceDetected {
+				resource_id,
+				last_signed_nonce: 1.into(),
+				next_nonce: 3.into(),
+			}]
+		);
+		assert_eq!(DKGProposalHandler::stuck_nonce(resource_id), Some(3.into()));
+
+		// skipping the missing nonce unblocks the resource
+		assert_ok!(DKGProposalHandler::force_set_last_signed_nonce(
+			RuntimeOrigin::root(),
+			resource_id,
+			2.into(),
+		));
+		assert_eq!(DKGProposalHandler::last_signed_nonce(resource_id), Some(2.into()));
+		assert!(DKGProposalHandler::stuck_nonce(resource_id).is_none());
+
+		run_n_blocks(12);
+		assert_eq!(stuck_nonce_events().len(), 1);
+	});
+}
+
+#[test]
+fn only_force_origin_can_set_the_last_signed_nonce() {
+	execute_test_with(|| {
+		let resource_id = make_header(TypedChainId::Substrate(1)).resource_id();
+		assert_noop!(
+			DKGProposalHandler::force_set_last_signed_nonce(
+				RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+				resource_id,
+				2.into(),
+			),
+			frame_support::error::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn merkleized_batches_store_the_inclusion_proof_of_each_proposal() {
+	execute_test_with(|| {
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+		let unsigned_proposal = Proposal::Unsigned {
+			kind: ProposalKind::EVM,
+			data: tx_v_2.encode().try_into().unwrap(),
+		};
+
+		assert_ok!(DKGProposalHandler::set_batch_encoding(
+			RuntimeOrigin::root(),
+			TypedChainId::Evm(0),
+			BatchEncoding::MerkleRoot,
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			unsigned_proposal.clone(),
+		));
+		run_n_blocks(5);
+
+		let unsigned_batch =
+			DKGProposalHandler::unsigned_proposal_queue(TypedChainId::Evm(0), 0_u32).unwrap();
+		assert_eq!(unsigned_batch.encoding, BatchEncoding::MerkleRoot);
+
+		let sig = mock_sign_msg(&unsigned_batch.hash().unwrap()).unwrap().unwrap();
+		let signed_proposal = SignedProposalBatchOf::<Test> {
+			batch_id: 0_u32,
+			proposals: vec![unsigned_proposal.clone()].try_into().unwrap(),
+			signature: sig.0.to_vec().try_into().unwrap(),
+			encoding: BatchEncoding::MerkleRoot,
+		};
+		assert_ok!(DKGProposalHandler::submit_signed_proposals(
+			RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+			vec![signed_proposal.clone()]
+		));
+
+		let proofs = DKGProposalHandler::signed_proposal_proofs(TypedChainId::Evm(0), 0).unwrap();
+		let root: [u8; 32] = signed_proposal.data().try_into().unwrap();
+		assert_eq!(proofs.len(), 1);
+		assert!(verify_batch_merkle_proof(&root, unsigned_proposal.data(), 0, &proofs[0]));
+	});
+}
+
+#[test]
+fn signed_batch_with_another_encoding_is_rejected() {
+	execute_test_with(|| {
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+
+		assert_ok!(DKGProposalHandler::set_batch_encoding(
+			RuntimeOrigin::root(),
+			TypedChainId::Evm(0),
+			BatchEncoding::MerkleRoot,
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			Proposal::Unsigned {
+				kind: ProposalKind::EVM,
+				data: tx_v_2.encode().try_into().unwrap()
+			},
+		));
+		run_n_blocks(5);
+
+		// the proposals are signed as concatenated bytes, as the batch was not merkleized
+		assert_err!(
+			DKGProposalHandler::submit_signed_proposals(
+				RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+				vec![mock_signed_proposal_batch(tx_v_2)]
+			),
+			Error::<Test>::InvalidBatchEncoding
+		);
+		assert!(DKGProposalHandler::signed_proposals(TypedChainId::Evm(0), 0).is_none());
+	});
+}
+
+#[test]
+fn batches_are_not_created_beyond_the_chain_limit() {
+	execute_test_with(|| {
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+		let evm_proposal = |tx: &TransactionV2| Proposal::Unsigned {
+			kind: ProposalKind::EVM,
+			data: tx.encode().try_into().unwrap(),
+		};
+
+		assert_ok!(DKGProposalHandler::set_chain_scheduling_config(
+			RuntimeOrigin::root(),
+			TypedChainId::Evm(0),
+			ChainSchedulingConfig {
+				priority: PriorityClass::Normal,
+				max_batches_in_flight: Some(1)
+			},
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			evm_proposal(&tx_v_2),
+		));
+		run_n_blocks(2);
+		assert_eq!(DKGProposalHandler::batches_in_flight(TypedChainId::Evm(0)), 1);
+
+		// the second proposal waits until the first batch is signed
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			evm_proposal(&TransactionV2::EIP2930(mock_eth_tx_eip2930(1))),
+		));
+		run_n_blocks(4);
+		assert_eq!(DKGProposalHandler::batches_in_flight(TypedChainId::Evm(0)), 1);
+		assert!(DKGProposalHandler::unsigned_proposals(TypedChainId::Evm(0)).is_some());
+
+		assert_ok!(DKGProposalHandler::submit_signed_proposals(
+			RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+			vec![mock_signed_proposal_batch(tx_v_2)]
+		));
+		run_n_blocks(6);
+		assert!(DKGProposalHandler::unsigned_proposals(TypedChainId::Evm(0)).is_none());
+		assert!(DKGProposalHandler::unsigned_proposal_queue(TypedChainId::Evm(0), 1).is_some());
+	});
+}
+
+#[test]
+fn proposals_beyond_a_full_staging_queue_are_deferred() {
+	execute_test_with(|| {
+		let evm_proposal = |nonce: u8| Proposal::Unsigned {
+			kind: ProposalKind::EVM,
+			data: TransactionV2::EIP2930(mock_eth_tx_eip2930(nonce)).encode().try_into().unwrap(),
+		};
+
+		assert_ok!(DKGProposalHandler::set_chain_scheduling_config(
+			RuntimeOrigin::root(),
+			TypedChainId::Evm(0),
+			ChainSchedulingConfig {
+				priority: PriorityClass::Normal,
+				max_batches_in_flight: Some(1)
+			},
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			evm_proposal(0),
+		));
+		run_n_blocks(2);
+		assert_eq!(DKGProposalHandler::batches_in_flight(TypedChainId::Evm(0)), 1);
+
+		// the staging queue fills up while the chain is at its limit, so the next proposal waits
+		// behind it instead of being rejected
+		for nonce in 1..=MaxProposalsPerBatch::get() as u8 + 1 {
+			assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+				RuntimeOrigin::root(),
+				evm_proposal(nonce),
+			));
+		}
+		assert_eq!(DKGProposalHandler::batches_in_flight(TypedChainId::Evm(0)), 1);
+		assert_eq!(
+			DKGProposalHandler::unsigned_proposals(TypedChainId::Evm(0)).unwrap().len(),
+			MaxProposalsPerBatch::get() as usize
+		);
+		assert!(DKGProposalHandler::deferred_unsigned_proposals(TypedChainId::Evm(0), 0).is_some());
+
+		assert_ok!(DKGProposalHandler::submit_signed_proposals(
+			RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+			vec![mock_signed_proposal_batch(TransactionV2::EIP2930(mock_eth_tx_eip2930(0)))]
+		));
+		run_n_blocks(4);
+		assert!(DKGProposalHandler::unsigned_proposal_queue(TypedChainId::Evm(0), 1).is_some());
+		let restaged = DKGProposalHandler::unsigned_proposals(TypedChainId::Evm(0)).unwrap();
+		assert_eq!(restaged.len(), 1);
+		assert_eq!(restaged[0].proposal, evm_proposal(MaxProposalsPerBatch::get() as u8 + 1));
+		assert!(DKGProposalHandler::deferred_unsigned_proposals(TypedChainId::Evm(0), 0).is_none());
+		assert_eq!(
+			DKGProposalHandler::deferred_unsigned_proposals_range(TypedChainId::Evm(0)),
+			(0, 0)
+		);
+	});
+}
+
+#[test]
+fn batches_of_higher_priority_chains_are_created_first() {
+	execute_test_with(|| {
+		assert_ok!(DKGProposalHandler::set_chain_scheduling_config(
+			RuntimeOrigin::root(),
+			TypedChainId::Substrate(1),
+			ChainSchedulingConfig {
+				priority: PriorityClass::Critical,
+				max_batches_in_flight: None
+			},
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			Proposal::Unsigned {
+				kind: ProposalKind::EVM,
+				data: TransactionV2::EIP2930(mock_eth_tx_eip2930(0)).encode().try_into().unwrap(),
+			},
+		));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			make_nonced_proposal(1),
+		));
+		run_n_blocks(2);
+
+		assert!(
+			DKGProposalHandler::unsigned_proposal_queue(TypedChainId::Substrate(1), 0).is_some()
+		);
+		assert!(DKGProposalHandler::unsigned_proposal_queue(TypedChainId::Evm(0), 1).is_some());
+		assert_eq!(
+			DKGProposalHandler::get_chain_scheduling_configs(),
+			vec![(
+				TypedChainId::Substrate(1),
+				ChainSchedulingConfig {
+					priority: PriorityClass::Critical,
+					max_batches_in_flight: None
+				}
+			)]
+		);
+	});
+}
+
+#[test]
+fn scheduling_of_refresh_proposals_cannot_be_configured() {
+	execute_test_with(|| {
+		let config =
+			ChainSchedulingConfig { priority: PriorityClass::Low, max_batches_in_flight: Some(1) };
+		assert_noop!(
+			DKGProposalHandler::set_chain_scheduling_config(
+				RuntimeOrigin::signed(sr25519::Public::from_raw([1; 32])),
+				TypedChainId::Evm(0),
+				config,
+			),
+			frame_support::error::BadOrigin
+		);
+		assert_noop!(
+			DKGProposalHandler::set_chain_scheduling_config(
+				RuntimeOrigin::root(),
+				TypedChainId::None,
+				config,
+			),
+			Error::<Test>::RefreshSchedulingNotConfigurable
+		);
+	});
+}
+
+#[test]
+fn proposal_batches_stored_without_an_encoding_are_migrated() {
+	use crate::{migrations::v1::MigrateToV1, SignedProposals, UnsignedProposalQueue};
+	use dkg_runtime_primitives::{SignedProposalBatchV0, StoredUnsignedProposalBatchV0};
+	use frame_support::{
+		storage::unhashed,
+		traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	};
+
+	execute_test_with(|| {
+		let tx_v_2 = TransactionV2::EIP2930(mock_eth_tx_eip2930(0));
+		assert_ok!(DKGProposalHandler::force_submit_unsigned_proposal(
+			RuntimeOrigin::root(),
+			Proposal::Unsigned {
+				kind: ProposalKind::EVM,
+				data: tx_v_2.encode().try_into().unwrap()
+			},
+		));
+		run_n_blocks(2);
+		let unsigned =
+			DKGProposalHandler::unsigned_proposal_queue(TypedChainId::Evm(0), 0).unwrap();
+		let signed = mock_signed_proposal_batch(tx_v_2);
+
+		// the batches as stored before they had an encoding
+		StorageVersion::new(0).put::<DKGProposalHandler>();
+		unhashed::put(
+			&UnsignedProposalQueue::<Test>::hashed_key_for(TypedChainId::Evm(0), 0),
+			&StoredUnsignedProposalBatchV0 {
+				batch_id: unsigned.batch_id,
+				proposals: unsigned.proposals.clone(),
+				timestamp: unsigned.timestamp,
+			},
+		);
+		unhashed::put(
+			&SignedProposals::<Test>::hashed_key_for(TypedChainId::Evm(1), 0),
+			&SignedProposalBatchV0 {
+				batch_id: signed.batch_id,
+				proposals: signed.proposals.clone(),
+				signature: signed.signature.clone(),
+			},
+		);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(DKGProposalHandler::on_chain_storage_version(), 1);
+		assert_eq!(
+			DKGProposalHandler::unsigned_proposal_queue(TypedChainId::Evm(0), 0),
+			Some(unsigned)
+		);
+		assert_eq!(DKGProposalHandler::signed_proposals(TypedChainId::Evm(1), 0), Some(signed));
+	});
+}
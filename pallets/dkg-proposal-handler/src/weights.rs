@@ -34,6 +34,9 @@ pub trait WeightInfo {
 	fn submit_signed_proposals(n: u32, ) -> Weight;
 	fn force_submit_unsigned_proposal() -> Weight;
 	fn force_remove_unsigned_proposal() -> Weight;
+	fn force_set_last_signed_nonce() -> Weight;
+	fn set_batch_encoding() -> Weight;
+	fn set_chain_scheduling_config() -> Weight;
 }
 
 /// Weights for pallet_dkg_proposal_handler using the Substrate node and recommended hardware.
@@ -82,6 +85,32 @@ impl<T: frame_system::Config> WeightInfo for WebbWeight<T> {
 		Weight::from_parts(12_000_000, 0)
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	/// Storage: DKGProposalHandler LastSignedNonce (r:0 w:1)
+	/// Proof: DKGProposalHandler LastSignedNonce (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Storage: DKGProposalHandler StuckNonces (r:0 w:1)
+	/// Proof: DKGProposalHandler StuckNonces (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	fn force_set_last_signed_nonce() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: DKGProposalHandler BatchEncodings (r:0 w:1)
+	/// Proof: DKGProposalHandler BatchEncodings (max_values: None, max_size: Some(27), added: 2502, mode: MaxEncodedLen)
+	fn set_batch_encoding() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKGProposalHandler ChainSchedulingConfigs (r:0 w:1)
+	/// Proof: DKGProposalHandler ChainSchedulingConfigs (max_values: None, max_size: Some(32), added: 2507, mode: MaxEncodedLen)
+	fn set_chain_scheduling_config() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
 }
 
 // For backwards compatibility and tests
@@ -129,4 +158,30 @@ impl WeightInfo for () {
 		Weight::from_parts(12_000_000, 0)
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
+	/// Storage: DKGProposalHandler LastSignedNonce (r:0 w:1)
+	/// Proof: DKGProposalHandler LastSignedNonce (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Storage: DKGProposalHandler StuckNonces (r:0 w:1)
+	/// Proof: DKGProposalHandler StuckNonces (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	fn force_set_last_signed_nonce() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: DKGProposalHandler BatchEncodings (r:0 w:1)
+	/// Proof: DKGProposalHandler BatchEncodings (max_values: None, max_size: Some(27), added: 2502, mode: MaxEncodedLen)
+	fn set_batch_encoding() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: DKGProposalHandler ChainSchedulingConfigs (r:0 w:1)
+	/// Proof: DKGProposalHandler ChainSchedulingConfigs (max_values: None, max_size: Some(32), added: 2507, mode: MaxEncodedLen)
+	fn set_chain_scheduling_config() -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
 }
\ No newline at end of file
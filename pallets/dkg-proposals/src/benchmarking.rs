@@ -58,6 +58,26 @@ pub fn make_proposal<T: Config>(
 	}
 }
 
+/// Registers the resource and the chain of `prop` and fills the proposer set, weighing the votes
+/// of the proposers against a weighted threshold since resolving those reads the most storage
+fn setup_proposal<T: Config>(
+	caller: &T::AccountId,
+	prop: &Proposal<<T as Config>::MaxProposalLength>,
+) -> (ProposalNonce, TypedChainId) {
+	let header = decode_proposal_header(prop.data()).expect("Failed to decode proposal header");
+	let chain_id = header.resource_id.typed_chain_id();
+	Pallet::<T>::register_resource(header.resource_id, vec![0u8; 12]).unwrap();
+	Pallet::<T>::whitelist(chain_id).unwrap();
+	let proposers: BoundedVec<T::AccountId, T::MaxProposers> = sp_std::iter::once(caller.clone())
+		.chain((1..T::MaxProposers::get()).map(|i| account("account", i, SEED)))
+		.collect::<Vec<_>>()
+		.try_into()
+		.expect("Failed to create proposers");
+	Proposers::<T>::put(proposers);
+	Pallet::<T>::set_weighted_proposer_threshold(Some(Perbill::from_percent(66))).unwrap();
+	(header.nonce, chain_id)
+}
+
 benchmarks! {
 
 	set_threshold {
@@ -68,6 +88,22 @@ benchmarks! {
 		assert_last_event::<T>(Event::ProposerThresholdChanged { new_threshold: c}.into());
 	}
 
+	set_weighted_threshold {
+		let p in 1 .. T::MaxProposers::get();
+		let admin = RawOrigin::Root;
+		let threshold = Some(Perbill::from_percent(66));
+		let proposers: BoundedVec<T::AccountId, T::MaxProposers> = (0..p)
+			.map(|i| account("account", i, SEED))
+			.collect::<Vec<_>>()
+			.try_into()
+			.expect("Failed to create proposers");
+		Proposers::<T>::put(proposers);
+	}: _(admin, threshold)
+	verify {
+		assert_eq!(ProposerVoteWeights::<T>::get().len(), p as usize);
+		assert_last_event::<T>(Event::WeightedProposerThresholdChanged { new_threshold: threshold }.into());
+	}
+
 	set_resource {
 		let c in 1 .. 500;
 		let admin = RawOrigin::Root;
@@ -97,87 +133,51 @@ benchmarks! {
 	}
 
 	acknowledge_proposal {
-		let c in 1 .. 500;
 		let caller: T::AccountId = whitelisted_caller();
-		let resource_id: ResourceId = [0; 32].into();
-		let bytes = vec![0u8; 12];
-		Pallet::<T>::register_resource(resource_id, bytes).unwrap();
-		let nonce = 1;
-		let chain_id: TypedChainId = TypedChainId::Evm(CHAIN_IDENTIFIER);
-		let bytes = vec![0u8; c as usize];
 		let proposal = make_proposal::<T>(Proposal::Unsigned {
 			kind: ProposalKind::AnchorUpdate,
 			data: vec![].try_into().unwrap(),
 		});
-		let mut proposers: BoundedVec<T::AccountId, T::MaxProposers> = vec![caller.clone()].try_into().expect("Failed to create proposers");
-		Proposers::<T>::put(proposers.clone());
-		Pallet::<T>::whitelist(chain_id).unwrap();
-		Pallet::<T>::set_proposer_threshold(10).unwrap();
-		for i in 1..9 {
+		let (nonce, chain_id) = setup_proposal::<T>(&caller, &proposal);
+		for i in 1..T::MaxVotes::get().min(T::MaxProposers::get()) {
 			let who: T::AccountId = account("account", i, SEED);
-			proposers.try_push(who.clone()).expect("Failed to push proposer");
-			Proposers::<T>::put(proposers.clone());
-			Pallet::<T>::commit_vote(who, i.into(), chain_id, &proposal, true).unwrap();
+			Pallet::<T>::commit_vote(who, nonce, chain_id, &proposal, true).unwrap();
 		}
-	}: _(RawOrigin::Signed(caller.clone()), nonce.into(), chain_id,  resource_id, proposal.clone())
+	}: _(RawOrigin::Signed(caller.clone()), proposal.clone())
 	verify {
-		assert_last_event::<T>(Event::VoteFor{ src_chain_id: chain_id, proposal_nonce: nonce.into(), who: caller, kind: proposal.kind()}.into());
+		assert!(Votes::<T>::get(chain_id, (nonce, proposal)).is_some());
 	}
 
 	reject_proposal {
-		let c in 1 .. 500;
 		let caller: T::AccountId = whitelisted_caller();
-		let resource_id: ResourceId = [0; 32].into();
-		let bytes = vec![0u8; 12];
-		Pallet::<T>::register_resource(resource_id, bytes).unwrap();
-		let nonce = 1;
-		let chain_id: TypedChainId = TypedChainId::Evm(CHAIN_IDENTIFIER);
-		let bytes = vec![0u8; c as usize];
-		let bytes = vec![0u8; 12];
 		let proposal = make_proposal::<T>(Proposal::Unsigned {
 			kind: ProposalKind::AnchorUpdate,
 			data: vec![].try_into().unwrap(),
 		});
-		let mut proposers: BoundedVec<T::AccountId, T::MaxProposers> = vec![caller.clone()].try_into().expect("Failed to create proposers");
-		Proposers::<T>::put(proposers.clone());
-		Pallet::<T>::whitelist(chain_id).unwrap();
-		Pallet::<T>::set_proposer_threshold(10).unwrap();
-		for i in 1..9 {
+		let (nonce, chain_id) = setup_proposal::<T>(&caller, &proposal);
+		for i in 1..T::MaxVotes::get().min(T::MaxProposers::get()) {
 			let who: T::AccountId = account("account", i, SEED);
-			proposers.try_push(who.clone()).expect("Failed to push proposer");
-			Proposers::<T>::put(proposers.clone());
-			Pallet::<T>::commit_vote(who, i.into(), chain_id, &proposal, false).unwrap();
+			Pallet::<T>::commit_vote(who, nonce, chain_id, &proposal, false).unwrap();
 		}
-	}: _(RawOrigin::Signed(caller.clone()), nonce.into(), chain_id,  resource_id, proposal.clone())
+	}: _(RawOrigin::Signed(caller.clone()), proposal.clone())
 	verify {
-		assert_last_event::<T>(Event::VoteAgainst{ src_chain_id: chain_id, proposal_nonce: nonce.into(), who: caller,kind: proposal.kind()}.into());
+		assert!(Votes::<T>::get(chain_id, (nonce, proposal)).is_some());
 	}
 
 	eval_vote_state {
 		let c in 1 .. 500;
 		let caller: T::AccountId = whitelisted_caller();
-		let bytes = vec![0u8; 12];
-		let nonce: ProposalNonce = 1.into();
-		let chain_id: TypedChainId = TypedChainId::Evm(CHAIN_IDENTIFIER);
-		let bytes = vec![0u8; c as usize];
-		let bytes = vec![0u8; 12];
 		let proposal = make_proposal::<T>(Proposal::Unsigned {
 			kind: ProposalKind::AnchorUpdate,
 			data: vec![].try_into().unwrap(),
 		});
-		let mut proposers: BoundedVec<T::AccountId, T::MaxProposers> = vec![caller.clone()].try_into().expect("Failed to create proposers");
-		Proposers::<T>::put(proposers.clone());
-		Pallet::<T>::whitelist(chain_id).unwrap();
-		Pallet::<T>::set_proposer_threshold(10).unwrap();
-		for i in 1..9 {
+		let (nonce, chain_id) = setup_proposal::<T>(&caller, &proposal);
+		for i in 1..T::MaxVotes::get().min(T::MaxProposers::get()) {
 			let who: T::AccountId = account("account", i, SEED);
-			proposers.try_push(who.clone()).expect("Failed to push proposer");
-			Proposers::<T>::put(proposers.clone());
-			Pallet::<T>::commit_vote(who, i.into(), chain_id, &proposal, false).unwrap();
+			Pallet::<T>::commit_vote(who, nonce, chain_id, &proposal, false).unwrap();
 		}
-
 		Pallet::<T>::commit_vote(caller.clone(), nonce, chain_id, &proposal, true).unwrap();
-	}: _(RawOrigin::Signed(caller.clone()), nonce, chain_id,  proposal.clone())
+	}: _(RawOrigin::Signed(caller.clone()), nonce, chain_id, proposal.clone())
 	verify {
 		assert!(Votes::<T>::get(chain_id, (nonce, proposal)).is_some());
 	}
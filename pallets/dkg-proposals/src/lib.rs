@@ -32,6 +32,17 @@
 //! proposal is met, the message is handled by a generic proposal handler.
 //! This pallet is intended to be used in conjunction with [`pallet-dkg-proposal-handler`].
 //!
+//! Votes can optionally be weighted: once a [`WeightedProposerThreshold`] is set, each vote
+//! weighs what the configured [`VoteWeightProvider`] returns for its proposer (e.g. its staking
+//! exposure or its DKG authority reputation) and a proposal is approved once the weight of its
+//! votes reaches that fraction of the total weight of the proposers.
+//!
+//! Votes are garbage collected with the leftover weight of blocks: once a proposal is approved,
+//! rejected or has expired its votes are pruned and only its outcome is kept in
+//! [`ArchivedProposals`], for [`Config::ArchiveWindow`] blocks. After that only the highest
+//! pruned nonce of each resource is kept, in [`PrunedProposalNonces`], and proposals at or below
+//! it cannot be voted on again.
+//!
 //! ### Terminology
 //!
 //! - Proposer: A valid account that can submit and vote on proposals.
@@ -95,6 +106,7 @@ pub mod mock;
 mod tests;
 pub mod types;
 pub mod utils;
+pub mod vote_weight;
 use dkg_runtime_primitives::{
 	handlers::decode_proposals::{decode_proposal_header, decode_proposal_identifier},
 	traits::OnAuthoritySetChangeHandler,
@@ -103,12 +115,17 @@ use dkg_runtime_primitives::{
 use frame_support::{
 	pallet_prelude::{ensure, DispatchResultWithPostInfo},
 	traits::{EnsureOrigin, EstimateNextSessionRotation, Get},
+	weights::WeightMeter,
 	BoundedVec,
 };
 
-use sp_runtime::{traits::Convert, RuntimeAppPublic};
+use sp_runtime::{
+	traits::{Convert, Hash, Saturating},
+	Perbill, RuntimeAppPublic,
+};
 use sp_std::prelude::*;
-use types::{ProposalStatus, ProposalVotes};
+use types::{ArchivedProposal, ProposalOutcome, ProposalStatus, ProposalVotes, PruneCursor};
+pub use vote_weight::VoteWeightProvider;
 
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
@@ -166,6 +183,9 @@ pub mod pallet {
 		/// The handler for proposals
 		type ProposalHandler: ProposalHandlerTrait<MaxProposalLength = Self::MaxProposalLength>;
 
+		/// The weight of the vote of each proposer when a weighted threshold is set
+		type VoteWeight: VoteWeightProvider<Self::AccountId>;
+
 		/// The identifier for this chain.
 		/// This must be unique and must not collide with existing IDs within a
 		/// set of bridged chains.
@@ -175,6 +195,10 @@ pub mod pallet {
 		#[pallet::constant]
 		type ProposalLifetime: Get<Self::BlockNumber>;
 
+		/// How long the outcome of a proposal is kept once it is reached
+		#[pallet::constant]
+		type ArchiveWindow: Get<Self::BlockNumber>;
+
 		/// The session period
 		#[pallet::constant]
 		type Period: Get<Self::BlockNumber>;
@@ -225,12 +249,27 @@ pub mod pallet {
 	pub type ProposerThreshold<T: Config> =
 		StorageValue<_, u32, ValueQuery, DefaultForProposerThreshold>;
 
+	/// Fraction of the total vote weight of the proposers required for a proposal to execute.
+	///
+	/// When set, it replaces the [`ProposerThreshold`] vote count.
+	#[pallet::storage]
+	#[pallet::getter(fn weighted_proposer_threshold)]
+	pub type WeightedProposerThreshold<T: Config> = StorageValue<_, Perbill, OptionQuery>;
+
 	/// Tracks current proposer set
 	#[pallet::storage]
 	#[pallet::getter(fn proposers)]
 	pub type Proposers<T: Config> =
 		StorageValue<_, BoundedVec<T::AccountId, T::MaxProposers>, ValueQuery>;
 
+	/// Vote weights of the current proposers, snapshotted when the weighted threshold is set
+	/// and on every change of the proposers while it is, so that resolving a proposal doesn't
+	/// query the weight of every proposer
+	#[pallet::storage]
+	#[pallet::getter(fn proposer_vote_weights)]
+	pub type ProposerVoteWeights<T: Config> =
+		StorageValue<_, BoundedVec<(T::AccountId, u128), T::MaxProposers>, ValueQuery>;
+
 	/// Tracks current ECDSA voting keys for each validator
 	pub type VotingKey<T> = BoundedVec<u8, <T as Config>::VotingKeySize>;
 	pub type VotingKeyTuple<T> = (<T as frame_system::Config>::AccountId, VotingKey<T>);
@@ -259,6 +298,48 @@ pub mod pallet {
 		ProposalVotes<T::AccountId, T::BlockNumber, T::MaxVotes>,
 	>;
 
+	/// Proposals that have votes, by the nonce and the hash of the proposal, for the garbage
+	/// collector to walk the [`Votes`], whose keys cannot be iterated
+	#[pallet::storage]
+	pub(super) type VotedProposals<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		TypedChainId,
+		Blake2_128Concat,
+		(ProposalNonce, T::Hash),
+		ProposalOf<T>,
+	>;
+
+	/// Outcomes of the proposals that were resolved or have expired, by the nonce and the hash
+	/// of the proposal
+	#[pallet::storage]
+	#[pallet::getter(fn archived_proposals)]
+	pub type ArchivedProposals<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		TypedChainId,
+		Blake2_128Concat,
+		(ProposalNonce, T::Hash),
+		ArchivedProposal<T::BlockNumber>,
+	>;
+
+	/// Highest nonce of the proposals of each resource whose outcome left the archive. Proposals
+	/// at or below it cannot be voted on again.
+	#[pallet::storage]
+	#[pallet::getter(fn pruned_proposal_nonces)]
+	pub type PrunedProposalNonces<T: Config> =
+		StorageMap<_, Blake2_128Concat, ResourceId, ProposalNonce>;
+
+	/// Position of the last [`VotedProposals`] entry visited by the garbage collector
+	#[pallet::storage]
+	pub(super) type VotesPruneCursor<T: Config> =
+		StorageValue<_, PruneCursor<T::Hash>, OptionQuery>;
+
+	/// Position of the last [`ArchivedProposals`] entry visited by the garbage collector
+	#[pallet::storage]
+	pub(super) type ArchivePruneCursor<T: Config> =
+		StorageValue<_, PruneCursor<T::Hash>, OptionQuery>;
+
 	/// Utilized by the bridge software to map resource IDs to actual methods
 	#[pallet::storage]
 	#[pallet::getter(fn resources)]
@@ -270,6 +351,8 @@ pub mod pallet {
 	pub enum Event<T: Config> {
 		/// Vote threshold has changed (new_threshold)
 		ProposerThresholdChanged { new_threshold: u32 },
+		/// Weighted vote threshold has changed, votes are counted if it is unset
+		WeightedProposerThresholdChanged { new_threshold: Option<Perbill> },
 		/// Chain now available for transfers (chain_id)
 		ChainWhitelisted { chain_id: TypedChainId },
 		/// Vote submitted in favour of proposal
@@ -312,6 +395,13 @@ pub mod pallet {
 		},
 		/// Proposers have been reset
 		ProposersReset { proposers: Vec<T::AccountId> },
+		/// The votes of a resolved or expired proposal have been pruned
+		ProposalVotesPruned {
+			kind: ProposalKind,
+			src_chain_id: TypedChainId,
+			proposal_nonce: ProposalNonce,
+			outcome: ProposalOutcome,
+		},
 	}
 
 	// Errors inform users that something went wrong.
@@ -402,6 +492,18 @@ pub mod pallet {
 		}
 	}
 
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Prunes the votes of resolved and expired proposals, then the outcomes that left the
+		/// archive window, with the leftover weight of the block
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let mut meter = WeightMeter::from_limit(remaining_weight);
+			Self::on_idle_prune_votes(now, &mut meter);
+			Self::on_idle_prune_archive(now, &mut meter);
+			meter.consumed
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Sets the vote threshold for proposals.
@@ -551,6 +653,22 @@ pub mod pallet {
 
 			Self::try_resolve_proposal(nonce, src_chain_id, &prop)
 		}
+
+		/// Sets the fraction of the total vote weight of the proposers required for a
+		/// proposal to execute, or goes back to counting votes if `None`.
+		///
+		/// # <weight>
+		/// - O(P) where P is the number of proposers, whose vote weights are snapshotted
+		/// # </weight>
+		#[pallet::weight(<T as Config>::WeightInfo::set_weighted_threshold(T::MaxProposers::get()))]
+		#[pallet::call_index(7)]
+		pub fn set_weighted_threshold(
+			origin: OriginFor<T>,
+			threshold: Option<Perbill>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_admin(origin)?;
+			Self::set_weighted_proposer_threshold(threshold)
+		}
 	}
 }
 
@@ -588,6 +706,19 @@ impl<T: Config> Pallet<T> {
 		Ok(().into())
 	}
 
+	/// Set a new weighted voting threshold, or remove it
+	pub fn set_weighted_proposer_threshold(
+		threshold: Option<Perbill>,
+	) -> DispatchResultWithPostInfo {
+		ensure!(threshold != Some(Perbill::zero()), Error::<T>::InvalidThreshold);
+		WeightedProposerThreshold::<T>::set(threshold);
+		if threshold.is_some() {
+			Self::snapshot_vote_weights();
+		}
+		Self::deposit_event(Event::WeightedProposerThresholdChanged { new_threshold: threshold });
+		Ok(().into())
+	}
+
 	/// Register a method for a resource Id, enabling associated transfers
 	pub fn register_resource(id: ResourceId, method: Vec<u8>) -> DispatchResultWithPostInfo {
 		let bounded_method: BoundedVec<_, _> =
@@ -625,15 +756,36 @@ impl<T: Config> Pallet<T> {
 		in_favour: bool,
 	) -> DispatchResultWithPostInfo {
 		let now = <frame_system::Pallet<T>>::block_number();
+		let prop_hash = T::Hashing::hash_of(prop);
 		let mut votes = match Votes::<T>::get(src_chain_id, (nonce, prop.clone())) {
 			Some(v) => v,
-			None => ProposalVotes::<
-				<T as frame_system::Config>::AccountId,
-				<T as frame_system::Config>::BlockNumber,
-				<T as Config>::MaxVotes,
-			> {
-				expiry: now + T::ProposalLifetime::get(),
-				..Default::default()
+			None => {
+				// the votes of the proposal may have been pruned already
+				if let Some(archived) =
+					ArchivedProposals::<T>::get(src_chain_id, (nonce, prop_hash))
+				{
+					ensure!(
+						archived.outcome != ProposalOutcome::Expired,
+						Error::<T>::ProposalExpired
+					);
+					return Err(Error::<T>::ProposalAlreadyComplete.into())
+				}
+				// and its outcome once it left the archive
+				let header =
+					decode_proposal_header(prop.data()).map_err(|_| Error::<T>::InvalidProposal)?;
+				ensure!(
+					PrunedProposalNonces::<T>::get(header.resource_id)
+						.map_or(true, |pruned| nonce.0 > pruned.0),
+					Error::<T>::ProposalAlreadyComplete
+				);
+				ProposalVotes::<
+					<T as frame_system::Config>::AccountId,
+					<T as frame_system::Config>::BlockNumber,
+					<T as Config>::MaxVotes,
+				> {
+					expiry: now + T::ProposalLifetime::get(),
+					..Default::default()
+				}
 			},
 		};
 
@@ -661,6 +813,8 @@ impl<T: Config> Pallet<T> {
 		}
 
 		Votes::<T>::insert(src_chain_id, (nonce, prop.clone()), votes.clone());
+		// also indexes the proposals voted on before the index existed
+		VotedProposals::<T>::insert(src_chain_id, (nonce, prop_hash), prop);
 
 		Ok(().into())
 	}
@@ -675,9 +829,25 @@ impl<T: Config> Pallet<T> {
 			let now = <frame_system::Pallet<T>>::block_number();
 			ensure!(!votes.is_complete(), Error::<T>::ProposalAlreadyComplete);
 			ensure!(!votes.is_expired(now), Error::<T>::ProposalExpired);
-			let status =
-				votes.try_to_complete(ProposerThreshold::<T>::get(), ProposerCount::<T>::get());
+			let status = match WeightedProposerThreshold::<T>::get() {
+				Some(threshold) => {
+					let weights = ProposerVoteWeights::<T>::get();
+					let total = Self::sum_vote_weights(&weights);
+					votes.try_to_complete_weighted(threshold.mul_ceil(total), total, |voter| {
+						weights
+							.iter()
+							.find(|(who, _)| who == voter)
+							.map(|(_, weight)| *weight)
+							.unwrap_or_default()
+					})
+				},
+				None =>
+					votes.try_to_complete(ProposerThreshold::<T>::get(), ProposerCount::<T>::get()),
+			};
 			Votes::<T>::insert(src_chain_id, (nonce, prop.clone()), votes.clone());
+			if let Some(outcome) = votes.outcome(now) {
+				Self::archive_outcome(src_chain_id, nonce, prop, outcome, now);
+			}
 
 			match status {
 				ProposalStatus::Approved => Self::finalize_execution(src_chain_id, nonce, prop),
@@ -689,6 +859,29 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// Sums the snapshotted vote weights of the current proposers
+	pub fn total_vote_weight() -> u128 {
+		Self::sum_vote_weights(&ProposerVoteWeights::<T>::get())
+	}
+
+	fn sum_vote_weights(weights: &[(T::AccountId, u128)]) -> u128 {
+		weights.iter().fold(0u128, |total, (_, weight)| total.saturating_add(*weight))
+	}
+
+	/// Snapshots the vote weights of the current proposers
+	pub fn snapshot_vote_weights() {
+		let weights: BoundedVec<_, T::MaxProposers> = Self::proposers()
+			.into_iter()
+			.map(|who| {
+				let weight = T::VoteWeight::vote_weight(&who);
+				(who, weight)
+			})
+			.collect::<Vec<_>>()
+			.try_into()
+			.expect("There is at most one weight per proposer");
+		ProposerVoteWeights::<T>::put(weights);
+	}
+
 	/// Commits a vote in favour of the proposal and executes it if the vote
 	/// threshold is met.
 	fn vote_for(
@@ -713,6 +906,133 @@ impl<T: Config> Pallet<T> {
 		Self::try_resolve_proposal(nonce, src_chain_id, prop)
 	}
 
+	/// Archives the outcome of a proposal by its nonce and hash
+	fn archive_outcome(
+		src_chain_id: TypedChainId,
+		nonce: ProposalNonce,
+		prop: &ProposalOf<T>,
+		outcome: ProposalOutcome,
+		block: T::BlockNumber,
+	) {
+		// proposals are only voted on once their header decodes
+		let Ok(header) = decode_proposal_header(prop.data()) else { return };
+		ArchivedProposals::<T>::insert(
+			src_chain_id,
+			(nonce, T::Hashing::hash_of(prop)),
+			ArchivedProposal { outcome, block, resource_id: header.resource_id },
+		);
+	}
+
+	/// Removes the votes of the resolved and expired proposals, archiving the outcome of the
+	/// expired ones. Resumes from where the previous block stopped when out of weight.
+	pub(crate) fn on_idle_prune_votes(now: T::BlockNumber, meter: &mut WeightMeter) {
+		// placeholder weights, not benchmarked yet: only the storage accesses are accounted for
+		let db_weight = T::DbWeight::get();
+		// read and update the cursor
+		if !meter.check_accrue(db_weight.reads_writes(1, 1)) {
+			return
+		}
+
+		let mut iter = match VotesPruneCursor::<T>::get() {
+			Some((src_chain_id, key)) => VotedProposals::<T>::iter_from(
+				VotedProposals::<T>::hashed_key_for(src_chain_id, key),
+			),
+			None => VotedProposals::<T>::iter(),
+		};
+		let mut pruned = Vec::new();
+		let mut last = None;
+		let mut exhausted = false;
+		// account for pruning an entry before reading it, so no entry is skipped
+		while meter.check_accrue(db_weight.reads_writes(2, 3)) {
+			let Some((src_chain_id, (nonce, prop_hash), prop)) = iter.next() else {
+				exhausted = true;
+				break
+			};
+			last = Some((src_chain_id, (nonce, prop_hash)));
+			match Votes::<T>::get(src_chain_id, (nonce, prop.clone())) {
+				Some(votes) =>
+					if let Some(outcome) = votes.outcome(now) {
+						pruned.push((
+							src_chain_id,
+							nonce,
+							prop_hash,
+							prop,
+							Some((votes.expiry, outcome)),
+						));
+					},
+				None => pruned.push((src_chain_id, nonce, prop_hash, prop, None)),
+			}
+		}
+
+		if exhausted {
+			VotesPruneCursor::<T>::kill();
+		} else {
+			VotesPruneCursor::<T>::set(last);
+		}
+
+		for (src_chain_id, nonce, prop_hash, prop, resolution) in pruned {
+			VotedProposals::<T>::remove(src_chain_id, (nonce, prop_hash));
+			let Some((expiry, outcome)) = resolution else { continue };
+			Votes::<T>::remove(src_chain_id, (nonce, prop.clone()));
+			// resolved proposals were archived when they were resolved
+			if outcome == ProposalOutcome::Expired {
+				Self::archive_outcome(src_chain_id, nonce, &prop, outcome, expiry);
+			}
+			Self::deposit_event(Event::ProposalVotesPruned {
+				kind: prop.kind(),
+				src_chain_id,
+				proposal_nonce: nonce,
+				outcome,
+			});
+		}
+	}
+
+	/// Removes the outcomes that are older than the archive window, remembering the highest
+	/// pruned nonce of each resource so the proposals cannot be voted on again. Resumes from
+	/// where the previous block stopped when out of weight.
+	pub(crate) fn on_idle_prune_archive(now: T::BlockNumber, meter: &mut WeightMeter) {
+		// placeholder weights, not benchmarked yet: only the storage accesses are accounted for
+		let db_weight = T::DbWeight::get();
+		if !meter.check_accrue(db_weight.reads_writes(1, 1)) {
+			return
+		}
+
+		let mut iter = match ArchivePruneCursor::<T>::get() {
+			Some((src_chain_id, key)) => ArchivedProposals::<T>::iter_from(
+				ArchivedProposals::<T>::hashed_key_for(src_chain_id, key),
+			),
+			None => ArchivedProposals::<T>::iter(),
+		};
+		let mut pruned = Vec::new();
+		let mut last = None;
+		let mut exhausted = false;
+		while meter.check_accrue(db_weight.reads_writes(2, 2)) {
+			let Some((src_chain_id, key, archived)) = iter.next() else {
+				exhausted = true;
+				break
+			};
+			last = Some((src_chain_id, key));
+			if archived.block.saturating_add(T::ArchiveWindow::get()) <= now {
+				pruned.push((src_chain_id, key, archived.resource_id));
+			}
+		}
+
+		if exhausted {
+			ArchivePruneCursor::<T>::kill();
+		} else {
+			ArchivePruneCursor::<T>::set(last);
+		}
+
+		for (src_chain_id, (nonce, prop_hash), resource_id) in pruned {
+			ArchivedProposals::<T>::remove(src_chain_id, (nonce, prop_hash));
+			PrunedProposalNonces::<T>::mutate(resource_id, |pruned| {
+				if pruned.as_ref().map_or(true, |pruned| nonce.0 > pruned.0) {
+					*pruned = Some(nonce);
+				}
+			});
+		}
+	}
+
 	/// Execute the proposal and signals the result as an RuntimeEvent
 	fn finalize_execution(
 		src_chain_id: TypedChainId,
@@ -772,6 +1092,9 @@ impl<T: Config>
 		let bounded_proposers: BoundedVec<T::AccountId, T::MaxProposers> =
 			authorities.to_vec().try_into().expect("Too many authorities!");
 		Proposers::<T>::put(bounded_proposers);
+		if WeightedProposerThreshold::<T>::exists() {
+			Self::snapshot_vote_weights();
+		}
 		let bounded_external_accounts: VoterList<T> = authorities
 			.iter()
 			.cloned()
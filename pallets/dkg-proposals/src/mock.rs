@@ -293,6 +293,9 @@ impl pallet_dkg_proposal_handler::Config for Test {
 	type OffChainAuthId = dkg_runtime_primitives::offchain::crypto::OffchainAuthId;
 	type BatchId = u32;
 	type UnsignedProposalExpiry = frame_support::traits::ConstU64<10>;
+	type StuckNonceThreshold = frame_support::traits::ConstU64<5>;
+	type SubmitterFallbackBlocks = frame_support::traits::ConstU64<3>;
+	type SigningOffenceKeyWindow = frame_support::traits::ConstU32<2>;
 	type SignedProposalHandler = ();
 	type MaxProposalsPerBatch = MaxProposers;
 	type ValidatorSet = Historical;
@@ -301,6 +304,21 @@ impl pallet_dkg_proposal_handler::Config for Test {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	pub static VoteWeights: Vec<(AccountId, u128)> = vec![];
+}
+
+/// Vote weights set by the tests, proposers without a weight have a weight of 1
+pub struct MockVoteWeight;
+impl VoteWeightProvider<AccountId> for MockVoteWeight {
+	fn vote_weight(who: &AccountId) -> u128 {
+		VoteWeights::get()
+			.into_iter()
+			.find_map(|(account, weight)| (&account == who).then_some(weight))
+			.unwrap_or(1)
+	}
+}
+
 impl pallet_dkg_proposals::Config for Test {
 	type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type DKGAuthorityToMerkleLeaf = DKGEcdsaToEthereumAddress;
@@ -310,7 +328,9 @@ impl pallet_dkg_proposals::Config for Test {
 	type MaxProposalLength = MaxProposalLength;
 	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
 	type ProposalLifetime = ProposalLifetime;
+	type ArchiveWindow = frame_support::traits::ConstU64<100>;
 	type ProposalHandler = DKGProposalHandler;
+	type VoteWeight = MockVoteWeight;
 	type Period = Period;
 	type MaxVotes = MaxVotes;
 	type MaxResources = MaxResources;
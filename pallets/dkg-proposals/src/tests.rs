@@ -24,20 +24,23 @@ use super::{
 };
 use crate::mock::{
 	assert_has_event, mock_ecdsa_address, mock_pub_key, new_test_ext_initialized, roll_to,
-	CollatorSelection, ExtBuilder, MaxProposers,
+	CollatorSelection, ExtBuilder, MaxProposers, VoteWeights,
 };
 use codec::Encode;
 use core::panic;
 use dkg_runtime_primitives::{
 	DKGPayloadKey, FunctionSignature, MaxKeyLength, ProposalHeader, ProposalNonce, TypedChainId,
 };
-use frame_support::{assert_err, assert_noop, assert_ok};
+use frame_support::{assert_err, assert_noop, assert_ok, traits::Hooks, weights::Weight};
 use std::vec;
 use webb_proposals::{Proposal, ProposalKind};
 
 use crate as pallet_dkg_proposals;
 
-use crate::utils::derive_resource_id;
+use crate::{
+	types::{ArchivedProposal, ProposalOutcome},
+	utils::derive_resource_id,
+};
 
 #[test]
 fn derive_ids() {
@@ -757,3 +760,323 @@ fn proposers_iter_keys_should_only_contain_active_proposers() {
 		assert_eq!(Proposers::<Test>::get().len(), 3);
 	});
 }
+
+#[test]
+fn set_weighted_threshold_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			DKGProposals::set_weighted_threshold(
+				RuntimeOrigin::signed(mock_pub_key(PROPOSER_A)),
+				Some(Perbill::from_percent(50)),
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			DKGProposals::set_weighted_threshold(RuntimeOrigin::root(), Some(Perbill::zero())),
+			Error::<Test>::InvalidThreshold
+		);
+
+		assert_ok!(DKGProposals::set_weighted_threshold(
+			RuntimeOrigin::root(),
+			Some(Perbill::from_percent(50)),
+		));
+		assert_eq!(DKGProposals::weighted_proposer_threshold(), Some(Perbill::from_percent(50)));
+		assert_has_event(RuntimeEvent::DKGProposals(
+			pallet_dkg_proposals::Event::WeightedProposerThresholdChanged {
+				new_threshold: Some(Perbill::from_percent(50)),
+			},
+		));
+
+		assert_ok!(DKGProposals::set_weighted_threshold(RuntimeOrigin::root(), None));
+		assert_eq!(DKGProposals::weighted_proposer_threshold(), None);
+	})
+}
+
+#[test]
+fn vote_weights_are_snapshotted_when_the_weighted_threshold_is_set() {
+	let typed_chain_id = TypedChainId::Evm(1);
+	let r_id = derive_resource_id(typed_chain_id.underlying_chain_id(), 0x0100, b"System.remark");
+
+	new_test_ext_initialized(r_id.typed_chain_id(), r_id, b"System.remark".to_vec()).execute_with(
+		|| {
+			VoteWeights::set(vec![
+				(mock_pub_key(PROPOSER_A), 10),
+				(mock_pub_key(PROPOSER_B), 1),
+				(mock_pub_key(PROPOSER_C), 1),
+			]);
+			assert_ok!(DKGProposals::set_weighted_threshold(
+				RuntimeOrigin::root(),
+				Some(Perbill::from_percent(50)),
+			));
+			assert_eq!(DKGProposals::total_vote_weight(), 12);
+
+			// later changes of the weights are only picked up by the next snapshot
+			VoteWeights::set(vec![(mock_pub_key(PROPOSER_A), 1)]);
+			assert_eq!(DKGProposals::total_vote_weight(), 12);
+
+			assert_ok!(DKGProposals::set_weighted_threshold(
+				RuntimeOrigin::root(),
+				Some(Perbill::from_percent(50)),
+			));
+			assert_eq!(DKGProposals::total_vote_weight(), 1);
+		},
+	)
+}
+
+#[test]
+fn weighted_proposal_needs_the_weight_of_its_votes() {
+	let typed_chain_id = TypedChainId::Evm(1);
+	let r_id = derive_resource_id(typed_chain_id.underlying_chain_id(), 0x0100, b"System.remark");
+
+	new_test_ext_initialized(r_id.typed_chain_id(), r_id, b"System.remark".to_vec()).execute_with(
+		|| {
+			let typed_chain_id = r_id.typed_chain_id();
+			// the vote of A outweighs the votes of B and C
+			VoteWeights::set(vec![
+				(mock_pub_key(PROPOSER_A), 10),
+				(mock_pub_key(PROPOSER_B), 1),
+				(mock_pub_key(PROPOSER_C), 1),
+			]);
+			assert_ok!(DKGProposals::set_weighted_threshold(
+				RuntimeOrigin::root(),
+				Some(Perbill::from_percent(50)),
+			));
+
+			let prop_id = ProposalNonce::from(1u32);
+			let proposal = make_proposal::<64>(
+				make_proposal_header(
+					prop_id,
+					r_id,
+					FunctionSignature::from([0x26, 0x57, 0x88, 0x01]),
+				),
+				Proposal::Unsigned {
+					kind: ProposalKind::AnchorUpdate,
+					data: vec![].try_into().unwrap(),
+				},
+			);
+
+			// B and C reach the vote count threshold but not the weighted one
+			for proposer in [PROPOSER_B, PROPOSER_C] {
+				assert_ok!(DKGProposals::acknowledge_proposal(
+					RuntimeOrigin::signed(mock_pub_key(proposer)),
+					proposal.clone(),
+				));
+			}
+			let votes = DKGProposals::votes(typed_chain_id, (prop_id, proposal.clone())).unwrap();
+			assert_eq!(votes.status, ProposalStatus::Initiated);
+
+			assert_ok!(DKGProposals::acknowledge_proposal(
+				RuntimeOrigin::signed(mock_pub_key(PROPOSER_A)),
+				proposal.clone(),
+			));
+			let votes = DKGProposals::votes(typed_chain_id, (prop_id, proposal.clone())).unwrap();
+			assert_eq!(votes.status, ProposalStatus::Approved);
+			assert_has_event(RuntimeEvent::DKGProposals(
+				pallet_dkg_proposals::Event::ProposalApproved {
+					src_chain_id: typed_chain_id,
+					kind: proposal.kind(),
+					proposal_nonce: prop_id,
+				},
+			));
+		},
+	)
+}
+
+#[test]
+fn weighted_proposal_is_rejected_once_approval_is_out_of_reach() {
+	let typed_chain_id = TypedChainId::Evm(1);
+	let r_id = derive_resource_id(typed_chain_id.underlying_chain_id(), 0x0100, b"System.remark");
+
+	new_test_ext_initialized(r_id.typed_chain_id(), r_id, b"System.remark".to_vec()).execute_with(
+		|| {
+			let typed_chain_id = r_id.typed_chain_id();
+			VoteWeights::set(vec![
+				(mock_pub_key(PROPOSER_A), 10),
+				(mock_pub_key(PROPOSER_B), 1),
+				(mock_pub_key(PROPOSER_C), 1),
+			]);
+			assert_ok!(DKGProposals::set_weighted_threshold(
+				RuntimeOrigin::root(),
+				Some(Perbill::from_percent(50)),
+			));
+
+			let prop_id = ProposalNonce::from(1u32);
+			let proposal = make_proposal::<64>(
+				make_proposal_header(
+					prop_id,
+					r_id,
+					FunctionSignature::from([0x26, 0x57, 0x88, 0x01]),
+				),
+				Proposal::Unsigned {
+					kind: ProposalKind::AnchorUpdate,
+					data: vec![].try_into().unwrap(),
+				},
+			);
+
+			assert_ok!(DKGProposals::acknowledge_proposal(
+				RuntimeOrigin::signed(mock_pub_key(PROPOSER_B)),
+				proposal.clone(),
+			));
+			// B and C cannot reach half of the total weight without A
+			assert_ok!(DKGProposals::reject_proposal(
+				RuntimeOrigin::signed(mock_pub_key(PROPOSER_A)),
+				proposal.clone(),
+			));
+			let votes = DKGProposals::votes(typed_chain_id, (prop_id, proposal.clone())).unwrap();
+			assert_eq!(votes.status, ProposalStatus::Rejected);
+		},
+	)
+}
+
+fn make_remark_proposal(
+	r_id: ResourceId,
+	prop_id: ProposalNonce,
+) -> Proposal<<Test as pallet_dkg_metadata::Config>::MaxProposalLength> {
+	make_proposal::<64>(
+		make_proposal_header(prop_id, r_id, FunctionSignature::from([0x26, 0x57, 0x88, 0x01])),
+		Proposal::Unsigned { kind: ProposalKind::AnchorUpdate, data: vec![].try_into().unwrap() },
+	)
+}
+
+#[test]
+fn on_idle_prunes_the_votes_of_resolved_proposals() {
+	let typed_chain_id = TypedChainId::Evm(1);
+	let r_id = derive_resource_id(typed_chain_id.underlying_chain_id(), 0x0100, b"System.remark");
+
+	new_test_ext_initialized(r_id.typed_chain_id(), r_id, b"System.remark".to_vec()).execute_with(
+		|| {
+			let typed_chain_id = r_id.typed_chain_id();
+			let prop_id = ProposalNonce::from(1u32);
+			let proposal = make_remark_proposal(r_id, prop_id);
+			let key = (prop_id, <Test as frame_system::Config>::Hashing::hash_of(&proposal));
+
+			for proposer in [PROPOSER_A, PROPOSER_B] {
+				assert_ok!(DKGProposals::acknowledge_proposal(
+					RuntimeOrigin::signed(mock_pub_key(proposer)),
+					proposal.clone(),
+				));
+			}
+			assert_eq!(
+				DKGProposals::archived_proposals(typed_chain_id, key),
+				Some(ArchivedProposal {
+					outcome: ProposalOutcome::Approved,
+					block: 1,
+					resource_id: r_id,
+				})
+			);
+
+			DKGProposals::on_idle(2, Weight::MAX);
+			assert!(DKGProposals::votes(typed_chain_id, (prop_id, proposal.clone())).is_none());
+			assert_has_event(RuntimeEvent::DKGProposals(
+				pallet_dkg_proposals::Event::ProposalVotesPruned {
+					kind: proposal.kind(),
+					src_chain_id: typed_chain_id,
+					proposal_nonce: prop_id,
+					outcome: ProposalOutcome::Approved,
+				},
+			));
+
+			// the proposal cannot be voted on again while its outcome is archived
+			assert_noop!(
+				DKGProposals::acknowledge_proposal(
+					RuntimeOrigin::signed(mock_pub_key(PROPOSER_C)),
+					proposal.clone(),
+				),
+				Error::<Test>::ProposalAlreadyComplete
+			);
+		},
+	)
+}
+
+#[test]
+fn on_idle_archives_expired_proposals_and_keeps_pending_ones() {
+	let typed_chain_id = TypedChainId::Evm(1);
+	let r_id = derive_resource_id(typed_chain_id.underlying_chain_id(), 0x0100, b"System.remark");
+
+	new_test_ext_initialized(r_id.typed_chain_id(), r_id, b"System.remark".to_vec()).execute_with(
+		|| {
+			let typed_chain_id = r_id.typed_chain_id();
+			let expired = make_remark_proposal(r_id, ProposalNonce::from(1u32));
+			assert_ok!(DKGProposals::acknowledge_proposal(
+				RuntimeOrigin::signed(mock_pub_key(PROPOSER_A)),
+				expired.clone(),
+			));
+
+			System::set_block_number(ProposalLifetime::get());
+			let pending = make_remark_proposal(r_id, ProposalNonce::from(2u32));
+			assert_ok!(DKGProposals::acknowledge_proposal(
+				RuntimeOrigin::signed(mock_pub_key(PROPOSER_A)),
+				pending.clone(),
+			));
+
+			let now = ProposalLifetime::get() + 1;
+			System::set_block_number(now);
+			DKGProposals::on_idle(now, Weight::MAX);
+
+			assert!(DKGProposals::votes(typed_chain_id, (1u32.into(), expired.clone())).is_none());
+			assert!(DKGProposals::votes(typed_chain_id, (2u32.into(), pending)).is_some());
+			let key = (
+				ProposalNonce::from(1u32),
+				<Test as frame_system::Config>::Hashing::hash_of(&expired),
+			);
+			assert_eq!(
+				DKGProposals::archived_proposals(typed_chain_id, key),
+				Some(ArchivedProposal {
+					outcome: ProposalOutcome::Expired,
+					block: now,
+					resource_id: r_id,
+				})
+			);
+			assert_noop!(
+				DKGProposals::acknowledge_proposal(
+					RuntimeOrigin::signed(mock_pub_key(PROPOSER_B)),
+					expired,
+				),
+				Error::<Test>::ProposalExpired
+			);
+		},
+	)
+}
+
+#[test]
+fn on_idle_prunes_outcomes_out_of_the_archive_window() {
+	let typed_chain_id = TypedChainId::Evm(1);
+	let r_id = derive_resource_id(typed_chain_id.underlying_chain_id(), 0x0100, b"System.remark");
+
+	new_test_ext_initialized(r_id.typed_chain_id(), r_id, b"System.remark".to_vec()).execute_with(
+		|| {
+			let typed_chain_id = r_id.typed_chain_id();
+			let prop_id = ProposalNonce::from(1u32);
+			let proposal = make_remark_proposal(r_id, prop_id);
+			let key = (prop_id, <Test as frame_system::Config>::Hashing::hash_of(&proposal));
+			for proposer in [PROPOSER_A, PROPOSER_B] {
+				assert_ok!(DKGProposals::acknowledge_proposal(
+					RuntimeOrigin::signed(mock_pub_key(proposer)),
+					proposal.clone(),
+				));
+			}
+
+			let window = <Test as Config>::ArchiveWindow::get();
+			DKGProposals::on_idle(window, Weight::MAX);
+			assert!(DKGProposals::archived_proposals(typed_chain_id, key).is_some());
+
+			DKGProposals::on_idle(window + 1, Weight::MAX);
+			assert!(DKGProposals::archived_proposals(typed_chain_id, key).is_none());
+			assert!(DKGProposals::votes(typed_chain_id, (prop_id, proposal.clone())).is_none());
+			assert_eq!(DKGProposals::pruned_proposal_nonces(r_id), Some(prop_id));
+
+			// the proposal cannot be voted on again once its outcome left the archive
+			assert_noop!(
+				DKGProposals::acknowledge_proposal(
+					RuntimeOrigin::signed(mock_pub_key(PROPOSER_C)),
+					proposal,
+				),
+				Error::<Test>::ProposalAlreadyComplete
+			);
+			assert_ok!(DKGProposals::acknowledge_proposal(
+				RuntimeOrigin::signed(mock_pub_key(PROPOSER_C)),
+				make_remark_proposal(r_id, ProposalNonce::from(2u32)),
+			));
+		},
+	)
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 //
 use codec::{Decode, Encode, MaxEncodedLen};
+use dkg_runtime_primitives::{ProposalNonce, ResourceId, TypedChainId};
 use frame_support::{pallet_prelude::Get, BoundedVec};
 use scale_info::TypeInfo;
 use sp_runtime::RuntimeDebug;
@@ -27,6 +28,26 @@ pub enum ProposalStatus {
 	Rejected,
 }
 
+/// Source chain, nonce and hash of the last proposal visited by a garbage collector
+pub type PruneCursor<Hash> = (TypedChainId, (ProposalNonce, Hash));
+
+/// How the voting on a proposal ended
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum ProposalOutcome {
+	Approved,
+	Rejected,
+	Expired,
+}
+
+/// The outcome of a proposal, the block at which it was reached and the resource of the
+/// proposal
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ArchivedProposal<BlockNumber> {
+	pub outcome: ProposalOutcome,
+	pub block: BlockNumber,
+	pub resource_id: ResourceId,
+}
+
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub struct ProposalVotes<AccountId, BlockNumber, MaxVotes: Get<u32> + Clone> {
 	pub votes_for: BoundedVec<AccountId, MaxVotes>,
@@ -52,6 +73,29 @@ impl<A: PartialEq, B: PartialOrd + Default, MaxVotes: Get<u32> + Clone>
 		}
 	}
 
+	/// Attempts to mark the proposal as approved or rejected once the weight of the votes
+	/// reaches `threshold` out of the `total` weight of the proposers.
+	pub fn try_to_complete_weighted(
+		&mut self,
+		threshold: u128,
+		total: u128,
+		vote_weight: impl Fn(&A) -> u128,
+	) -> ProposalStatus {
+		let weight_of =
+			|votes: &[A]| votes.iter().fold(0u128, |sum, who| sum.saturating_add(vote_weight(who)));
+		if threshold > 0 && weight_of(&self.votes_for) >= threshold {
+			self.status = ProposalStatus::Approved;
+			ProposalStatus::Approved
+		} else if total >= threshold &&
+			weight_of(&self.votes_against).saturating_add(threshold) > total
+		{
+			self.status = ProposalStatus::Rejected;
+			ProposalStatus::Rejected
+		} else {
+			ProposalStatus::Initiated
+		}
+	}
+
 	/// Returns true if the proposal has been rejected or approved, otherwise
 	/// false.
 	pub fn is_complete(&self) -> bool {
@@ -67,6 +111,16 @@ impl<A: PartialEq, B: PartialOrd + Default, MaxVotes: Get<u32> + Clone>
 	pub fn is_expired(&self, now: B) -> bool {
 		self.expiry <= now
 	}
+
+	/// Returns the outcome of the proposal if it was resolved or has expired
+	pub fn outcome(&self, now: B) -> Option<ProposalOutcome> {
+		match self.status {
+			ProposalStatus::Approved => Some(ProposalOutcome::Approved),
+			ProposalStatus::Rejected => Some(ProposalOutcome::Rejected),
+			ProposalStatus::Initiated if self.is_expired(now) => Some(ProposalOutcome::Expired),
+			ProposalStatus::Initiated => None,
+		}
+	}
 }
 
 impl<AccountId, BlockNumber: Default, MaxVotes: Get<u32> + Clone> Default
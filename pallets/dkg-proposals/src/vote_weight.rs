@@ -0,0 +1,47 @@
+// Copyright 2022 Webb Technologies Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sources of the weight of a proposer's vote, used when proposals are resolved against a
+//! weighted threshold.
+use sp_runtime::traits::{Convert, UniqueSaturatedInto};
+use sp_std::marker::PhantomData;
+
+/// Provides the weight of the vote of a proposer
+pub trait VoteWeightProvider<AccountId> {
+	/// Returns the weight of the vote of `who`, a proposer without weight cannot approve
+	/// proposals on its own
+	fn vote_weight(who: &AccountId) -> u128;
+}
+
+/// Every proposer has the same weight
+impl<AccountId> VoteWeightProvider<AccountId> for () {
+	fn vote_weight(_who: &AccountId) -> u128 {
+		1
+	}
+}
+
+/// Weighs the vote of a proposer with the reputation of its DKG authority in
+/// `pallet-dkg-metadata`
+pub struct ReputationVoteWeight<T>(PhantomData<T>);
+
+impl<T: pallet_dkg_metadata::Config> VoteWeightProvider<T::AccountId> for ReputationVoteWeight<T> {
+	fn vote_weight(who: &T::AccountId) -> u128 {
+		T::AuthorityIdOf::convert(who.clone())
+			.map(|authority| {
+				pallet_dkg_metadata::AuthorityReputations::<T>::get(authority)
+					.unique_saturated_into()
+			})
+			.unwrap_or_default()
+	}
+}
@@ -38,6 +38,7 @@ pub trait WeightInfo {
 	fn acknowledge_proposal() -> Weight;
 	fn reject_proposal() -> Weight;
 	fn eval_vote_state(c: u32, ) -> Weight;
+	fn set_weighted_threshold(p: u32, ) -> Weight;
 }
 
 /// Weights for pallet_dkg_proposals using the Substrate node and recommended hardware.
@@ -98,15 +99,23 @@ impl<T: frame_system::Config> WeightInfo for WebbWeight<T> {
 	/// Proof: DKGProposals ProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
 	/// Storage: DKGProposals ProposerCount (r:1 w:0)
 	/// Proof: DKGProposals ProposerCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:1 w:0)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ProposerVoteWeights (r:1 w:0)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ArchivedProposals (r:1 w:1)
+	/// Proof: DKGProposals ArchivedProposals (max_values: None, max_size: Some(125), added: 2600, mode: MaxEncodedLen)
+	/// Storage: DKGProposals PrunedProposalNonces (r:1 w:0)
+	/// Proof: DKGProposals PrunedProposalNonces (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Storage: DKGProposals VotedProposals (r:0 w:1)
+	/// Proof: DKGProposals VotedProposals (max_values: None, max_size: Some(1126), added: 3601, mode: MaxEncodedLen)
 	/// The range of component `c` is `[1, 500]`.
 	fn acknowledge_proposal() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `1162`
-		//  Estimated: `18490`
-		// Minimum execution time: 39_000_000 picoseconds.
-		Weight::from_parts(41_586_869, 18490)
-			.saturating_add(T::DbWeight::get().reads(6_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+		// Placeholder weight, not re-benchmarked yet: the execution time was measured before the
+		// vote weights and the vote index were added, the storage accesses are counted by hand.
+		Weight::from_parts(41_586_869, 72614)
+			.saturating_add(T::DbWeight::get().reads(10_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
 	}
 	/// Storage: DKGProposals Proposers (r:1 w:0)
 	/// Proof: DKGProposals Proposers (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
@@ -120,15 +129,23 @@ impl<T: frame_system::Config> WeightInfo for WebbWeight<T> {
 	/// Proof: DKGProposals ProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
 	/// Storage: DKGProposals ProposerCount (r:1 w:0)
 	/// Proof: DKGProposals ProposerCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:1 w:0)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ProposerVoteWeights (r:1 w:0)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ArchivedProposals (r:1 w:1)
+	/// Proof: DKGProposals ArchivedProposals (max_values: None, max_size: Some(125), added: 2600, mode: MaxEncodedLen)
+	/// Storage: DKGProposals PrunedProposalNonces (r:1 w:0)
+	/// Proof: DKGProposals PrunedProposalNonces (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Storage: DKGProposals VotedProposals (r:0 w:1)
+	/// Proof: DKGProposals VotedProposals (max_values: None, max_size: Some(1126), added: 3601, mode: MaxEncodedLen)
 	/// The range of component `c` is `[1, 500]`.
 	fn reject_proposal() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `1162`
-		//  Estimated: `18490`
-		// Minimum execution time: 39_000_000 picoseconds.
-		Weight::from_parts(40_500_598, 18490)
-			.saturating_add(T::DbWeight::get().reads(6_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+		// Placeholder weight, not re-benchmarked yet: the execution time was measured before the
+		// vote weights and the vote index were added, the storage accesses are counted by hand.
+		Weight::from_parts(40_500_598, 72614)
+			.saturating_add(T::DbWeight::get().reads(10_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
 	}
 	/// Storage: DKGProposals Votes (r:1 w:1)
 	/// Proof: DKGProposals Votes (max_values: None, max_size: Some(6473), added: 8948, mode: MaxEncodedLen)
@@ -136,17 +153,41 @@ impl<T: frame_system::Config> WeightInfo for WebbWeight<T> {
 	/// Proof: DKGProposals ProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
 	/// Storage: DKGProposals ProposerCount (r:1 w:0)
 	/// Proof: DKGProposals ProposerCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:1 w:0)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ProposerVoteWeights (r:1 w:0)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ArchivedProposals (r:0 w:1)
+	/// Proof: DKGProposals ArchivedProposals (max_values: None, max_size: Some(125), added: 2600, mode: MaxEncodedLen)
 	/// The range of component `c` is `[1, 500]`.
 	fn eval_vote_state(c: u32, ) -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `620`
-		//  Estimated: `9946`
-		// Minimum execution time: 18_000_000 picoseconds.
-		Weight::from_parts(19_200_182, 9946)
-			// Standard Error: 2_471
+		// Placeholder weight, not re-benchmarked yet: the execution time was measured before the
+		// vote weights and the vote index were added, the storage accesses are counted by hand.
+		Weight::from_parts(19_200_182, 58943)
 			.saturating_add(Weight::from_parts(599, 0).saturating_mul(c.into()))
-			.saturating_add(T::DbWeight::get().reads(3_u64))
-			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: DKGProposals Proposers (r:1 w:0)
+	/// Proof: DKGProposals Proposers (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Staking ActiveEra (r:1 w:0)
+	/// Proof: Staking ActiveEra (max_values: Some(1), max_size: Some(13), added: 508, mode: MaxEncodedLen)
+	/// Storage: Staking ErasStakers (r:1000 w:0)
+	/// Proof Skipped: Staking ErasStakers (max_values: None, max_size: None, mode: Measured)
+	/// Storage: DKGProposals ProposerVoteWeights (r:0 w:1)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:0 w:1)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// The range of component `p` is `[1, 1000]`.
+	fn set_weighted_threshold(p: u32, ) -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(18_000_000, 3650)
+			.saturating_add(Weight::from_parts(8_000_000, 0).saturating_mul(p.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(Weight::from_parts(0, 5166).saturating_mul(p.into()))
 	}
 }
 
@@ -207,15 +248,23 @@ impl WeightInfo for () {
 	/// Proof: DKGProposals ProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
 	/// Storage: DKGProposals ProposerCount (r:1 w:0)
 	/// Proof: DKGProposals ProposerCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:1 w:0)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ProposerVoteWeights (r:1 w:0)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ArchivedProposals (r:1 w:1)
+	/// Proof: DKGProposals ArchivedProposals (max_values: None, max_size: Some(125), added: 2600, mode: MaxEncodedLen)
+	/// Storage: DKGProposals PrunedProposalNonces (r:1 w:0)
+	/// Proof: DKGProposals PrunedProposalNonces (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Storage: DKGProposals VotedProposals (r:0 w:1)
+	/// Proof: DKGProposals VotedProposals (max_values: None, max_size: Some(1126), added: 3601, mode: MaxEncodedLen)
 	/// The range of component `c` is `[1, 500]`.
 	fn acknowledge_proposal() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `1162`
-		//  Estimated: `18490`
-		// Minimum execution time: 39_000_000 picoseconds.
-		Weight::from_parts(41_586_869, 18490)
-			.saturating_add(RocksDbWeight::get().reads(6_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+		// Placeholder weight, not re-benchmarked yet: the execution time was measured before the
+		// vote weights and the vote index were added, the storage accesses are counted by hand.
+		Weight::from_parts(41_586_869, 72614)
+			.saturating_add(RocksDbWeight::get().reads(10_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
 	}
 	/// Storage: DKGProposals Proposers (r:1 w:0)
 	/// Proof: DKGProposals Proposers (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
@@ -229,15 +278,23 @@ impl WeightInfo for () {
 	/// Proof: DKGProposals ProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
 	/// Storage: DKGProposals ProposerCount (r:1 w:0)
 	/// Proof: DKGProposals ProposerCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:1 w:0)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ProposerVoteWeights (r:1 w:0)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ArchivedProposals (r:1 w:1)
+	/// Proof: DKGProposals ArchivedProposals (max_values: None, max_size: Some(125), added: 2600, mode: MaxEncodedLen)
+	/// Storage: DKGProposals PrunedProposalNonces (r:1 w:0)
+	/// Proof: DKGProposals PrunedProposalNonces (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Storage: DKGProposals VotedProposals (r:0 w:1)
+	/// Proof: DKGProposals VotedProposals (max_values: None, max_size: Some(1126), added: 3601, mode: MaxEncodedLen)
 	/// The range of component `c` is `[1, 500]`.
 	fn reject_proposal() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `1162`
-		//  Estimated: `18490`
-		// Minimum execution time: 39_000_000 picoseconds.
-		Weight::from_parts(40_500_598, 18490)
-			.saturating_add(RocksDbWeight::get().reads(6_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+		// Placeholder weight, not re-benchmarked yet: the execution time was measured before the
+		// vote weights and the vote index were added, the storage accesses are counted by hand.
+		Weight::from_parts(40_500_598, 72614)
+			.saturating_add(RocksDbWeight::get().reads(10_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
 	}
 	/// Storage: DKGProposals Votes (r:1 w:1)
 	/// Proof: DKGProposals Votes (max_values: None, max_size: Some(6473), added: 8948, mode: MaxEncodedLen)
@@ -245,16 +302,40 @@ impl WeightInfo for () {
 	/// Proof: DKGProposals ProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
 	/// Storage: DKGProposals ProposerCount (r:1 w:0)
 	/// Proof: DKGProposals ProposerCount (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:1 w:0)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ProposerVoteWeights (r:1 w:0)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals ArchivedProposals (r:0 w:1)
+	/// Proof: DKGProposals ArchivedProposals (max_values: None, max_size: Some(125), added: 2600, mode: MaxEncodedLen)
 	/// The range of component `c` is `[1, 500]`.
 	fn eval_vote_state(c: u32, ) -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `620`
-		//  Estimated: `9946`
-		// Minimum execution time: 18_000_000 picoseconds.
-		Weight::from_parts(19_200_182, 9946)
-			// Standard Error: 2_471
+		// Placeholder weight, not re-benchmarked yet: the execution time was measured before the
+		// vote weights and the vote index were added, the storage accesses are counted by hand.
+		Weight::from_parts(19_200_182, 58943)
 			.saturating_add(Weight::from_parts(599, 0).saturating_mul(c.into()))
-			.saturating_add(RocksDbWeight::get().reads(3_u64))
-			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: DKGProposals Proposers (r:1 w:0)
+	/// Proof: DKGProposals Proposers (max_values: None, max_size: Some(49), added: 2524, mode: MaxEncodedLen)
+	/// Storage: Staking ActiveEra (r:1 w:0)
+	/// Proof: Staking ActiveEra (max_values: Some(1), max_size: Some(13), added: 508, mode: MaxEncodedLen)
+	/// Storage: Staking ErasStakers (r:1000 w:0)
+	/// Proof Skipped: Staking ErasStakers (max_values: None, max_size: None, mode: Measured)
+	/// Storage: DKGProposals ProposerVoteWeights (r:0 w:1)
+	/// Proof: DKGProposals ProposerVoteWeights (max_values: Some(1), max_size: Some(48003), added: 48498, mode: MaxEncodedLen)
+	/// Storage: DKGProposals WeightedProposerThreshold (r:0 w:1)
+	/// Proof: DKGProposals WeightedProposerThreshold (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// The range of component `p` is `[1, 1000]`.
+	fn set_weighted_threshold(p: u32, ) -> Weight {
+		// Placeholder weight, not benchmarked yet: the storage accesses are counted by hand and
+		// the execution time is an estimate.
+		Weight::from_parts(18_000_000, 3650)
+			.saturating_add(Weight::from_parts(8_000_000, 0).saturating_mul(p.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(Weight::from_parts(0, 5166).saturating_mul(p.into()))
 	}
 }
\ No newline at end of file
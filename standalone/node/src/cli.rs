@@ -13,6 +13,9 @@
 // limitations under the License.
 //
 
+use dkg_gadget::constants::signing_manager::{
+	JOB_POLL_INTERVAL_IN_MILLISECONDS, MAX_ENQUEUED_TASKS, MAX_RUNNING_TASKS,
+};
 use sc_cli::RunCmd;
 
 #[derive(Debug, clap::Parser)]
@@ -23,10 +26,53 @@ pub struct Cli {
 	pub run: RunCmd,
 	#[arg(long, short = 'o')]
 	pub output_path: Option<std::path::PathBuf>,
+	/// Store the DKG key shares in a standalone database at this path, instead of the offchain
+	/// storage of the chain database.
+	#[arg(long)]
+	pub dkg_db_path: Option<std::path::PathBuf>,
+	#[clap(flatten)]
+	pub dkg_signing: DKGSigningCmd,
 	#[clap(flatten)]
 	pub relayer_cmd: webb_relayer_gadget_cli::WebbRelayerCmd,
 }
 
+/// Limits of the DKG signing protocols run by the node
+#[derive(Debug, Clone, clap::Args)]
+pub struct DKGSigningCmd {
+	/// Maximum number of DKG signing protocols running at the same time
+	#[arg(
+		long,
+		default_value_t = MAX_RUNNING_TASKS,
+		value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+	)]
+	pub dkg_max_signing_tasks: usize,
+	/// Maximum number of DKG signing protocols waiting to be started
+	#[arg(long, default_value_t = MAX_ENQUEUED_TASKS)]
+	pub dkg_max_enqueued_signing_tasks: usize,
+	/// How often the DKG signing protocols are polled for completion, in milliseconds
+	#[arg(
+		long,
+		default_value_t = JOB_POLL_INTERVAL_IN_MILLISECONDS,
+		value_parser = clap::value_parser!(u64).range(1..),
+	)]
+	pub dkg_signing_poll_interval: u64,
+	/// Start with one DKG signing protocol at a time, run more as they complete quickly, and
+	/// run fewer when they stall, up to `--dkg-max-signing-tasks`
+	#[arg(long)]
+	pub dkg_adaptive_signing: bool,
+}
+
+impl From<DKGSigningCmd> for dkg_gadget::WorkManagerConfig {
+	fn from(cmd: DKGSigningCmd) -> Self {
+		Self {
+			max_running_tasks: cmd.dkg_max_signing_tasks,
+			max_enqueued_tasks: cmd.dkg_max_enqueued_signing_tasks,
+			poll_method: dkg_gadget::PollMethod::Interval { millis: cmd.dkg_signing_poll_interval },
+			adaptive_concurrency: cmd.dkg_adaptive_signing,
+		}
+	}
+}
+
 #[derive(Debug, clap::Subcommand)]
 #[allow(clippy::large_enum_variant)]
 pub enum Subcommand {
@@ -59,7 +59,7 @@ pub fn run() -> sc_cli::Result<()> {
 
 	match &cli.subcommand {
 		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
-		Some(Subcommand::DKGKey(cmd)) => cmd.run(&cli),
+		Some(Subcommand::DKGKey(cmd)) => cmd.run(&cli, service::open_dkg_db),
 		Some(Subcommand::BuildSpec(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
@@ -213,6 +213,8 @@ pub fn run() -> sc_cli::Result<()> {
 				service::new_full(service::RunFullParams {
 					config,
 					debug_output: cli.output_path,
+					dkg_db_path: cli.dkg_db_path,
+					signing_work_config: cli.dkg_signing.into(),
 					relayer_cmd: cli.relayer_cmd,
 				})
 				.map_err(sc_cli::Error::Service)
@@ -7,8 +7,11 @@
 
 use std::sync::Arc;
 
-use dkg_standalone_runtime::{opaque::Block, AccountId, Balance, Index};
+use dkg_gadget::DKGRpcLinks;
+use dkg_runtime_primitives::{crypto::AuthorityId, DKGApi, MaxAuthorities, MaxProposalLength};
+use dkg_standalone_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Index};
 use jsonrpsee::RpcModule;
+use sc_rpc::SubscriptionTaskExecutor;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -24,6 +27,10 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Links to the local DKG worker
+	pub dkg_rpc_links: DKGRpcLinks,
+	/// Executor for the RPC subscriptions
+	pub subscription_executor: SubscriptionTaskExecutor,
 }
 
 /// Instantiate all full RPC extensions.
@@ -37,16 +44,20 @@ where
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: DKGApi<Block, AuthorityId, BlockNumber, MaxProposalLength, MaxAuthorities>,
 	P: TransactionPool + 'static,
 {
+	use dkg_rpc::{DKGRpc, DKGRpcApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
 
 	let mut module = RpcModule::new(());
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps { client, pool, deny_unsafe, dkg_rpc_links, subscription_executor } = deps;
 
 	module.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	module
+		.merge(DKGRpc::new(client, dkg_rpc_links, subscription_executor, deny_unsafe).into_rpc())?;
 
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
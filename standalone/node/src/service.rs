@@ -156,15 +156,47 @@ pub fn new_partial(
 	})
 }
 
+/// Opens the DKG database of the node, for the `dkg-key` subcommands: the file backed database
+/// at `dkg_db_path` if given, or the offchain storage database otherwise.
+pub fn open_dkg_db(
+	config: &Configuration,
+	dkg_db_path: Option<&std::path::Path>,
+) -> Result<Box<dyn dkg_gadget::db::DKGDbBackend>, sc_cli::Error> {
+	let debug_logger = DebugLogger::new("dkg-key", None)?;
+	if let Some(path) = dkg_db_path {
+		let db = dkg_gadget::db::DKGSledDb::open(path, debug_logger)
+			.map_err(|e| sc_cli::Error::Application(Box::from(format!("{e:?}"))))?;
+		return Ok(Box::new(db))
+	}
+
+	let sc_service::PartialComponents { backend, keystore_container, .. } = new_partial(config)?;
+	let dkg_keystore =
+		dkg_gadget::DKGKeystore::new(Some(keystore_container.keystore()), debug_logger.clone());
+	Ok(Box::new(dkg_gadget::db::DKGOffchainStorageDb::<Block, _>::new(
+		backend,
+		dkg_keystore,
+		Some(keystore_container.local_keystore()),
+		debug_logger,
+	)))
+}
+
 pub struct RunFullParams {
 	pub config: Configuration,
 	pub debug_output: Option<std::path::PathBuf>,
+	pub dkg_db_path: Option<std::path::PathBuf>,
+	pub signing_work_config: dkg_gadget::WorkManagerConfig,
 	pub relayer_cmd: webb_relayer_gadget_cli::WebbRelayerCmd,
 }
 
 /// Builds a new service for a full client.
 pub fn new_full(
-	RunFullParams { config, debug_output, relayer_cmd }: RunFullParams,
+	RunFullParams {
+		config,
+		debug_output,
+		dkg_db_path,
+		signing_work_config,
+		relayer_cmd,
+	}: RunFullParams,
 ) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
@@ -232,6 +264,7 @@ pub fn new_full(
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
+	let dkg_rpc_links = dkg_gadget::DKGRpcLinks::default();
 
 	if role.is_authority() {
 		dkg_primitives::utils::insert_controller_account_keys_into_keystore(
@@ -257,15 +290,21 @@ pub fn new_full(
 			prometheus_registry: prometheus_registry.clone(),
 			local_keystore: Some(keystore_container.local_keystore()),
 			_block: std::marker::PhantomData::<Block>,
-			debug_logger,
+			debug_logger: debug_logger.clone(),
+			db_path: dkg_db_path,
+			rpc_links: dkg_rpc_links.clone(),
+			signing_work_config,
 		};
 
 		// Start the DKG gadget.
-		task_manager.spawn_essential_handle().spawn_blocking(
-			"dkg-gadget",
-			None,
-			dkg_gadget::start_dkg_gadget::<_, _, _>(dkg_params),
-		);
+		// An error stops the essential task, which shuts the node down.
+		task_manager
+			.spawn_essential_handle()
+			.spawn_blocking("dkg-gadget", None, async move {
+				if let Err(e) = dkg_gadget::start_dkg_gadget::<_, _, _>(dkg_params).await {
+					debug_logger.error(format!("The DKG gadget failed to start: {e:?}"));
+				}
+			});
 
 		let relayer_params = webb_relayer_gadget::WebbRelayerParams {
 			local_keystore: keystore_container.local_keystore(),
@@ -289,9 +328,14 @@ pub fn new_full(
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				dkg_rpc_links: dkg_rpc_links.clone(),
+				subscription_executor,
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
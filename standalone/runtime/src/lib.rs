@@ -22,7 +22,8 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use codec::{Decode, Encode, MaxEncodedLen};
 use dkg_runtime_primitives::{
-	MaxAuthorities, MaxKeyLength, MaxProposalLength, MaxReporters, MaxSignatureLength, TypedChainId,
+	ChainSchedulingConfig, MaxAuthorities, MaxKeyLength, MaxProposalLength, MaxReporters,
+	MaxSignatureLength, ProposalNonce, ResourceId, TypedChainId,
 };
 use frame_election_provider_support::{onchain, SequentialPhragmen, VoteWeight};
 use frame_support::{
@@ -35,7 +36,7 @@ use frame_system::{
 	limits::{BlockLength, BlockWeights},
 	EnsureRoot,
 };
-use pallet_dkg_proposal_handler::StoredUnsignedProposalBatchOf;
+use pallet_dkg_proposal_handler::{SignedProposalBatchOf, StoredUnsignedProposalBatchOf};
 use pallet_election_provider_multi_phase::SolutionAccuracyOf;
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
@@ -139,10 +140,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("dkg-standalone-node"),
 	impl_name: create_runtime_str!("dkg-standalone-node"),
 	authoring_version: 1,
-	spec_version: 19,
+	spec_version: 20,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -632,10 +633,14 @@ impl pallet_dkg_metadata::Config for Runtime {
 parameter_types! {
   pub const ChainIdentifier: TypedChainId = TypedChainId::Substrate(5);
   pub const ProposalLifetime: BlockNumber = HOURS / 5;
+  pub const ProposalArchiveWindow: BlockNumber = DAYS;
   pub const DKGAccountId: PalletId = PalletId(*b"dw/dkgac");
   pub const RefreshDelay: Permill = Permill::from_percent(50);
   pub const TimeToRestart: BlockNumber = 3;
   pub const UnsignedProposalExpiry: BlockNumber = Period::get() * 2;
+  pub const StuckNonceThreshold: BlockNumber = Period::get();
+  pub const SubmitterFallbackBlocks: BlockNumber = 3;
+  pub const SigningOffenceKeyWindow: u32 = 5;
 }
 
 impl pallet_dkg_proposal_handler::Config for Runtime {
@@ -644,6 +649,9 @@ impl pallet_dkg_proposal_handler::Config for Runtime {
 	type MaxProposalsPerBatch = dkg_runtime_primitives::MaxProposalsInBatch;
 	type BatchId = u32;
 	type UnsignedProposalExpiry = UnsignedProposalExpiry;
+	type StuckNonceThreshold = StuckNonceThreshold;
+	type SubmitterFallbackBlocks = SubmitterFallbackBlocks;
+	type SigningOffenceKeyWindow = SigningOffenceKeyWindow;
 	type SignedProposalHandler = (BridgeRegistry, DKG);
 	type ForceOrigin = EnsureRoot<Self::AccountId>;
 	type ValidatorSet = Historical;
@@ -666,6 +674,16 @@ parameter_types! {
 	pub const MaxProposers : u32 = 1000;
 }
 
+/// Weighs the vote of a proposer with its total staking exposure in the active era
+pub struct StakingVoteWeight;
+impl pallet_dkg_proposals::VoteWeightProvider<AccountId> for StakingVoteWeight {
+	fn vote_weight(who: &AccountId) -> u128 {
+		pallet_staking::ExposureOf::<Runtime>::convert(who.clone())
+			.map(|exposure| exposure.total)
+			.unwrap_or_default()
+	}
+}
+
 impl pallet_dkg_proposals::Config for Runtime {
 	type AdminOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type DKGAuthorityToMerkleLeaf = pallet_dkg_proposals::DKGEcdsaToEthereumAddress;
@@ -675,7 +693,9 @@ impl pallet_dkg_proposals::Config for Runtime {
 	type MaxProposalLength = MaxProposalLength;
 	type NextSessionRotation = pallet_dkg_metadata::DKGPeriodicSessions<Period, Offset, Runtime>;
 	type ProposalLifetime = ProposalLifetime;
+	type ArchiveWindow = ProposalArchiveWindow;
 	type ProposalHandler = DKGProposalHandler;
+	type VoteWeight = StakingVoteWeight;
 	type Period = Period;
 	type MaxVotes = MaxVotes;
 	type MaxResources = MaxResources;
@@ -830,6 +850,9 @@ pub type SignedExtra = (
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
+/// The storage migrations run on the next runtime upgrade
+type Migrations = (pallet_dkg_proposal_handler::migrations::v1::MigrateToV1<Runtime>,);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -837,6 +860,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 impl_runtime_apis! {
@@ -992,6 +1016,22 @@ impl_runtime_apis! {
 	  DKG::next_keygen_threshold()
 	}
 
+	fn dkg_protocol() -> dkg_runtime_primitives::DKGProtocol {
+	  DKG::dkg_protocol()
+	}
+
+	fn next_dkg_protocol() -> dkg_runtime_primitives::DKGProtocol {
+	  DKG::next_dkg_protocol()
+	}
+
+	fn should_refresh_key_shares() -> bool {
+	  DKG::should_refresh_key_shares()
+	}
+
+	fn should_reshare_key() -> bool {
+	  DKG::should_reshare_key()
+	}
+
 	fn should_refresh(block_number: BlockNumber) -> bool {
 	  DKG::should_refresh(block_number)
 	}
@@ -1008,6 +1048,10 @@ impl_runtime_apis! {
 	  (DKG::dkg_public_key().0, DKG::dkg_public_key().1.into())
 	}
 
+	fn dkg_pub_key_sig() -> Vec<u8> {
+	  DKG::public_key_signature().into()
+	}
+
 	fn get_best_authorities() -> Vec<(u16, DKGId)> {
 	  DKG::best_authorities().into()
 	}
@@ -1025,6 +1069,18 @@ impl_runtime_apis! {
 	  DKGProposalHandler::get_unsigned_proposal_batches()
 	}
 
+	fn get_signed_proposal_batches() -> Vec<(TypedChainId, SignedProposalBatchOf<Runtime>)> {
+	  DKGProposalHandler::get_signed_proposal_batches()
+	}
+
+	fn get_last_signed_nonces() -> Vec<(ResourceId, ProposalNonce)> {
+	  DKGProposalHandler::get_last_signed_nonces()
+	}
+
+	fn get_chain_scheduling_configs() -> Vec<(TypedChainId, ChainSchedulingConfig)> {
+	  DKGProposalHandler::get_chain_scheduling_configs()
+	}
+
 	fn get_authority_accounts() -> (Vec<AccountId>, Vec<AccountId>) {
 	  (DKG::current_authorities_accounts().into(), DKG::next_authorities_accounts().into())
 	}